@@ -0,0 +1,10 @@
+#ifndef __STDARG_H
+#define __STDARG_H
+
+// va_list とその操作はコンパイラの組み込み
+typedef __builtin_va_list va_list;
+#define va_start(ap, last) __builtin_va_start(ap, last)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_end(ap) __builtin_va_end(ap)
+
+#endif
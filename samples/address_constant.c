@@ -0,0 +1,32 @@
+struct Point {
+  int x;
+  int y;
+};
+
+int arr[4] = {1, 2, 3, 4};
+struct Point pt = {5, 6};
+
+char *s = "hi";
+char *strs[] = {"x", "yz"};
+int *q = arr;
+int *second = &arr[1];
+int *last = arr + 3;
+int *py = &pt.y;
+void *v = &arr[2];
+
+int count() {
+  static char *msg = "abc";
+  static int *p = &arr[2];
+  return msg[2] - 'a' + *p;
+}
+
+int main() {
+  int total = s[1] - 'h';                 // 1
+  total = total + strs[1][1] - 'y';       // 2
+  total = total + *q + *second + *last;   // 9
+  total = total + *py;                    // 15
+  int *iv = v;
+  total = total + *iv;                    // 18
+  total = total + count();                // 23
+  return total;
+}
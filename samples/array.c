@@ -0,0 +1,32 @@
+int main() {
+  int a[4];
+  int m[2][3];
+  int *p;
+  int i;
+  int j;
+  int sum;
+
+  i = 0;
+  while (i < 4) {
+    a[i] = i * 2;
+    i++;
+  }
+
+  // m[i][j] は *(*(m + i) + j)
+  i = 0;
+  while (i < 2) {
+    j = 0;
+    while (j < 3) {
+      m[i][j] = i * 3 + j;
+      j++;
+    }
+    i++;
+  }
+
+  // 配列は先頭要素へのポインタに変換される
+  p = a;
+  sum = *(p + 3) + p[1] + m[1][2] + 1[a];
+
+  // sizeof は配列全体のサイズ
+  return sum + sizeof(a) + sizeof m[1] + sizeof(int[2][2]) / sizeof(int);
+}
@@ -0,0 +1,11 @@
+int main() {
+  int hash;
+  int i;
+  hash = 5381;
+  i = 0;
+  while (i < 4) {
+    hash = ((hash << 5) + hash) ^ (i | 96);
+    i = i + 1;
+  }
+  return (hash & 255) + (~hash & 0) + (~0 & 3) + (-17 >> 2);
+}
@@ -0,0 +1,26 @@
+int x;
+int f(int a) {
+  int r;
+  r = a;
+  { int a; a = 5; r += a; }
+  return r + a;
+}
+int main() {
+  int sum;
+  int i;
+  sum = 0;
+  x = 7;
+  { int x; x = 1; sum += x; { int x; x = 2; sum += x; } sum += x; }
+  { int x; x = 10; sum += x; }
+  sum += x;
+  i = 100;
+  for (int i = 0; i < 3; i++) {
+    int t;
+    t = i * 2;
+    sum += t;
+    if (i == 1) { int i; i = 40; sum += i; }
+  }
+  for (int j = 1; j < 3; j++) sum += j;
+  switch (sum) { default: { int y; y = 3; sum += y; } }
+  return sum + i;
+}
@@ -0,0 +1,36 @@
+int main() {
+  int i;
+  int j;
+  int sum;
+  sum = 0;
+  for (i = 0; i < 10; i++) {
+    if (i == 2) continue;
+    if (i == 8) break;
+    j = 0;
+    while (1) {
+      j++;
+      if (j > i) break;
+      if (j % 2) continue;
+      sum += j;
+    }
+    switch (i) {
+      case 1: sum += 100; break;
+      case 3: continue;
+      case 4: sum += 1;
+      case 5: sum += 2; break;
+      default: sum += 1000;
+    }
+    sum += 1;
+  }
+  i = 0;
+  do {
+    i++;
+    if (i < 5) continue;
+    sum += i;
+  } while (i < 7);
+  while (1) {
+    sum += 10;
+    if (sum > 130) break;
+  }
+  return sum;
+}
@@ -0,0 +1,31 @@
+long sum8(long a, long b, long c, long d, long e, long f, long g, long h);
+int twice(int x);
+void bump(int n);
+int counter;
+
+int main(void) {
+  int x;
+  int y;
+  int r;
+  long s;
+  x = 3;
+  y = 4;
+  r = x + y + twice(twice(5));
+  bump(2);
+  bump(0);
+  s = sum8(1, 2, 3, 4, 5, 6, 7, x);
+  return r + counter + s - 100;
+}
+
+long sum8(long a, long b, long c, long d, long e, long f, long g, long h) {
+  return a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 + h * 8;
+}
+int twice(int x) {
+  return x * 2;
+}
+void bump(int n) {
+  if (n == 0) {
+    return;
+  }
+  counter = counter + n;
+}
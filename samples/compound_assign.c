@@ -0,0 +1,22 @@
+int main() {
+  int a;
+  int b;
+  int c;
+  a = 10;
+  b = 3;
+  a += 5;
+  a -= b;
+  a *= 2;
+  a /= 4;
+  a %= 4;
+  b <<= 3;
+  b >>= 1;
+  b |= 1;
+  b &= 13;
+  b ^= 4;
+  c = a++ + ++a;
+  c = c + b-- - --b;
+  for (a = 0, b = 0; a < 5; a++)
+    b += a;
+  return (c, b) + a * 10;
+}
@@ -0,0 +1,8 @@
+int main() {
+  int i;
+  int n;
+  n = 0;
+  for (i = 0; i < 10; i++)
+    n += i % 3 == 0 ? i : i > 5 ? 10 : 1;
+  return n + (n > 40 ? 2 : 3) * (0 ? 5 : 7);
+}
@@ -0,0 +1,68 @@
+typedef int myint;
+typedef struct point {
+  int x;
+  int y;
+} Point;
+typedef int (*binop)(int, int);
+typedef int F(int);
+typedef struct node Node;
+struct node {
+  int v;
+  Node *next;
+};
+
+enum color { RED, GREEN = 5, BLUE, LAST = BLUE * 2 };
+typedef enum { A, B, C } abc;
+
+static int counter = 10;
+const int limit = 3;
+int table[LAST];
+
+static int add(int a, int b) { return a + b; }
+int twice(int x) { return x * 2; }
+int apply(F *f, int v) { return f(v); }
+
+int next(void) {
+  static int n;
+  static int base = 100;
+  n++;
+  return base + n;
+}
+int other(void) {
+  static int n = 50;
+  return ++n;
+}
+
+int main(void) {
+  myint i = 3;
+  Point p = {1, 2};
+  binop op = add;
+  register int r = 4;
+  const char *s = "hi";
+  char *const t = "yo";
+  volatile int v = 7;
+  enum color c = BLUE;
+  abc k = C;
+  Node a = {1, 0}, b = {2, &a};
+  int sum = 0;
+
+  for (Node *q = &b; q; q = q->next) {
+    sum += q->v;
+  }
+  {
+    typedef long myint;
+    int T = 2;
+    myint big = 8;
+    sum += sizeof(myint) + big + T;
+  }
+  // 変数で隠された typedef 名
+  int myint = 2;
+
+  next();
+  next();
+  other();
+  sum += next() + other() + counter;
+  sum += i + p.x + p.y + op(1, 2) + r + s[0] + t[1] + v + c + k + myint + limit;
+  sum += sizeof(table) + apply(twice, 5) + GREEN + RED;
+  return sum % 256;
+}
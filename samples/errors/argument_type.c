@@ -0,0 +1,13 @@
+struct point {
+  int x;
+  int y;
+};
+
+int norm(int scale, struct point *p) { return scale * (p->x + p->y); }
+
+int main() {
+  struct point p;
+  p.x = 1;
+  p.y = 2;
+  return norm(2, p);
+}
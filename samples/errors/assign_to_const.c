@@ -0,0 +1,5 @@
+int main() {
+  const int x = 3;
+  x = 4;
+  return x;
+}
@@ -0,0 +1,7 @@
+int g(int *p) { return *p; }
+
+int main() {
+  const int y = 4;
+  int *q = &y;
+  return g(&y) + *q;
+}
@@ -0,0 +1,13 @@
+int main() {
+  int x = 1;
+  switch (x) {
+  case 1:
+    return 1;
+  case 1:
+    return 2;
+  default:
+  default:
+    return 3;
+  }
+  return 0;
+}
@@ -0,0 +1 @@
+int main() { return undefined_fn(3); }
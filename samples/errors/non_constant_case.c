@@ -0,0 +1,9 @@
+int main() {
+  int x = 1;
+  int y = 1;
+  switch (x) {
+  case y:
+    return 1;
+  }
+  return 0;
+}
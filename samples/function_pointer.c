@@ -0,0 +1,41 @@
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int mul(int a, int b);
+
+struct handler {
+  int id;
+  int (*fn)(int, int);
+};
+
+int (*ops[])(int, int) = {add, sub, mul};
+struct handler handlers[2] = {{1, add}, {2, &mul}};
+
+int apply(int (*f)(int, int), int x, int y) { return f(x, y); }
+int apply_deref(int f(int, int), int x, int y) { return (*f)(x, y); }
+
+long sum8(long a, long b, long c, long d, long e, long f, long g, long h) {
+  return a + b + c + d + e + f + g + h;
+}
+
+int main(void) {
+  int (*fp)(int, int) = add;
+  long (*s8)(long, long, long, long, long, long, long, long) = &sum8;
+  struct handler h;
+  struct handler *hp = &h;
+  int i;
+  int r;
+  h.fn = sub;
+  r = fp(1, 2);
+  for (i = 0; i < 3; i++) {
+    r = r + ops[i](6, 3);
+  }
+  r = r + handlers[1].fn(2, 5) + h.fn(9, 4) + hp->fn(3, 1);
+  r = r + apply(mul, 3, 3) + apply_deref(sub, 7, 2);
+  r = r + s8(1, 2, 3, 4, 5, 6, 7, 8);
+  if (fp == add && fp != 0) {
+    r = r + 1;
+  }
+  return r + sizeof(fp);
+}
+
+int mul(int a, int b) { return a * b; }
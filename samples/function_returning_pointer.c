@@ -0,0 +1,22 @@
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int mul(int a, int b) { return a * b; }
+
+int (*pick(int i))(int, int);
+
+int (*pick(int i))(int, int) {
+  if (i == 0)
+    return add;
+  if (i == 1)
+    return sub;
+  return mul;
+}
+
+static int (*choose(int i, int (*fallback)(int, int)))(int, int) {
+  return i < 3 ? pick(i) : fallback;
+}
+
+int main() {
+  int (*op)(int, int) = pick(1);
+  return pick(0)(3, 4) + op(10, 3) + choose(2, add)(2, 5) + choose(7, sub)(9, 1);
+}
@@ -0,0 +1,26 @@
+int counter;
+long total = 100;
+char flag = -1;
+unsigned char mask = 256 + 15;
+int limit = (3 + 4) * 2 - (1 << 2);
+int table[5];
+struct point {
+  int x;
+  long y;
+} origin;
+int *cursor;
+
+int sum() {
+  return table[0] + table[4] + origin.x + origin.y / 10 + flag;
+}
+
+int main() {
+  counter = counter + 3;
+  counter = counter + 4;
+  table[0] = counter;
+  table[4] = limit;
+  origin.y = total;
+  cursor = &table[4];
+  *cursor = *cursor + mask;
+  return sum();
+}
@@ -0,0 +1,6 @@
+#ifndef MACRO_H
+#define MACRO_H
+
+#define TWICE(x) ((x) + (x))
+
+#endif
@@ -0,0 +1,35 @@
+struct P { int x; char c; long y; };
+struct Q { struct P p; int arr[3]; };
+int g1 = 5, g2[4] = {1, 2}, g3[] = {7, 8, 9};
+struct P gp = { .y = 11, .x = 3 };
+struct Q gq = { {1, 'a', 2}, {[2] = 30, [0] = 10} };
+char gs[] = "hi";
+char gs2[8] = "abc";
+int gm[2][3] = {1, 2, 3, 4};
+union U { char c; int i; } gu = { 65 };
+int main() {
+  int a = 1, *b = &a, c[3] = {1, 2, 3};
+  int d[5] = {[3] = 4, 5};
+  struct P p = {1, 2, 3};
+  struct P p2 = p;
+  struct Q q = { .p.y = 6, .arr = {7, 8} };
+  struct Q q2 = { 1, 2, 3, 4, 5 };
+  char s[] = "xyz";
+  int m[2][2] = {{1}, {2, 3}};
+  long sum = 0;
+  int z[10] = {0};
+  struct P ps[2] = { p, {.c = 9} };
+  *b = *b + 1;
+  sum += a + c[0] + c[1] + c[2];
+  sum += d[0] + d[3] + d[4];
+  sum += p.x + p.c + p.y + p2.x + p2.y;
+  sum += q.p.x + q.p.y + q.arr[0] + q.arr[1] + q.arr[2];
+  sum += q2.p.x + q2.p.c + q2.p.y + q2.arr[0] + q2.arr[1];
+  sum += s[0] - 'x' + s[2] - 'z' + s[3] + sizeof(s);
+  sum += m[0][0] + m[0][1] + m[1][0] + m[1][1];
+  sum += z[9] + ps[0].y + ps[1].c + ps[1].x;
+  sum += g1 + g2[1] + g2[3] + g3[2] + sizeof(g3);
+  sum += gp.x + gp.y + gq.p.c + gq.arr[0] + gq.arr[1] + gq.arr[2];
+  sum += gs[1] + sizeof(gs) + gs2[2] + gs2[7] + gm[1][0] + gm[1][2] + gu.c;
+  return sum % 256;
+}
@@ -0,0 +1,23 @@
+long h(unsigned x) { return x; }
+
+long store_and_compare() {
+  long values[2];
+  long *p = values;
+  *p = 68719476736;
+  values[1] = -68719476736;
+  return (values[0] >> 36) + (values[1] >> 36 == -1) * 2;
+}
+
+int main() {
+  long big = 1099511627776;
+  int total = big >> 40;                           // 1
+  total = total + (h(-1) == 4294967295) * 2;       // 3
+  total = total + (0xff == 255) + (010 == 8);      // 5
+  total = total + (sizeof(1u) == 4) + (sizeof(1L) == 8) + (sizeof(0xffffffff) == 4); // 8
+  total = total + (-1 > 0u) + (1u - 2 > 0);        // 10
+  total = total + (0xffffffffffffffff == -1);      // 11
+  long m = 0x7fffffffffffffffL;
+  total = total + (m > 4294967296) * 10;           // 21
+  total = total + store_and_compare() * 10;        // 51
+  return total;
+}
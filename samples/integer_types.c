@@ -0,0 +1,27 @@
+int main() {
+  char c;
+  unsigned char uc;
+  short s;
+  unsigned int u;
+  long long ll;
+  int sum;
+
+  // 範囲外の値は型の幅で切り詰められる
+  c = 127;
+  c++;
+  uc = 255;
+  uc += 2;
+  s = 40000;
+
+  // 符号なしの比較/除算/シフト
+  u = 0;
+  u = u - 1;
+  sum = 0;
+  if (u > 5)
+    sum += 1;
+  if (-1 < u)
+    sum += 100;
+  ll = u / 16 + (u >> 28);
+
+  return sum + c + uc + (s == -25536) * 10 + ll % 100 + sizeof(c) + sizeof(s) + sizeof(u) + sizeof(ll);
+}
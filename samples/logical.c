@@ -0,0 +1,12 @@
+int main() {
+  int i;
+  int n;
+  n = 0;
+  i = 0;
+  while (i != 20) {
+    if (i > 5 && i < 10 || i == 1)
+      n = n + 1;
+    i = i + 1;
+  }
+  return n * 2 + !0 + !n + (0 || 3) + (2 && 0);
+}
@@ -0,0 +1,32 @@
+#include "include/macro.h"
+#include "include/macro.h"
+
+#define SQUARE(x) ((x) * (x))
+#define CAT(a, b) a ## b
+
+#ifdef __STDC__
+#define VALUE 3
+#else
+#define VALUE 100
+#endif
+
+// 短絡評価で読み飛ばす側の0除算はエラーにしない
+#if defined(DIVISOR) && 10 / DIVISOR > 1
+#error DIVISOR is not defined
+#endif
+
+// 符号なしの値と比べると,-1 は符号なしに変換される
+#if -1 > 0u && 18446744073709551615u > 0
+#define UNSIGNED_PROMOTION 1
+#endif
+
+int main() {
+  int CAT(va, lue);
+  value = SQUARE(VALUE) + TWICE(2);
+#if VALUE * 2 == 6 && defined(TWICE) && UNSIGNED_PROMOTION
+  value = value + 1;
+#elif 1
+  value = 0;
+#endif
+  return value;
+}
@@ -0,0 +1,7 @@
+int main() {
+  int a;
+  int b;
+  a = 47;
+  b = 5;
+  return a % b + (0 - a) % b + a % (b + 2) * 10 + 17 % 4;
+}
@@ -0,0 +1,21 @@
+int main() {
+  int x;
+  int y;
+  int *p;
+  int *q;
+  int **pp;
+  x = 3;
+  y = 4;
+  p = &x;
+  q = &y;
+  pp = &p;
+
+  // *p を介した読み書き
+  *p = *p * 10;
+  *q += *p;
+  (*q)++;
+  **pp = **pp + 1;
+
+  // ポインタ同士の差は要素数
+  return x + y + ((p + 5) - p) + (&*q == q);
+}
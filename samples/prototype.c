@@ -0,0 +1,24 @@
+int add(int, int);
+char narrow(char c);
+long scale(long v, int factor);
+extern int base;
+
+int main(void) {
+  int x;
+  int y;
+  x = add(3, 4);
+  y = narrow(300);
+  x = scale(x, y);
+  return x - base;
+}
+
+int add(int a, int b) {
+  return a + b;
+}
+char narrow(char c) {
+  return c;
+}
+long scale(long v, int factor) {
+  return v * factor;
+}
+int base = 200;
@@ -0,0 +1,14 @@
+int main() {
+  int i;
+  int sum;
+  sum = 0;
+  for (i = 0; i < 10; i = i + 1) {
+    if (i <= 2)
+      sum = sum + i;
+    if (i > 7)
+      sum = sum + i;
+    if (i >= 9)
+      sum = sum + 1;
+  }
+  return sum == 21 + (sum != 21) * 100;
+}
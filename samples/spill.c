@@ -0,0 +1,22 @@
+int twice(int x);
+
+int main(void) {
+  int a;
+  int b;
+  int c;
+  int i;
+  int s;
+  a = 1;
+  b = 2;
+  c = 3;
+  s = 0;
+  // 右結合の深い木は,左辺の値を全て生かしたまま右辺を評価する
+  for (i = 0; i < 3; i++) {
+    s = s + (a + (b + (c + (i + (a * (b + (c * (twice(i) + (a - (b + (c + (i / (a + 1)))))))))))));
+  }
+  return s;
+}
+
+int twice(int x) {
+  return x * 2;
+}
@@ -0,0 +1,18 @@
+int main() {
+  char *msg;
+  char *p;
+  int sum;
+  int n;
+  msg = "Hello, " "world!\n";
+  sum = 0;
+  for (p = msg; *p; p++) {
+    if (*p == 'o') sum += 1;
+  }
+  sum += sizeof("abc") + sizeof "x" "yz";
+  sum += "\x41\102\t"[2] + '\\' - 'A';
+  p = "\0hidden";
+  sum += p[0] + p[1];
+  n = 0;
+  while (msg[n] != '\0') n++;
+  return sum + n;
+}
@@ -0,0 +1,57 @@
+struct node;
+
+struct point {
+  char tag;
+  int x;
+  int y;
+};
+
+struct node {
+  struct node *next;
+  long value;
+};
+
+int main() {
+  struct point p;
+  struct point q;
+  struct point *pp;
+  struct node a;
+  struct node b;
+  struct node *cur;
+  union {
+    char bytes[4];
+    int word;
+  } u;
+  struct {
+    int kind;
+    union {
+      int ival;
+      char cval;
+    };
+  } anon;
+  long sum;
+
+  // 構造体の代入はメンバごとコピーされる
+  p.tag = 1;
+  p.x = 3;
+  p.y = 4;
+  q = p;
+  pp = &q;
+  pp->y += 10;
+
+  // 自己参照する構造体
+  a.value = 5;
+  b.value = 7;
+  a.next = &b;
+  b.next = 0;
+  sum = 0;
+  for (cur = &a; cur; cur = cur->next)
+    sum += cur->value;
+
+  // 共用体のメンバは同じ領域を共有する
+  u.word = 0;
+  u.bytes[1] = 1;
+  anon.ival = 258;
+
+  return p.x + q.y + q.tag + sum + (u.word >> 8) + anon.cval + sizeof(struct point) + sizeof(u);
+}
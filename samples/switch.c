@@ -0,0 +1,28 @@
+int main() {
+  int x;
+  int sum;
+  char c;
+  sum = 0;
+  for (x = -2; x < 9; x++) {
+    switch (x) {
+      case -2: sum += 1;
+      case -1: sum += 2;
+      case 0: sum += 4;
+      case 3: sum += 8;
+      case 5: sum += 16;
+      default: sum += 0;
+    }
+    switch (x) {
+      case 100: sum += 3;
+      case 7: sum += 1;
+    }
+  }
+  c = 'b';
+  switch (c) {
+    default:
+      sum += 100;
+    case 'a':
+      sum += 1;
+  }
+  return sum;
+}
@@ -0,0 +1,33 @@
+struct s {
+  int a;
+};
+
+enum color { RED, GREEN };
+
+struct s global;
+
+int inner_size() {
+  // ブロック内で定義したタグは,ファイルスコープの struct s を隠す
+  struct s {
+    long a;
+    long b;
+  } local;
+  return sizeof(local);
+}
+
+int main() {
+  int sum;
+  enum color c;
+  global.a = 3;
+  sum = inner_size();
+  {
+    struct s {
+      char x;
+    } t;
+    t.x = 2;
+    sum += sizeof(t) + t.x;
+  }
+  // ブロックを抜けると,外側の struct s に戻る
+  c = GREEN;
+  return sum + sizeof(struct s) + global.a + c;
+}
@@ -0,0 +1,51 @@
+#include <stdarg.h>
+
+long sum(int count, ...) {
+  va_list ap;
+  long total = 0;
+  int i;
+  va_start(ap, count);
+  for (i = 0; i < count; i++) {
+    total = total + va_arg(ap, int);
+  }
+  va_end(ap);
+  return total;
+}
+
+long vweigh(int n, va_list ap) {
+  long total = 0;
+  int i;
+  for (i = 1; i <= n; i++) {
+    total = total + i * va_arg(ap, long);
+  }
+  return total;
+}
+
+long weigh(int n, ...) {
+  va_list ap;
+  long r;
+  va_start(ap, n);
+  r = vweigh(n, ap);
+  va_end(ap);
+  return r;
+}
+
+int first_char(int a, int b, int c, int d, int e, int f, int g, ...) {
+  va_list ap;
+  char *s;
+  va_start(ap, g);
+  s = va_arg(ap, char *);
+  va_end(ap);
+  return s[0] + a + g;
+}
+
+int main(void) {
+  long a = sum(3, 1, 2, 3);
+  long b = sum(10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10);
+  long one = 1;
+  long two = 2;
+  long c = weigh(8, one, one, one, one, one, one, one, two);
+  int d = first_char(1, 2, 3, 4, 5, 6, 7, "A");
+  long e = sum(3, one && two, one || 0, 5);
+  return a + b + c + d + e - 100;
+}
@@ -0,0 +1,50 @@
+typedef struct Node {
+  int value;
+  struct Node *next;
+} Node;
+
+char heap[256];
+int used;
+
+void *alloc(long size) {
+  void *p = heap + used;
+  used = used + size;
+  return p;
+}
+
+int compare(const void *a, const void *b) {
+  const int *x = a;
+  const int *y = b;
+  return *x - *y;
+}
+
+void sort(void *base, int n, int (*cmp)(const void *, const void *)) {
+  int *arr = base;
+  for (int i = 0; i < n; i++) {
+    for (int j = i + 1; j < n; j++) {
+      if (cmp(arr + i, arr + j) > 0) {
+        int t = arr[i];
+        arr[i] = arr[j];
+        arr[j] = t;
+      }
+    }
+  }
+}
+
+int *as_int(void *p) { return p; }
+
+int main() {
+  Node *n = alloc(sizeof(Node));
+  n->value = 3;
+  Node *m;
+  m = alloc(sizeof(Node));
+  m->value = 4;
+  n->next = m;
+
+  int a[4] = {7, 2, 9, 1};
+  sort(a, 4, compare);
+
+  void *v = n;
+  int same = v == n && n == v && as_int(v) == &n->value;
+  return n->next->value * 10 + n->value + a[0] * 100 + a[3] + same;
+}
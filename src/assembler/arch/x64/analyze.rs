@@ -1,6 +1,6 @@
 use crate::assembler::arch::x64::assembler::X64Assembler;
 use crate::assembler::arch::x64::inst;
-use crate::elf::elf64::rela;
+use crate::error::{Error, ErrorKind, ErrorMsg};
 use inst::{
     inst_kind::{X64InstKind, X64OpeKind, X64Operand},
     inst_name::X64InstName,
@@ -18,22 +18,27 @@ pub enum OperandSize {
 
 impl X64Assembler {
     pub fn analyze(&mut self) {
+        let mut out_of_range = false;
         for (_name, symbol) in self.src_file.symbols_map.iter_mut() {
             for inst in symbol.insts.iter_mut() {
                 inst.analyze_operand();
 
-                // call(r/m64)以外
-                if let X64InstName::CALLRM64 = inst.name {
-                } else {
-                    continue;
+                // 即値が命令の即値の幅に収まらなければ,切り詰めずにエラーにする
+                if !inst.immediate_is_in_range() {
+                    let err = Error::new(
+                        ErrorKind::AsmParse,
+                        (0, 0),
+                        ErrorMsg::ImmediateOutOfRange(inst.immediate_value),
+                    );
+                    err.compile_error();
+                    out_of_range = true;
                 }
-                // もしcallなら再配置情報に加えておく
-                let label_name = inst.get_called_label();
-                self.src_file
-                    .relocations_map
-                    .insert(label_name, rela::Rela64::new(-4));
             }
         }
+
+        if out_of_range {
+            std::process::exit(1);
+        }
     }
 }
 
@@ -78,6 +83,31 @@ impl X64Instruction {
             X64InstKind::LABEL(_name) => (),
         }
     }
+    // 即値の範囲
+    // 64bitの演算では imm32 が符号拡張されるので,符号付きで収まる必要がある
+    // 64bitより狭い演算では,符号無しで収まる値も書ける (e.g. mov DWORD PTR -4[rbp], 4294967295)
+    pub fn immediate_is_in_range(&self) -> bool {
+        let value = self.immediate_value;
+        let fits = |bits: u32| -(1 << (bits - 1)) <= value && value < 1 << bits;
+        match &self.name {
+            X64InstName::ADDRM64IMM32
+            | X64InstName::SUBRM64IMM32
+            | X64InstName::IMULR64RM64IMM32
+            | X64InstName::CMPRM64IMM32
+            | X64InstName::MOVRM64IMM32
+            | X64InstName::ANDRM64IMM32
+            | X64InstName::ORRM64IMM32
+            | X64InstName::XORRM64IMM32 => i32::MIN as i128 <= value && value <= i32::MAX as i128,
+            X64InstName::MOVR64IMM64 => fits(64),
+            X64InstName::MOVRM32IMM32 => fits(32),
+            X64InstName::MOVRM16IMM16 => fits(16),
+            X64InstName::MOVRM8IMM8
+            | X64InstName::SHLRM64IMM8
+            | X64InstName::SARRM64IMM8
+            | X64InstName::SHRRM64IMM8 => fits(8),
+            _ => true,
+        }
+    }
     fn change_binary_opcode(
         name: &X64InstName,
         size: &OperandSize,
@@ -90,6 +120,17 @@ impl X64Instruction {
             X64InstName::MOV => Self::change_mov_opcode(size, src, dst),
             X64InstName::IMUL => Self::change_imul_opcode(size, src, dst),
             X64InstName::CMP => Self::change_cmp_opcode(size, src, dst),
+            X64InstName::MOVZX => Self::change_movzx_opcode(size, src, dst),
+            X64InstName::MOVSX | X64InstName::MOVSXD => {
+                Self::change_movsx_opcode(name, size, src, dst)
+            }
+            X64InstName::AND => Self::change_and_opcode(size, src, dst),
+            X64InstName::OR => Self::change_or_opcode(size, src, dst),
+            X64InstName::XOR => Self::change_xor_opcode(size, src, dst),
+            X64InstName::SHL | X64InstName::SAR | X64InstName::SHR => {
+                Self::change_shift_opcode(name, size, src, dst)
+            }
+            X64InstName::LEA => Self::change_lea_opcode(size, src, dst),
             // 何も変化させない
             _ => X64InstName::ADD,
         }
@@ -99,27 +140,42 @@ impl X64Instruction {
             X64InstName::CALL => Self::change_call_opcode(size, op),
             X64InstName::JMP => Self::change_jmp_opcode(size, op),
             X64InstName::JZ => Self::change_jz_opcode(size, op),
+            X64InstName::JNZ
+            | X64InstName::JL
+            | X64InstName::JLE
+            | X64InstName::JG
+            | X64InstName::JGE => Self::change_jcc_opcode(name, size, op),
+            X64InstName::SETE
+            | X64InstName::SETNE
+            | X64InstName::SETL
+            | X64InstName::SETLE
+            | X64InstName::SETG
+            | X64InstName::SETGE
+            | X64InstName::SETB
+            | X64InstName::SETBE
+            | X64InstName::SETA
+            | X64InstName::SETAE => Self::change_setcc_opcode(name, size, op),
             X64InstName::IDIV => Self::change_idiv_opcode(size, op),
+            X64InstName::DIV => Self::change_div_opcode(size, op),
             X64InstName::PUSH => Self::change_push_opcode(size, op),
             X64InstName::POP => Self::change_pop_opcode(size, op),
             X64InstName::NEG => Self::change_neg_opcode(size, op),
+            X64InstName::NOT => Self::change_not_opcode(size, op),
             // 何も変化させない
             _ => X64InstName::CALL,
         }
     }
-    fn get_called_label(&self) -> String {
-        match &self.kind {
-            X64InstKind::UNARY(op) => op.label_name(),
-            _ => String::new(),
-        }
-    }
 }
 
 impl X64Operand {
     pub fn check_operand_size(&self) -> OperandSize {
         match &self.kind {
             X64OpeKind::REG(name) => Self::check_register_name(name),
-            X64OpeKind::ADDRESSING(_offset, name) => Self::check_register_name(name),
+            // サイズ指定が無ければベースレジスタのサイズに合わせる
+            X64OpeKind::ADDRESSING(_offset, name) => match &self.ptr_size {
+                OperandSize::UNKNOWN => Self::check_register_name(name),
+                size => size.clone(),
+            },
             _ => OperandSize::UNKNOWN,
         }
     }
@@ -141,6 +197,9 @@ impl X64Operand {
             _ => false,
         }
     }
+    pub fn is_rip_relative(&self) -> bool {
+        matches!(self.kind, X64OpeKind::RIPRELATIVE(_))
+    }
     pub fn check_register_name(name: &String) -> OperandSize {
         match name.as_str() {
             // 64bit registers
@@ -162,11 +221,12 @@ impl X64Operand {
         }
     }
     pub fn check_used_register_is_expand(&self) -> bool {
-        if let X64OpeKind::REG(name) = &self.kind {
+        match &self.kind {
             // 2文字目が数字じゃなければ非拡張レジスタ,数字なら拡張レジスタ
-            (name.as_bytes()[1] as char).is_ascii_digit()
-        } else {
-            false
+            X64OpeKind::REG(name)
+            | X64OpeKind::ADDRESSING(_, name)
+            | X64OpeKind::LABELINDEXED(_, name, _) => (name.as_bytes()[1] as char).is_ascii_digit(),
+            _ => false,
         }
     }
     pub fn label_name(&self) -> String {
@@ -176,6 +236,11 @@ impl X64Operand {
             String::new()
         }
     }
+    // mov 以外の64bitの演算に,即値として直接書けるか
+    pub fn fits_in_imm32(&self) -> bool {
+        let value = self.immediate_value();
+        i32::MIN as i128 <= value && value <= i32::MAX as i128
+    }
     fn immediate_value(&self) -> i128 {
         if let X64OpeKind::INTEGER(val) = &self.kind {
             *val
@@ -192,14 +257,14 @@ impl X64Operand {
     }
     fn check_register_number(name: &String) -> usize {
         match name.as_str() {
-            "al" | "ax" | "eax" | "rax" | "r8" => 0,
-            "cl" | "cx" | "ecx" | "rcx" | "r9" => 1,
-            "dl" | "dx" | "edx" | "rdx" | "r10" => 2,
-            "bl" | "bx" | "ebx" | "rbx" | "r11" => 3,
-            "ah" | "sp" | "esp" | "rsp" | "r12" => 4,
-            "ch" | "bp" | "ebp" | "rbp" | "r13" => 5,
-            "dh" | "si" | "esi" | "rsi" | "r14" => 6,
-            "bh" | "di" | "edi" | "rdi" | "r15" => 7,
+            "al" | "ax" | "eax" | "rax" | "r8" | "r8d" | "r8w" | "r8b" => 0,
+            "cl" | "cx" | "ecx" | "rcx" | "r9" | "r9d" | "r9w" | "r9b" => 1,
+            "dl" | "dx" | "edx" | "rdx" | "r10" | "r10d" | "r10w" | "r10b" => 2,
+            "bl" | "bx" | "ebx" | "rbx" | "r11" | "r11d" | "r11w" | "r11b" => 3,
+            "ah" | "spl" | "sp" | "esp" | "rsp" | "r12" | "r12d" | "r12w" | "r12b" => 4,
+            "ch" | "bpl" | "bp" | "ebp" | "rbp" | "r13" | "r13d" | "r13w" | "r13b" => 5,
+            "dh" | "sil" | "si" | "esi" | "rsi" | "r14" | "r14d" | "r14w" | "r14b" => 6,
+            "bh" | "dil" | "di" | "edi" | "rdi" | "r15" | "r15d" | "r15w" | "r15b" => 7,
             _ => 0,
         }
     }
@@ -207,6 +272,7 @@ impl X64Operand {
         match &self.kind {
             X64OpeKind::REG(name) => Self::check_register_number(name),
             X64OpeKind::ADDRESSING(_offset, name) => Self::check_register_number(name),
+            X64OpeKind::LABELINDEXED(_label, index, _scale) => Self::check_register_number(index),
             _ => 0,
         }
     }
@@ -237,8 +303,9 @@ mod analyze_tests {
         //   ret
         let mut assembler = preprocess(".global main\nmain:\n  call foo\n  add rax, 10\n  ret\n");
         assembler.analyze();
+        assembler.codegen();
 
-        assert!(assembler.src_file.relocations_map.contains_key("foo"));
+        assert_eq!("foo", assembler.src_file.text_relocations[0].0);
     }
 
     #[test]
@@ -268,6 +335,27 @@ mod analyze_tests {
             }
         }
     }
+    #[test]
+    fn test_immediate_range() {
+        let mut assembler = preprocess(
+            "main:\n  mov r10, 1099511627776\n  mov QWORD PTR -8[rbp], 1099511627776\n  cmp r10, 4294967295\n  cmp r10, -2147483648\n  mov DWORD PTR -4[rbp], 4294967295\n  mov BYTE PTR -1[rbp], 256\n",
+        );
+        let symbol = assembler.src_file.symbols_map.get_mut("main").unwrap();
+        for inst in symbol.insts.iter_mut() {
+            inst.analyze_operand();
+        }
+
+        // レジスタへの mov だけは imm64 を取れる
+        let insts = &symbol.insts;
+        assert_eq!(X64InstName::MOVR64IMM64, insts[0].name);
+        assert_eq!(X64InstName::MOVRM64IMM32, insts[1].name);
+        let in_range: Vec<bool> = insts
+            .iter()
+            .map(|inst| inst.immediate_is_in_range())
+            .collect();
+        assert_eq!(vec![true, false, false, true, true, false], in_range);
+    }
+
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
@@ -37,27 +37,64 @@ pub enum AsmTokenKind {
 
     // 命令
     // AT&T記法
-    MOVQ,  // movq命令
-    ADDQ,  // addq命令
-    CLTD,  // cltd命令
-    SUBQ,  // subq命令
-    IMULQ, // imulq命令
-    IDIVQ, // idivq命令
-    NEGQ,  // negq命令
+    MOVQ,   // movq命令
+    ADDQ,   // addq命令
+    CLTD,   // cltd命令
+    SUBQ,   // subq命令
+    IMULQ,  // imulq命令
+    IDIVQ,  // idivq命令
+    NEGQ,   // negq命令
+    CMPQ,   // cmpq命令
+    MOVZBQ, // movzbq命令
+    ANDQ,   // andq命令
+    ORQ,    // orq命令
+    XORQ,   // xorq命令
+    NOTQ,   // notq命令
+    SHLQ,   // shlq命令
+    SARQ,   // sarq命令
+    SHRQ,   // shrq命令
+    LEAQ,   // leaq命令
 
     // intel記法
-    NEG,  // neg命令
-    MOV,  // mov命令
-    ADD,  // add命令
-    CMP,  // cmp命令
-    CQO,  // cqo命令
-    SUB,  // sub命令
-    IMUL, // imul命令
-    IDIV, // idiv命令
+    NEG,    // neg命令
+    MOV,    // mov命令
+    ADD,    // add命令
+    CMP,    // cmp命令
+    CQO,    // cqo命令
+    SUB,    // sub命令
+    IMUL,   // imul命令
+    IDIV,   // idiv命令
+    MOVZX,  // movzx命令
+    AND,    // and命令
+    OR,     // or命令
+    XOR,    // xor命令
+    NOT,    // not命令
+    SHL,    // shl命令
+    SAR,    // sar命令
+    SHR,    // shr命令
+    LEA,    // lea命令
+    MOVSX,  // movsx命令
+    MOVSXD, // movsxd命令
+    DIV,    // div命令
 
     // 汎用記法
     JMP,     // jmp命令
     JZ,      // jz命令
+    JNZ,     // jnz命令
+    JL,      // jl命令
+    JLE,     // jle命令
+    JG,      // jg命令
+    JGE,     // jge命令
+    SETE,    // sete命令
+    SETNE,   // setne命令
+    SETL,    // setl命令
+    SETLE,   // setle命令
+    SETG,    // setg命令
+    SETGE,   // setge命令
+    SETB,    // setb命令
+    SETBE,   // setbe命令
+    SETA,    // seta命令
+    SETAE,   // setae命令
     CALL,    // call命令
     RET,     // ret命令
     SYSCALL, // syscall命令
@@ -66,11 +103,15 @@ pub enum AsmTokenKind {
 
     // その他
     QWORD,
+    DWORD,
+    WORD,
+    BYTE,
     PTR,
     COMMA,             // , 記号
     LBRACKET,          // [ 記号
     RBRACKET,          // [ 記号
     MINUS,             // - 記号
+    ASTERISK,          // * 記号
     LABEL(String),     // ラベル
     INTEGER(i128),     // 整数
     DIRECTIVE(String), // ディレクティブ
@@ -87,13 +128,40 @@ impl AsmTokenKind {
             Self::SUB | Self::SUBQ => X64InstName::SUB,
             Self::JMP => X64InstName::JMP,
             Self::JZ => X64InstName::JZ,
+            Self::JNZ => X64InstName::JNZ,
+            Self::JL => X64InstName::JL,
+            Self::JLE => X64InstName::JLE,
+            Self::JG => X64InstName::JG,
+            Self::JGE => X64InstName::JGE,
+            Self::SETE => X64InstName::SETE,
+            Self::SETNE => X64InstName::SETNE,
+            Self::SETL => X64InstName::SETL,
+            Self::SETLE => X64InstName::SETLE,
+            Self::SETG => X64InstName::SETG,
+            Self::SETGE => X64InstName::SETGE,
+            Self::SETB => X64InstName::SETB,
+            Self::SETBE => X64InstName::SETBE,
+            Self::SETA => X64InstName::SETA,
+            Self::SETAE => X64InstName::SETAE,
+            Self::MOVZX | Self::MOVZBQ => X64InstName::MOVZX,
+            Self::MOVSX => X64InstName::MOVSX,
+            Self::MOVSXD => X64InstName::MOVSXD,
             Self::NEG | Self::NEGQ => X64InstName::NEG,
+            Self::NOT | Self::NOTQ => X64InstName::NOT,
+            Self::AND | Self::ANDQ => X64InstName::AND,
+            Self::OR | Self::ORQ => X64InstName::OR,
+            Self::XOR | Self::XORQ => X64InstName::XOR,
+            Self::SHL | Self::SHLQ => X64InstName::SHL,
+            Self::SAR | Self::SARQ => X64InstName::SAR,
+            Self::SHR | Self::SHRQ => X64InstName::SHR,
+            Self::LEA | Self::LEAQ => X64InstName::LEA,
             Self::IMUL | Self::IMULQ => X64InstName::IMUL,
             Self::IDIV | Self::IDIVQ => X64InstName::IDIV,
+            Self::DIV => X64InstName::DIV,
             Self::CALL => X64InstName::CALL,
             Self::SYSCALL => X64InstName::SYSCALL,
             Self::RET => X64InstName::RET,
-            Self::CMP => X64InstName::CMP,
+            Self::CMP | Self::CMPQ => X64InstName::CMP,
             Self::CQO | Self::CLTD => X64InstName::CQO,
             Self::MOV | Self::MOVQ => X64InstName::MOV,
             Self::PUSH => X64InstName::PUSH,
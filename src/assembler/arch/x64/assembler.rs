@@ -1,5 +1,18 @@
 use crate::assembler::arch::x64::asmtoken;
 use crate::assembler::arch::x64::file::X64AssemblyFile;
+use crate::assembler::arch::x64::symbol::X64DataSymbol;
+use crate::elf::elf64::rela;
+
+use std::collections::BTreeMap;
+
+// パース中の命令/データを配置するセクション
+#[derive(PartialEq, Debug, Clone)]
+pub enum X64Section {
+    TEXT,
+    RODATA,
+    DATA,
+    BSS,
+}
 
 pub struct X64Assembler {
     pub src_file: X64AssemblyFile,
@@ -10,6 +23,11 @@ pub struct X64Assembler {
     pub cur_token: usize,
     pub next_token: usize,
 
+    // データ定義用
+    pub cur_section: X64Section,
+    pub cur_data_symbol: String,
+    pub data_alignment: usize, // .align で指定された,次のデータシンボルの境界
+
     // コード生成用
     pub all_bytes: u64,
 }
@@ -21,6 +39,9 @@ impl X64Assembler {
             tokens: Vec::new(),
             cur_token: 0,
             next_token: 1,
+            cur_section: X64Section::TEXT,
+            cur_data_symbol: String::new(),
+            data_alignment: 1,
             all_bytes: 0,
         }
     }
@@ -33,10 +54,70 @@ impl X64Assembler {
         }
     }
     pub fn setup_relocations(&mut self) {
-        for (sym_idx, (sym_name, _symbol)) in self.src_file.symbols_map.iter().enumerate() {
-            if let Some(rela) = self.src_file.relocations_map.get_mut(sym_name) {
-                rela.r_info = (((sym_idx + 1) << 32) + 1) as u64;
+        // 定義の見つからないシンボルは未定義シンボルとして登録し,リンク時に解決する
+        // .data 中でアドレスを置くシンボル(関数ポインタの初期値など)も同様
+        let data_label_names: Vec<&String> = self
+            .src_file
+            .rodata_map
+            .values()
+            .chain(self.src_file.data_map.values())
+            .flat_map(|data_symbol| data_symbol.label_refs.iter())
+            .map(|(_ref_offset, label_name, _addend)| label_name)
+            .filter(|label_name| !self.src_file.text_labels.contains_key(*label_name))
+            .collect();
+        let undefined_names: Vec<String> = self
+            .src_file
+            .text_relocations
+            .iter()
+            .map(|(sym_name, _rela)| sym_name)
+            .chain(data_label_names)
+            .filter(|sym_name| !self.src_file.symbol_names().contains(sym_name))
+            .cloned()
+            .collect();
+        self.src_file.undefined_symbols.extend(undefined_names);
+
+        // .text 中の参照は,データシンボルも含めたシンボルテーブル上の位置で結びつける
+        let symbol_names: Vec<String> = self
+            .src_file
+            .symbol_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for (sym_name, rela) in self.src_file.text_relocations.iter_mut() {
+            if let Some(sym_idx) = symbol_names.iter().position(|name| name == sym_name) {
+                rela.r_info += ((sym_idx + 1) << 32) as u64;
+            }
+        }
+
+        self.src_file.rodata_relocations =
+            self.label_relocations(&self.src_file.rodata_map, &symbol_names);
+        self.src_file.data_relocations =
+            self.label_relocations(&self.src_file.data_map, &symbol_names);
+    }
+    // .rodata/.data 中のラベル参照
+    // ローカルラベルはシンボルテーブルに無いので,含まれる関数シンボルからのオフセットで表す
+    fn label_relocations(
+        &self,
+        data_map: &BTreeMap<String, X64DataSymbol>,
+        symbol_names: &[String],
+    ) -> Vec<rela::Rela64> {
+        let mut relocations = Vec::new();
+        let offsets = X64DataSymbol::layout(data_map);
+        for (data_symbol, offset) in data_map.values().zip(offsets.iter()) {
+            for (ref_offset, label_name, label_addend) in data_symbol.label_refs.iter() {
+                let (target_name, addend) = match self.src_file.text_labels.get(label_name) {
+                    Some((symbol_name, label_pos)) => (symbol_name, *label_pos as i64),
+                    None => (label_name, 0),
+                };
+                let addend = addend + label_addend;
+                if let Some(sym_idx) = symbol_names.iter().position(|name| name == target_name) {
+                    let mut rela = rela::Rela64::new(addend);
+                    rela.r_offset = (offset + ref_offset) as u64;
+                    rela.r_info = (((sym_idx + 1) << 32) as u64) + rela::R_X86_64_64;
+                    relocations.push(rela);
+                }
             }
         }
+        relocations
     }
 }
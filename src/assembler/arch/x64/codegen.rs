@@ -1,56 +1,67 @@
 use crate::assembler::arch::x64::assembler::X64Assembler;
-use crate::assembler::arch::x64::inst::{inst_kind::X64InstKind, inst_name::X64InstName};
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64OpeKind, X64Operand},
+    inst_name::X64InstName,
+};
+
+use crate::elf::elf64::rela::{self, Rela64};
 
 use std::collections::BTreeMap;
 
+// 16bitオペランドを扱う時のプレフィックス
+pub const OPERAND_SIZE_PREFIX: u8 = 0x66;
+
 pub const REX_PREFIX_BASE: u8 = 0x40;
 pub const REX_PREFIX_WBIT: u8 = 0x08;
 pub const REX_PREFIX_RBIT: u8 = 0x04;
-// pub const REX_PREFIX_XBIT: u8 = 0x02;
+pub const REX_PREFIX_XBIT: u8 = 0x02;
 pub const REX_PREFIX_BBIT: u8 = 0x01;
 
 pub const MODRM_REGISTER_REGISTER: u8 = 0xc0;
 pub const MODRM_REGISTER_DISPLACEMENT8: u8 = 0x40;
+pub const MODRM_REGISTER_DISPLACEMENT32: u8 = 0x80;
+pub const MODRM_REGISTER_INDIRECT: u8 = 0x00;
+// mod=00 で r/m=101 の時は [rip + disp32] を表す
+pub const MODRM_RM_RIP_RELATIVE: u8 = 0x05;
+
+// rsp/r12をベースにする時に必要なSIB(インデックス無し)
+pub const SIB_BASE_ONLY: u8 = 0x24;
+// mod=00 で r/m=100 の時はSIBが続く
+pub const MODRM_RM_SIB: u8 = 0x04;
+// SIBの base=101 (mod=00) はベース無しで disp32 を足す
+pub const SIB_NO_BASE: u8 = 0x05;
 impl X64Assembler {
     pub fn codegen(&mut self) {
-        // BTreeMap<LabelName, (CodeIndex, Offset)>
-        let mut jump_map: BTreeMap<String, (usize, usize)> = BTreeMap::new();
-        for (_name, symbol) in self.src_file.symbols_map.iter_mut() {
+        for (symbol_name, symbol) in self.src_file.symbols_map.iter_mut() {
             // コードの初期化
             let mut codes: Vec<u8> = Vec::new();
 
+            // ラベル名 -> シンボル内でのオフセット
+            let mut label_map: BTreeMap<String, usize> = BTreeMap::new();
+            // (ジャンプ先のラベル名, rel32を書き込む位置)
+            let mut jump_fixups: Vec<(String, usize)> = Vec::new();
+
             // 各命令を機械語に変換
             for inst in symbol.insts.iter() {
                 match &inst.name {
                     X64InstName::LABEL => {
                         if let X64InstKind::LABEL(name) = &inst.kind {
-                            // jump系命令がラベルの前に存在した場合
-                            if let Some(tup) = jump_map.get_mut(name) {
-                                // jump系命令の位置 - 現在位置 - 1 => 相対オフセット
-                                tup.1 = codes.len() - tup.1 - 1;
-                                continue;
-                            }
-
-                            // ラベルがjump系命令の前に存在した場合
-                            jump_map.insert(name.to_string(), (0, codes.len()));
+                            label_map.insert(name.to_string(), codes.len());
                         }
                     }
-                    X64InstName::JZREL32 => {
+                    X64InstName::JMPREL32
+                    | X64InstName::JZREL32
+                    | X64InstName::JNZREL32
+                    | X64InstName::JLREL32
+                    | X64InstName::JLEREL32
+                    | X64InstName::JGREL32
+                    | X64InstName::JGEREL32 => {
                         // opcode
-                        codes.push(0x0f);
-                        codes.push(0x84);
+                        codes.append(&mut Self::jump_opcode(&inst.name));
+
+                        // オフセットはラベルの位置が全て分かってから埋める
                         if let X64InstKind::UNARY(op) = &inst.kind {
-                            let label_name = op.label_name();
-
-                            if let Some(tup) = jump_map.get_mut(&label_name) {
-                                // ラベルがjump系命令の前に存在した場合
-                                tup.0 = codes.len();
-                                tup.1 = !(codes.len() + 4 - tup.1) + 1;
-                            } else {
-                                // jump系命令がラベルの前に存在した場合
-                                jump_map
-                                    .insert(label_name.to_string(), (codes.len(), codes.len() + 3));
-                            }
+                            jump_fixups.push((op.label_name(), codes.len()));
                         }
 
                         // immediate-value
@@ -58,85 +69,128 @@ impl X64Assembler {
                             codes.push(*b);
                         }
                     }
-                    X64InstName::JMPREL32 => {
-                        // opcode
-                        codes.push(0xe9);
+                    X64InstName::JMPRM64 => {
+                        Self::generate_jmprm64_inst(&mut codes, inst);
+
+                        // テーブルのアドレス(命令の末尾4バイト)を再配置情報に加える
+                        // disp32 は符号拡張されるので R_X86_64_32S
                         if let X64InstKind::UNARY(op) = &inst.kind {
-                            let label_name = op.label_name();
-
-                            if let Some(tup) = jump_map.get_mut(&label_name) {
-                                // ラベルがjump系命令の前に存在した場合
-                                tup.0 = codes.len();
-                                tup.1 = !(codes.len() + 4 - tup.1) + 1;
-                            } else {
-                                // jump系命令がラベルの前に存在した場合
-                                jump_map
-                                    .insert(label_name.to_string(), (codes.len(), codes.len() + 3));
+                            if let X64OpeKind::LABELINDEXED(table_name, _, _) = &op.kind {
+                                let mut rela = Rela64::new(0);
+                                rela.r_offset = self.all_bytes + codes.len() as u64 - 4;
+                                rela.r_info = rela::R_X86_64_32S;
+                                self.src_file
+                                    .text_relocations
+                                    .push((table_name.to_string(), rela));
                             }
                         }
-
-                        // immediate-value
-                        for b in (0x00 as u32).to_le_bytes().to_vec().iter() {
-                            codes.push(*b);
-                        }
                     }
                     // add
                     X64InstName::ADDRM64R64 => Self::generate_addrm64r64_inst(&mut codes, &inst),
                     X64InstName::ADDRM64IMM32 => {
                         Self::generate_addrm64imm32_inst(&mut codes, &inst)
                     }
+                    X64InstName::ADDR64RM64 => Self::generate_addr64rm64_inst(&mut codes, inst),
                     // call
-                    X64InstName::CALLRM64 => {
-                        /* ただのcallではなく,raxにアドレス即値をmovしてからcallするやつに変換 */
-                        // mov-rex-prefix
-                        codes.push(0x48);
-
-                        // mov-opcode
-                        codes.push(0xc7);
+                    X64InstName::CALLRM64 => Self::generate_callrm64_inst(&mut codes, inst),
+                    X64InstName::CALLREL32 => {
+                        Self::generate_callrel32_inst(&mut codes);
 
-                        // mov-modrm
-                        codes.push(0xc0);
+                        // 呼び出し先が他のファイルにあってもよいように,呼び出し箇所ごとに再配置する
+                        // rel32 は次の命令の先頭からの差分なので,addendで4バイト分を引く
                         if let X64InstKind::UNARY(op) = &inst.kind {
-                            let label_name = op.label_name();
-                            if let Some(rela) = self.src_file.relocations_map.get_mut(&label_name) {
-                                rela.r_offset = self.all_bytes + codes.len() as u64;
-                            }
+                            let mut rela = Rela64::new(-4);
+                            rela.r_offset = self.all_bytes + codes.len() as u64 - 4;
+                            rela.r_info = rela::R_X86_64_PLT32;
+                            self.src_file.text_relocations.push((op.label_name(), rela));
                         }
-                        //  TODO: 関数実装したときに追加
-                        //  if let None = self.src_file.symbols_map.get(label_name) {
-                        //     self.src_file.symbols_map.insert(label_name, new_global_symbol());
-                        //  }
-                        // mov-immediate-value
-                        for b in (0 as u32).to_le_bytes().to_vec().iter() {
-                            codes.push(*b);
-                        }
-
-                        Self::generate_callrm64_inst(&mut codes, &inst);
                     }
                     X64InstName::MOVRM64R64 => Self::generate_movrm64r64_inst(&mut codes, &inst),
                     X64InstName::MOVRM64IMM32 => {
                         Self::generate_movrm64imm32_inst(&mut codes, &inst)
                     }
+                    X64InstName::MOVR64IMM64 => Self::generate_movr64imm64_inst(&mut codes, inst),
                     X64InstName::MOVR64RM64 => Self::generate_movr64rm64_inst(&mut codes, &inst),
+                    X64InstName::MOVR32RM32 => Self::generate_movr32rm32_inst(&mut codes, inst),
+                    X64InstName::MOVRM32R32 | X64InstName::MOVRM16R16 | X64InstName::MOVRM8R8 => {
+                        Self::generate_movrm_sized_r_inst(&mut codes, inst)
+                    }
+                    X64InstName::MOVRM32IMM32
+                    | X64InstName::MOVRM16IMM16
+                    | X64InstName::MOVRM8IMM8 => {
+                        Self::generate_movrm_sized_imm_inst(&mut codes, inst)
+                    }
+                    X64InstName::LEAR64M => {
+                        Self::generate_lear64m_inst(&mut codes, inst);
+
+                        // rip相対のディスプレースメント(命令の末尾4バイト)を再配置情報に加える
+                        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+                            if let X64OpeKind::RIPRELATIVE(label_name) = &src.kind {
+                                let mut rela = Rela64::new(-4);
+                                rela.r_offset = self.all_bytes + codes.len() as u64 - 4;
+                                rela.r_info = rela::R_X86_64_PC32;
+                                self.src_file
+                                    .text_relocations
+                                    .push((label_name.to_string(), rela));
+                            }
+                        }
+                    }
                     X64InstName::IMULR64RM64IMM32 => {
                         Self::generate_imulr64rm64imm32_inst(&mut codes, &inst)
                     }
                     X64InstName::IMULR64RM64 => Self::generate_imulr64rm64_inst(&mut codes, &inst),
                     X64InstName::IDIVRM64 => Self::generate_idivrm64_inst(&mut codes, &inst),
+                    X64InstName::DIVRM64 => Self::generate_divrm64_inst(&mut codes, inst),
                     X64InstName::SUBRM64R64 => Self::generate_subrm64r64_inst(&mut codes, &inst),
                     X64InstName::SUBRM64IMM32 => {
                         Self::generate_subrm64imm32_inst(&mut codes, &inst)
                     }
+                    X64InstName::SUBR64RM64 => Self::generate_subr64rm64_inst(&mut codes, inst),
                     X64InstName::CMPRM64R64 => Self::generate_cmprm64r64_inst(&mut codes, &inst),
                     X64InstName::CMPRM64IMM32 => {
                         Self::generate_cmprm64imm32_inst(&mut codes, &inst)
                     }
+                    X64InstName::CMPR64RM64 => Self::generate_cmpr64rm64_inst(&mut codes, inst),
+                    X64InstName::SETERM8
+                    | X64InstName::SETNERM8
+                    | X64InstName::SETLRM8
+                    | X64InstName::SETLERM8
+                    | X64InstName::SETGRM8
+                    | X64InstName::SETGERM8
+                    | X64InstName::SETBRM8
+                    | X64InstName::SETBERM8
+                    | X64InstName::SETARM8
+                    | X64InstName::SETAERM8 => Self::generate_setccrm8_inst(&mut codes, inst),
+                    X64InstName::MOVZXR64RM8 | X64InstName::MOVZXR64RM16 => {
+                        Self::generate_movzx_inst(&mut codes, inst)
+                    }
+                    X64InstName::MOVSXR64RM8
+                    | X64InstName::MOVSXR64RM16
+                    | X64InstName::MOVSXDR64RM32 => Self::generate_movsx_inst(&mut codes, inst),
                     X64InstName::CQO => Self::generate_cqo_inst(&mut codes, &inst),
                     X64InstName::RET => Self::generate_ret_inst(&mut codes, &inst),
                     X64InstName::SYSCALL => Self::generate_syscall_inst(&mut codes),
                     X64InstName::PUSHR64 => Self::generate_pushr64_inst(&mut codes, &inst),
                     X64InstName::POPR64 => Self::generate_popr64_inst(&mut codes, &inst),
                     X64InstName::NEGRM64 => Self::generate_negrm64_inst(&mut codes, &inst),
+                    X64InstName::NOTRM64 => Self::generate_notrm64_inst(&mut codes, inst),
+                    X64InstName::ANDRM64IMM32 => Self::generate_andrm64imm32_inst(&mut codes, inst),
+                    X64InstName::ANDRM64R64 => Self::generate_andrm64r64_inst(&mut codes, inst),
+                    X64InstName::ANDR64RM64 => Self::generate_andr64rm64_inst(&mut codes, inst),
+                    X64InstName::ORRM64IMM32 => Self::generate_orrm64imm32_inst(&mut codes, inst),
+                    X64InstName::ORRM64R64 => Self::generate_orrm64r64_inst(&mut codes, inst),
+                    X64InstName::ORR64RM64 => Self::generate_orr64rm64_inst(&mut codes, inst),
+                    X64InstName::XORRM64IMM32 => Self::generate_xorrm64imm32_inst(&mut codes, inst),
+                    X64InstName::XORRM64R64 => Self::generate_xorrm64r64_inst(&mut codes, inst),
+                    X64InstName::XORR64RM64 => Self::generate_xorr64rm64_inst(&mut codes, inst),
+                    X64InstName::SHLRM64IMM8
+                    | X64InstName::SARRM64IMM8
+                    | X64InstName::SHRRM64IMM8 => {
+                        Self::generate_shiftrm64imm8_inst(&mut codes, inst)
+                    }
+                    X64InstName::SHLRM64CL | X64InstName::SARRM64CL | X64InstName::SHRRM64CL => {
+                        Self::generate_shiftrm64cl_inst(&mut codes, inst)
+                    }
                     _ => {
                         eprintln!("not generate ... {:?}", inst.name);
                     }
@@ -144,34 +198,24 @@ impl X64Assembler {
             }
 
             // ジャンプ系命令のオフセットを解決する
-            for inst in symbol.insts.iter() {
-                match &inst.name {
-                    X64InstName::JMPREL32 => {
-                        if let X64InstKind::UNARY(op) = &inst.kind {
-                            let label_name = op.label_name();
-
-                            if let Some(tup) = jump_map.get(&label_name) {
-                                for (idx, b) in (tup.1 as u32).to_le_bytes().iter().enumerate() {
-                                    codes[idx + tup.0] = *b;
-                                }
-                            }
-                        }
+            // rel32は次の命令の先頭からの相対オフセット
+            for (label_name, patch_pos) in jump_fixups.iter() {
+                if let Some(label_pos) = label_map.get(label_name) {
+                    let rel = *label_pos as i32 - (*patch_pos + 4) as i32;
+                    for (idx, b) in rel.to_le_bytes().iter().enumerate() {
+                        codes[idx + patch_pos] = *b;
                     }
-                    X64InstName::JZREL32 => {
-                        if let X64InstKind::UNARY(op) = &inst.kind {
-                            let label_name = op.label_name();
-
-                            if let Some(tup) = jump_map.get(&label_name) {
-                                for (idx, b) in (tup.1 as u32).to_le_bytes().iter().enumerate() {
-                                    codes[idx + tup.0] = *b;
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
                 }
             }
 
+            // .rodata のジャンプテーブルから参照できるよう,ラベルの位置を残しておく
+            for (label_name, label_pos) in label_map.iter() {
+                self.src_file.text_labels.insert(
+                    label_name.to_string(),
+                    (symbol_name.to_string(), *label_pos as u64),
+                );
+            }
+
             // アラインメント調整
             let rest_bytes = codes.len() % 4;
             for _ in 0..(4 - rest_bytes) {
@@ -183,6 +227,18 @@ impl X64Assembler {
             symbol.codes = codes;
         }
     }
+    fn jump_opcode(name: &X64InstName) -> Vec<u8> {
+        match name {
+            X64InstName::JMPREL32 => vec![0xe9],
+            X64InstName::JZREL32 => vec![0x0f, 0x84],
+            X64InstName::JNZREL32 => vec![0x0f, 0x85],
+            X64InstName::JLREL32 => vec![0x0f, 0x8c],
+            X64InstName::JGEREL32 => vec![0x0f, 0x8d],
+            X64InstName::JLEREL32 => vec![0x0f, 0x8e],
+            X64InstName::JGREL32 => vec![0x0f, 0x8f],
+            _ => panic!("not a jump instruction -> {:?}", name),
+        }
+    }
     pub fn rex_prefix_rbit(cond: bool) -> u8 {
         if cond {
             REX_PREFIX_RBIT
@@ -190,6 +246,13 @@ impl X64Assembler {
             0
         }
     }
+    pub fn rex_prefix_xbit(cond: bool) -> u8 {
+        if cond {
+            REX_PREFIX_XBIT
+        } else {
+            0
+        }
+    }
     pub fn rex_prefix_bbit(cond: bool) -> u8 {
         if cond {
             REX_PREFIX_BBIT
@@ -197,12 +260,72 @@ impl X64Assembler {
             0
         }
     }
+    // REX.Wが要らない命令では,拡張レジスタを使う時だけREXを付加する
+    pub fn push_rex_prefix_if_needed(codes: &mut Vec<u8>, expanded_bits: u8) {
+        if expanded_bits != 0 {
+            codes.push(REX_PREFIX_BASE | expanded_bits);
+        }
+    }
     pub fn modrm_reg_field(reg_number: usize) -> u8 {
         (reg_number as u8) << 3
     }
     pub fn modrm_rm_field(reg_number: usize) -> u8 {
         reg_number as u8
     }
+    // r/mオペランドに応じてModR/M(とSIB,ディスプレースメント)を生成する.
+    // [reg] は mod=00 だが,rbp/r13 はディスプレースメント0で表す必要がある.
+    // ディスプレースメントが1バイトに収まらなければ4バイトで表す.
+    pub fn push_modrm(codes: &mut Vec<u8>, reg_field: u8, rm_regnumber: usize, rm: &X64Operand) {
+        let rm_field = Self::modrm_rm_field(rm_regnumber);
+        match &rm.kind {
+            X64OpeKind::ADDRESSING(offset, _name) => {
+                // -8[rbp] の offset は 8 として保持している
+                let displacement = -*offset;
+                let has_displacement = displacement != 0 || rm_field == 5;
+                let is_displacement8 =
+                    i8::MIN as i128 <= displacement && displacement <= i8::MAX as i128;
+                if !has_displacement {
+                    codes.push(MODRM_REGISTER_INDIRECT | reg_field | rm_field);
+                } else if is_displacement8 {
+                    codes.push(MODRM_REGISTER_DISPLACEMENT8 | reg_field | rm_field);
+                } else {
+                    codes.push(MODRM_REGISTER_DISPLACEMENT32 | reg_field | rm_field);
+                }
+                if rm_field == 4 {
+                    codes.push(SIB_BASE_ONLY);
+                }
+
+                if !has_displacement {
+                    return;
+                }
+                if is_displacement8 {
+                    codes.push(displacement as u8);
+                } else {
+                    for byte in (displacement as i32).to_le_bytes().iter() {
+                        codes.push(*byte);
+                    }
+                }
+            }
+            // [index * scale + disp32]
+            // ディスプレースメント(ラベルのアドレス)はリンク時に埋める
+            X64OpeKind::LABELINDEXED(_label, _index, scale) => {
+                codes.push(MODRM_REGISTER_INDIRECT | reg_field | MODRM_RM_SIB);
+                let scale_field = (scale.trailing_zeros() as u8) << 6;
+                codes.push(scale_field | Self::modrm_reg_field(rm_regnumber) | SIB_NO_BASE);
+                for byte in 0u32.to_le_bytes().iter() {
+                    codes.push(*byte);
+                }
+            }
+            // ディスプレースメントはリンク時に埋めるので0にしておく
+            X64OpeKind::RIPRELATIVE(_label) => {
+                codes.push(MODRM_REGISTER_INDIRECT | reg_field | MODRM_RM_RIP_RELATIVE);
+                for byte in 0u32.to_le_bytes().iter() {
+                    codes.push(*byte);
+                }
+            }
+            _ => codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -236,6 +359,130 @@ mod codegen_tests {
         }
     }
 
+    #[test]
+    fn test_codegen_with_jumps() {
+        // 0f 85 0a 00 00 00    jnz .L1
+        // e9 05 00 00 00       jmp .L1
+        // .L0:
+        // e9 fb ff ff ff       jmp .L0
+        // .L1:
+        // 0f 8c f5 ff ff ff    jl .L0
+        // c3                   ret
+        let expected_codes: Vec<u8> = vec![
+            0x0f, 0x85, 0x0a, 0x00, 0x00, 0x00, 0xe9, 0x05, 0x00, 0x00, 0x00, 0xe9, 0xfb, 0xff,
+            0xff, 0xff, 0x0f, 0x8c, 0xf5, 0xff, 0xff, 0xff, 0xc3,
+        ];
+
+        let mut assembler =
+            preprocess("main:\n  jnz .L1\n  jmp .L1\n.L0:\n  jmp .L0\n.L1:\n  jl .L0\n  ret\n");
+
+        assembler.codegen();
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(
+            expected_codes,
+            symbol.codes[..expected_codes.len()].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_codegen_with_bitwise_operations() {
+        // 49 81 e2 ff 00 00 00    and r10, 255
+        // 4d 09 da                or r10, r11
+        // 4c 33 55 f8             xor r10, -8[rbp]
+        // 49 f7 d2                not r10
+        let expected_codes: Vec<u8> = vec![
+            0x49, 0x81, 0xe2, 0xff, 0x00, 0x00, 0x00, 0x4d, 0x09, 0xda, 0x4c, 0x33, 0x55, 0xf8,
+            0x49, 0xf7, 0xd2,
+        ];
+
+        let mut assembler =
+            preprocess("main:\n  and r10, 255\n  or r10, r11\n  xor r10, -8[rbp]\n  not r10\n");
+
+        assembler.codegen();
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(
+            expected_codes,
+            symbol.codes[..expected_codes.len()].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_codegen_with_wide_immediates() {
+        // 49 ba 00 00 00 00 00 01 00 00 movabs r10, 0x10000000000
+        // 48 b8 00 00 00 00 ff ff ff ff movabs rax, 0xffffffff00000000
+        // c7 45 fc ff ff ff ff          mov DWORD PTR -4[rbp], 0xffffffff
+        let expected_codes: Vec<u8> = vec![
+            0x49, 0xba, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x48, 0xb8, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xc7, 0x45, 0xfc, 0xff, 0xff, 0xff, 0xff,
+        ];
+
+        let mut assembler = preprocess(
+            "main:\n  mov r10, 1099511627776\n  mov rax, -4294967296\n  mov DWORD PTR -4[rbp], 4294967295\n",
+        );
+
+        assembler.codegen();
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(
+            expected_codes,
+            symbol.codes[..expected_codes.len()].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_codegen_with_pointer_operations() {
+        // 4c 8d 55 f8             lea r10, -8[rbp]
+        // 4d 8b 1a                mov r11, [r10]
+        // 4d 89 1c 24             mov [r12], r11
+        // 49 c7 45 00 03 00 00 00 mov QWORD PTR [r13], 3
+        let expected_codes: Vec<u8> = vec![
+            0x4c, 0x8d, 0x55, 0xf8, 0x4d, 0x8b, 0x1a, 0x4d, 0x89, 0x1c, 0x24, 0x49, 0xc7, 0x45,
+            0x00, 0x03, 0x00, 0x00, 0x00,
+        ];
+
+        let mut assembler = preprocess(
+            "main:\n  lea r10, -8[rbp]\n  mov r11, [r10]\n  mov [r12], r11\n  mov QWORD PTR [r13], 3\n",
+        );
+
+        assembler.codegen();
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(
+            expected_codes,
+            symbol.codes[..expected_codes.len()].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_codegen_with_jump_table() {
+        // 42 ff 24 d5 00 00 00 00 jmp QWORD PTR .L1[r10*8]
+        // .L0:
+        // c3                      ret
+        let expected_codes: Vec<u8> = vec![0x42, 0xff, 0x24, 0xd5, 0x00, 0x00, 0x00, 0x00, 0xc3];
+
+        let mut assembler = preprocess(
+            "main:\n  jmp QWORD PTR .L1[r10*8]\n.L0:\n  ret\n.section .rodata\n.align 8\n.L1:\n  .quad .L0\n.text\n",
+        );
+
+        assembler.codegen();
+        assembler.setup_relocations();
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(
+            expected_codes,
+            symbol.codes[..expected_codes.len()].to_vec()
+        );
+
+        // テーブルの先頭アドレスは disp32 に埋める
+        let (table_name, rela) = &assembler.src_file.text_relocations[0];
+        assert_eq!(".L1", table_name);
+        assert_eq!(4, rela.r_offset);
+        assert_eq!((2 << 32) + rela::R_X86_64_32S, rela.r_info);
+
+        // テーブルの要素は main からのオフセットで表す
+        let rela = &assembler.src_file.rodata_relocations[0];
+        assert_eq!(0, rela.r_offset);
+        assert_eq!((1 << 32) + rela::R_X86_64_64, rela.r_info);
+        assert_eq!(8, rela.r_addend);
+    }
+
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
@@ -1,9 +1,12 @@
 use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::symbol::X64DataSymbol;
 use crate::elf::elf64::rela::Rela64;
 use crate::elf::elf64::shdr::Shdr64;
 use crate::elf::elf64::symbol::Symbol64;
 use crate::elf::elf64::*;
 
+use std::collections::BTreeMap;
+
 impl ELF64 {
     pub fn add_text_section_x64(&mut self, assembler: &X64Assembler) {
         // 全ての機械語を一つのVectorに統合させる.
@@ -14,40 +17,106 @@ impl ELF64 {
             total_machine_code.append(&mut src_codes);
         }
 
+        // 直後に置くデータの先頭が8バイト境界に来るように揃える
+        while !total_machine_code.len().is_multiple_of(8) {
+            total_machine_code.push(0x00);
+        }
+
         // .textセクションヘッダの作成
         let text_header = Shdr64::init_text_header(total_machine_code.len() as Elf64Xword);
         self.add_section(total_machine_code, text_header, ".text");
     }
+    pub fn add_rodata_section_x64(&mut self, assembler: &X64Assembler) {
+        let rodata_bytes = Self::layout_data_bytes(&assembler.src_file.rodata_map);
+        let rodata_header = Shdr64::init_rodata_header(rodata_bytes.len() as Elf64Xword);
+        self.add_section(rodata_bytes, rodata_header, ".rodata");
+    }
+    pub fn add_data_section_x64(&mut self, assembler: &X64Assembler) {
+        let data_bytes = Self::layout_data_bytes(&assembler.src_file.data_map);
+        let data_header = Shdr64::init_data_header(data_bytes.len() as Elf64Xword);
+        self.add_section(data_bytes, data_header, ".data");
+    }
+    // 各シンボルの初期値を,境界に合わせて並べる
+    fn layout_data_bytes(data_map: &BTreeMap<String, X64DataSymbol>) -> Vec<u8> {
+        let offsets = X64DataSymbol::layout(data_map);
+        let mut data_bytes: Vec<u8> = vec![0x00; *offsets.last().unwrap()];
+        for (symbol, offset) in data_map.values().zip(offsets.iter()) {
+            data_bytes[*offset..*offset + symbol.bytes.len()].copy_from_slice(&symbol.bytes);
+        }
+
+        // 直後に置くセクションの先頭が8バイト境界に来るように揃える
+        while !data_bytes.len().is_multiple_of(8) {
+            data_bytes.push(0x00);
+        }
+        data_bytes
+    }
+    pub fn add_bss_section_x64(&mut self, assembler: &X64Assembler) {
+        // サイズだけを持ち,バイト列は空
+        let offsets = X64DataSymbol::layout(&assembler.src_file.bss_map);
+        let bss_size = *offsets.last().unwrap() as Elf64Xword;
+
+        let bss_header = Shdr64::init_bss_header(bss_size);
+        self.add_section(Vec::new(), bss_header, ".bss");
+    }
     pub fn add_symtab_section_x64(&mut self, assembler: &X64Assembler) {
-        // 必ずnullシンボルを含む
-        let mut symbols: Vec<Symbol64> = vec![Symbol64::new_null_symbol()];
+        // 定義済みシンボルのエントリを,名前をキーとして作っておく
+        // st_name は,シンボルテーブルに並べる順番が決まってから埋める
+        let mut defined_symbols: BTreeMap<&String, Symbol64> = BTreeMap::new();
 
-        // シンボルを走査する
-        // name_indexの操作も行う.
-        // また,各シンボルのオフセットも計算する.
-        let mut symbol_name_index: Elf64Word = 1; // 最初のnull文字を飛ばす
+        // .text のシンボルは,symbols_map の順に機械語が並んでいることを決め打ち
         let mut symbol_offset: Elf64Addr = 0; // st_value用
-
-        // 注意! symbols_mapからイテレーションしたときの順番が一意であることを決め打ち.
         for (symbol_name, asm_symbol) in assembler.src_file.symbols_map.iter() {
             // 後々実体を参照するだけのasm_symbolが存在する為このifを用いる
             if asm_symbol.is_defined() {
                 let symbol_length = asm_symbol.codes.len() as Elf64Xword;
+                let defined_symbol = if asm_symbol.is_global {
+                    Symbol64::new_global_function(0, symbol_length, symbol_offset)
+                } else {
+                    Symbol64::new_local_function(0, symbol_length, symbol_offset)
+                };
+                defined_symbols.insert(symbol_name, defined_symbol);
+            }
 
-                let defined_symbol =
-                    Symbol64::new_global_function(symbol_name_index, symbol_length, symbol_offset);
+            // オフセットの更新
+            // 後ろのシンボルのオフセット <- 前のシンボルのサイズの総合値
+            symbol_offset += asm_symbol.codes.len() as Elf64Addr;
+        }
 
-                symbols.push(defined_symbol);
-            } else {
-                // let reference_symbol = Symbol64::init_reference_symbol();
+        // .rodata/.data/.bss のシンボルは,各セクションの先頭からのオフセットを持つ
+        // セクション番号は .rodata から順に2,3,4番目にあることを決め打ち
+        for (data_map, section_i) in [
+            (&assembler.src_file.rodata_map, 2),
+            (&assembler.src_file.data_map, 3),
+            (&assembler.src_file.bss_map, 4),
+        ] {
+            let offsets = X64DataSymbol::layout(data_map);
+            for ((symbol_name, data_symbol), offset) in data_map.iter().zip(offsets.iter()) {
+                let length = data_symbol.bytes.len() as Elf64Xword;
+                let offset = *offset as Elf64Addr;
+                let defined_symbol = if data_symbol.is_global {
+                    Symbol64::new_global_object(0, length, offset, section_i)
+                } else {
+                    Symbol64::new_local_object(0, length, offset, section_i)
+                };
+                defined_symbols.insert(symbol_name, defined_symbol);
             }
+        }
+
+        // 必ずnullシンボルを含む
+        let mut symbols: Vec<Symbol64> = vec![Symbol64::new_null_symbol()];
+
+        // .strtab と同じ順番(ローカル -> グローバル -> 未定義)で並べ,name_indexを埋める
+        let mut symbol_name_index: Elf64Word = 1; // 最初のnull文字を飛ばす
+        for symbol_name in assembler.src_file.symbol_names() {
+            // 未定義シンボルはセクションに属さない
+            let mut symbol = defined_symbols
+                .remove(symbol_name)
+                .unwrap_or_else(|| Symbol64::new_undefined(0));
+            symbol.st_name = symbol_name_index;
+            symbols.push(symbol);
 
             // シンボル名を指すインデックスの更新( null byte を見越して+1する)
             symbol_name_index += symbol_name.len() as Elf64Word + 1;
-
-            // オフセットの更新
-            // 後ろのシンボルのオフセット <- 前のシンボルのサイズの総合値
-            symbol_offset += asm_symbol.codes.len() as Elf64Addr;
         }
 
         // Vec<Symbol64> をバイナリ列に変換する
@@ -57,16 +126,19 @@ impl ELF64 {
             symbol_table.append(&mut symbol_entry);
         }
 
-        let symtab_header = Shdr64::init_symtab_header(symbol_table.len() as Elf64Xword);
+        let symtab_header = Shdr64::init_symtab_header(
+            symbol_table.len() as Elf64Xword,
+            assembler.src_file.first_global_index() as Elf64Word,
+        );
         self.add_section(symbol_table, symtab_header, ".symtab");
     }
     pub fn add_strtab_section_x64(&mut self, assembler: &X64Assembler) {
         // シンボルマップをイテレートして,名前を集める.
         let symbol_names: Vec<&str> = assembler
             .src_file
-            .symbols_map
+            .symbol_names()
             .iter()
-            .map(|(name, _)| name.as_str())
+            .map(|name| name.as_str())
             .collect::<Vec<&str>>();
 
         let symbol_string_table = Self::build_strtab_from_names(symbol_names);
@@ -82,11 +154,12 @@ impl ELF64 {
         self.add_section(section_string_table, section_strtab_header, ".shstrtab");
     }
     pub fn add_relatext_section_x64(&mut self, assembler: &X64Assembler) {
-        // BTreeMap<String, Rela64> -> Vec<&Rela64>
+        // 関数呼び出しも含め,.text 中のシンボル参照を並べる
         let rela_vector = assembler
             .src_file
-            .relocations_map
-            .values()
+            .text_relocations
+            .iter()
+            .map(|(_, rela)| rela)
             .collect::<Vec<&Rela64>>();
 
         // Relaオブジェクトをバイナリに変換
@@ -100,6 +173,24 @@ impl ELF64 {
 
         self.add_section(rela_table, rela_text_header, ".rela.text");
     }
+    pub fn add_relarodata_section_x64(&mut self, assembler: &X64Assembler) {
+        let mut rela_table: Vec<u8> = Vec::new();
+        for rela in assembler.src_file.rodata_relocations.iter() {
+            rela_table.append(&mut rela.to_binary());
+        }
+
+        let rela_rodata_header = Shdr64::init_relarodata_header(rela_table.len() as Elf64Xword);
+        self.add_section(rela_table, rela_rodata_header, ".rela.rodata");
+    }
+    pub fn add_reladata_section_x64(&mut self, assembler: &X64Assembler) {
+        let mut rela_table: Vec<u8> = Vec::new();
+        for rela in assembler.src_file.data_relocations.iter() {
+            rela_table.append(&mut rela.to_binary());
+        }
+
+        let rela_data_header = Shdr64::init_reladata_header(rela_table.len() as Elf64Xword);
+        self.add_section(rela_table, rela_data_header, ".rela.data");
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +198,7 @@ mod x64_elf_tests {
     use super::*;
     use crate::assembler::arch::x64::file::X64AssemblyFile;
     use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::elf::elf64::symbol::{STB_GLOBAL, STB_LOCAL};
     use crate::structure::AssemblyFile;
     use crate::target::Target;
 
@@ -135,6 +227,108 @@ mod x64_elf_tests {
         assert_eq!(test_section.bytes.len() % 4, 0);
     }
 
+    #[test]
+    fn test_add_data_and_bss_section_x64() {
+        let assembler = preprocess(
+            ".data\n.align 4\nx:\n  .long 3\n.align 1\ny:\n  .byte 1\n.bss\nz:\n  .zero 20\n.text\nmain:\n  lea r10, x[rip]\n  ret\n",
+        );
+        let mut test_elf = ELF64::new_object_file();
+        test_elf.add_data_section_x64(&assembler);
+        test_elf.add_bss_section_x64(&assembler);
+
+        // .data は8バイト境界まで埋める
+        assert_eq!(vec![3, 0, 0, 0, 1, 0, 0, 0], test_elf.sections[0].bytes);
+
+        // .bss はサイズだけを持つ
+        assert!(test_elf.sections[1].bytes.is_empty());
+        assert_eq!(20, test_elf.sections[1].header.sh_size);
+
+        // 参照箇所が再配置情報に記録される
+        assert_eq!(1, assembler.src_file.text_relocations.len());
+        assert_eq!("x", assembler.src_file.text_relocations[0].0);
+        assert_eq!(3, assembler.src_file.text_relocations[0].1.r_offset);
+    }
+
+    #[test]
+    fn test_add_symtab_section_x64_binding() {
+        let mut assembler = preprocess(
+            ".global main\n.data\n.global gv\ngv:\n  .long 1\ncounter:\n  .long 2\n.text\nhelper:\n  ret\nmain:\n  call helper\n  call puts\n  ret\n",
+        );
+        assembler.setup_relocations();
+        let mut test_elf = ELF64::new_object_file();
+        test_elf.add_symtab_section_x64(&assembler);
+
+        let symtab = &test_elf.sections[0];
+        let symbols: Vec<Symbol64> = symtab
+            .bytes
+            .chunks(Symbol64::size())
+            .map(|entry| Symbol64::new_unsafe(entry.to_vec()))
+            .collect();
+        let bindings: Vec<u8> = symbols.iter().map(|sym| sym.st_info >> 4).collect();
+
+        // null, helper, counter | main, gv, puts
+        let names = assembler.src_file.symbol_names();
+        assert_eq!(vec!["helper", "counter", "main", "gv", "puts"], names);
+        assert_eq!(
+            vec![STB_LOCAL, STB_LOCAL, STB_LOCAL, STB_GLOBAL, STB_GLOBAL, STB_GLOBAL],
+            bindings
+        );
+        // sh_info は最初のグローバルシンボル
+        assert_eq!(3, symtab.header.sh_info);
+        assert_eq!(1, symbols[1].st_shndx);
+        assert_eq!(3, symbols[2].st_shndx);
+    }
+
+    #[test]
+    fn test_string_literal_symbol_is_local() {
+        // 文字列リテラルのラベルは .global されないので,他のファイルの .LC0 と衝突しない
+        let mut assembler = preprocess(
+            ".global main\n.section .rodata\n.LC0:\n  .string \"abc\"\n.text\nmain:\n  lea r10, .LC0[rip]\n  ret\n",
+        );
+        assembler.setup_relocations();
+        let mut test_elf = ELF64::new_object_file();
+        test_elf.add_symtab_section_x64(&assembler);
+
+        let names = assembler.src_file.symbol_names();
+        assert_eq!(vec![".LC0", "main"], names);
+
+        let literal = Symbol64::new_unsafe(test_elf.sections[0].bytes[Symbol64::size()..].to_vec());
+        assert_eq!(STB_LOCAL, literal.st_info >> 4);
+        assert_eq!(2, literal.st_shndx);
+        assert_eq!(4, literal.st_size);
+        assert_eq!(2, test_elf.sections[0].header.sh_info);
+
+        // 参照箇所は .LC0 のシンボル番号(1)を指す
+        let (name, rela) = &assembler.src_file.text_relocations[0];
+        assert_eq!(".LC0", name);
+        assert_eq!(1, rela.r_info >> 32);
+    }
+
+    #[test]
+    fn test_jump_table_symbol_is_local() {
+        // ジャンプテーブルのラベルはローカルシンボルとして,グローバルな main より前に並ぶ
+        let mut assembler = preprocess(
+            ".global main\nmain:\n  jmp QWORD PTR .L1[r10*8]\n.L0:\n  ret\n.section .rodata\n.align 8\n.L1:\n  .quad .L0\n.text\n",
+        );
+        assembler.setup_relocations();
+        let mut test_elf = ELF64::new_object_file();
+        test_elf.add_symtab_section_x64(&assembler);
+
+        let names = assembler.src_file.symbol_names();
+        assert_eq!(vec![".L1", "main"], names);
+
+        let table = Symbol64::new_unsafe(test_elf.sections[0].bytes[Symbol64::size()..].to_vec());
+        assert_eq!(STB_LOCAL, table.st_info >> 4);
+        assert_eq!(2, table.st_shndx);
+        assert_eq!(2, test_elf.sections[0].header.sh_info);
+
+        // テーブルの先頭は .L1(1番),要素は main(2番)を指す
+        let (_, rela) = &assembler.src_file.text_relocations[0];
+        assert_eq!(1, rela.r_info >> 32);
+        let rela = &assembler.src_file.rodata_relocations[0];
+        assert_eq!(2, rela.r_info >> 32);
+    }
+
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
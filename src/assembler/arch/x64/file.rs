@@ -1,13 +1,26 @@
-use crate::assembler::arch::x64::symbol::X64Symbol;
+use crate::assembler::arch::x64::symbol::{X64DataSymbol, X64Symbol};
 use crate::elf::elf64;
 use crate::structure::AssemblyFile;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 pub struct X64AssemblyFile {
     pub base_file: AssemblyFile,
     pub symbols_map: BTreeMap<String, X64Symbol>,
+    pub rodata_map: BTreeMap<String, X64DataSymbol>,
+    pub data_map: BTreeMap<String, X64DataSymbol>,
+    pub bss_map: BTreeMap<String, X64DataSymbol>,
 
-    pub relocations_map: BTreeMap<String, elf64::rela::Rela64>,
+    // .text 中でシンボルを参照する箇所.同じシンボルを何度も参照するので,名前との組で持つ
+    // r_info には型だけを入れておき,シンボル番号は後で埋める
+    pub text_relocations: Vec<(String, elf64::rela::Rela64)>,
+    // .rodata 中でラベルのアドレスを置く箇所(ジャンプテーブル)
+    pub rodata_relocations: Vec<elf64::rela::Rela64>,
+    // .data 中でシンボルのアドレスを置く箇所(関数ポインタの初期値など)
+    pub data_relocations: Vec<elf64::rela::Rela64>,
+    // .text 中のローカルラベル -> (含まれるシンボル名, シンボル内のオフセット)
+    pub text_labels: BTreeMap<String, (String, u64)>,
+    // 参照されているが,このファイルで定義されていないシンボル
+    pub undefined_symbols: BTreeSet<String>,
 }
 
 impl X64AssemblyFile {
@@ -15,7 +28,53 @@ impl X64AssemblyFile {
         Self {
             base_file: base_file,
             symbols_map: BTreeMap::new(),
-            relocations_map: BTreeMap::new(),
+            rodata_map: BTreeMap::new(),
+            data_map: BTreeMap::new(),
+            bss_map: BTreeMap::new(),
+            text_relocations: Vec::new(),
+            rodata_relocations: Vec::new(),
+            data_relocations: Vec::new(),
+            text_labels: BTreeMap::new(),
+            undefined_symbols: BTreeSet::new(),
         }
     }
+    // 定義済みのシンボル名と,.global されているか(.text -> .rodata -> .data -> .bss の順)
+    fn defined_symbols(&self) -> Vec<(&String, bool)> {
+        self.symbols_map
+            .iter()
+            .filter(|(_, symbol)| symbol.is_defined())
+            .map(|(name, symbol)| (name, symbol.is_global))
+            .chain(
+                self.rodata_map
+                    .iter()
+                    .chain(self.data_map.iter())
+                    .chain(self.bss_map.iter())
+                    .map(|(name, symbol)| (name, symbol.is_global)),
+            )
+            .collect()
+    }
+    // シンボルテーブルに並べる順番でのシンボル名
+    // ELF ではローカルシンボルを全てのグローバルシンボルより前に置く必要があるので,
+    // 定義済みのローカル -> 定義済みのグローバル -> 未定義 の順に並べる
+    pub fn symbol_names(&self) -> Vec<&String> {
+        let (globals, locals): (Vec<_>, Vec<_>) = self
+            .defined_symbols()
+            .into_iter()
+            .partition(|(_, is_global)| *is_global);
+        locals
+            .into_iter()
+            .chain(globals)
+            .map(|(name, _)| name)
+            .chain(self.undefined_symbols.iter())
+            .collect()
+    }
+    // シンボルテーブル中の最初のグローバルシンボルの番号(先頭の null シンボルも数える)
+    pub fn first_global_index(&self) -> usize {
+        let locals = self
+            .defined_symbols()
+            .iter()
+            .filter(|(_, is_global)| !is_global)
+            .count();
+        locals + 1
+    }
 }
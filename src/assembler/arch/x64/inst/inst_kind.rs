@@ -1,3 +1,5 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+
 type SrcOperand = X64Operand;
 type DstOperand = X64Operand;
 #[derive(PartialEq, Debug, Clone)]
@@ -16,35 +18,66 @@ pub enum X64InstKind {
 #[derive(PartialEq, Debug, Clone)]
 pub struct X64Operand {
     pub kind: X64OpeKind,
+
+    // DWORD PTR などで指定されたメモリオペランドのサイズ
+    pub ptr_size: OperandSize,
 }
 
 impl X64Operand {
     pub fn new_label(name: String) -> Self {
         Self {
             kind: X64OpeKind::LABEL(name),
+            ptr_size: OperandSize::UNKNOWN,
         }
     }
     pub fn new_register(name: String) -> Self {
         Self {
             kind: X64OpeKind::REG(name),
+            ptr_size: OperandSize::UNKNOWN,
         }
     }
     pub fn new_integer(value: i128) -> Self {
         Self {
             kind: X64OpeKind::INTEGER(value),
+            ptr_size: OperandSize::UNKNOWN,
         }
     }
     pub fn new_addressing(offset: i128, name: String) -> Self {
         Self {
             kind: X64OpeKind::ADDRESSING(offset, name),
+            ptr_size: OperandSize::UNKNOWN,
+        }
+    }
+    pub fn new_rip_relative(label: String) -> Self {
+        Self {
+            kind: X64OpeKind::RIPRELATIVE(label),
+            ptr_size: OperandSize::UNKNOWN,
         }
     }
+    pub fn new_label_indexed(label: String, index: String, scale: u8) -> Self {
+        Self {
+            kind: X64OpeKind::LABELINDEXED(label, index, scale),
+            ptr_size: OperandSize::UNKNOWN,
+        }
+    }
+    pub fn with_ptr_size(mut self, size: OperandSize) -> Self {
+        self.ptr_size = size;
+        self
+    }
     pub fn to_string(&self) -> String {
         match &self.kind {
             X64OpeKind::REG(name) => name.to_string(),
             X64OpeKind::INTEGER(val) => format!("{}", val),
             X64OpeKind::LABEL(name) => name.to_string(),
+            X64OpeKind::ADDRESSING(0, name) => format!("[{}]", name),
+            X64OpeKind::ADDRESSING(offset, name) if *offset < 0 => {
+                format!("{}[{}]", -offset, name)
+            }
             X64OpeKind::ADDRESSING(offset, name) => format!("-{}[{}]", offset, name),
+            X64OpeKind::RIPRELATIVE(label) => format!("{}[rip]", label),
+            X64OpeKind::LABELINDEXED(label, index, scale) => {
+                format!("{}[{}*{}]", label, index, scale)
+            }
         }
     }
 }
@@ -62,5 +95,15 @@ pub enum X64OpeKind {
 
     // メモリアドレッシング
     // 簡易実装なので,後々良くする.
+    // オフセットが0なら [reg] を表す.
     ADDRESSING(i128, String), // offset, RegisterName
+
+    // ラベルのアドレスを,次の命令の先頭からの相対で表す
+    // ディスプレースメントはリンク時に決まる
+    RIPRELATIVE(String),
+
+    // ラベルのアドレスに インデックスレジスタ*スケール を足したもの
+    // e.g. ジャンプテーブルの要素 .L5[r10*8]
+    // ラベルのアドレスはリンク時に決まる
+    LABELINDEXED(String, String, u8), // label, IndexRegisterName, scale
 }
@@ -10,32 +10,110 @@ pub enum X64InstName {
     CALL,
     JMP,
     JZ,
+    JNZ,
+    JL,
+    JLE,
+    JG,
+    JGE,
+    SETE,
+    SETNE,
+    SETL,
+    SETLE,
+    SETG,
+    SETGE,
+    SETB,
+    SETBE,
+    SETA,
+    SETAE,
     MOV,
+    MOVZX,
+    MOVSX,
+    MOVSXD,
+    DIV,
     RET,
     SYSCALL,
     PUSH,
     POP,
     NEG,
+    NOT,
+    AND,
+    OR,
+    XOR,
+    SHL,
+    SAR,
+    SHR,
+    LEA,
 
     // 具体的なオペコード
     ADDRM64IMM32,
     ADDRM64R64,
+    ADDR64RM64,
     SUBRM64IMM32,
     SUBRM64R64,
+    SUBR64RM64,
     IMULR64RM64IMM32,
     IMULR64RM64,
     CALLRM64,
+    CALLREL32,
     CMPRM64IMM32,
     CMPRM64R64,
+    CMPR64RM64,
     IDIVRM64,
+    DIVRM64,
     MOVRM64IMM32,
+    MOVR64IMM64,
     MOVR64RM64,
     MOVRM64R64,
+    MOVR32RM32,
+    MOVRM32R32,
+    MOVRM16R16,
+    MOVRM8R8,
+    MOVRM32IMM32,
+    MOVRM16IMM16,
+    MOVRM8IMM8,
+    MOVZXR64RM8,
+    MOVZXR64RM16,
+    MOVSXR64RM8,
+    MOVSXR64RM16,
+    MOVSXDR64RM32,
     JMPREL32,
+    JMPRM64,
     JZREL32,
+    JNZREL32,
+    JLREL32,
+    JLEREL32,
+    JGREL32,
+    JGEREL32,
+    SETERM8,
+    SETNERM8,
+    SETLRM8,
+    SETLERM8,
+    SETGRM8,
+    SETGERM8,
+    SETBRM8,
+    SETBERM8,
+    SETARM8,
+    SETAERM8,
     PUSHR64,
     POPR64,
     NEGRM64,
+    NOTRM64,
+    ANDRM64IMM32,
+    ANDRM64R64,
+    ANDR64RM64,
+    ORRM64IMM32,
+    ORRM64R64,
+    ORR64RM64,
+    XORRM64IMM32,
+    XORRM64R64,
+    XORR64RM64,
+    SHLRM64IMM8,
+    SHLRM64CL,
+    SARRM64IMM8,
+    SARRM64CL,
+    SHRRM64IMM8,
+    SHRRM64CL,
+    LEAR64M,
 
     // その他
     LABEL,
@@ -47,10 +125,12 @@ impl X64InstName {
             Self::ADD => "add".to_string(),
             Self::ADDRM64IMM32 => "add(r/m64 imm32)".to_string(),
             Self::ADDRM64R64 => "add(r/m64 r64)".to_string(),
+            Self::ADDR64RM64 => "add(r64 r/m64)".to_string(),
             // sub
             Self::SUB => "sub".to_string(),
             Self::SUBRM64IMM32 => "sub(r/m64 imm32)".to_string(),
             Self::SUBRM64R64 => "sub(r/m64 r64)".to_string(),
+            Self::SUBR64RM64 => "sub(r64 r/m64)".to_string(),
             // imul
             Self::IMUL => "imul".to_string(),
             Self::IMULR64RM64IMM32 => "imul(r64 r/m64 imm32)".to_string(),
@@ -58,24 +138,93 @@ impl X64InstName {
             // idiv
             Self::IDIV => "idiv".to_string(),
             Self::IDIVRM64 => "idiv(r/m64)".to_string(),
+            // div
+            Self::DIV => "div".to_string(),
+            Self::DIVRM64 => "div(r/m64)".to_string(),
             // call
             Self::CALL => "call".to_string(),
             Self::CALLRM64 => "call(r/m64)".to_string(),
+            Self::CALLREL32 => "call(rel32)".to_string(),
             // cmp
             Self::CMP => "cmp".to_string(),
             Self::CMPRM64IMM32 => "cmp(r/m64 imm32)".to_string(),
             Self::CMPRM64R64 => "cmp(r/m64 r64)".to_string(),
+            Self::CMPR64RM64 => "cmp(r64 r/m64)".to_string(),
             // cqo
             Self::CQO => "cqo".to_string(),
             // jmp
             Self::JMP => "jmp".to_string(),
             Self::JMPREL32 => "jmp (rel32)".to_string(),
+            Self::JMPRM64 => "jmp (r/m64)".to_string(),
             // jz
             Self::JZ => "jz".to_string(),
             Self::JZREL32 => "jz (rel32)".to_string(),
+            // jcc
+            Self::JNZ => "jnz".to_string(),
+            Self::JNZREL32 => "jnz (rel32)".to_string(),
+            Self::JL => "jl".to_string(),
+            Self::JLREL32 => "jl (rel32)".to_string(),
+            Self::JLE => "jle".to_string(),
+            Self::JLEREL32 => "jle (rel32)".to_string(),
+            Self::JG => "jg".to_string(),
+            Self::JGREL32 => "jg (rel32)".to_string(),
+            Self::JGE => "jge".to_string(),
+            Self::JGEREL32 => "jge (rel32)".to_string(),
+            // setcc
+            Self::SETE => "sete".to_string(),
+            Self::SETERM8 => "sete (r/m8)".to_string(),
+            Self::SETNE => "setne".to_string(),
+            Self::SETNERM8 => "setne (r/m8)".to_string(),
+            Self::SETL => "setl".to_string(),
+            Self::SETLRM8 => "setl (r/m8)".to_string(),
+            Self::SETLE => "setle".to_string(),
+            Self::SETLERM8 => "setle (r/m8)".to_string(),
+            Self::SETG => "setg".to_string(),
+            Self::SETGRM8 => "setg (r/m8)".to_string(),
+            Self::SETGE => "setge".to_string(),
+            Self::SETGERM8 => "setge (r/m8)".to_string(),
+            Self::SETB => "setb".to_string(),
+            Self::SETBRM8 => "setb (r/m8)".to_string(),
+            Self::SETBE => "setbe".to_string(),
+            Self::SETBERM8 => "setbe (r/m8)".to_string(),
+            Self::SETA => "seta".to_string(),
+            Self::SETARM8 => "seta (r/m8)".to_string(),
+            Self::SETAE => "setae".to_string(),
+            Self::SETAERM8 => "setae (r/m8)".to_string(),
             // neg
             Self::NEG => "neg".to_string(),
             Self::NEGRM64 => "neg (r/m64)".to_string(),
+            // not
+            Self::NOT => "not".to_string(),
+            Self::NOTRM64 => "not (r/m64)".to_string(),
+            // and
+            Self::AND => "and".to_string(),
+            Self::ANDRM64IMM32 => "and(r/m64 imm32)".to_string(),
+            Self::ANDRM64R64 => "and(r/m64 r64)".to_string(),
+            Self::ANDR64RM64 => "and(r64 r/m64)".to_string(),
+            // or
+            Self::OR => "or".to_string(),
+            Self::ORRM64IMM32 => "or(r/m64 imm32)".to_string(),
+            Self::ORRM64R64 => "or(r/m64 r64)".to_string(),
+            Self::ORR64RM64 => "or(r64 r/m64)".to_string(),
+            // xor
+            Self::XOR => "xor".to_string(),
+            Self::XORRM64IMM32 => "xor(r/m64 imm32)".to_string(),
+            Self::XORRM64R64 => "xor(r/m64 r64)".to_string(),
+            Self::XORR64RM64 => "xor(r64 r/m64)".to_string(),
+            // shift
+            Self::SHL => "shl".to_string(),
+            Self::SHLRM64IMM8 => "shl(r/m64 imm8)".to_string(),
+            Self::SHLRM64CL => "shl(r/m64 cl)".to_string(),
+            Self::SAR => "sar".to_string(),
+            Self::SARRM64IMM8 => "sar(r/m64 imm8)".to_string(),
+            Self::SARRM64CL => "sar(r/m64 cl)".to_string(),
+            Self::SHR => "shr".to_string(),
+            Self::SHRRM64IMM8 => "shr(r/m64 imm8)".to_string(),
+            Self::SHRRM64CL => "shr(r/m64 cl)".to_string(),
+            // lea
+            Self::LEA => "lea".to_string(),
+            Self::LEAR64M => "lea(r64 m)".to_string(),
             // push
             Self::PUSH => "push".to_string(),
             Self::PUSHR64 => "push (r64)".to_string(),
@@ -86,7 +235,25 @@ impl X64InstName {
             Self::MOV => "mov".to_string(),
             Self::MOVR64RM64 => "mov(r64 r/m64)".to_string(),
             Self::MOVRM64IMM32 => "mov(r/m64 imm32)".to_string(),
+            Self::MOVR64IMM64 => "mov(r64 imm64)".to_string(),
             Self::MOVRM64R64 => "mov(r/m64 r64)".to_string(),
+            Self::MOVR32RM32 => "mov(r32 r/m32)".to_string(),
+            Self::MOVRM32R32 => "mov(r/m32 r32)".to_string(),
+            Self::MOVRM16R16 => "mov(r/m16 r16)".to_string(),
+            Self::MOVRM8R8 => "mov(r/m8 r8)".to_string(),
+            Self::MOVRM32IMM32 => "mov(r/m32 imm32)".to_string(),
+            Self::MOVRM16IMM16 => "mov(r/m16 imm16)".to_string(),
+            Self::MOVRM8IMM8 => "mov(r/m8 imm8)".to_string(),
+            // movzx
+            Self::MOVZX => "movzx".to_string(),
+            Self::MOVZXR64RM8 => "movzx(r64 r/m8)".to_string(),
+            Self::MOVZXR64RM16 => "movzx(r64 r/m16)".to_string(),
+            // movsx
+            Self::MOVSX => "movsx".to_string(),
+            Self::MOVSXR64RM8 => "movsx(r64 r/m8)".to_string(),
+            Self::MOVSXR64RM16 => "movsx(r64 r/m16)".to_string(),
+            Self::MOVSXD => "movsxd".to_string(),
+            Self::MOVSXDR64RM32 => "movsxd(r64 r/m32)".to_string(),
 
             // ret
             Self::RET => "ret".to_string(),
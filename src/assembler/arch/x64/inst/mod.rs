@@ -52,9 +52,26 @@ impl X64Instruction {
         match name {
             inst_name::X64InstName::CALL => Self::new_call(unop),
             inst_name::X64InstName::NEG => Self::new_neg(unop),
+            inst_name::X64InstName::NOT => Self::new_not(unop),
             inst_name::X64InstName::JMP => Self::new_jmp(unop),
             inst_name::X64InstName::JZ => Self::new_jz(unop),
+            inst_name::X64InstName::JNZ
+            | inst_name::X64InstName::JL
+            | inst_name::X64InstName::JLE
+            | inst_name::X64InstName::JG
+            | inst_name::X64InstName::JGE => Self::new_jcc(name, unop),
+            inst_name::X64InstName::SETE
+            | inst_name::X64InstName::SETNE
+            | inst_name::X64InstName::SETL
+            | inst_name::X64InstName::SETLE
+            | inst_name::X64InstName::SETG
+            | inst_name::X64InstName::SETGE
+            | inst_name::X64InstName::SETB
+            | inst_name::X64InstName::SETBE
+            | inst_name::X64InstName::SETA
+            | inst_name::X64InstName::SETAE => Self::new_setcc(name, unop),
             inst_name::X64InstName::IDIV => Self::new_idiv(unop),
+            inst_name::X64InstName::DIV => Self::new_div(unop),
             inst_name::X64InstName::PUSH => Self::new_push(unop),
             inst_name::X64InstName::POP => Self::new_pop(unop),
             _ => panic!("no such an unary instruction"),
@@ -71,6 +88,17 @@ impl X64Instruction {
             inst_name::X64InstName::IMUL => Self::new_imul(src, dst),
             inst_name::X64InstName::MOV => Self::new_mov(src, dst),
             inst_name::X64InstName::CMP => Self::new_cmp(src, dst),
+            inst_name::X64InstName::MOVZX => Self::new_movzx(src, dst),
+            inst_name::X64InstName::MOVSX | inst_name::X64InstName::MOVSXD => {
+                Self::new_movsx(name, src, dst)
+            }
+            inst_name::X64InstName::AND => Self::new_and(src, dst),
+            inst_name::X64InstName::OR => Self::new_or(src, dst),
+            inst_name::X64InstName::XOR => Self::new_xor(src, dst),
+            inst_name::X64InstName::SHL
+            | inst_name::X64InstName::SAR
+            | inst_name::X64InstName::SHR => Self::new_shift(name, src, dst),
+            inst_name::X64InstName::LEA => Self::new_lea(src, dst),
             _ => panic!("no such a binary instruction"),
         }
     }
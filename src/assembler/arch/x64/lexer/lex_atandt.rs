@@ -54,6 +54,7 @@ impl AsmLexer {
             }
 
             // アルファベットの場合 -> 命令かシンボル/ラベル
+            '.' if self.is_directive_head() => self.scan_directive(),
             '_' | '.' => Some(self.scan_word()),
             c if c.is_ascii_alphabetic() => Some(self.scan_word()),
 
@@ -106,6 +107,19 @@ impl AsmLexer {
         self.keywords.insert("subq".to_string(), AsmTokenKind::SUBQ);
         self.keywords.insert("push".to_string(), AsmTokenKind::PUSH);
         self.keywords.insert("pop".to_string(), AsmTokenKind::POP);
+        self.keywords.insert("cmpq".to_string(), AsmTokenKind::CMPQ);
+        self.keywords.insert("jz".to_string(), AsmTokenKind::JZ);
+        self.keywords
+            .insert("movzbq".to_string(), AsmTokenKind::MOVZBQ);
+        self.keywords.insert("andq".to_string(), AsmTokenKind::ANDQ);
+        self.keywords.insert("orq".to_string(), AsmTokenKind::ORQ);
+        self.keywords.insert("xorq".to_string(), AsmTokenKind::XORQ);
+        self.keywords.insert("notq".to_string(), AsmTokenKind::NOTQ);
+        self.keywords.insert("shlq".to_string(), AsmTokenKind::SHLQ);
+        self.keywords.insert("sarq".to_string(), AsmTokenKind::SARQ);
+        self.keywords.insert("shrq".to_string(), AsmTokenKind::SHRQ);
+        self.keywords.insert("leaq".to_string(), AsmTokenKind::LEAQ);
+        self.build_common_keywords();
     }
 }
 
@@ -47,6 +47,7 @@ impl AsmLexer {
         match head_char {
             // アルファベットの場合
             c if c.is_ascii_alphabetic() => Some(self.scan_word()),
+            '.' if self.is_directive_head() => self.scan_directive(),
             '_' | '.' => Some(self.scan_word()),
 
             // 数字の場合
@@ -56,6 +57,7 @@ impl AsmLexer {
             '[' => Some(self.scan_symbol(AsmTokenKind::LBRACKET)),
             ']' => Some(self.scan_symbol(AsmTokenKind::RBRACKET)),
             '-' => Some(self.scan_symbol(AsmTokenKind::MINUS)),
+            '*' => Some(self.scan_symbol(AsmTokenKind::ASTERISK)),
             ',' => Some(self.scan_symbol(AsmTokenKind::COMMA)),
 
             // comment
@@ -90,11 +92,31 @@ impl AsmLexer {
         self.keywords.insert("push".to_string(), AsmTokenKind::PUSH);
         self.keywords.insert("pop".to_string(), AsmTokenKind::POP);
         self.keywords.insert("neg".to_string(), AsmTokenKind::NEG);
+        self.keywords.insert("and".to_string(), AsmTokenKind::AND);
+        self.keywords.insert("or".to_string(), AsmTokenKind::OR);
+        self.keywords.insert("xor".to_string(), AsmTokenKind::XOR);
+        self.keywords.insert("not".to_string(), AsmTokenKind::NOT);
+        self.keywords.insert("shl".to_string(), AsmTokenKind::SHL);
+        self.keywords.insert("sar".to_string(), AsmTokenKind::SAR);
+        self.keywords.insert("shr".to_string(), AsmTokenKind::SHR);
+        self.keywords.insert("lea".to_string(), AsmTokenKind::LEA);
+        self.keywords.insert("div".to_string(), AsmTokenKind::DIV);
+        self.keywords
+            .insert("movzx".to_string(), AsmTokenKind::MOVZX);
+        self.keywords
+            .insert("movsx".to_string(), AsmTokenKind::MOVSX);
+        self.keywords
+            .insert("movsxd".to_string(), AsmTokenKind::MOVSXD);
         self.keywords
             .insert("syscall".to_string(), AsmTokenKind::SYSCALL);
         self.keywords
             .insert("QWORD".to_string(), AsmTokenKind::QWORD);
+        self.keywords
+            .insert("DWORD".to_string(), AsmTokenKind::DWORD);
+        self.keywords.insert("WORD".to_string(), AsmTokenKind::WORD);
+        self.keywords.insert("BYTE".to_string(), AsmTokenKind::BYTE);
         self.keywords.insert("PTR".to_string(), AsmTokenKind::PTR);
+        self.build_common_keywords();
     }
 }
 
@@ -132,6 +154,25 @@ mod intel_lexer_tests {
         }
     }
 
+    #[test]
+    fn test_build_tokens_for_intel_syntax_with_mnemonic_like_labels() {
+        // 命令と同じ名前の関数も,定義と call の飛び先ではラベルになる
+        let expected_tokens = [
+            AsmToken::new((1, 1), AsmTokenKind::LABEL("add".to_string())),
+            AsmToken::new((2, 3), AsmTokenKind::CALL),
+            AsmToken::new((2, 8), AsmTokenKind::LABEL("add".to_string())),
+            AsmToken::new((3, 3), AsmTokenKind::CALL),
+            AsmToken::new((3, 8), AsmTokenKind::REG("r10".to_string())),
+            AsmToken::new((4, 3), AsmTokenKind::ADD),
+        ];
+        let mut lexer = create_lexer("add:\n  call add\n  call r10\n  add rax, 1\n");
+        let tokens = lexer.build_tokens_for_intel_syntax();
+
+        for (i, expected) in expected_tokens.iter().enumerate() {
+            assert_eq!(expected, &tokens[i]);
+        }
+    }
+
     #[test]
     fn test_scan_one_intel_token_with_single_int() {
         let expected_int = AsmToken::new((1, 1), AsmTokenKind::INTEGER(12345));
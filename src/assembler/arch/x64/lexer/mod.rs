@@ -10,6 +10,7 @@ pub struct AsmLexer {
     pub row: usize,                               // y軸の座標
     pub contents: String, // メモリコピーし, AssemblyrFile構造体の文字列を破壊しないように
     pub keywords: BTreeMap<String, AsmTokenKind>, // 予約語をO(1)で取り出すためのメンバ
+    after_call: bool,     // 直前の語が call 命令だったか
 }
 
 impl AsmLexer {
@@ -19,6 +20,7 @@ impl AsmLexer {
             column: 1,
             contents: contents,
             keywords: BTreeMap::new(),
+            after_call: false,
         }
     }
     // 文字列を切り取って,ディレクティブトークンを返す
@@ -29,7 +31,8 @@ impl AsmLexer {
 
         let head_char = self.contents.as_bytes()[0] as char;
 
-        if head_char != '.' {
+        // .LC0: のようなラベルはディレクティブではない
+        if head_char != '.' || !self.is_directive_head() {
             return None;
         }
 
@@ -54,6 +57,15 @@ impl AsmLexer {
         ))
     }
 
+    // 途中に現れる . から始まる語が,ラベルでなくディレクティブか
+    // ラベルは .L0 のように大文字,ディレクティブは .data のように小文字で始まる
+    pub fn is_directive_head(&self) -> bool {
+        match self.contents.as_bytes().get(1) {
+            Some(c) => (*c as char).is_ascii_lowercase(),
+            None => false,
+        }
+    }
+
     // 文字列を切り取って,レジスタ/命令/ラベルトークンを返す
     pub fn scan_word(&mut self) -> AsmToken {
         // 現在のオフセットを退避
@@ -75,8 +87,18 @@ impl AsmLexer {
         // ,や:があればトリム
         let word_trimmed = word.trim_end_matches(',').trim_end_matches(':');
 
+        // ラベルの定義や call の飛び先, add[rip] のようなメモリ参照のシンボルは,
+        // 命令と同じ名前(add など)でもラベルとして扱う
+        let after_call = self.after_call;
+        self.after_call = false;
+        let is_symbol_operand = after_call || self.contents.starts_with('[');
+        if word.ends_with(':') || (is_symbol_operand && !Self::check_register(word_trimmed)) {
+            return AsmToken::new(cur_position, AsmTokenKind::LABEL(word_trimmed.to_string()));
+        }
+
         // 命令かチェック
         if let Some(t_kind) = self.keywords.get(word_trimmed) {
+            self.after_call = t_kind == &AsmTokenKind::CALL;
             return AsmToken::new(cur_position, t_kind.clone());
         }
 
@@ -148,6 +170,33 @@ impl AsmLexer {
         self.contents.drain(..len);
     }
 
+    // 両記法で同じ綴りの命令
+    pub fn build_common_keywords(&mut self) {
+        // je/jneはjz/jnzの別名
+        self.keywords.insert("jnz".to_string(), AsmTokenKind::JNZ);
+        self.keywords.insert("je".to_string(), AsmTokenKind::JZ);
+        self.keywords.insert("jne".to_string(), AsmTokenKind::JNZ);
+        self.keywords.insert("jl".to_string(), AsmTokenKind::JL);
+        self.keywords.insert("jle".to_string(), AsmTokenKind::JLE);
+        self.keywords.insert("jg".to_string(), AsmTokenKind::JG);
+        self.keywords.insert("jge".to_string(), AsmTokenKind::JGE);
+        self.keywords.insert("sete".to_string(), AsmTokenKind::SETE);
+        self.keywords
+            .insert("setne".to_string(), AsmTokenKind::SETNE);
+        self.keywords.insert("setl".to_string(), AsmTokenKind::SETL);
+        self.keywords
+            .insert("setle".to_string(), AsmTokenKind::SETLE);
+        self.keywords.insert("setg".to_string(), AsmTokenKind::SETG);
+        self.keywords
+            .insert("setge".to_string(), AsmTokenKind::SETGE);
+        self.keywords.insert("setb".to_string(), AsmTokenKind::SETB);
+        self.keywords
+            .insert("setbe".to_string(), AsmTokenKind::SETBE);
+        self.keywords.insert("seta".to_string(), AsmTokenKind::SETA);
+        self.keywords
+            .insert("setae".to_string(), AsmTokenKind::SETAE);
+    }
+
     pub fn take_conditional_string(input: &str, f: fn(ch: &char) -> bool) -> String {
         input.chars().take_while(f).collect::<String>()
     }
@@ -169,7 +218,7 @@ impl AsmLexer {
             "r12b" | "r12w" | "r12d" | "r12" => true,
             "r13b" | "r13w" | "r13d" | "r13" => true,
             "r14b" | "r14w" | "r14d" | "r14" => true,
-            "r15b" | "15w" | "r15d" | "r15" => true,
+            "r15b" | "r15w" | "r15d" | "r15" => true,
             _ => false,
         }
     }
@@ -233,6 +282,27 @@ mod general_lexer_tests {
         assert_eq!(expected, actual.unwrap());
     }
 
+    #[test]
+    fn test_is_directive_head() {
+        assert!(create_lexer(".data\n").is_directive_head());
+        assert!(!create_lexer(".L0:\n").is_directive_head());
+    }
+
+    #[test]
+    fn test_scan_word_as_symbol() {
+        // 命令と同じ名前でも,メモリ参照のシンボルならラベル
+        let mut lexer = create_lexer("add[rip]");
+        assert_eq!(
+            AsmTokenKind::LABEL("add".to_string()),
+            lexer.scan_word().kind
+        );
+
+        let mut lexer = create_lexer("add r10");
+        lexer.build_common_keywords();
+        lexer.keywords.insert("add".to_string(), AsmTokenKind::ADD);
+        assert_eq!(AsmTokenKind::ADD, lexer.scan_word().kind);
+    }
+
     #[test]
     fn test_skip_whitespace() {
         let mut lexer = create_lexer("     ");
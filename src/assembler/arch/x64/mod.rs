@@ -72,14 +72,35 @@ pub fn assemble(
     reloc_elf.add_null_section();
     /* .text */
     reloc_elf.add_text_section_x64(&assembler);
+    /* .rodata */
+    reloc_elf.add_rodata_section_x64(&assembler);
+    /* .data */
+    reloc_elf.add_data_section_x64(&assembler);
+    /* .bss */
+    reloc_elf.add_bss_section_x64(&assembler);
     /* .symtab */
     reloc_elf.add_symtab_section_x64(&assembler);
     /* .strtab */
     reloc_elf.add_strtab_section_x64(&assembler);
     /* .rela.text */
     reloc_elf.add_relatext_section_x64(&assembler);
+    /* .rela.rodata */
+    reloc_elf.add_relarodata_section_x64(&assembler);
+    /* .rela.data */
+    reloc_elf.add_reladata_section_x64(&assembler);
     /* .shstrtab */
-    let section_names = vec![".text", ".symtab", ".strtab", ".rela.text", ".shstrtab"];
+    let section_names = vec![
+        ".text",
+        ".rodata",
+        ".data",
+        ".bss",
+        ".symtab",
+        ".strtab",
+        ".rela.text",
+        ".rela.rodata",
+        ".rela.data",
+        ".shstrtab",
+    ];
     reloc_elf.add_shstrtab_section_x64(section_names);
 
     reloc_elf.finalize();
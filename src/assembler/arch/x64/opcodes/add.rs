@@ -25,20 +25,9 @@ impl X64Assembler {
         codes.push(0x81);
 
         // modr/m (MI)
-        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
-
         // オフセットが設定されている -> アドレッシング方法が異なる
-        if inst.store_offset != 0 {
-            codes.push(MODRM_REGISTER_DISPLACEMENT8 | rm_field);
-        } else {
-            codes.push(MODRM_REGISTER_REGISTER | rm_field);
-        }
-
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.store_offset != 0 {
-            codes.push((-inst.store_offset) as u8);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0, inst.dst_regnumber, dst);
         }
 
         // immediate-value
@@ -63,6 +52,24 @@ impl X64Assembler {
         let reg_field = Self::modrm_reg_field(inst.src_regnumber);
         codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
     }
+    pub fn generate_addr64rm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. add rax, -8[rbp]
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x03);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
 }
 
 impl X64Instruction {
@@ -84,6 +91,10 @@ impl X64Instruction {
                     // add r/m64, r64
                     return X64InstName::ADDRM64R64;
                 }
+                if dst.is_register() && src.is_addressing() {
+                    // add r64, r/m64
+                    return X64InstName::ADDR64RM64;
+                }
                 X64InstName::ADD
             }
             // 何も変化させない
@@ -148,6 +159,18 @@ mod add_opcode_tests {
             }
         }
     }
+    #[test]
+    fn test_generate_addr64rm64_with_displacement32() {
+        // 1バイトに収まらないオフセットは disp32 で表す
+        let expected: Vec<u8> = vec![0x4c, 0x03, 0x95, 0x78, 0xff, 0xff, 0xff];
+        // add r10, -136[rbp]
+        let mut assembler = preprocess("main:\n  add r10, -136[rbp]\n");
+        assembler.codegen();
+
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(expected, symbol.codes[..expected.len()].to_vec());
+        }
+    }
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
@@ -1,7 +1,8 @@
 use crate::assembler::arch::x64::analyze::OperandSize;
 use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::{MODRM_REGISTER_REGISTER, REX_PREFIX_BASE};
 use crate::assembler::arch::x64::inst::{
-    inst_kind::{X64InstKind, X64Operand},
+    inst_kind::{X64InstKind, X64OpeKind, X64Operand},
     inst_name::X64InstName,
     X64Instruction,
 };
@@ -13,18 +14,34 @@ impl X64Instruction {
 }
 
 impl X64Assembler {
-    pub fn generate_callrm64_inst(codes: &mut Vec<u8>, _inst: &X64Instruction) {
+    pub fn generate_callrel32_inst(codes: &mut Vec<u8>) {
+        // call-opcode
+        codes.push(0xe8);
+
+        // 呼び出し先との差分はリンク時に埋める
+        codes.extend_from_slice(&0u32.to_le_bytes());
+    }
+    pub fn generate_callrm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // 0xff /2
+        // 呼び出し先のレジスタ -> ModR/M の r/m フィールド, r8-r15 は REX.B で指定する
+        if inst.dst_expanded {
+            codes.push(REX_PREFIX_BASE | Self::rex_prefix_bbit(true));
+        }
+
         // call-opcode
         codes.push(0xff);
 
-        // call - register
-        codes.push(0xd0);
+        // modr/m (/2 なので reg フィールドは 2)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | 0x10 | rm_field);
     }
 }
 
 impl X64Instruction {
-    pub fn change_call_opcode(op_size: &OperandSize, _op: &X64Operand) -> X64InstName {
-        match op_size {
+    pub fn change_call_opcode(_op_size: &OperandSize, op: &X64Operand) -> X64InstName {
+        match &op.kind {
+            // call rel32
+            X64OpeKind::LABEL(_name) => X64InstName::CALLREL32,
             // call r/m64
             _ => X64InstName::CALLRM64,
         }
@@ -40,16 +57,57 @@ mod call_opcode_tests {
     use crate::target::Target;
 
     #[test]
-    fn test_change_callrm64() {
+    fn test_change_callrel32() {
         // main:
         //   call foo
         let mut assembler = preprocess("main:\n  call foo\n");
         assembler.analyze();
         if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
             let call_inst = &symbol.insts[0];
-            assert_eq!(X64InstName::CALLRM64, call_inst.name);
+            assert_eq!(X64InstName::CALLREL32, call_inst.name);
         }
     }
+    #[test]
+    fn test_generate_callrel32() {
+        // main:
+        //   call foo
+        //   call foo
+        let mut assembler = preprocess("main:\n  call foo\n  call foo\n");
+        assembler.analyze();
+        assembler.codegen();
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(
+                vec![0xe8, 0, 0, 0, 0, 0xe8, 0, 0, 0, 0],
+                symbol.codes[..10].to_vec()
+            );
+        }
+
+        // 呼び出しごとに再配置情報を持つ
+        let relocations = &assembler.src_file.text_relocations;
+        assert_eq!(2, relocations.len());
+        assert_eq!("foo", relocations[1].0);
+        assert_eq!(6, relocations[1].1.r_offset);
+        assert_eq!(-4, relocations[1].1.r_addend);
+    }
+    #[test]
+    fn test_generate_callrm64() {
+        // main:
+        //   call rax
+        //   call r12
+        let mut assembler = preprocess("main:\n  call rax\n  call r12\n");
+        assembler.analyze();
+        assembler.codegen();
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(X64InstName::CALLRM64, symbol.insts[1].name);
+            assert_eq!(
+                vec![0xff, 0xd0, 0x41, 0xff, 0xd4],
+                symbol.codes[..5].to_vec()
+            );
+        }
+
+        // レジスタ経由の呼び出しは再配置情報を持たない
+        assert!(assembler.src_file.text_relocations.is_empty());
+    }
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
@@ -21,18 +21,8 @@ impl X64Assembler {
 
         // modr/m (MIだけど /7 なのでマスクする)
         // オフセットが設定されている -> アドレッシング方法が異なる
-        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
-        if inst.store_offset != 0 {
-            codes.push(MODRM_REGISTER_DISPLACEMENT8 | rm_field | 0x38);
-        } else {
-            codes.push(MODRM_REGISTER_REGISTER | rm_field | 0x38);
-        }
-
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.store_offset != 0 {
-            codes.push((-inst.store_offset) as u8);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0x38, inst.dst_regnumber, dst);
         }
 
         // immediate-value
@@ -57,6 +47,24 @@ impl X64Assembler {
         let reg_field = Self::modrm_reg_field(inst.src_regnumber);
         codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
     }
+    pub fn generate_cmpr64rm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. cmp rax, -8[rbp]
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x3b);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
 }
 
 impl X64Instruction {
@@ -86,6 +94,10 @@ impl X64Instruction {
                     // cmp r/m64, r64
                     return X64InstName::CMPRM64R64;
                 }
+                if dst.is_register() && src.is_addressing() {
+                    // cmp r64, r/m64
+                    return X64InstName::CMPR64RM64;
+                }
                 X64InstName::CMP
             }
             // 何も変化させない
@@ -0,0 +1,73 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_div(div_op: X64Operand) -> Self {
+        Self::new(X64InstName::DIV, X64InstKind::UNARY(div_op))
+    }
+    pub fn change_div_opcode(op_size: &OperandSize, _op: &X64Operand) -> X64InstName {
+        match op_size {
+            // div r/m64
+            OperandSize::QUADWORD => X64InstName::DIVRM64,
+            // 何も変化させない
+            _ => X64InstName::DIV,
+        }
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_divrm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // REX.W + 0xf7 /6
+        // dst-operand -> r/m field in ModR/M and related b-bit in REX
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // opcode
+        codes.push(0xf7);
+
+        // modr/m (Mだけど /6 なのでregフィールドに6を入れる)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | Self::modrm_reg_field(6) | rm_field);
+    }
+}
+
+#[cfg(test)]
+mod div_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_generate_divrm64() {
+        // 48 f7 f1    div rcx
+        // 49 f7 f2    div r10
+        let assembler = preprocess("main:\n  div rcx\n  div r10\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::DIVRM64, symbol.insts[0].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_divrm64_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_divrm64_inst(&mut codes, &symbol.insts[1]);
+        assert_eq!(vec![0x48, 0xf7, 0xf1, 0x49, 0xf7, 0xf2], codes);
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
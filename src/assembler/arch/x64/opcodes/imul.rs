@@ -55,9 +55,11 @@ impl X64Assembler {
         codes.push(0xaf);
 
         // ModR/M(RM)
+        // オフセットが設定されている -> アドレッシング方法が異なる
         let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
-        let rm_field = Self::modrm_rm_field(inst.src_regnumber);
-        codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
     }
 }
 
@@ -74,7 +76,9 @@ impl X64Instruction {
                     return X64InstName::IMULR64RM64IMM32;
                 }
 
-                if dst.is_register() && src.is_register() {
+                if dst.is_register() && src.is_register()
+                    || dst.is_register() && src.is_addressing()
+                {
                     // imul r64, r/m64
                     return X64InstName::IMULR64RM64;
                 }
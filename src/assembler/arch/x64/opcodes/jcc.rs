@@ -0,0 +1,63 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+// jz以外の条件付きジャンプ
+impl X64Instruction {
+    pub fn new_jcc(name: X64InstName, jump_op: X64Operand) -> Self {
+        Self::new(name, X64InstKind::UNARY(jump_op))
+    }
+    pub fn change_jcc_opcode(
+        name: &X64InstName,
+        _op_size: &OperandSize,
+        _op: &X64Operand,
+    ) -> X64InstName {
+        // jcc rel32
+        match name {
+            X64InstName::JNZ => X64InstName::JNZREL32,
+            X64InstName::JL => X64InstName::JLREL32,
+            X64InstName::JLE => X64InstName::JLEREL32,
+            X64InstName::JG => X64InstName::JGREL32,
+            X64InstName::JGE => X64InstName::JGEREL32,
+            _ => panic!("not a conditional jump -> {:?}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod jcc_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::assembler::X64Assembler;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_change_jccrel32() {
+        // main:
+        //   jne .L1
+        //   jge .L1
+        // .L1:
+        let assembler = preprocess("main:\n  jne .L1\n  jge .L1\n.L1:\n");
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(X64InstName::JNZREL32, symbol.insts[0].name);
+            assert_eq!(X64InstName::JGEREL32, symbol.insts[1].name);
+        }
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
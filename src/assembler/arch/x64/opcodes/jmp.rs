@@ -1,6 +1,7 @@
 use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
 use crate::assembler::arch::x64::inst::{
-    inst_kind::{X64InstKind, X64Operand},
+    inst_kind::{X64InstKind, X64OpeKind, X64Operand},
     inst_name::X64InstName,
     X64Instruction,
 };
@@ -9,10 +10,30 @@ impl X64Instruction {
     pub fn new_jmp(jump_op: X64Operand) -> Self {
         Self::new(X64InstName::JMP, X64InstKind::UNARY(jump_op))
     }
-    pub fn change_jmp_opcode(op_size: &OperandSize, _op: &X64Operand) -> X64InstName {
-        match op_size {
+    pub fn change_jmp_opcode(_op_size: &OperandSize, op: &X64Operand) -> X64InstName {
+        match &op.kind {
+            // jmp r/m64 (ジャンプテーブル経由の間接ジャンプ)
+            X64OpeKind::LABELINDEXED(_label, _index, _scale) => X64InstName::JMPRM64,
             // jmp rel32
             _ => X64InstName::JMPREL32,
         }
     }
 }
+
+impl X64Assembler {
+    pub fn generate_jmprm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // 0xff /4
+        // 飛び先は64bitで読むが,REX.Wは要らない
+        // インデックスレジスタが拡張レジスタであればREX.Xを立てる
+        let index_expanded_bit = Self::rex_prefix_xbit(inst.dst_expanded);
+        Self::push_rex_prefix_if_needed(codes, index_expanded_bit);
+
+        // jmp-opcode
+        codes.push(0xff);
+
+        // modr/m, sib, disp32
+        if let X64InstKind::UNARY(op) = &inst.kind {
+            Self::push_modrm(codes, 0x20, inst.dst_regnumber, op);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_lea(src: X64Operand, dst: X64Operand) -> Self {
+        Self::new(X64InstName::LEA, X64InstKind::BINARY(src, dst))
+    }
+    pub fn change_lea_opcode(
+        op_size: &OperandSize,
+        src: &X64Operand,
+        dst: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            // lea r64, m
+            OperandSize::QUADWORD
+                if dst.is_register() && (src.is_addressing() || src.is_rip_relative()) =>
+            {
+                X64InstName::LEAR64M
+            }
+            // 何も変化させない
+            _ => X64InstName::LEA,
+        }
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_lear64m_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. lea rax, -8[rbp]
+        // REX.W + 0x8d /r
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x8d);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+}
+
+#[cfg(test)]
+mod lea_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_change_lear64m() {
+        // main:
+        //   lea rax, -8[rbp]
+        //   lea r10, [r11]
+        let assembler = preprocess("main:\n  lea rax, -8[rbp]\n  lea r10, [r11]\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        for lea_inst in symbol.insts.iter() {
+            assert_eq!(X64InstName::LEAR64M, lea_inst.name);
+        }
+        assert!(symbol.insts[1].src_expanded);
+        assert_eq!(0, symbol.insts[1].load_offset);
+    }
+
+    #[test]
+    fn test_generate_lea_rip_relative() {
+        // 4c 8d 15 00 00 00 00    lea r10, x[rip]
+        let assembler = preprocess("main:\n  lea r10, x[rip]\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::LEAR64M, symbol.insts[0].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_lear64m_inst(&mut codes, &symbol.insts[0]);
+        assert_eq!(vec![0x4c, 0x8d, 0x15, 0x00, 0x00, 0x00, 0x00], codes);
+    }
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
@@ -1,15 +1,26 @@
 pub mod add;
+pub mod and;
 pub mod call;
 pub mod cmp;
 pub mod cqo;
+pub mod div;
 pub mod idiv;
 pub mod imul;
+pub mod jcc;
 pub mod jmp;
 pub mod jz;
+pub mod lea;
 pub mod mov;
+pub mod movsx;
+pub mod movzx;
 pub mod neg;
+pub mod not;
+pub mod or;
 pub mod pop;
 pub mod push;
 pub mod ret;
+pub mod setcc;
+pub mod shift;
 pub mod sub;
 pub mod syscall;
+pub mod xor;
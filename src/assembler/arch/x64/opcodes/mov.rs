@@ -13,8 +13,6 @@ impl X64Instruction {
     }
 }
 
-pub const MODRM_REGISTER_REGISTER: u8 = 0xc0;
-
 impl X64Assembler {
     pub fn generate_movrm64imm32_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
         // e.g. mov rax, 30
@@ -27,20 +25,9 @@ impl X64Assembler {
         codes.push(0xc7);
 
         // modr/m (MI)
-        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
-
         // オフセットが設定されている -> アドレッシング方法が異なる
-        if inst.store_offset != 0 {
-            codes.push(MODRM_REGISTER_DISPLACEMENT8 | rm_field);
-        } else {
-            codes.push(MODRM_REGISTER_REGISTER | rm_field);
-        }
-
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.store_offset != 0 {
-            codes.push((-inst.store_offset) as u8);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0, inst.dst_regnumber, dst);
         }
 
         // immediate-value
@@ -48,6 +35,19 @@ impl X64Assembler {
             codes.push(*b);
         }
     }
+    pub fn generate_movr64imm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. mov rax, 1099511627776
+        // REX.W + 0xb8+rd io
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // opcode (レジスタ番号を下位3bitに埋め込む)
+        let op_reg_number = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(0xb8 | op_reg_number);
+
+        // immediate-value
+        codes.extend_from_slice(&(inst.immediate_value as u64).to_le_bytes());
+    }
     pub fn generate_movrm64r64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
         // e.g. mov rax, r15
         // dst-operand -> r/m field in ModR/M and related b-bit
@@ -62,19 +62,9 @@ impl X64Assembler {
 
         // modr/m (MR)
         // オフセットが設定されている -> アドレッシング方法が異なる
-        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
         let reg_field = Self::modrm_reg_field(inst.src_regnumber);
-        if inst.store_offset != 0 {
-            codes.push(MODRM_REGISTER_DISPLACEMENT8 | reg_field | rm_field);
-        } else {
-            codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
-        }
-
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.store_offset != 0 {
-            codes.push((-inst.store_offset) as u8);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.dst_regnumber, dst);
         }
     }
     pub fn generate_movr64rm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
@@ -90,17 +80,91 @@ impl X64Assembler {
         codes.push(0x8b);
 
         // modr/m (RM)
-        let rm_field = Self::modrm_rm_field(inst.src_regnumber);
         let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
-        codes.push(MODRM_REGISTER_DISPLACEMENT8 | reg_field | rm_field);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+    pub fn generate_movr32rm32_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. mov eax, DWORD PTR -4[rbp]
+        // 0x8b /r
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        Self::push_rex_prefix_if_needed(codes, dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x8b);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+    pub fn generate_movrm_sized_r_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. mov DWORD PTR -4[rbp], eax
+        // r/m32, r32 -> 0x89 /r
+        // r/m16, r16 -> 0x66 0x89 /r
+        // r/m8, r8   -> REX 0x88 /r
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_rbit(inst.src_expanded);
+        match &inst.name {
+            X64InstName::MOVRM16R16 => {
+                codes.push(OPERAND_SIZE_PREFIX);
+                Self::push_rex_prefix_if_needed(codes, dst_expanded_bit | src_expanded_bit);
+                codes.push(0x89);
+            }
+            X64InstName::MOVRM8R8 => {
+                // sil/dilなどを指定するために,拡張レジスタでなくても付加する
+                codes.push(REX_PREFIX_BASE | dst_expanded_bit | src_expanded_bit);
+                codes.push(0x88);
+            }
+            _ => {
+                Self::push_rex_prefix_if_needed(codes, dst_expanded_bit | src_expanded_bit);
+                codes.push(0x89);
+            }
+        }
 
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.load_offset != 0 {
-            codes.push((-inst.load_offset) as u8);
+        // modr/m (MR)
+        let reg_field = Self::modrm_reg_field(inst.src_regnumber);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.dst_regnumber, dst);
         }
     }
+    pub fn generate_movrm_sized_imm_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. mov BYTE PTR -1[rbp], 3
+        // r/m32, imm32 -> 0xc7 /0 id
+        // r/m16, imm16 -> 0x66 0xc7 /0 iw
+        // r/m8, imm8   -> 0xc6 /0 ib
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        let imm_size = match &inst.name {
+            X64InstName::MOVRM16IMM16 => {
+                codes.push(OPERAND_SIZE_PREFIX);
+                Self::push_rex_prefix_if_needed(codes, dst_expanded_bit);
+                codes.push(0xc7);
+                2
+            }
+            X64InstName::MOVRM8IMM8 => {
+                Self::push_rex_prefix_if_needed(codes, dst_expanded_bit);
+                codes.push(0xc6);
+                1
+            }
+            _ => {
+                Self::push_rex_prefix_if_needed(codes, dst_expanded_bit);
+                codes.push(0xc7);
+                4
+            }
+        };
+
+        // modr/m (MI)
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0, inst.dst_regnumber, dst);
+        }
+
+        // immediate-value
+        let bytes = (inst.immediate_value as u32).to_le_bytes();
+        codes.extend_from_slice(&bytes[..imm_size]);
+    }
 }
 
 impl X64Instruction {
@@ -111,6 +175,11 @@ impl X64Instruction {
     ) -> X64InstName {
         match op_size {
             OperandSize::QUADWORD => {
+                // imm32 は符号拡張されるので,収まらなければ imm64 を直接レジスタに置く
+                if dst.is_register() && src.is_immediate() && !src.fits_in_imm32() {
+                    // mov r64, imm64
+                    return X64InstName::MOVR64IMM64;
+                }
                 if dst.is_register() && src.is_immediate()
                     || dst.is_addressing() && src.is_immediate()
                 {
@@ -130,6 +199,38 @@ impl X64Instruction {
                 }
                 X64InstName::MOV
             }
+            OperandSize::DOUBLEWORD => {
+                if src.is_immediate() {
+                    // mov r/m32, imm32
+                    return X64InstName::MOVRM32IMM32;
+                }
+                if src.is_register() {
+                    // mov r/m32, r32
+                    return X64InstName::MOVRM32R32;
+                }
+                if dst.is_register() && src.is_addressing() {
+                    // mov r32, r/m32
+                    return X64InstName::MOVR32RM32;
+                }
+                X64InstName::MOV
+            }
+            // 16bit/8bitはメモリへの書き込みのみ
+            OperandSize::WORD if dst.is_addressing() => {
+                if src.is_immediate() {
+                    // mov r/m16, imm16
+                    return X64InstName::MOVRM16IMM16;
+                }
+                // mov r/m16, r16
+                X64InstName::MOVRM16R16
+            }
+            OperandSize::BYTE if dst.is_addressing() => {
+                if src.is_immediate() {
+                    // mov r/m8, imm8
+                    return X64InstName::MOVRM8IMM8;
+                }
+                // mov r/m8, r8
+                X64InstName::MOVRM8R8
+            }
             // 何も変化させない
             _ => X64InstName::MOV,
         }
@@ -164,6 +265,72 @@ mod mov_opcode_tests {
             assert_eq!(X64InstName::MOVRM64R64, mov_inst.name);
         }
     }
+    #[test]
+    fn test_generate_sized_store() {
+        // 44 89 55 fc       mov DWORD PTR -4[rbp], r10d
+        // 66 44 89 5d fa    mov WORD PTR -6[rbp], r11w
+        // 40 88 45 f9       mov BYTE PTR -7[rbp], al
+        // c6 45 f8 03       mov BYTE PTR -8[rbp], 3
+        let assembler = preprocess(
+            "main:\n  mov DWORD PTR -4[rbp], r10d\n  mov WORD PTR -6[rbp], r11w\n  mov BYTE PTR -7[rbp], al\n  mov BYTE PTR -8[rbp], 3\n",
+        );
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::MOVRM32R32, symbol.insts[0].name);
+        assert_eq!(X64InstName::MOVRM16R16, symbol.insts[1].name);
+        assert_eq!(X64InstName::MOVRM8R8, symbol.insts[2].name);
+        assert_eq!(X64InstName::MOVRM8IMM8, symbol.insts[3].name);
+
+        let mut codes = Vec::new();
+        for inst in symbol.insts[..3].iter() {
+            X64Assembler::generate_movrm_sized_r_inst(&mut codes, inst);
+        }
+        X64Assembler::generate_movrm_sized_imm_inst(&mut codes, &symbol.insts[3]);
+        assert_eq!(
+            vec![
+                0x44, 0x89, 0x55, 0xfc, 0x66, 0x44, 0x89, 0x5d, 0xfa, 0x40, 0x88, 0x45, 0xf9, 0xc6,
+                0x45, 0xf8, 0x03
+            ],
+            codes
+        );
+    }
+    #[test]
+    fn test_generate_movr32rm32() {
+        // 44 8b 55 fc    mov r10d, DWORD PTR -4[rbp]
+        // 45 89 d2       mov r10d, r10d
+        let assembler = preprocess("main:\n  mov r10d, DWORD PTR -4[rbp]\n  mov r10d, r10d\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::MOVR32RM32, symbol.insts[0].name);
+        assert_eq!(X64InstName::MOVRM32R32, symbol.insts[1].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_movr32rm32_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_movrm_sized_r_inst(&mut codes, &symbol.insts[1]);
+        assert_eq!(vec![0x44, 0x8b, 0x55, 0xfc, 0x45, 0x89, 0xd2], codes);
+    }
+    #[test]
+    fn test_generate_mov_with_displacement() {
+        // 4d 8b 53 08                mov r10, 8[r11]
+        // 4c 8b 95 50 ff ff ff       mov r10, -176[rbp]
+        // 45 89 93 a0 00 00 00       mov DWORD PTR 160[r11], r10d
+        let assembler = preprocess(
+            "main:\n  mov r10, 8[r11]\n  mov r10, -176[rbp]\n  mov DWORD PTR 160[r11], r10d\n",
+        );
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::MOVR64RM64, symbol.insts[0].name);
+        assert_eq!(X64InstName::MOVRM32R32, symbol.insts[2].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_movr64rm64_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_movr64rm64_inst(&mut codes, &symbol.insts[1]);
+        X64Assembler::generate_movrm_sized_r_inst(&mut codes, &symbol.insts[2]);
+        assert_eq!(
+            vec![
+                0x4d, 0x8b, 0x53, 0x08, 0x4c, 0x8b, 0x95, 0x50, 0xff, 0xff, 0xff, 0x45, 0x89, 0x93,
+                0xa0, 0x00, 0x00, 0x00
+            ],
+            codes
+        );
+    }
     fn preprocess(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
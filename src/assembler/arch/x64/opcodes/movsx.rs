@@ -0,0 +1,118 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_movsx(name: X64InstName, src: X64Operand, dst: X64Operand) -> Self {
+        Self::new(name, X64InstKind::BINARY(src, dst))
+    }
+    pub fn change_movsx_opcode(
+        name: &X64InstName,
+        op_size: &OperandSize,
+        src: &X64Operand,
+        dst: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            OperandSize::QUADWORD if dst.is_register() => {
+                match (name, src.check_operand_size()) {
+                    // movsx r64, r/m8
+                    (X64InstName::MOVSX, OperandSize::BYTE) => X64InstName::MOVSXR64RM8,
+                    // movsx r64, r/m16
+                    (X64InstName::MOVSX, OperandSize::WORD) => X64InstName::MOVSXR64RM16,
+                    // movsxd r64, r/m32
+                    (X64InstName::MOVSXD, OperandSize::DOUBLEWORD) => X64InstName::MOVSXDR64RM32,
+                    _ => name.clone(),
+                }
+            }
+            // 何も変化させない
+            _ => name.clone(),
+        }
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_movsx_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. movsx rax, BYTE PTR -1[rbp]
+        // e.g. movsxd r10, r10d
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        match &inst.name {
+            X64InstName::MOVSXR64RM8 => codes.extend_from_slice(&[0x0f, 0xbe]),
+            X64InstName::MOVSXR64RM16 => codes.extend_from_slice(&[0x0f, 0xbf]),
+            X64InstName::MOVSXDR64RM32 => codes.push(0x63),
+            _ => panic!("not a movsx instruction -> {:?}", inst.name),
+        }
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+}
+
+#[cfg(test)]
+mod movsx_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_change_movsx() {
+        // main:
+        //   movsx rax, BYTE PTR -1[rbp]
+        //   movsx r10, r11w
+        //   movsxd r10, DWORD PTR [r11]
+        let assembler = preprocess(
+            "main:\n  movsx rax, BYTE PTR -1[rbp]\n  movsx r10, r11w\n  movsxd r10, DWORD PTR [r11]\n",
+        );
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::MOVSXR64RM8, symbol.insts[0].name);
+        assert_eq!(X64InstName::MOVSXR64RM16, symbol.insts[1].name);
+        assert_eq!(X64InstName::MOVSXDR64RM32, symbol.insts[2].name);
+    }
+
+    #[test]
+    fn test_generate_movsx() {
+        // 48 0f be 45 ff    movsx rax, BYTE PTR -1[rbp]
+        // 4d 0f bf d3       movsx r10, r11w
+        // 4d 63 d2          movsxd r10, r10d
+        let assembler = preprocess(
+            "main:\n  movsx rax, BYTE PTR -1[rbp]\n  movsx r10, r11w\n  movsxd r10, r10d\n",
+        );
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+
+        let mut codes = Vec::new();
+        for inst in symbol.insts.iter() {
+            X64Assembler::generate_movsx_inst(&mut codes, inst);
+        }
+        assert_eq!(
+            vec![0x48, 0x0f, 0xbe, 0x45, 0xff, 0x4d, 0x0f, 0xbf, 0xd3, 0x4d, 0x63, 0xd2],
+            codes
+        );
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
@@ -0,0 +1,120 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_movzx(src: X64Operand, dst: X64Operand) -> Self {
+        Self::new(X64InstName::MOVZX, X64InstKind::BINARY(src, dst))
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_movzx_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. movzx r10, r10b
+        // e.g. movzx rax, WORD PTR -2[rbp]
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x0f);
+        match &inst.name {
+            X64InstName::MOVZXR64RM16 => codes.push(0xb7),
+            _ => codes.push(0xb6),
+        }
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+}
+
+impl X64Instruction {
+    pub fn change_movzx_opcode(
+        op_size: &OperandSize,
+        src: &X64Operand,
+        dst: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            OperandSize::QUADWORD => {
+                if !dst.is_register() {
+                    return X64InstName::MOVZX;
+                }
+                match src.check_operand_size() {
+                    // movzx r64, r/m8
+                    OperandSize::BYTE => X64InstName::MOVZXR64RM8,
+                    // movzx r64, r/m16
+                    OperandSize::WORD => X64InstName::MOVZXR64RM16,
+                    _ => X64InstName::MOVZX,
+                }
+            }
+            // 何も変化させない
+            _ => X64InstName::MOVZX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod movzx_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_generate_movzxr64rm8() {
+        // 48 0f b6 c0    movzx rax, al
+        // 4d 0f b6 d2    movzx r10, r10b
+        let assembler = preprocess("main:\n  movzx rax, al\n  movzx r10, r10b\n");
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(X64InstName::MOVZXR64RM8, symbol.insts[0].name);
+
+            let mut codes = Vec::new();
+            X64Assembler::generate_movzx_inst(&mut codes, &symbol.insts[0]);
+            X64Assembler::generate_movzx_inst(&mut codes, &symbol.insts[1]);
+            assert_eq!(vec![0x48, 0x0f, 0xb6, 0xc0, 0x4d, 0x0f, 0xb6, 0xd2], codes);
+        }
+    }
+
+    #[test]
+    fn test_generate_movzx_with_memory() {
+        // 4c 0f b6 55 ff    movzx r10, BYTE PTR -1[rbp]
+        // 4d 0f b7 1c 24    movzx r11, WORD PTR [r12]
+        let assembler =
+            preprocess("main:\n  movzx r10, BYTE PTR -1[rbp]\n  movzx r11, WORD PTR [r12]\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::MOVZXR64RM8, symbol.insts[0].name);
+        assert_eq!(X64InstName::MOVZXR64RM16, symbol.insts[1].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_movzx_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_movzx_inst(&mut codes, &symbol.insts[1]);
+        assert_eq!(
+            vec![0x4c, 0x0f, 0xb6, 0x55, 0xff, 0x4d, 0x0f, 0xb7, 0x1c, 0x24],
+            codes
+        );
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
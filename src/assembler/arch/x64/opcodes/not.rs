@@ -0,0 +1,39 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_not(not_op: X64Operand) -> Self {
+        Self::new(X64InstName::NOT, X64InstKind::UNARY(not_op))
+    }
+    pub fn change_not_opcode(op_size: &OperandSize, _op: &X64Operand) -> X64InstName {
+        match op_size {
+            // not r/m64
+            OperandSize::QUADWORD => X64InstName::NOTRM64,
+            // 何も変化させない
+            _ => X64InstName::NOT,
+        }
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_notrm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // REX.W + 0xf7 /2
+        // dst-operand -> r/m field in ModR/M and related b-bit in REX
+
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // not-opcode
+        codes.push(0xf7);
+
+        // modr/m (Mだけど /2 なのでマスクする)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | rm_field | 0x10);
+    }
+}
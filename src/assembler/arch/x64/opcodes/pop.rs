@@ -1,5 +1,6 @@
 use crate::assembler::arch::x64::analyze::OperandSize;
 use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::REX_PREFIX_BASE;
 use crate::assembler::arch::x64::inst::{
     inst_kind::{X64InstKind, X64Operand},
     inst_name::X64InstName,
@@ -24,6 +25,10 @@ impl X64Instruction {
 impl X64Assembler {
     pub fn generate_popr64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
         // pop r64 -> pop opcode と 引数のレジスタ番号
+        // r8-r15 は REX.B で指定する
+        if inst.dst_expanded {
+            codes.push(REX_PREFIX_BASE | Self::rex_prefix_bbit(true));
+        }
         let op_reg_number = Self::modrm_rm_field(inst.dst_regnumber);
         codes.push(0x58 | op_reg_number);
     }
@@ -1,5 +1,6 @@
 use crate::assembler::arch::x64::analyze::OperandSize;
 use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::REX_PREFIX_BASE;
 use crate::assembler::arch::x64::inst::{
     inst_kind::{X64InstKind, X64Operand},
     inst_name::X64InstName,
@@ -24,7 +25,47 @@ impl X64Instruction {
 impl X64Assembler {
     pub fn generate_pushr64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
         // push r64 -> push opcode と 引数のレジスタ番号
+        // r8-r15 は REX.B で指定する
+        if inst.dst_expanded {
+            codes.push(REX_PREFIX_BASE | Self::rex_prefix_bbit(true));
+        }
         let op_reg_number = Self::modrm_rm_field(inst.dst_regnumber);
         codes.push(0x50 | op_reg_number);
     }
 }
+
+#[cfg(test)]
+mod push_opcode_tests {
+    use crate::assembler::arch::x64::assembler::X64Assembler;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_generate_pushr64_and_popr64() {
+        // main:
+        //   push rbp
+        //   push r10
+        //   pop r10
+        let mut assembler = preprocess("main:\n  push rbp\n  push r10\n  pop r10\n");
+        assembler.analyze();
+        assembler.codegen();
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(
+                vec![0x55, 0x41, 0x52, 0x41, 0x5a],
+                symbol.codes[..5].to_vec()
+            );
+        }
+    }
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler
+    }
+}
@@ -0,0 +1,134 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_setcc(name: X64InstName, dst: X64Operand) -> Self {
+        Self::new(name, X64InstKind::UNARY(dst))
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_setccrm8_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. setl r10b
+        // dst-operand -> r/m field in ModR/M and related b-bit in REX
+        // rex-prefix
+        // sil/dilなどを指定するために,拡張レジスタでなくても付加する
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | dst_expanded_bit);
+
+        // opcode
+        codes.push(0x0f);
+        codes.push(Self::setcc_opcode(&inst.name));
+
+        // modr/m (M)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | rm_field);
+    }
+    fn setcc_opcode(name: &X64InstName) -> u8 {
+        match name {
+            X64InstName::SETBRM8 => 0x92,
+            X64InstName::SETAERM8 => 0x93,
+            X64InstName::SETERM8 => 0x94,
+            X64InstName::SETNERM8 => 0x95,
+            X64InstName::SETBERM8 => 0x96,
+            X64InstName::SETARM8 => 0x97,
+            X64InstName::SETLRM8 => 0x9c,
+            X64InstName::SETGERM8 => 0x9d,
+            X64InstName::SETLERM8 => 0x9e,
+            X64InstName::SETGRM8 => 0x9f,
+            _ => panic!("not a setcc instruction -> {:?}", name),
+        }
+    }
+}
+
+impl X64Instruction {
+    pub fn change_setcc_opcode(
+        name: &X64InstName,
+        op_size: &OperandSize,
+        _op: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            // setcc r/m8
+            OperandSize::BYTE => match name {
+                X64InstName::SETE => X64InstName::SETERM8,
+                X64InstName::SETNE => X64InstName::SETNERM8,
+                X64InstName::SETL => X64InstName::SETLRM8,
+                X64InstName::SETLE => X64InstName::SETLERM8,
+                X64InstName::SETG => X64InstName::SETGRM8,
+                X64InstName::SETGE => X64InstName::SETGERM8,
+                X64InstName::SETB => X64InstName::SETBRM8,
+                X64InstName::SETBE => X64InstName::SETBERM8,
+                X64InstName::SETA => X64InstName::SETARM8,
+                X64InstName::SETAE => X64InstName::SETAERM8,
+                _ => name.clone(),
+            },
+            // 何も変化させない
+            _ => name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod setcc_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_change_setccrm8() {
+        // main:
+        //   setle al
+        let assembler = preprocess("main:\n  setle al\n");
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            assert_eq!(X64InstName::SETLERM8, symbol.insts[0].name);
+        }
+    }
+
+    #[test]
+    fn test_generate_setccrm8() {
+        // 40 0f 94 c0    sete al
+        // 41 0f 9c c2    setl r10b
+        let assembler = preprocess("main:\n  sete al\n  setl r10b\n");
+        if let Some(symbol) = assembler.src_file.symbols_map.get("main") {
+            let mut codes = Vec::new();
+            X64Assembler::generate_setccrm8_inst(&mut codes, &symbol.insts[0]);
+            X64Assembler::generate_setccrm8_inst(&mut codes, &symbol.insts[1]);
+            assert_eq!(vec![0x40, 0x0f, 0x94, 0xc0, 0x41, 0x0f, 0x9c, 0xc2], codes);
+        }
+    }
+
+    #[test]
+    fn test_generate_unsigned_setccrm8() {
+        // 40 0f 92 c0    setb al
+        // 41 0f 97 c3    seta r11b
+        let assembler = preprocess("main:\n  setb al\n  seta r11b\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::SETBRM8, symbol.insts[0].name);
+        assert_eq!(X64InstName::SETARM8, symbol.insts[1].name);
+
+        let mut codes = Vec::new();
+        X64Assembler::generate_setccrm8_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_setccrm8_inst(&mut codes, &symbol.insts[1]);
+        assert_eq!(vec![0x40, 0x0f, 0x92, 0xc0, 0x41, 0x0f, 0x97, 0xc3], codes);
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
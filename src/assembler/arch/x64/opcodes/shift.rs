@@ -0,0 +1,146 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Instruction {
+    pub fn new_shift(name: X64InstName, src: X64Operand, dst: X64Operand) -> Self {
+        Self::new(name, X64InstKind::BINARY(src, dst))
+    }
+}
+
+impl X64Assembler {
+    pub fn generate_shiftrm64imm8_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. shl rax, 3
+        // REX.W + 0xc1 /n ib
+        // dst-operand -> r/m field in ModR/M and related b-bit in REX
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // opcode
+        codes.push(0xc1);
+
+        // modr/m (MI)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | Self::shift_reg_field(&inst.name) | rm_field);
+
+        // immediate-value
+        codes.push(inst.immediate_value as u8);
+    }
+    pub fn generate_shiftrm64cl_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. sar rax, cl
+        // REX.W + 0xd3 /n
+        // シフト量はclに決まっているので,srcはエンコードしない
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // opcode
+        codes.push(0xd3);
+
+        // modr/m (MC)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | Self::shift_reg_field(&inst.name) | rm_field);
+    }
+    // シフトの種類はModR/Mのregフィールドで区別する
+    fn shift_reg_field(name: &X64InstName) -> u8 {
+        match name {
+            X64InstName::SHLRM64IMM8 | X64InstName::SHLRM64CL => Self::modrm_reg_field(4),
+            X64InstName::SHRRM64IMM8 | X64InstName::SHRRM64CL => Self::modrm_reg_field(5),
+            X64InstName::SARRM64IMM8 | X64InstName::SARRM64CL => Self::modrm_reg_field(7),
+            _ => panic!("not a shift instruction -> {:?}", name),
+        }
+    }
+}
+
+impl X64Instruction {
+    pub fn change_shift_opcode(
+        name: &X64InstName,
+        op_size: &OperandSize,
+        src: &X64Operand,
+        _dst: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            OperandSize::QUADWORD => {
+                if src.is_immediate() {
+                    // shift r/m64, imm8
+                    return match name {
+                        X64InstName::SHL => X64InstName::SHLRM64IMM8,
+                        X64InstName::SAR => X64InstName::SARRM64IMM8,
+                        X64InstName::SHR => X64InstName::SHRRM64IMM8,
+                        _ => name.clone(),
+                    };
+                }
+                if src.is_register() {
+                    // shift r/m64, cl
+                    return match name {
+                        X64InstName::SHL => X64InstName::SHLRM64CL,
+                        X64InstName::SAR => X64InstName::SARRM64CL,
+                        X64InstName::SHR => X64InstName::SHRRM64CL,
+                        _ => name.clone(),
+                    };
+                }
+                name.clone()
+            }
+            // 何も変化させない
+            _ => name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod shift_opcode_tests {
+    use super::*;
+    use crate::assembler::arch::x64::file::X64AssemblyFile;
+    use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::structure::AssemblyFile;
+    use crate::target::Target;
+
+    #[test]
+    fn test_change_shift_opcode() {
+        // main:
+        //   shl rax, 3
+        //   sar r10, cl
+        let assembler = preprocess("main:\n  shl rax, 3\n  sar r10, cl\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        assert_eq!(X64InstName::SHLRM64IMM8, symbol.insts[0].name);
+        assert_eq!(X64InstName::SARRM64CL, symbol.insts[1].name);
+    }
+
+    #[test]
+    fn test_generate_shift() {
+        // 48 c1 e0 03    shl rax, 3
+        // 49 d3 fa       sar r10, cl
+        // 49 c1 eb 02    shr r11, 2
+        // 48 d3 e1       shl rcx, cl
+        let assembler =
+            preprocess("main:\n  shl rax, 3\n  sar r10, cl\n  shr r11, 2\n  shl rcx, cl\n");
+        let symbol = assembler.src_file.symbols_map.get("main").unwrap();
+        let mut codes = Vec::new();
+        X64Assembler::generate_shiftrm64imm8_inst(&mut codes, &symbol.insts[0]);
+        X64Assembler::generate_shiftrm64cl_inst(&mut codes, &symbol.insts[1]);
+        X64Assembler::generate_shiftrm64imm8_inst(&mut codes, &symbol.insts[2]);
+        X64Assembler::generate_shiftrm64cl_inst(&mut codes, &symbol.insts[3]);
+        assert_eq!(
+            vec![
+                0x48, 0xc1, 0xe0, 0x03, 0x49, 0xd3, 0xfa, 0x49, 0xc1, 0xeb, 0x02, 0x48, 0xd3, 0xe1
+            ],
+            codes
+        );
+    }
+
+    fn preprocess(input: &str) -> X64Assembler {
+        let target = Target::new();
+        let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
+        let x64_assembly_file = X64AssemblyFile::new(assembly_file);
+        let mut assembler = X64Assembler::new(x64_assembly_file);
+
+        lex_intel::lexing_intel_syntax(&mut assembler);
+        assembler.parse_intel_syntax();
+        assembler.analyze();
+        assembler
+    }
+}
@@ -25,20 +25,9 @@ impl X64Assembler {
         codes.push(0x81);
 
         // modr/m (MI だけど /5なのでマスクする )
-        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
-
         // オフセットが設定されている -> アドレッシング方法が異なる
-        if inst.store_offset != 0 {
-            codes.push(MODRM_REGISTER_DISPLACEMENT8 | rm_field | 0x28);
-        } else {
-            codes.push(MODRM_REGISTER_REGISTER | rm_field | 0x28);
-        }
-
-        // displacement
-        // もしoffsetが設定されていれば加える
-        // TODO: 今はマイナスに決め打ち
-        if inst.store_offset != 0 {
-            codes.push((-inst.store_offset) as u8);
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0x28, inst.dst_regnumber, dst);
         }
 
         // immediate-value
@@ -63,6 +52,24 @@ impl X64Assembler {
         let reg_field = Self::modrm_reg_field(inst.src_regnumber);
         codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
     }
+    pub fn generate_subr64rm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. sub rax, -8[rbp]
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x2b);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
 }
 
 impl X64Instruction {
@@ -84,6 +91,10 @@ impl X64Instruction {
                     // sub r/m64, r64
                     return X64InstName::SUBRM64R64;
                 }
+                if dst.is_register() && src.is_addressing() {
+                    // sub r64, r/m64
+                    return X64InstName::SUBR64RM64;
+                }
                 X64InstName::SUB
             }
             // 何も変化させない
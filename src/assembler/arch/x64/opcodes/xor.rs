@@ -0,0 +1,107 @@
+use crate::assembler::arch::x64::analyze::OperandSize;
+use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::codegen::*;
+use crate::assembler::arch::x64::inst::{
+    inst_kind::{X64InstKind, X64Operand},
+    inst_name::X64InstName,
+    X64Instruction,
+};
+
+impl X64Assembler {
+    pub fn generate_xorrm64imm32_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. xor rax, 1
+        // dst-operand -> r/m field in ModR/M and related b-bit in REX
+        // rex-prefix
+
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit);
+
+        // opcode
+        codes.push(0x81);
+
+        // modr/m (MIだけど /6 なのでマスクする)
+        // オフセットが設定されている -> アドレッシング方法が異なる
+        if let X64InstKind::BINARY(_src, dst) = &inst.kind {
+            Self::push_modrm(codes, 0x30, inst.dst_regnumber, dst);
+        }
+
+        // immediate-value
+        for b in (inst.immediate_value as u32).to_le_bytes().to_vec().iter() {
+            codes.push(*b);
+        }
+    }
+    pub fn generate_xorrm64r64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. xor rax, r15
+        // dst-operand -> r/m field in ModR/M and related b-bit
+        // src-operand -> reg field in ModR/M and related r-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_bbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_rbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x31);
+
+        // modr/m (MR)
+        let rm_field = Self::modrm_rm_field(inst.dst_regnumber);
+        let reg_field = Self::modrm_reg_field(inst.src_regnumber);
+        codes.push(MODRM_REGISTER_REGISTER | reg_field | rm_field);
+    }
+    pub fn generate_xorr64rm64_inst(codes: &mut Vec<u8>, inst: &X64Instruction) {
+        // e.g. xor rax, -8[rbp]
+        // dst-operand -> reg field in ModR/M and related r-bit
+        // src-operand -> r/m field in ModR/M and related b-bit
+        // rex-prefix
+        let dst_expanded_bit = Self::rex_prefix_rbit(inst.dst_expanded);
+        let src_expanded_bit = Self::rex_prefix_bbit(inst.src_expanded);
+        codes.push(REX_PREFIX_BASE | REX_PREFIX_WBIT | dst_expanded_bit | src_expanded_bit);
+
+        // opcode
+        codes.push(0x33);
+
+        // modr/m (RM)
+        let reg_field = Self::modrm_reg_field(inst.dst_regnumber);
+        if let X64InstKind::BINARY(src, _dst) = &inst.kind {
+            Self::push_modrm(codes, reg_field, inst.src_regnumber, src);
+        }
+    }
+}
+
+impl X64Instruction {
+    pub fn new_xor(src: X64Operand, dst: X64Operand) -> Self {
+        Self::new(X64InstName::XOR, X64InstKind::BINARY(src, dst))
+    }
+}
+
+impl X64Instruction {
+    pub fn change_xor_opcode(
+        op_size: &OperandSize,
+        src: &X64Operand,
+        dst: &X64Operand,
+    ) -> X64InstName {
+        match op_size {
+            OperandSize::QUADWORD => {
+                if dst.is_register() && src.is_immediate()
+                    || dst.is_addressing() && src.is_immediate()
+                {
+                    // xor r/m64, imm32
+                    return X64InstName::XORRM64IMM32;
+                }
+
+                if dst.is_register() && src.is_register()
+                    || dst.is_addressing() && src.is_register()
+                {
+                    // xor r/m64, r64
+                    return X64InstName::XORRM64R64;
+                }
+                if dst.is_register() && src.is_addressing() {
+                    // xor r64, r/m64
+                    return X64InstName::XORR64RM64;
+                }
+                X64InstName::XOR
+            }
+            // 何も変化させない
+            _ => X64InstName::XOR,
+        }
+    }
+}
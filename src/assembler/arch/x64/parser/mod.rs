@@ -1,23 +1,44 @@
 pub mod parse_atandt;
 pub mod parse_intel;
 
+use crate::assembler::arch::x64::analyze::OperandSize;
 use crate::assembler::arch::x64::asmtoken;
-use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::assembler::{X64Assembler, X64Section};
 use crate::assembler::arch::x64::inst::inst_kind::X64Operand;
-use crate::assembler::arch::x64::symbol::X64Symbol;
+use crate::assembler::arch::x64::inst::X64Instruction;
+use crate::assembler::arch::x64::symbol::{X64DataSymbol, X64Symbol};
 use crate::error::*;
 use asmtoken::{AsmToken, AsmTokenKind};
 
+use std::collections::BTreeMap;
+
 impl X64Assembler {
     pub fn consume_operand(&mut self) -> X64Operand {
         let cur = self.looking_token_clone();
         let cur_operand = match cur.kind {
+            // <size> PTR <memory>
+            // PTRは字句解析で読み飛ばしている
+            AsmTokenKind::DWORD | AsmTokenKind::WORD | AsmTokenKind::BYTE => {
+                self.read_token();
+                let memory = self.consume_operand();
+                let size = match cur.kind {
+                    AsmTokenKind::DWORD => OperandSize::DOUBLEWORD,
+                    AsmTokenKind::WORD => OperandSize::WORD,
+                    _ => OperandSize::BYTE,
+                };
+                return memory.with_ptr_size(size);
+            }
             AsmTokenKind::MINUS => {
                 // - <offset> [ <register> ]
                 self.read_token();
                 let offset_token = self.looking_token_clone();
                 if let AsmTokenKind::INTEGER(offset) = offset_token.kind {
                     self.read_token();
+
+                    // [ が続かなければ負の即値
+                    if self.looking_token_clone().kind != AsmTokenKind::LBRACKET {
+                        return X64Operand::new_integer(-offset);
+                    }
                     self.read_token(); // [
 
                     let reg_token = self.looking_token_clone();
@@ -31,9 +52,72 @@ impl X64Assembler {
                     panic!("offset must be integer in memory addressing");
                 }
             }
+            AsmTokenKind::LBRACKET => {
+                // [ <register> ]
+                self.read_token();
+                let reg_token = self.looking_token_clone();
+                if let AsmTokenKind::REG(name) = reg_token.kind {
+                    self.read_token();
+                    X64Operand::new_addressing(0, name.to_string())
+                } else {
+                    panic!("invalid register in memory addressing");
+                }
+            }
             AsmTokenKind::REG(name) => X64Operand::new_register(name),
-            AsmTokenKind::LABEL(name) => X64Operand::new_label(name),
-            AsmTokenKind::INTEGER(val) => X64Operand::new_integer(val),
+            AsmTokenKind::LABEL(name) => {
+                // <label> [ rip ]
+                self.read_token();
+
+                // [ が続かなければラベル
+                if self.looking_token_clone().kind != AsmTokenKind::LBRACKET {
+                    return X64Operand::new_label(name);
+                }
+                self.read_token(); // [
+
+                let base_token = self.looking_token_clone();
+                match base_token.kind {
+                    AsmTokenKind::LABEL(base) if base == "rip" => {
+                        self.read_token();
+                        X64Operand::new_rip_relative(name)
+                    }
+                    // <label> [ <index> * <scale> ]
+                    AsmTokenKind::REG(index) => {
+                        self.read_token();
+                        if self.looking_token_clone().kind != AsmTokenKind::ASTERISK {
+                            panic!("index register must be scaled in label addressing");
+                        }
+                        self.read_token(); // *
+
+                        match self.looking_token_clone().kind {
+                            AsmTokenKind::INTEGER(scale @ (1 | 2 | 4 | 8)) => {
+                                self.read_token();
+                                X64Operand::new_label_indexed(name, index, scale as u8)
+                            }
+                            _ => panic!("scale must be 1, 2, 4 or 8"),
+                        }
+                    }
+                    _ => panic!("label addressing must be relative to rip"),
+                }
+            }
+            AsmTokenKind::INTEGER(val) => {
+                // <offset> [ <register> ]
+                // -<offset> [ <register> ] に合わせて,符号を反転して保持する
+                self.read_token();
+
+                // [ が続かなければ即値
+                if self.looking_token_clone().kind != AsmTokenKind::LBRACKET {
+                    return X64Operand::new_integer(val);
+                }
+                self.read_token(); // [
+
+                let reg_token = self.looking_token_clone();
+                if let AsmTokenKind::REG(name) = reg_token.kind {
+                    self.read_token();
+                    X64Operand::new_addressing(-val, name.to_string())
+                } else {
+                    panic!("invalid register in memory addressing");
+                }
+            }
             // エラー生成
             _ => {
                 panic!("invalid operand found -> {:?}", cur);
@@ -48,10 +132,209 @@ impl X64Assembler {
         self.read_token();
 
         // directiveのチェック
-        if directive.starts_with("global") || directive.starts_with("globl") {
+        let directive_name = directive.split_whitespace().next().unwrap_or("");
+        match directive_name {
             // グローバルシンボルの指定
-            self.parse_global_directive(directive, position);
+            "global" | "globl" => self.parse_global_directive(directive, position),
+            // 以降の命令/データを配置するセクションの指定
+            "text" => self.cur_section = X64Section::TEXT,
+            "data" => self.cur_section = X64Section::DATA,
+            "bss" => self.cur_section = X64Section::BSS,
+            // e.g. .section .rodata
+            "section" => match directive.split_whitespace().nth(1) {
+                Some(".text") => self.cur_section = X64Section::TEXT,
+                Some(".rodata") => self.cur_section = X64Section::RODATA,
+                Some(".data") => self.cur_section = X64Section::DATA,
+                Some(".bss") => self.cur_section = X64Section::BSS,
+                _ => {}
+            },
+            // 次に定義するデータシンボルの境界
+            "align" => {
+                if let Some(alignment) = Self::directive_argument(&directive, position) {
+                    self.data_alignment = alignment as usize;
+                }
+            }
+            // データの定義
+            "byte" | "short" | "word" | "long" | "quad" | "zero" => {
+                self.parse_data_directive(directive, position)
+            }
+            // .string は終端のnull文字を付け, .ascii は付けない
+            "string" | "ascii" => self.parse_string_directive(directive, position),
+            _ => {}
+        }
+    }
+    // 直前に定義したデータシンボルに,値のバイト列を追加する
+    fn parse_data_directive(&mut self, directive: String, position: (usize, usize)) {
+        // .quad <label> はラベルのアドレスを置く(e.g. ジャンプテーブル)
+        // .quad <label>+8 のように,ラベルからのオフセットを続けられる
+        // 中身はリンク時に埋めるので,ここでは0にしておく
+        let mut arguments = directive.split_whitespace();
+        if let (Some("quad"), Some(label)) = (arguments.next(), arguments.next()) {
+            if label.starts_with(|c: char| c == '.' || c == '_' || c.is_ascii_alphabetic()) {
+                let (label_name, addend) = match label.find(['+', '-']) {
+                    Some(sign_pos) => match label[sign_pos..].trim_start_matches('+').parse() {
+                        Ok(addend) => (label[..sign_pos].to_string(), addend),
+                        Err(_) => {
+                            let err = Error::new(
+                                ErrorKind::AsmParse,
+                                position,
+                                ErrorMsg::MustBeIntegerLiteral,
+                            );
+                            err.found();
+                            return;
+                        }
+                    },
+                    None => (label.to_string(), 0),
+                };
+                let symbol_name = self.cur_data_symbol.to_string();
+                if let Some(symbol) = self.current_data_map().get_mut(&symbol_name) {
+                    symbol
+                        .label_refs
+                        .push((symbol.bytes.len(), label_name, addend));
+                }
+                self.append_data_bytes(vec![0x00; 8]);
+                return;
+            }
         }
+
+        let value = match Self::directive_argument(&directive, position) {
+            Some(value) => value,
+            None => return,
+        };
+        let bytes = match directive.split_whitespace().next().unwrap() {
+            "byte" => value.to_le_bytes()[..1].to_vec(),
+            "short" | "word" => value.to_le_bytes()[..2].to_vec(),
+            "long" => value.to_le_bytes()[..4].to_vec(),
+            "quad" => value.to_le_bytes()[..8].to_vec(),
+            _ => vec![0x00; value as usize],
+        };
+
+        self.append_data_bytes(bytes);
+    }
+    // e.g. string "hi\n" -> [0x68, 0x69, 0x0a, 0x00]
+    fn parse_string_directive(&mut self, directive: String, position: (usize, usize)) {
+        let (name, argument) = directive.split_at(directive.find(' ').unwrap_or(directive.len()));
+        let mut bytes = match Self::unescape_string(argument.trim()) {
+            Some(bytes) => bytes,
+            None => {
+                let err = Error::new(ErrorKind::AsmParse, position, ErrorMsg::MustBeStringLiteral);
+                err.found();
+                return;
+            }
+        };
+        if name == "string" {
+            bytes.push(0x00);
+        }
+        self.append_data_bytes(bytes);
+    }
+    // "..." の中身を,エスケープシーケンスを解釈したバイト列にする
+    fn unescape_string(literal: &str) -> Option<Vec<u8>> {
+        let input = literal.as_bytes();
+        if input.len() < 2 || input[0] != b'"' || input[input.len() - 1] != b'"' {
+            return None;
+        }
+
+        let body = &input[1..input.len() - 1];
+        let mut bytes = Vec::new();
+        let mut idx = 0;
+        while idx < body.len() {
+            if body[idx] != b'\\' || idx + 1 == body.len() {
+                bytes.push(body[idx]);
+                idx += 1;
+                continue;
+            }
+
+            idx += 1;
+            match body[idx] {
+                b'0'..=b'7' => {
+                    let length = body[idx..]
+                        .iter()
+                        .take(3)
+                        .take_while(|c| (b'0'..=b'7').contains(*c))
+                        .count();
+                    let value = body[idx..idx + length]
+                        .iter()
+                        .fold(0u32, |acc, c| acc * 8 + (c - b'0') as u32);
+                    bytes.push(value as u8);
+                    idx += length;
+                }
+                b'x' => {
+                    let length = body[idx + 1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_hexdigit())
+                        .count();
+                    let value = body[idx + 1..idx + 1 + length].iter().fold(0u32, |acc, c| {
+                        acc.wrapping_mul(16) + (*c as char).to_digit(16).unwrap()
+                    });
+                    bytes.push(value as u8);
+                    idx += length + 1;
+                }
+                c => {
+                    bytes.push(match c {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        b'b' => 0x08,
+                        b'f' => 0x0c,
+                        _ => c,
+                    });
+                    idx += 1;
+                }
+            }
+        }
+        Some(bytes)
+    }
+    // 直前に定義したデータシンボルに,バイト列を追加する
+    fn append_data_bytes(&mut self, bytes: Vec<u8>) {
+        let symbol_name = self.cur_data_symbol.to_string();
+        if let Some(symbol) = self.current_data_map().get_mut(&symbol_name) {
+            symbol.bytes.extend(bytes);
+        }
+    }
+    // 現在のセクションに対応するデータシンボルの表
+    fn current_data_map(&mut self) -> &mut BTreeMap<String, X64DataSymbol> {
+        match self.cur_section {
+            X64Section::RODATA => &mut self.src_file.rodata_map,
+            X64Section::BSS => &mut self.src_file.bss_map,
+            _ => &mut self.src_file.data_map,
+        }
+    }
+    // .text 中のラベルを関数シンボルとして登録する
+    // .global で先に登録されていれば,グローバルシンボルのままにする
+    pub fn define_text_symbol(&mut self, name: String, insts: Vec<X64Instruction>) {
+        let mut symbol = match self.src_file.symbols_map.remove(&name) {
+            Some(declared) if declared.is_global => X64Symbol::new_global(),
+            _ => X64Symbol::new_local(),
+        };
+        symbol.insts = insts;
+        self.src_file.symbols_map.insert(name, symbol);
+    }
+    // .data/.bss 中のラベルをデータシンボルとして登録する
+    pub fn define_data_symbol(&mut self, name: String) {
+        // .global で先に登録された,中身の無いシンボルは取り除く
+        let declared = self.src_file.symbols_map.remove(&name);
+
+        let mut symbol = X64DataSymbol::new(self.data_alignment);
+        symbol.is_global = declared.is_some_and(|s| s.is_global);
+        self.data_alignment = 1;
+        self.current_data_map().insert(name.to_string(), symbol);
+        self.cur_data_symbol = name;
+    }
+    // e.g. "long 3" -> 3
+    fn directive_argument(directive: &str, position: (usize, usize)) -> Option<i128> {
+        let argument = directive
+            .split_whitespace()
+            .nth(1)
+            .and_then(|arg| arg.parse::<i128>().ok());
+        if argument.is_none() {
+            let err = Error::new(
+                ErrorKind::AsmParse,
+                position,
+                ErrorMsg::MustBeIntegerLiteral,
+            );
+            err.found();
+        }
+        argument
     }
     pub fn parse_global_directive(&mut self, directive: String, position: (usize, usize)) {
         let symbol_name_vector: Vec<&str> = directive.rsplit(' ').collect();
@@ -67,9 +350,23 @@ impl X64Assembler {
         }
 
         // グローバルシンボルとして,シンボルマップにエントリを登録しておく
+        // 定義済みであれば,そのシンボルをグローバルにする
         let symbol_name = symbol_name_vector[0].to_string();
-        let global_symbol = X64Symbol::new_global();
-        self.src_file.symbols_map.insert(symbol_name, global_symbol);
+        let src_file = &mut self.src_file;
+        if let Some(symbol) = src_file
+            .rodata_map
+            .get_mut(&symbol_name)
+            .or(src_file.data_map.get_mut(&symbol_name))
+            .or(src_file.bss_map.get_mut(&symbol_name))
+        {
+            symbol.is_global = true;
+            return;
+        }
+        src_file
+            .symbols_map
+            .entry(symbol_name)
+            .or_insert_with(X64Symbol::new_global)
+            .is_global = true;
     }
 
     pub fn looking_token_clone(&mut self) -> AsmToken {
@@ -115,6 +412,24 @@ mod general_parser_tests {
         assert_eq!(expected_int, actual_int);
     }
 
+    #[test]
+    fn test_intel_consume_memory_operand() {
+        // オフセットは符号を反転して保持する
+        let mut assembler = preprocess_intel("-8[rbp], 16[r10], [rax]");
+        assert_eq!(
+            X64Operand::new_addressing(8, "rbp".to_string()),
+            assembler.consume_operand()
+        );
+        assert_eq!(
+            X64Operand::new_addressing(-16, "r10".to_string()),
+            assembler.consume_operand()
+        );
+        assert_eq!(
+            X64Operand::new_addressing(0, "rax".to_string()),
+            assembler.consume_operand()
+        );
+    }
+
     #[test]
     fn test_atandt_consume_operand() {
         let expected_reg = X64Operand::new_register("rax".to_string());
@@ -135,6 +450,15 @@ mod general_parser_tests {
         assert_eq!(expected_int, actual_int);
     }
 
+    #[test]
+    fn test_intel_consume_label_indexed_operand() {
+        let mut assembler = preprocess_intel("QWORD PTR .L5[r10*8]");
+        assert_eq!(
+            X64Operand::new_label_indexed(".L5".to_string(), "r10".to_string(), 8),
+            assembler.consume_operand()
+        );
+    }
+
     #[test]
     fn test_parse_directive() {
         let mut assembler = preprocess_intel("");
@@ -151,6 +475,75 @@ mod general_parser_tests {
         assert_eq!(2, assembler.next_token);
     }
 
+    #[test]
+    fn test_intel_consume_rip_relative_operand() {
+        let mut assembler = preprocess_intel("x[rip], main");
+        assert_eq!(
+            X64Operand::new_rip_relative("x".to_string()),
+            assembler.consume_operand()
+        );
+        assert_eq!(
+            X64Operand::new_label("main".to_string()),
+            assembler.consume_operand()
+        );
+    }
+
+    #[test]
+    fn test_parse_data_directives() {
+        let mut assembler = preprocess_intel(
+            ".global x\n.data\n.align 4\nx:\n  .long -2\n  .byte 1\n.bss\n.align 8\ny:\n  .zero 16\n.text\nmain:\n  ret\n",
+        );
+        assembler.parse_intel_syntax();
+
+        let x = assembler.src_file.data_map.get("x").unwrap();
+        assert_eq!(vec![0xfe, 0xff, 0xff, 0xff, 0x01], x.bytes);
+        assert_eq!(4, x.alignment);
+        let y = assembler.src_file.bss_map.get("y").unwrap();
+        assert_eq!(16, y.bytes.len());
+        assert_eq!(8, y.alignment);
+
+        // データシンボルは命令を持つシンボルとは別に管理する
+        assert!(!assembler.src_file.symbols_map.contains_key("x"));
+        assert!(assembler.src_file.symbols_map.contains_key("main"));
+    }
+
+    #[test]
+    fn test_parse_label_reference_directives() {
+        let mut assembler = preprocess_intel(
+            ".data\n.align 8\np:\n  .quad x\n  .quad x+8\n  .quad .LC0-4\n.text\nmain:\n  ret\n",
+        );
+        assembler.parse_intel_syntax();
+
+        // ラベルの指す先はリンク時に埋める
+        let p = assembler.src_file.data_map.get("p").unwrap();
+        assert_eq!(vec![0x00; 24], p.bytes);
+        assert_eq!(
+            vec![
+                (0, "x".to_string(), 0),
+                (8, "x".to_string(), 8),
+                (16, ".LC0".to_string(), -4)
+            ],
+            p.label_refs
+        );
+    }
+
+    #[test]
+    fn test_parse_string_directives() {
+        let mut assembler = preprocess_intel(
+            ".section .rodata\n.LC0:\n  .string \"a\\\"b\\n\\001\\x7f\"\n.LC1:\n  .ascii \"x y\"\n  .byte 0\n.text\nmain:\n  lea r10, .LC0[rip]\n  ret\n",
+        );
+        assembler.parse_intel_syntax();
+
+        // .string は終端のnull文字を含む
+        let lc0 = assembler.src_file.rodata_map.get(".LC0").unwrap();
+        assert_eq!(vec![b'a', b'"', b'b', b'\n', 0x01, 0x7f, 0x00], lc0.bytes);
+        let lc1 = assembler.src_file.rodata_map.get(".LC1").unwrap();
+        assert_eq!(b"x y\0".to_vec(), lc1.bytes);
+
+        assert!(assembler.src_file.data_map.is_empty());
+        assert!(assembler.src_file.symbols_map.contains_key("main"));
+    }
+
     fn preprocess_intel(input: &str) -> X64Assembler {
         let target = Target::new();
         let assembly_file = AssemblyFile::new_intel_file(input.to_string(), target);
@@ -1,7 +1,6 @@
 use crate::assembler::arch::x64::asmtoken;
-use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::assembler::{X64Assembler, X64Section};
 use crate::assembler::arch::x64::inst::X64Instruction;
-use crate::assembler::arch::x64::symbol::X64Symbol;
 use asmtoken::AsmTokenKind;
 
 impl X64Assembler {
@@ -12,6 +11,11 @@ impl X64Assembler {
             let cur = self.looking_token_clone();
             match cur.kind {
                 AsmTokenKind::DIRECTIVE(name) => self.parse_directive(name, cur.position),
+                // .data/.bss 中のラベルはデータシンボル
+                AsmTokenKind::LABEL(name) if self.cur_section != X64Section::TEXT => {
+                    self.read_token();
+                    self.define_data_symbol(name);
+                }
                 AsmTokenKind::LABEL(name) => {
                     // オフセットを進める
                     self.read_token();
@@ -23,11 +27,7 @@ impl X64Assembler {
                     }
 
                     // シンボルマップにエントリを登録
-                    let mut global_symbol = X64Symbol::new_global();
-                    global_symbol.insts = insts_in_label;
-                    self.src_file
-                        .symbols_map
-                        .insert(name.to_string(), global_symbol);
+                    self.define_text_symbol(name, insts_in_label);
                 }
                 // パース終了
                 _ => break,
@@ -41,7 +41,19 @@ impl X64Assembler {
         let cur = self.looking_token_clone();
         match cur.kind {
             // 2つのオペランドを持つ命令
-            AsmTokenKind::ADDQ | AsmTokenKind::SUBQ | AsmTokenKind::MOVQ | AsmTokenKind::IMULQ => {
+            AsmTokenKind::ADDQ
+            | AsmTokenKind::SUBQ
+            | AsmTokenKind::MOVQ
+            | AsmTokenKind::CMPQ
+            | AsmTokenKind::MOVZBQ
+            | AsmTokenKind::ANDQ
+            | AsmTokenKind::ORQ
+            | AsmTokenKind::XORQ
+            | AsmTokenKind::SHLQ
+            | AsmTokenKind::SARQ
+            | AsmTokenKind::SHRQ
+            | AsmTokenKind::LEAQ
+            | AsmTokenKind::IMULQ => {
                 self.read_token();
 
                 // 2つのオペランドを取得
@@ -56,7 +68,20 @@ impl X64Assembler {
             AsmTokenKind::CALL
             | AsmTokenKind::IDIVQ
             | AsmTokenKind::NEGQ
+            | AsmTokenKind::NOTQ
             | AsmTokenKind::JMP
+            | AsmTokenKind::JZ
+            | AsmTokenKind::JNZ
+            | AsmTokenKind::JL
+            | AsmTokenKind::JLE
+            | AsmTokenKind::JG
+            | AsmTokenKind::JGE
+            | AsmTokenKind::SETE
+            | AsmTokenKind::SETNE
+            | AsmTokenKind::SETL
+            | AsmTokenKind::SETLE
+            | AsmTokenKind::SETG
+            | AsmTokenKind::SETGE
             | AsmTokenKind::PUSH
             | AsmTokenKind::POP => {
                 self.read_token();
@@ -95,6 +120,7 @@ mod parse_atandt_tests {
     use crate::assembler::arch::x64::file::X64AssemblyFile;
     use crate::assembler::arch::x64::inst::{inst_kind::X64Operand, inst_name::X64InstName};
     use crate::assembler::arch::x64::lexer::lex_atandt;
+    use crate::assembler::arch::x64::symbol::X64Symbol;
     use crate::structure::AssemblyFile;
     use crate::target::Target;
 
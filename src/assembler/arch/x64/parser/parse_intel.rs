@@ -1,7 +1,6 @@
 use crate::assembler::arch::x64::asmtoken;
-use crate::assembler::arch::x64::assembler::X64Assembler;
+use crate::assembler::arch::x64::assembler::{X64Assembler, X64Section};
 use crate::assembler::arch::x64::inst::X64Instruction;
-use crate::assembler::arch::x64::symbol::X64Symbol;
 use asmtoken::AsmTokenKind;
 
 impl X64Assembler {
@@ -12,6 +11,11 @@ impl X64Assembler {
             let cur = self.looking_token_clone();
             match cur.kind {
                 AsmTokenKind::DIRECTIVE(name) => self.parse_directive(name, cur.position),
+                // .data/.bss 中のラベルはデータシンボル
+                AsmTokenKind::LABEL(name) if self.cur_section != X64Section::TEXT => {
+                    self.read_token();
+                    self.define_data_symbol(name);
+                }
                 AsmTokenKind::LABEL(name) => {
                     // オフセットを進める
                     self.read_token();
@@ -23,11 +27,7 @@ impl X64Assembler {
                     }
 
                     // シンボルマップにエントリを登録
-                    let mut global_symbol = X64Symbol::new_global();
-                    global_symbol.insts = insts_in_label;
-                    self.src_file
-                        .symbols_map
-                        .insert(name.to_string(), global_symbol);
+                    self.define_text_symbol(name, insts_in_label);
                 }
                 // パース終了
                 _ => break,
@@ -47,6 +47,16 @@ impl X64Assembler {
             | AsmTokenKind::SUB
             | AsmTokenKind::MOV
             | AsmTokenKind::CMP
+            | AsmTokenKind::MOVZX
+            | AsmTokenKind::MOVSX
+            | AsmTokenKind::MOVSXD
+            | AsmTokenKind::AND
+            | AsmTokenKind::OR
+            | AsmTokenKind::XOR
+            | AsmTokenKind::SHL
+            | AsmTokenKind::SAR
+            | AsmTokenKind::SHR
+            | AsmTokenKind::LEA
             | AsmTokenKind::IMUL => {
                 self.read_token();
 
@@ -60,9 +70,26 @@ impl X64Assembler {
             // 1つのオペランドを持つ命令
             AsmTokenKind::CALL
             | AsmTokenKind::IDIV
+            | AsmTokenKind::DIV
             | AsmTokenKind::NEG
+            | AsmTokenKind::NOT
             | AsmTokenKind::JMP
             | AsmTokenKind::JZ
+            | AsmTokenKind::JNZ
+            | AsmTokenKind::JL
+            | AsmTokenKind::JLE
+            | AsmTokenKind::JG
+            | AsmTokenKind::JGE
+            | AsmTokenKind::SETE
+            | AsmTokenKind::SETNE
+            | AsmTokenKind::SETL
+            | AsmTokenKind::SETLE
+            | AsmTokenKind::SETG
+            | AsmTokenKind::SETGE
+            | AsmTokenKind::SETB
+            | AsmTokenKind::SETBE
+            | AsmTokenKind::SETA
+            | AsmTokenKind::SETAE
             | AsmTokenKind::PUSH
             | AsmTokenKind::POP => {
                 self.read_token();
@@ -101,6 +128,7 @@ mod parse_intel_tests {
     use crate::assembler::arch::x64::file::X64AssemblyFile;
     use crate::assembler::arch::x64::inst::{inst_kind::X64Operand, inst_name::X64InstName};
     use crate::assembler::arch::x64::lexer::lex_intel;
+    use crate::assembler::arch::x64::symbol::X64Symbol;
     use crate::structure::AssemblyFile;
     use crate::target::Target;
 
@@ -121,12 +149,12 @@ mod parse_intel_tests {
 
     #[test]
     fn test_parse_intel_syntax_with_call_inst() {
-        let mut expected_main = X64Symbol::new_global();
+        let mut expected_main = X64Symbol::new_local();
         expected_main.insts = vec![
             X64Instruction::new_call(X64Operand::new_label("foo".to_string())),
             X64Instruction::new_ret(),
         ];
-        let mut expected_foo = X64Symbol::new_global();
+        let mut expected_foo = X64Symbol::new_local();
         expected_foo.insts = vec![
             X64Instruction::new_mov(
                 X64Operand::new_integer(3),
@@ -134,6 +162,7 @@ mod parse_intel_tests {
             ),
             X64Instruction::new_ret(),
         ];
+        // .global が無いので,ローカルシンボルになる
         // main:
         //   call foo
         //   ret
@@ -156,9 +185,9 @@ mod parse_intel_tests {
 
     #[test]
     fn test_parse_intel_syntax_with_multi_symbols() {
-        let mut expected_main = X64Symbol::new_global();
+        let mut expected_main = X64Symbol::new_local();
         expected_main.insts = vec![X64Instruction::new_ret()];
-        let mut expected_foo = X64Symbol::new_global();
+        let mut expected_foo = X64Symbol::new_local();
         expected_foo.insts = vec![
             X64Instruction::new_mov(
                 X64Operand::new_integer(3),
@@ -166,6 +195,7 @@ mod parse_intel_tests {
             ),
             X64Instruction::new_ret(),
         ];
+        // .global が無いので,ローカルシンボルになる
         // main:
         //   ret
         // foo:
@@ -1,5 +1,7 @@
 use crate::assembler::arch::x64::inst;
 
+use std::collections::BTreeMap;
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct X64Symbol {
     pub codes: Vec<u8>,
@@ -27,3 +29,58 @@ impl X64Symbol {
         self.codes.len() != 0
     }
 }
+
+// .data/.bss に配置されるシンボル
+// .bss のシンボルは0埋めされたバイト列でサイズだけを表す
+#[derive(PartialEq, Debug, Clone)]
+pub struct X64DataSymbol {
+    pub bytes: Vec<u8>,
+    pub alignment: usize,
+    // .quad <label>+<addend> で参照するラベル(シンボル内のオフセット, ラベル名, 加数)
+    // アドレスはリンク時に埋める
+    pub label_refs: Vec<(usize, String, i64)>,
+    // .global で外部結合にしたか
+    pub is_global: bool,
+}
+
+impl X64DataSymbol {
+    pub fn new(alignment: usize) -> Self {
+        Self {
+            bytes: Vec::new(),
+            alignment,
+            label_refs: Vec::new(),
+            is_global: false,
+        }
+    }
+    // 各シンボルを境界に合わせて並べた時の,セクション先頭からのオフセット
+    // 最後の要素はセクション全体のサイズ
+    pub fn layout(symbols: &BTreeMap<String, Self>) -> Vec<usize> {
+        let mut offsets = Vec::new();
+        let mut offset: usize = 0;
+        for symbol in symbols.values() {
+            offset = offset.div_ceil(symbol.alignment) * symbol.alignment;
+            offsets.push(offset);
+            offset += symbol.bytes.len();
+        }
+        offsets.push(offset);
+        offsets
+    }
+}
+
+#[cfg(test)]
+mod symbol_tests {
+    use super::*;
+
+    #[test]
+    fn test_data_symbol_layout() {
+        let mut symbols = BTreeMap::new();
+        let mut a = X64DataSymbol::new(1);
+        a.bytes = vec![0xff];
+        let mut b = X64DataSymbol::new(8);
+        b.bytes = vec![0x00; 8];
+        symbols.insert("a".to_string(), a);
+        symbols.insert("b".to_string(), b);
+
+        assert_eq!(vec![0, 8, 16], X64DataSymbol::layout(&symbols));
+    }
+}
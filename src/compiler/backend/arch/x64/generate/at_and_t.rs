@@ -1,7 +1,10 @@
-use crate::compiler::backend::arch::x64::generate::Registers;
+use crate::compiler::backend::arch::x64::generate::{is_return, stack_param_offset, Registers};
 use crate::compiler::backend::arch::x64::optimizer::X64Optimizer;
 use crate::compiler::ir::arch::x64::{
-    basicblock::X64BasicBlock, function::X64Function, ir::X64IR, ir_kind::X64IRKind,
+    basicblock::X64BasicBlock,
+    function::X64Function,
+    ir::X64IR,
+    ir_kind::{X64IRKind, X64Operand, REGISTER_ARGS},
 };
 
 impl X64Optimizer {
@@ -15,10 +18,8 @@ impl X64Optimizer {
         output
     }
     pub fn generate_directive(&self) -> String {
-        let mut output = String::new();
-        for func in self.functions.iter() {
-            output += &(format!(".global {}\n", func.func_name).as_str());
-        }
+        let mut output = self.generate_global_directives();
+        output += &self.generate_global_variables();
         output
     }
 }
@@ -31,26 +32,48 @@ impl X64Function {
         // 関数プロローグ
         output += &(format!("  push %rbp\n").as_str());
         output += &(format!("  movq %rsp, %rbp\n").as_str());
-        if self.frame_size != 0 {
-            output += &(format!("  subq ${}, %rsp\n", !7 & self.frame_size + 7).as_str());
+        let stack_size = self.stack_size();
+        if stack_size != 0 {
+            output += &format!("  subq ${}, %rsp\n", stack_size);
+        }
+        for (reg, offset) in self.callee_saved_slots() {
+            output += &format!("  movq %{}, -{}(%rbp)\n", reg.to_string(), offset);
         }
 
         // 関数本体
+        let epilogue = self.at_and_t_epilogue();
         for block in self.blocks.iter() {
-            output += &block.to_at_and_t_code();
+            output += &block.to_at_and_t_code(&epilogue);
         }
+
+        output += &self.generate_jump_tables();
+        output
+    }
+
+    // 関数エピローグ
+    fn at_and_t_epilogue(&self) -> String {
+        let mut output = String::new();
+        for (reg, offset) in self.callee_saved_slots() {
+            output += &format!("  movq -{}(%rbp), %{}\n", offset, reg.to_string());
+        }
+        output += "  movq %rbp, %rsp\n";
+        output += "  pop %rbp\n";
+        output += "  ret\n";
         output
     }
 }
 
 impl X64BasicBlock {
-    fn to_at_and_t_code(&self) -> String {
+    fn to_at_and_t_code(&self, epilogue: &str) -> String {
         let mut output = String::new();
         if self.label != "entry" {
             output += &(format!("{}:\n", self.label).as_str());
         }
         for ir in self.irs.iter() {
             output += &(format!("  {}\n", ir.to_at_and_t_code()).as_str());
+            if is_return(ir) {
+                output += epilogue;
+            }
         }
         output
     }
@@ -69,6 +92,15 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 format!("addq ${}, %{}", immediate.int_value(), dst_reg.to_string())
             }
+            X64IRKind::ADDMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "addq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
 
             // mov
             X64IRKind::MOVREGTOREG(dst, src) => {
@@ -80,6 +112,30 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 format!("movq ${}, %{}", immediate.int_value(), dst_reg.to_string())
             }
+            X64IRKind::MOVMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let memory = format!("-{}(%rbp)", var.var_offset());
+                format!(
+                    "{} # {}",
+                    load_with_extension(&dst_reg, var, &memory),
+                    var.var_name()
+                )
+            }
+            // 型変換 -> 下位のビットから64bitに拡張し直す
+            X64IRKind::EXTENDREG(dst) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let sized_reg = dst_reg.to_sized_string(dst.size);
+                if dst.size == 4 && dst.is_unsigned {
+                    // 32bitレジスタへの書き込みは上位32bitをゼロにする
+                    return format!("movl %{}, %{}", sized_reg, sized_reg);
+                }
+                format!(
+                    "{} %{}, %{}",
+                    extend_inst(dst),
+                    sized_reg,
+                    dst_reg.to_string()
+                )
+            }
 
             // sub
             X64IRKind::SUBREGTOREG(dst, src) => {
@@ -91,6 +147,15 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 format!("subq ${}, %{}", immediate.int_value(), dst_reg.to_string())
             }
+            X64IRKind::SUBMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "subq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
 
             // mul
             X64IRKind::MULREGTOREG(dst, src) => {
@@ -102,6 +167,15 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 format!("imulq ${}, %{}", immediate.int_value(), dst_reg.to_string())
             }
+            X64IRKind::MULMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "imulq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
 
             // div
             X64IRKind::DIVREGTOREG(dst, src) => {
@@ -109,8 +183,8 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 let src_reg = Registers::from_number_ir(src.phys);
                 output += &(format!("movq %{}, %rax\n", dst_reg.to_string()).as_str());
-                output += "  cltd\n";
-                output += &(format!("  idivq %{}\n", src_reg.to_string()).as_str());
+                output += sign_extend_dividend(dst);
+                output += &(format!("  {} %{}\n", divide_inst(dst), src_reg.to_string()).as_str());
                 output += &(format!("  movq %rax, %{}", dst_reg.to_string()).as_str());
                 output
             }
@@ -119,20 +193,172 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 output += &(format!("movq %{}, %rax\n", dst_reg.to_string()).as_str());
                 output += &(format!("  movq ${}, %rcx\n", immediate.int_value()).as_str());
-                output += "  cltd\n";
-                output += "  idivq %rcx\n";
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} %rcx\n", divide_inst(dst));
                 output += &(format!("  movq %rax, %{}", dst_reg.to_string()).as_str());
                 output
             }
 
+            // mod
+            X64IRKind::MODREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("movq %{}, %rax\n", dst_reg.to_string());
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} %{}\n", divide_inst(dst), src_reg.to_string());
+                output += &format!("  movq %rdx, %{}", dst_reg.to_string());
+                output
+            }
+            X64IRKind::MODIMMTOREG(dst, immediate) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("movq %{}, %rax\n", dst_reg.to_string());
+                output += &format!("  movq ${}, %rcx\n", immediate.int_value());
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} %rcx\n", divide_inst(dst));
+                output += &format!("  movq %rdx, %{}", dst_reg.to_string());
+                output
+            }
+            X64IRKind::MODMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("movq %{}, %rax\n", dst_reg.to_string());
+                output += &format!(
+                    "  movq -{}(%rbp), %rcx # {}\n",
+                    var.var_offset(),
+                    var.var_name()
+                );
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} %rcx\n", divide_inst(dst));
+                output += &format!("  movq %rdx, %{}", dst_reg.to_string());
+                output
+            }
+
+            // shl
+            X64IRKind::SHLREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("movq %{}, %rcx\n", src_reg.to_string());
+                output += &format!("  shlq %cl, %{}", dst_reg.to_string());
+                output
+            }
+            X64IRKind::SHLIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("shlq ${}, %{}", immediate.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::SHLMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!(
+                    "movq -{}(%rbp), %rcx # {}\n",
+                    var.var_offset(),
+                    var.var_name()
+                );
+                output += &format!("  shlq %cl, %{}", dst_reg.to_string());
+                output
+            }
+
+            // sar
+            X64IRKind::SARREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("movq %{}, %rcx\n", src_reg.to_string());
+                output += &format!("  {} %cl, %{}", right_shift_inst(dst), dst_reg.to_string());
+                output
+            }
+            X64IRKind::SARIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "{} ${}, %{}",
+                    right_shift_inst(dst),
+                    immediate.int_value(),
+                    dst_reg.to_string()
+                )
+            }
+            X64IRKind::SARMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!(
+                    "movq -{}(%rbp), %rcx # {}\n",
+                    var.var_offset(),
+                    var.var_name()
+                );
+                output += &format!("  {} %cl, %{}", right_shift_inst(dst), dst_reg.to_string());
+                output
+            }
+
+            // and
+            X64IRKind::ANDREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("andq %{}, %{}", src_reg.to_string(), dst_reg.to_string())
+            }
+            X64IRKind::ANDIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("andq ${}, %{}", immediate.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::ANDMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "andq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
+
+            // or
+            X64IRKind::ORREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("orq %{}, %{}", src_reg.to_string(), dst_reg.to_string())
+            }
+            X64IRKind::ORIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("orq ${}, %{}", immediate.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::ORMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "orq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
+
+            // xor
+            X64IRKind::XORREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("xorq %{}, %{}", src_reg.to_string(), dst_reg.to_string())
+            }
+            X64IRKind::XORIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("xorq ${}, %{}", immediate.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::XORMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "xorq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
+
             // store
             X64IRKind::STOREREG(dst, src) => {
                 let src_reg = Registers::from_number_ir(src.phys);
                 let dst_name = dst.var_name();
                 let dst_offset = dst.var_offset();
                 format!(
-                    "movq %{}, -{}(rbp) # {}",
-                    src_reg.to_string(),
+                    "mov{} %{}, -{}(%rbp) # {}",
+                    size_suffix(dst.size),
+                    src_reg.to_sized_string(dst.size),
                     dst_offset,
                     dst_name
                 )
@@ -141,50 +367,179 @@ impl X64IR {
                 let src_value = src.int_value();
                 let dst_name = dst.var_name();
                 let dst_offset = dst.var_offset();
-                format!("movq ${}, -{}(%rbp) # {}", src_value, dst_offset, dst_name)
+                format!(
+                    "mov{} ${}, -{}(%rbp) # {}",
+                    size_suffix(dst.size),
+                    src_value,
+                    dst_offset,
+                    dst_name
+                )
             }
             // negative
             X64IRKind::NEGREG(inner_op) => {
                 let negative_reg = Registers::from_number_ir(inner_op.phys);
                 format!("negl %{}", negative_reg.to_string())
             }
+            // not
+            X64IRKind::NOTREG(inner_op) => {
+                let not_reg = Registers::from_number_ir(inner_op.phys);
+                format!("notq %{}", not_reg.to_string())
+            }
             // ret
+            // エピローグはブロック側で続けて出力する
             X64IRKind::RETREG(return_op) => {
-                let mut output = String::new();
                 let return_reg = Registers::from_number_ir(return_op.phys);
-                format!("movq %{}, %rax\n", return_reg.to_string());
-
-                // 関数エピローグ
-                output += &(format!("  movq %rbp, %rsp\n").as_str());
-                output += &(format!("  pop %rbp\n").as_str());
-                output += &(format!("  ret").as_str());
-                output
+                format!("movq %{}, %rax", return_reg.to_string())
             }
-            X64IRKind::RETIMM(return_op) => {
+            X64IRKind::RETIMM(return_op) => format!("movq ${}, %rax", return_op.int_value()),
+            X64IRKind::RETMEM(return_op) => {
+                let return_name = return_op.var_name();
+                let return_off = return_op.var_offset();
+                format!("movq -{}(%rbp), %rax # {}", return_off, return_name)
+            }
+            // cmp
+            X64IRKind::CMPREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("cmpq %{}, %{}", src_reg.to_string(), dst_reg.to_string())
+            }
+            X64IRKind::CMPIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("cmpq ${}, %{}", immediate.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::CMPMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "cmpq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
+            // setcc
+            X64IRKind::SETREG(cond, dst) => {
                 let mut output = String::new();
-                let return_value = return_op.int_value();
-                format!("movq ${}, %rax\n", return_value);
-
-                // 関数エピローグ
-                output += &(format!("  movq %rbp, %rsp\n").as_str());
-                output += &(format!("  pop %rbp\n").as_str());
-                output += &(format!("  ret").as_str());
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("set{} %{}\n", cond.to_suffix(), dst_reg.to_byte_string());
+                output += &format!(
+                    "  movzbq %{}, %{}",
+                    dst_reg.to_byte_string(),
+                    dst_reg.to_string()
+                );
                 output
             }
-            X64IRKind::RETMEM(return_op) => {
+            // ポインタ
+            X64IRKind::LEAMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "leaq -{}(%rbp), %{} # {}",
+                    var.var_offset(),
+                    dst_reg.to_string(),
+                    var.var_name()
+                )
+            }
+            X64IRKind::LEAGLOBALTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("leaq {}(%rip), %{}", var.var_name(), dst_reg.to_string())
+            }
+            // 読み出す値の型はdstが持つ
+            X64IRKind::LOADREG(dst, address) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let memory = pointer_memory(address);
+                load_with_extension(&dst_reg, dst, &memory)
+            }
+            X64IRKind::STOREREGTOPTR(dst, src) => {
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!(
+                    "mov{} %{}, {}",
+                    size_suffix(dst.size),
+                    src_reg.to_sized_string(dst.size),
+                    pointer_memory(dst)
+                )
+            }
+            X64IRKind::STOREIMMTOPTR(dst, src) => {
+                format!(
+                    "mov{} ${}, {}",
+                    size_suffix(dst.size),
+                    src.int_value(),
+                    pointer_memory(dst)
+                )
+            }
+            X64IRKind::STOREMEMTOPTR(dst, src) => {
                 let mut output = String::new();
-                let return_name = return_op.var_name();
-                let return_off = return_op.var_offset();
-                output +=
-                    &(format!("movq -{}(%rbp), %rax # {}\n", return_off, return_name).as_str());
-
-                // 関数エピローグ
-                output += &(format!("  movq %rbp, %rsp\n").as_str());
-                output += &(format!("  pop %rbp\n").as_str());
-                output += &(format!("  ret").as_str());
+                output += &format!(
+                    "movq -{}(%rbp), %rax # {}\n",
+                    src.var_offset(),
+                    src.var_name()
+                );
+                output += &format!(
+                    "  mov{} %{}, {}",
+                    size_suffix(dst.size),
+                    Registers::RAX.to_sized_string(dst.size),
+                    pointer_memory(dst)
+                );
                 output
             }
             X64IRKind::JMP(label_name) => format!("jmp {}", label_name),
+            X64IRKind::JMPTABLE(index, table_label, _targets) => {
+                let index_reg = Registers::from_number_ir(index.phys);
+                format!("jmp *{}(,%{},8)", table_label, index_reg.to_string())
+            }
+            X64IRKind::JZ(label_name) => format!("jz {}", label_name),
+            X64IRKind::JNZ(label_name) => format!("jnz {}", label_name),
+            // genparam
+            X64IRKind::GENPARAMIMM(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                format!("movq ${}, %rax\n  push %rax", gen_op.int_value())
+            }
+            X64IRKind::GENPARAMIMM(reg_num, gen_op) => {
+                let dst_reg = Registers::from_arg_number(*reg_num);
+                format!("movq ${}, %{}", gen_op.int_value(), dst_reg.to_string())
+            }
+            X64IRKind::GENPARAMREG(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                let src_reg = Registers::from_number_ir(gen_op.phys);
+                format!("push %{}", src_reg.to_string())
+            }
+            X64IRKind::GENPARAMREG(reg_num, gen_op) => {
+                let dst_reg = Registers::from_arg_number(*reg_num);
+                let src_reg = Registers::from_number_ir(gen_op.phys);
+                format!("movq %{}, %{}", src_reg.to_string(), dst_reg.to_string())
+            }
+            X64IRKind::GENPARAMMEM(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                let memory = format!("-{}(%rbp)", gen_op.var_offset());
+                let load = load_with_extension(&Registers::RAX, gen_op, &memory);
+                format!("{}\n  push %rax", load)
+            }
+            X64IRKind::GENPARAMMEM(reg_num, gen_op) => {
+                let dst_reg = Registers::from_arg_number(*reg_num);
+                let memory = format!("-{}(%rbp)", gen_op.var_offset());
+                load_with_extension(&dst_reg, gen_op, &memory)
+            }
+            X64IRKind::CALL(name) => format!("call {}", name),
+            X64IRKind::CALLREG(callee) => {
+                format!(
+                    "call *%{}",
+                    Registers::from_number_ir(callee.phys).to_string()
+                )
+            }
+            X64IRKind::PUSHPARAM(reg_num, offset) if *reg_num >= REGISTER_ARGS => {
+                let src_offset = stack_param_offset(*reg_num);
+                format!(
+                    "movq {}(%rbp), %rax\n  movq %rax, -{}(%rbp)",
+                    src_offset, offset
+                )
+            }
+            X64IRKind::PUSHPARAM(reg_num, offset) => {
+                let src_reg = Registers::from_arg_number(*reg_num);
+                format!("movq %{}, -{}(%rbp)", src_reg.to_string(), offset)
+            }
+            X64IRKind::PUSHREG(reg) => {
+                format!("push %{}", Registers::from_number_ir(reg.phys).to_string())
+            }
+            X64IRKind::POPREG(reg) => {
+                format!("pop %{}", Registers::from_number_ir(reg.phys).to_string())
+            }
+            X64IRKind::ALLOCSTACK(size) => format!("subq ${}, %rsp", size),
+            X64IRKind::FREESTACK(size) => format!("addq ${}, %rsp", size),
             _ => {
                 eprintln!("can't emit with invalid ir -> {:?}", self.kind);
                 String::new()
@@ -192,3 +547,63 @@ impl X64IR {
         }
     }
 }
+
+// 型に合わせてメモリからレジスタに読み込む
+// 8バイト未満の値は64bitに符号/ゼロ拡張する
+fn load_with_extension(dst_reg: &Registers, value: &X64Operand, memory: &str) -> String {
+    match (value.size, value.is_unsigned) {
+        (8, _) => format!("movq {}, %{}", memory, dst_reg.to_string()),
+        // 32bitレジスタへの読み込みは上位32bitをゼロにする
+        (4, true) => format!("movl {}, %{}", memory, dst_reg.to_dword_string()),
+        _ => format!(
+            "{} {}, %{}",
+            extend_inst(value),
+            memory,
+            dst_reg.to_string()
+        ),
+    }
+}
+// レジスタの指すメモリ.変位があれば N(%reg) の形にする
+fn pointer_memory(address: &X64Operand) -> String {
+    let address_reg = Registers::from_number_ir(address.phys);
+    match address.displacement() {
+        0 => format!("(%{})", address_reg.to_string()),
+        offset => format!("{}(%{})", offset, address_reg.to_string()),
+    }
+}
+// movs{b,w,l}q / movz{b,w}q
+fn extend_inst(value: &X64Operand) -> String {
+    let sign = if value.is_unsigned { "z" } else { "s" };
+    format!("mov{}{}q", sign, size_suffix(value.size))
+}
+fn size_suffix(size: usize) -> &'static str {
+    match size {
+        1 => "b",
+        2 => "w",
+        4 => "l",
+        _ => "q",
+    }
+}
+// 除算の前に被除数をrdx:raxに拡張する
+fn sign_extend_dividend(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "  movq $0, %rdx\n"
+    } else {
+        "  cltd\n"
+    }
+}
+fn divide_inst(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "divq"
+    } else {
+        "idivq"
+    }
+}
+// 符号なしの値は論理シフト
+fn right_shift_inst(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "shrq"
+    } else {
+        "sarq"
+    }
+}
@@ -1,7 +1,10 @@
-use crate::compiler::backend::arch::x64::generate::Registers;
+use crate::compiler::backend::arch::x64::generate::{is_return, stack_param_offset, Registers};
 use crate::compiler::backend::arch::x64::optimizer::X64Optimizer;
 use crate::compiler::ir::arch::x64::{
-    basicblock::X64BasicBlock, function::X64Function, ir::X64IR, ir_kind::X64IRKind,
+    basicblock::X64BasicBlock,
+    function::X64Function,
+    ir::X64IR,
+    ir_kind::{X64IRKind, X64Operand, REGISTER_ARGS},
 };
 
 impl X64Optimizer {
@@ -19,9 +22,8 @@ impl X64Optimizer {
         let mut output = String::new();
         // intel記法のprefix
         output += ".intel_syntax noprefix\n";
-        for func in self.functions.iter() {
-            output += &(format!(".global {}\n", func.func_name).as_str());
-        }
+        output += &self.generate_global_directives();
+        output += &self.generate_global_variables();
         output
     }
 }
@@ -34,27 +36,48 @@ impl X64Function {
         // 関数プロローグ
         output += &(format!("  push rbp\n").as_str());
         output += &(format!("  mov rbp, rsp\n").as_str());
-        if self.frame_size != 0 {
-            output += &(format!("  sub rsp, {}\n", !7 & self.frame_size + 7).as_str());
+        let stack_size = self.stack_size();
+        if stack_size != 0 {
+            output += &format!("  sub rsp, {}\n", stack_size);
+        }
+        for (reg, offset) in self.callee_saved_slots() {
+            output += &format!("  mov QWORD PTR -{}[rbp], {}\n", offset, reg.to_string());
         }
 
         // 関数本体
+        let epilogue = self.intel_epilogue();
         for block in self.blocks.iter() {
-            output += &block.to_intel_code();
+            output += &block.to_intel_code(&epilogue);
         }
 
+        output += &self.generate_jump_tables();
+        output
+    }
+
+    // 関数エピローグ
+    fn intel_epilogue(&self) -> String {
+        let mut output = String::new();
+        for (reg, offset) in self.callee_saved_slots() {
+            output += &format!("  mov {}, QWORD PTR -{}[rbp]\n", reg.to_string(), offset);
+        }
+        output += "  mov rsp, rbp\n";
+        output += "  pop rbp\n";
+        output += "  ret\n";
         output
     }
 }
 
 impl X64BasicBlock {
-    fn to_intel_code(&self) -> String {
+    fn to_intel_code(&self, epilogue: &str) -> String {
         let mut output = String::new();
         if self.label != "entry" {
             output += &(format!("{}:\n", self.label).as_str());
         }
         for ir in self.irs.iter() {
             output += &(format!("  {}\n", ir.to_intel_code()).as_str());
+            if is_return(ir) {
+                output += epilogue;
+            }
         }
         output
     }
@@ -83,6 +106,15 @@ impl X64IR {
                     dst_name,
                 )
             }
+            X64IRKind::ADDMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "add {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
 
             // mov
             X64IRKind::MOVREGTOREG(dst, src) => {
@@ -98,14 +130,26 @@ impl X64IR {
                 let src_name = var.var_name();
                 let src_off = var.var_offset();
                 let dst_reg = Registers::from_number_ir(dst.phys);
+                let memory = format!("-{}[rbp]", src_off);
 
                 format!(
-                    "mov {}, -{}[rbp] # {}",
-                    dst_reg.to_string(),
-                    src_off,
+                    "{} # {}",
+                    load_with_extension(&dst_reg, var, &memory),
                     src_name
                 )
             }
+            // 型変換 -> 下位のビットから64bitに拡張し直す
+            X64IRKind::EXTENDREG(dst) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let sized_reg = dst_reg.to_sized_string(dst.size);
+                match (dst.size, dst.is_unsigned) {
+                    (4, false) => format!("movsxd {}, {}", dst_reg.to_string(), sized_reg),
+                    // 32bitレジスタへの書き込みは上位32bitをゼロにする
+                    (4, true) => format!("mov {}, {}", sized_reg, sized_reg),
+                    (_, false) => format!("movsx {}, {}", dst_reg.to_string(), sized_reg),
+                    (_, true) => format!("movzx {}, {}", dst_reg.to_string(), sized_reg),
+                }
+            }
 
             // sub
             X64IRKind::SUBREGTOREG(dst, src) => {
@@ -127,6 +171,15 @@ impl X64IR {
                     dst_name,
                 )
             }
+            X64IRKind::SUBMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "sub {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
 
             // mul
             X64IRKind::MULREGTOREG(dst, src) => {
@@ -138,6 +191,15 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 format!("imul {}, {}", dst_reg.to_string(), immediate.int_value())
             }
+            X64IRKind::MULMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "imul {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
 
             // div
             X64IRKind::DIVREGTOREG(dst, src) => {
@@ -145,8 +207,8 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 let src_reg = Registers::from_number_ir(src.phys);
                 output += &(format!("mov rax, {}\n", dst_reg.to_string()).as_str());
-                output += "  cqo\n";
-                output += &(format!("  idiv {}\n", src_reg.to_string()).as_str());
+                output += sign_extend_dividend(dst);
+                output += &(format!("  {} {}\n", divide_inst(dst), src_reg.to_string()).as_str());
                 output += &(format!("  mov {}, rax", dst_reg.to_string()).as_str());
                 output
             }
@@ -155,31 +217,187 @@ impl X64IR {
                 let dst_reg = Registers::from_number_ir(dst.phys);
                 output += &(format!("mov rax, {}\n", dst_reg.to_string()).as_str());
                 output += &(format!("  mov rcx, {}\n", immediate.int_value()).as_str());
-                output += "  cqo\n";
-                output += "  idiv rcx\n";
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} rcx\n", divide_inst(dst));
                 output += &(format!("  mov {}, rax", dst_reg.to_string()).as_str());
                 output
             }
+            X64IRKind::DIVMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("mov rax, {}\n", dst_reg.to_string());
+                output += &format!(
+                    "  mov rcx, -{}[rbp] # {}\n",
+                    var.var_offset(),
+                    var.var_name()
+                );
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} rcx\n", divide_inst(dst));
+                output += &format!("  mov {}, rax", dst_reg.to_string());
+                output
+            }
+
+            // mod -> idivの剰余はrdxに入る
+            X64IRKind::MODREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("mov rax, {}\n", dst_reg.to_string());
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} {}\n", divide_inst(dst), src_reg.to_string());
+                output += &format!("  mov {}, rdx", dst_reg.to_string());
+                output
+            }
+            X64IRKind::MODIMMTOREG(dst, immediate) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("mov rax, {}\n", dst_reg.to_string());
+                output += &format!("  mov rcx, {}\n", immediate.int_value());
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} rcx\n", divide_inst(dst));
+                output += &format!("  mov {}, rdx", dst_reg.to_string());
+                output
+            }
+            X64IRKind::MODMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("mov rax, {}\n", dst_reg.to_string());
+                output += &format!(
+                    "  mov rcx, -{}[rbp] # {}\n",
+                    var.var_offset(),
+                    var.var_name()
+                );
+                output += sign_extend_dividend(dst);
+                output += &format!("  {} rcx\n", divide_inst(dst));
+                output += &format!("  mov {}, rdx", dst_reg.to_string());
+                output
+            }
+
+            // shl -> シフト量がレジスタ/メモリならclに移す
+            X64IRKind::SHLREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("mov rcx, {}\n", src_reg.to_string());
+                output += &format!("  shl {}, cl", dst_reg.to_string());
+                output
+            }
+            X64IRKind::SHLIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("shl {}, {}", dst_reg.to_string(), immediate.int_value())
+            }
+            X64IRKind::SHLMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("mov rcx, -{}[rbp] # {}\n", var.var_offset(), var.var_name());
+                output += &format!("  shl {}, cl", dst_reg.to_string());
+                output
+            }
+
+            // sar -> シフト量がレジスタ/メモリならclに移す
+            X64IRKind::SARREGTOREG(dst, src) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                output += &format!("mov rcx, {}\n", src_reg.to_string());
+                output += &format!("  {} {}, cl", right_shift_inst(dst), dst_reg.to_string());
+                output
+            }
+            X64IRKind::SARIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "{} {}, {}",
+                    right_shift_inst(dst),
+                    dst_reg.to_string(),
+                    immediate.int_value()
+                )
+            }
+            X64IRKind::SARMEMTOREG(dst, var) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("mov rcx, -{}[rbp] # {}\n", var.var_offset(), var.var_name());
+                output += &format!("  {} {}, cl", right_shift_inst(dst), dst_reg.to_string());
+                output
+            }
+
+            // and
+            X64IRKind::ANDREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("and {}, {}", dst_reg.to_string(), src_reg.to_string())
+            }
+            X64IRKind::ANDIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("and {}, {}", dst_reg.to_string(), immediate.int_value())
+            }
+            X64IRKind::ANDMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "and {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
+
+            // or
+            X64IRKind::ORREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("or {}, {}", dst_reg.to_string(), src_reg.to_string())
+            }
+            X64IRKind::ORIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("or {}, {}", dst_reg.to_string(), immediate.int_value())
+            }
+            X64IRKind::ORMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "or {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
+
+            // xor
+            X64IRKind::XORREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("xor {}, {}", dst_reg.to_string(), src_reg.to_string())
+            }
+            X64IRKind::XORIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("xor {}, {}", dst_reg.to_string(), immediate.int_value())
+            }
+            X64IRKind::XORMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "xor {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
 
             // store
             X64IRKind::STOREREG(dst, src) => {
                 let src_reg = Registers::from_number_ir(src.phys);
                 let dst_name = dst.var_name();
                 let dst_offset = dst.var_offset();
-                format!(
-                    "mov -{}[rbp], {} # {}",
-                    dst_offset,
-                    src_reg.to_string(),
-                    dst_name
-                )
+                let memory = format!("-{}[rbp]", dst_offset);
+                format!("{} # {}", store_with_size(&memory, dst, &src_reg), dst_name)
             }
             X64IRKind::STOREIMM(dst, src) => {
                 let src_value = src.int_value();
                 let dst_name = dst.var_name();
                 let dst_offset = dst.var_offset();
                 format!(
-                    "mov QWORD PTR -{}[rbp], {} # {}",
-                    dst_offset, src_value, dst_name
+                    "mov {} -{}[rbp], {} # {}",
+                    size_directive(dst.size),
+                    dst_offset,
+                    src_value,
+                    dst_name
                 )
             }
             X64IRKind::STOREMEM(dst, src) => {
@@ -192,8 +410,12 @@ impl X64IR {
                 output += &(format!("mov rax, -{}[rbp] # {}\n", src_offset, src_name).as_str());
 
                 let dst_name = dst.var_name();
-                let dst_offset = dst.var_offset();
-                output += &(format!("  mov -{}[rbp], rax # {}", dst_offset, dst_name).as_str());
+                let memory = format!("-{}[rbp]", dst.var_offset());
+                output += &format!(
+                    "  {} # {}",
+                    store_with_size(&memory, dst, &Registers::RAX),
+                    dst_name
+                );
 
                 output
             }
@@ -202,51 +424,22 @@ impl X64IR {
                 let negative_reg = Registers::from_number_ir(inner_op.phys);
                 format!("neg {}", negative_reg.to_string())
             }
+            // not
+            X64IRKind::NOTREG(inner_op) => {
+                let not_reg = Registers::from_number_ir(inner_op.phys);
+                format!("not {}", not_reg.to_string())
+            }
             // ret
+            // エピローグはブロック側で続けて出力する
             X64IRKind::RETREG(return_op) => {
-                let mut output = String::new();
                 let return_reg = Registers::from_number_ir(return_op.phys);
-                output += &(format!("mov rax, {}\n", return_reg.to_string()).as_str());
-
-                // 関数エピローグ
-                output += &(format!("  mov rsp, rbp\n").as_str());
-                output += &(format!("  pop rbp\n").as_str());
-                output += &(format!("  ret").as_str());
-                output
-            }
-            X64IRKind::RETIMM(return_op) => {
-                let mut output = String::new();
-                let return_value = return_op.int_value();
-                output += &(format!("mov rax, {}\n", return_value).as_str());
-
-                // 関数エピローグ
-                output += &(format!("  mov rsp, rbp\n").as_str());
-                output += &(format!("  pop rbp\n").as_str());
-                output += &(format!("  ret").as_str());
-                output
+                format!("mov rax, {}", return_reg.to_string())
             }
+            X64IRKind::RETIMM(return_op) => format!("mov rax, {}", return_op.int_value()),
             X64IRKind::RETMEM(return_op) => {
-                let mut output = String::new();
                 let return_name = return_op.var_name();
                 let return_off = return_op.var_offset();
-                output += &(format!("mov rax, -{}[rbp] # {}\n", return_off, return_name).as_str());
-
-                // 関数エピローグ
-                output += &(format!("  mov rsp, rbp\n").as_str());
-                output += &(format!("  pop rbp\n").as_str());
-                output += &(format!("  ret").as_str());
-                output
-            }
-            X64IRKind::RETCALL(return_op) => {
-                let mut output = String::new();
-                let return_name = return_op.var_name();
-                output += &(format!("call {}\n", return_name).as_str());
-
-                // 関数エピローグ
-                output += &(format!("  mov rsp, rbp\n").as_str());
-                output += &(format!("  pop rbp\n").as_str());
-                output += &(format!("  ret").as_str());
-                output
+                format!("mov rax, -{}[rbp] # {}", return_off, return_name)
             }
             // cmpzero
             X64IRKind::CMPZEROREG(cmp_op) => {
@@ -258,18 +451,140 @@ impl X64IR {
                 let cmp_off = cmp_op.var_offset();
                 format!("cmp QWORD PTR -{}[rbp], 0 # {}", cmp_off, cmp_name)
             }
+            // cmp
+            X64IRKind::CMPREGTOREG(dst, src) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let src_reg = Registers::from_number_ir(src.phys);
+                format!("cmp {}, {}", dst_reg.to_string(), src_reg.to_string())
+            }
+            X64IRKind::CMPIMMTOREG(dst, immediate) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("cmp {}, {}", dst_reg.to_string(), immediate.int_value())
+            }
+            X64IRKind::CMPMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "cmp {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
+            // setcc -> 下位8bitに書き込んでからゼロ拡張
+            X64IRKind::SETREG(cond, dst) => {
+                let mut output = String::new();
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                output += &format!("set{} {}\n", cond.to_suffix(), dst_reg.to_byte_string());
+                output += &format!(
+                    "  movzx {}, {}",
+                    dst_reg.to_string(),
+                    dst_reg.to_byte_string()
+                );
+                output
+            }
+            // ポインタ
+            X64IRKind::LEAMEMTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!(
+                    "lea {}, -{}[rbp] # {}",
+                    dst_reg.to_string(),
+                    var.var_offset(),
+                    var.var_name()
+                )
+            }
+            X64IRKind::LEAGLOBALTOREG(dst, var) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                format!("lea {}, {}[rip]", dst_reg.to_string(), var.var_name())
+            }
+            // 読み出す値の型はdstが持つ
+            X64IRKind::LOADREG(dst, address) => {
+                let dst_reg = Registers::from_number_ir(dst.phys);
+                let memory = pointer_memory(address);
+                load_with_extension(&dst_reg, dst, &memory)
+            }
+            X64IRKind::STOREREGTOPTR(dst, src) => {
+                let src_reg = Registers::from_number_ir(src.phys);
+                let memory = pointer_memory(dst);
+                store_with_size(&memory, dst, &src_reg)
+            }
+            X64IRKind::STOREIMMTOPTR(dst, src) => {
+                format!(
+                    "mov {} {}, {}",
+                    size_directive(dst.size),
+                    pointer_memory(dst),
+                    src.int_value()
+                )
+            }
+            X64IRKind::STOREMEMTOPTR(dst, src) => {
+                let mut output = String::new();
+                let memory = pointer_memory(dst);
+                output += &format!("mov rax, -{}[rbp] # {}\n", src.var_offset(), src.var_name());
+                output += &format!("  {}", store_with_size(&memory, dst, &Registers::RAX));
+                output
+            }
             X64IRKind::JMP(label_name) => format!("jmp {}", label_name),
+            X64IRKind::JMPTABLE(index, table_label, _targets) => {
+                let index_reg = Registers::from_number_ir(index.phys);
+                format!("jmp QWORD PTR {}[{}*8]", table_label, index_reg.to_string())
+            }
             X64IRKind::JZ(label_name) => format!("jz {}", label_name),
+            X64IRKind::JNZ(label_name) => format!("jnz {}", label_name),
             // genparam
+            // 7番目以降の引数はスタックに積む
+            X64IRKind::GENPARAMIMM(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                format!("mov rax, {}\n  push rax", gen_op.int_value())
+            }
             X64IRKind::GENPARAMIMM(reg_num, gen_op) => {
                 let dst_reg = Registers::from_arg_number(*reg_num);
                 let gen_value = gen_op.int_value();
                 format!("mov {}, {}", dst_reg.to_string(), gen_value)
             }
+            X64IRKind::GENPARAMREG(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                let src_reg = Registers::from_number_ir(gen_op.phys);
+                format!("push {}", src_reg.to_string())
+            }
+            X64IRKind::GENPARAMREG(reg_num, gen_op) => {
+                let dst_reg = Registers::from_arg_number(*reg_num);
+                let src_reg = Registers::from_number_ir(gen_op.phys);
+                format!("mov {}, {}", dst_reg.to_string(), src_reg.to_string())
+            }
+            X64IRKind::GENPARAMMEM(reg_num, gen_op) if *reg_num >= REGISTER_ARGS => {
+                let memory = format!("-{}[rbp]", gen_op.var_offset());
+                let load = load_with_extension(&Registers::RAX, gen_op, &memory);
+                format!("{}\n  push rax", load)
+            }
+            X64IRKind::GENPARAMMEM(reg_num, gen_op) => {
+                let dst_reg = Registers::from_arg_number(*reg_num);
+                let memory = format!("-{}[rbp]", gen_op.var_offset());
+                load_with_extension(&dst_reg, gen_op, &memory)
+            }
+            X64IRKind::CALL(name) => format!("call {}", name),
+            X64IRKind::CALLREG(callee) => {
+                format!(
+                    "call {}",
+                    Registers::from_number_ir(callee.phys).to_string()
+                )
+            }
+            X64IRKind::PUSHPARAM(reg_num, offset) if *reg_num >= REGISTER_ARGS => {
+                let mut output = String::new();
+                let src_offset = stack_param_offset(*reg_num);
+                output += &format!("mov rax, QWORD PTR {}[rbp]\n", src_offset);
+                output += &format!("  mov QWORD PTR -{}[rbp], rax", offset);
+                output
+            }
             X64IRKind::PUSHPARAM(reg_num, offset) => {
                 let src_reg = Registers::from_arg_number(*reg_num);
                 format!("mov QWORD PTR -{}[rbp], {}", offset, src_reg.to_string())
             }
+            // 呼び出しの前後で caller-saved なレジスタを退避する
+            X64IRKind::PUSHREG(reg) => {
+                format!("push {}", Registers::from_number_ir(reg.phys).to_string())
+            }
+            X64IRKind::POPREG(reg) => {
+                format!("pop {}", Registers::from_number_ir(reg.phys).to_string())
+            }
+            X64IRKind::ALLOCSTACK(size) => format!("sub rsp, {}", size),
+            X64IRKind::FREESTACK(size) => format!("add rsp, {}", size),
             _ => {
                 eprintln!("can't emit with invalid ir -> {:?}", self.kind);
                 String::new()
@@ -277,3 +592,73 @@ impl X64IR {
         }
     }
 }
+
+// 型に合わせてメモリからレジスタに読み込む
+// 8バイト未満の値は64bitに符号/ゼロ拡張する
+fn load_with_extension(dst_reg: &Registers, value: &X64Operand, memory: &str) -> String {
+    let directive = size_directive(value.size);
+    match (value.size, value.is_unsigned) {
+        (8, _) => format!("mov {}, {}", dst_reg.to_string(), memory),
+        (4, false) => format!("movsxd {}, {} {}", dst_reg.to_string(), directive, memory),
+        // 32bitレジスタへの読み込みは上位32bitをゼロにする
+        (4, true) => format!(
+            "mov {}, {} {}",
+            dst_reg.to_dword_string(),
+            directive,
+            memory
+        ),
+        (_, false) => format!("movsx {}, {} {}", dst_reg.to_string(), directive, memory),
+        (_, true) => format!("movzx {}, {} {}", dst_reg.to_string(), directive, memory),
+    }
+}
+// レジスタの指すメモリ.変位があれば N[reg] の形にする
+fn pointer_memory(address: &X64Operand) -> String {
+    let address_reg = Registers::from_number_ir(address.phys);
+    match address.displacement() {
+        0 => format!("[{}]", address_reg.to_string()),
+        offset => format!("{}[{}]", offset, address_reg.to_string()),
+    }
+}
+// 書き込み先の型のサイズだけメモリに書き込む
+fn store_with_size(memory: &str, dst: &X64Operand, src_reg: &Registers) -> String {
+    if dst.size == 8 {
+        return format!("mov {}, {}", memory, src_reg.to_string());
+    }
+    format!(
+        "mov {} {}, {}",
+        size_directive(dst.size),
+        memory,
+        src_reg.to_sized_string(dst.size)
+    )
+}
+fn size_directive(size: usize) -> &'static str {
+    match size {
+        1 => "BYTE PTR",
+        2 => "WORD PTR",
+        4 => "DWORD PTR",
+        _ => "QWORD PTR",
+    }
+}
+// 除算の前に被除数をrdx:raxに拡張する
+fn sign_extend_dividend(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "  mov rdx, 0\n"
+    } else {
+        "  cqo\n"
+    }
+}
+fn divide_inst(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "div"
+    } else {
+        "idiv"
+    }
+}
+// 符号なしの値は論理シフト
+fn right_shift_inst(dst: &X64Operand) -> &'static str {
+    if dst.is_unsigned {
+        "shr"
+    } else {
+        "sar"
+    }
+}
@@ -1,6 +1,177 @@
 pub mod at_and_t;
 pub mod intel;
 
+use crate::compiler::backend::arch::x64::optimizer::X64Optimizer;
+use crate::compiler::backend::arch::x64::registers::IR_REGISTERS;
+use crate::compiler::frontend::variable::{StaticValue, VarKind};
+use crate::compiler::ir::arch::x64::{
+    function::X64Function,
+    ir::X64IR,
+    ir_kind::{X64IRKind, REGISTER_ARGS},
+};
+
+impl X64Optimizer {
+    // グローバル変数の定義.ディレクティブは両記法で共通
+    // 初期値があれば .data に,無ければ .bss に0埋めで配置する
+    // 文字列リテラルは書き換えられないので .rodata に置く
+    fn generate_global_variables(&self) -> String {
+        if self.globals.is_empty() && self.string_literals.is_empty() {
+            return String::new();
+        }
+
+        let mut rodata_section = String::new();
+        for (idx, contents) in self.string_literals.iter().enumerate() {
+            rodata_section += &format!(".LC{}:\n  .string \"{}\"\n", idx, escape_string(contents));
+        }
+
+        let mut data_section = String::new();
+        let mut bss_section = String::new();
+        for (name, var) in self.globals.iter() {
+            let definition = format!(".align {}\n{}:\n", var.ctype.alignment(), name);
+            match &var.kind {
+                VarKind::GLOBAL(Some(values)) => {
+                    data_section += &definition;
+                    data_section += &generate_static_values(values, var.ctype.byte_size);
+                }
+                // 定義は他のオブジェクトファイルにある
+                VarKind::EXTERN => continue,
+                _ => {
+                    bss_section += &definition;
+                    bss_section += &format!("  .zero {}\n", var.ctype.byte_size);
+                }
+            }
+        }
+
+        let mut output = String::new();
+        if !rodata_section.is_empty() {
+            output += &format!(".section .rodata\n{}", rodata_section);
+        }
+        if !data_section.is_empty() {
+            output += &format!(".data\n{}", data_section);
+        }
+        if !bss_section.is_empty() {
+            output += &format!(".bss\n{}", bss_section);
+        }
+        output + ".text\n"
+    }
+    // static な関数/変数には .global を付けない
+    fn generate_global_directives(&self) -> String {
+        let mut output = String::new();
+        for func in self.functions.iter().filter(|func| !func.is_static) {
+            output += &format!(".global {}\n", func.func_name);
+        }
+        for (name, var) in self.globals.iter() {
+            if var.kind != VarKind::EXTERN && !var.is_static {
+                output += &format!(".global {}\n", name);
+            }
+        }
+        output
+    }
+}
+
+impl X64Function {
+    // プロローグで確保するスタックの大きさ
+    // 退避領域を含めて16の倍数にし, call 命令の時点で rsp が16バイト境界に揃うようにする
+    fn stack_size(&self) -> usize {
+        let saved_size = self.callee_saved.len() * 8;
+        (align_to_8(self.frame_size) + saved_size + 15) & !15
+    }
+    // callee-saved なレジスタと,ローカル変数の下に置く退避先のオフセット
+    fn callee_saved_slots(&self) -> Vec<(Registers, usize)> {
+        let locals_size = align_to_8(self.frame_size);
+        self.callee_saved
+            .iter()
+            .enumerate()
+            .map(|(i, phys)| (Registers::from_number_ir(*phys), locals_size + 8 * (i + 1)))
+            .collect()
+    }
+    // switch文のジャンプテーブル.ディレクティブは両記法で共通
+    // 関数の途中でセクションを切り替えないよう,関数本体の後ろにまとめて置く
+    fn generate_jump_tables(&self) -> String {
+        let mut output = String::new();
+        for ir in self.blocks.iter().flat_map(|block| block.irs.iter()) {
+            if let X64IRKind::JMPTABLE(_index, table_label, targets) = &ir.kind {
+                output += &format!(".align 8\n{}:\n", table_label);
+                for target in targets.iter() {
+                    output += &format!("  .quad {}\n", target);
+                }
+            }
+        }
+        if output.is_empty() {
+            return output;
+        }
+        format!(".section .rodata\n{}.text\n", output)
+    }
+}
+
+fn align_to_8(size: usize) -> usize {
+    (size + 7) & !7
+}
+
+// エピローグを続けて出力する命令か
+fn is_return(ir: &X64IR) -> bool {
+    matches!(
+        ir.kind,
+        X64IRKind::RETREG(_) | X64IRKind::RETIMM(_) | X64IRKind::RETMEM(_)
+    )
+}
+
+// スタックで渡された引数の rbp からのオフセット
+// リターンアドレスと退避した rbp の上に,7番目の引数から順に並ぶ
+fn stack_param_offset(reg_num: usize) -> usize {
+    16 + 8 * (reg_num - REGISTER_ARGS)
+}
+
+// .string の引数として書けるように,表示できない文字を8進エスケープにする
+fn escape_string(contents: &[u8]) -> String {
+    let mut escaped = String::new();
+    for byte in contents.iter() {
+        match byte {
+            b'"' | b'\\' => escaped += &format!("\\{}", *byte as char),
+            0x20..=0x7e => escaped.push(*byte as char),
+            _ => escaped += &format!("\\{:03o}", byte),
+        }
+    }
+    escaped
+}
+
+// 初期値を先頭から並べ,値の無い隙間は .zero で埋める
+// アドレスはリンク時に埋めるので, .quad にシンボル名を書く
+fn generate_static_values(values: &[(usize, usize, StaticValue)], byte_size: usize) -> String {
+    let mut output = String::new();
+    let mut position = 0;
+    for (offset, size, value) in values.iter() {
+        // 共用体のメンバのように,前の値と重なるものは置けない
+        if *offset < position {
+            continue;
+        }
+        if *offset > position {
+            output += &format!("  .zero {}\n", offset - position);
+        }
+        output += &match value {
+            StaticValue::INTEGER(value) => format!("  {} {}\n", data_directive(*size), value),
+            StaticValue::ADDRESS(name, 0) => format!("  .quad {}\n", name),
+            StaticValue::ADDRESS(name, offset) => format!("  .quad {}{:+}\n", name, offset),
+        };
+        position = offset + size;
+    }
+    if byte_size > position {
+        output += &format!("  .zero {}\n", byte_size - position);
+    }
+    output
+}
+
+// 初期値のサイズに合わせたディレクティブ
+fn data_directive(size: usize) -> &'static str {
+    match size {
+        1 => ".byte",
+        2 => ".short",
+        4 => ".long",
+        _ => ".quad",
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Registers {
     RAX,
     RCX,
@@ -22,18 +193,11 @@ pub enum Registers {
 
 #[allow(dead_code)]
 impl Registers {
+    // IR上の物理レジスタ番号は,ターゲットのレジスタ記述の並びに従う
     fn from_number_ir(num: usize) -> Self {
-        match num {
-            0 => Self::R10,
-            1 => Self::R11,
-            2 => Self::R12,
-            3 => Self::R13,
-            4 => Self::R14,
-            5 => Self::R15,
-            6 => Self::RAX,
-            7 => Self::RDX,
-            8 => Self::RCX,
-            _ => {
+        match IR_REGISTERS.get(num) {
+            Some((reg, _, _)) => reg.clone(),
+            None => {
                 eprintln!("can't use any registers!");
                 Self::R10
             }
@@ -63,7 +227,7 @@ impl Registers {
             }
         }
     }
-    fn from_arg_number(number: usize) -> Self {
+    pub(super) fn from_arg_number(number: usize) -> Self {
         match number {
             0 => Self::RDI,
             1 => Self::RSI,
@@ -76,6 +240,10 @@ impl Registers {
             }
         }
     }
+    // レジスタ記述に載せる名前
+    pub(super) fn name(&self) -> String {
+        self.to_string()
+    }
     fn to_string(&self) -> String {
         match self {
             Self::RAX => "rax",
@@ -97,4 +265,79 @@ impl Registers {
         }
         .to_string()
     }
+    // 下位32bitのレジスタ名
+    fn to_dword_string(&self) -> String {
+        match self {
+            Self::RAX => "eax",
+            Self::RCX => "ecx",
+            Self::RDX => "edx",
+            Self::RBX => "ebx",
+            Self::RSP => "esp",
+            Self::RBP => "ebp",
+            Self::RSI => "esi",
+            Self::RDI => "edi",
+            Self::R8 => "r8d",
+            Self::R9 => "r9d",
+            Self::R10 => "r10d",
+            Self::R11 => "r11d",
+            Self::R12 => "r12d",
+            Self::R13 => "r13d",
+            Self::R14 => "r14d",
+            Self::R15 => "r15d",
+        }
+        .to_string()
+    }
+    // 下位16bitのレジスタ名
+    fn to_word_string(&self) -> String {
+        match self {
+            Self::RAX => "ax",
+            Self::RCX => "cx",
+            Self::RDX => "dx",
+            Self::RBX => "bx",
+            Self::RSP => "sp",
+            Self::RBP => "bp",
+            Self::RSI => "si",
+            Self::RDI => "di",
+            Self::R8 => "r8w",
+            Self::R9 => "r9w",
+            Self::R10 => "r10w",
+            Self::R11 => "r11w",
+            Self::R12 => "r12w",
+            Self::R13 => "r13w",
+            Self::R14 => "r14w",
+            Self::R15 => "r15w",
+        }
+        .to_string()
+    }
+    // 値のサイズに合わせたレジスタ名
+    fn to_sized_string(&self, size: usize) -> String {
+        match size {
+            1 => self.to_byte_string(),
+            2 => self.to_word_string(),
+            4 => self.to_dword_string(),
+            _ => self.to_string(),
+        }
+    }
+    // 下位8bitのレジスタ名(setccで使う)
+    fn to_byte_string(&self) -> String {
+        match self {
+            Self::RAX => "al",
+            Self::RCX => "cl",
+            Self::RDX => "dl",
+            Self::RBX => "bl",
+            Self::RSP => "spl",
+            Self::RBP => "bpl",
+            Self::RSI => "sil",
+            Self::RDI => "dil",
+            Self::R8 => "r8b",
+            Self::R9 => "r9b",
+            Self::R10 => "r10b",
+            Self::R11 => "r11b",
+            Self::R12 => "r12b",
+            Self::R13 => "r13b",
+            Self::R14 => "r14b",
+            Self::R15 => "r15b",
+        }
+        .to_string()
+    }
 }
@@ -2,6 +2,7 @@ extern crate clap;
 
 pub mod generate;
 pub mod optimizer;
+pub mod registers;
 pub mod selection;
 pub mod translate;
 
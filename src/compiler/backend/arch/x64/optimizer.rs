@@ -1,11 +1,24 @@
+use crate::compiler::frontend::variable::Variable;
 use crate::compiler::ir::arch::x64::function::X64Function;
 
+use std::collections::BTreeMap;
+
 #[derive(Clone)]
 pub struct X64Optimizer {
     pub functions: Vec<X64Function>,
+    pub globals: BTreeMap<String, Variable>,
+    pub string_literals: Vec<Vec<u8>>,
 }
 impl X64Optimizer {
-    pub fn new(functions: Vec<X64Function>) -> Self {
-        Self { functions }
+    pub fn new(
+        functions: Vec<X64Function>,
+        globals: BTreeMap<String, Variable>,
+        string_literals: Vec<Vec<u8>>,
+    ) -> Self {
+        Self {
+            functions,
+            globals,
+            string_literals,
+        }
     }
 }
@@ -0,0 +1,125 @@
+use crate::compiler::backend::arch::x64::generate::Registers;
+use crate::compiler::backend::register_description::{
+    PhysicalRegister, RegisterConstraint, RegisterDescription,
+};
+use crate::compiler::ir::arch::x64::ir_kind::REGISTER_ARGS;
+use crate::compiler::ir::three_address_code::tac_kind::{OpeKind, Operator, TacKind};
+
+// IR上の物理レジスタ番号の順に並べた x86_64 のレジスタ
+// (レジスタ, 割付に使えるか, callee-saved か)
+// rax は演算やストア,返り値の作業用に空けておく (X64Operand::new_rax() が6番を使う)
+pub const IR_REGISTERS: [(Registers, bool, bool); 14] = [
+    (Registers::R10, true, false),
+    (Registers::R11, true, false),
+    (Registers::R12, true, true),
+    (Registers::R13, true, true),
+    (Registers::R14, true, true),
+    (Registers::R15, true, true),
+    (Registers::RAX, false, false),
+    (Registers::RBX, true, true),
+    (Registers::RSI, true, false),
+    (Registers::RDI, true, false),
+    (Registers::R8, true, false),
+    (Registers::R9, true, false),
+    (Registers::RCX, true, false),
+    (Registers::RDX, true, false),
+];
+
+pub fn register_description() -> RegisterDescription {
+    let registers = IR_REGISTERS
+        .iter()
+        .map(|(reg, is_allocatable, is_callee_saved)| PhysicalRegister {
+            name: reg.name(),
+            is_allocatable: *is_allocatable,
+            is_callee_saved: *is_callee_saved,
+        })
+        .collect();
+    RegisterDescription::new(registers, constraint_of)
+}
+
+// コード生成で決まったレジスタを使う命令
+fn constraint_of(kind: &TacKind) -> RegisterConstraint {
+    let mut constraint = RegisterConstraint::default();
+    match kind {
+        // idiv は rdx:rax を被除数にし,即値やメモリの除数は rcx に置く
+        TacKind::EXPR(_, Operator::SLASH, _, _) | TacKind::EXPR(_, Operator::PERCENT, _, _) => {
+            constraint.clobbered.insert(ir_number(Registers::RDX));
+            constraint.clobbered.insert(ir_number(Registers::RCX));
+        }
+        // 即値でないシフト量は cl で渡す
+        TacKind::EXPR(_, Operator::LSHIFT, _, right)
+        | TacKind::EXPR(_, Operator::RSHIFT, _, right)
+            if !matches!(right.kind, OpeKind::INTLIT(_)) =>
+        {
+            constraint.clobbered.insert(ir_number(Registers::RCX));
+        }
+        // 引数レジスタに実引数を置き始めた後でも,オペランドを読めるようにする
+        // 呼び出しを跨いで生きる値は,呼び出し側で退避するので壊されない
+        TacKind::CALL(_, _, _, _, _) => {
+            constraint.operands = argument_registers();
+        }
+        // 引数レジスタの値を読み終えるまで,他の値を置かない
+        TacKind::PUSHPARAM(_, _) => {
+            constraint.clobbered = argument_registers();
+        }
+        _ => (),
+    }
+    constraint
+}
+
+fn argument_registers() -> std::collections::BTreeSet<usize> {
+    (0..REGISTER_ARGS)
+        .map(|number| ir_number(Registers::from_arg_number(number)))
+        .collect()
+}
+
+fn ir_number(reg: Registers) -> usize {
+    IR_REGISTERS
+        .iter()
+        .position(|(ir_reg, _, _)| ir_reg == &reg)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod registers_tests {
+    use super::*;
+    use crate::compiler::ir::three_address_code::tac_kind::Operand;
+
+    #[test]
+    fn test_register_description() {
+        let description = register_description();
+
+        // X64Operand::new_rax() と同じ番号で,割り付けには使わない
+        assert_eq!("rax", description.registers[6].name);
+        assert!(!description.allocatable_registers().contains(&6));
+        assert_eq!(13, description.allocatable_registers().len());
+
+        assert!(description.is_callee_saved(ir_number(Registers::RBX)));
+        assert!(description.is_caller_saved(ir_number(Registers::RDI)));
+        assert!(!description.is_caller_saved(6));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let description = register_description();
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+
+        let div = TacKind::EXPR(reg(0), Operator::PERCENT, reg(1), reg(2));
+        let clobbered = description.constraint(&div).clobbered;
+        assert!(clobbered.contains(&ir_number(Registers::RDX)));
+        assert!(clobbered.contains(&ir_number(Registers::RCX)));
+
+        // 即値のシフトは rcx を使わない
+        let shift = TacKind::EXPR(reg(0), Operator::LSHIFT, reg(1), int(3));
+        assert!(description.constraint(&shift).clobbered.is_empty());
+        let shift = TacKind::EXPR(reg(0), Operator::LSHIFT, reg(1), reg(2));
+        assert!(description
+            .constraint(&shift)
+            .clobbered
+            .contains(&ir_number(Registers::RCX)));
+
+        let call = TacKind::CALL(reg(0), reg(1), Vec::new(), false, Vec::new());
+        assert_eq!(6, description.constraint(&call).operands.len());
+    }
+}
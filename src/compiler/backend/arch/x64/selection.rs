@@ -43,6 +43,10 @@ impl X64Optimizer {
                         X64OpeKind::AUTOVAR(_name, _offset) => {
                             ir.kind = X64IRKind::MOVMEMTOREG(dst.clone(), src.clone());
                         }
+                        // mov reg, [reg + offset]
+                        X64OpeKind::DEREFERENCE(_offset) => {
+                            ir.kind = X64IRKind::LOADREG(dst.clone(), src.clone());
+                        }
                         _ => self.not_selection_panic("mov", ir),
                     }
                 }
@@ -59,6 +63,11 @@ impl X64Optimizer {
                             X64OpeKind::INTLIT(_value) => {
                                 ir.kind = X64IRKind::ADDIMMTOREG(dst.clone(), src.clone());
                             }
+
+                            // add reg, var
+                            X64OpeKind::AUTOVAR(_name, _offset) => {
+                                ir.kind = X64IRKind::ADDMEMTOREG(dst.clone(), src.clone());
+                            }
                             _ => self.not_selection_panic("add", ir),
                         },
                         X64OpeKind::AUTOVAR(_name, _offset) => match &src.kind {
@@ -85,6 +94,11 @@ impl X64Optimizer {
                             X64OpeKind::INTLIT(_value) => {
                                 ir.kind = X64IRKind::SUBIMMTOREG(dst.clone(), src.clone());
                             }
+
+                            // sub reg, var
+                            X64OpeKind::AUTOVAR(_name, _offset) => {
+                                ir.kind = X64IRKind::SUBMEMTOREG(dst.clone(), src.clone());
+                            }
                             _ => self.not_selection_panic("sub", ir),
                         },
                         X64OpeKind::AUTOVAR(_name, _offset) => match &src.kind {
@@ -109,6 +123,11 @@ impl X64Optimizer {
                         X64OpeKind::INTLIT(_value) => {
                             ir.kind = X64IRKind::MULIMMTOREG(dst.clone(), src.clone());
                         }
+
+                        // mul reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::MULMEMTOREG(dst.clone(), src.clone());
+                        }
                         _ => panic!("not implemented in mul selection"),
                     }
                 }
@@ -124,9 +143,134 @@ impl X64Optimizer {
                         X64OpeKind::INTLIT(_value) => {
                             ir.kind = X64IRKind::DIVIMMTOREG(dst.clone(), src.clone());
                         }
+
+                        // div reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::DIVMEMTOREG(dst.clone(), src.clone());
+                        }
                         _ => panic!("not implemented in div selection"),
                     }
                 }
+                // mod
+                X64IRKind::MOD(dst, src) => {
+                    match &src.kind {
+                        // mod reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::MODREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // mod reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::MODIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // mod reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::MODMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("mod", ir),
+                    }
+                }
+                // shl
+                X64IRKind::SHL(dst, src) => {
+                    match &src.kind {
+                        // shl reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::SHLREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // shl reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::SHLIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // shl reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::SHLMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("shl", ir),
+                    }
+                }
+                // sar
+                X64IRKind::SAR(dst, src) => {
+                    match &src.kind {
+                        // sar reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::SARREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // sar reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::SARIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // sar reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::SARMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("sar", ir),
+                    }
+                }
+                // and
+                X64IRKind::AND(dst, src) => {
+                    match &src.kind {
+                        // and reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::ANDREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // and reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::ANDIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // and reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::ANDMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("and", ir),
+                    }
+                }
+                // or
+                X64IRKind::OR(dst, src) => {
+                    match &src.kind {
+                        // or reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::ORREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // or reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::ORIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // or reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::ORMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("or", ir),
+                    }
+                }
+                // xor
+                X64IRKind::XOR(dst, src) => {
+                    match &src.kind {
+                        // xor reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::XORREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // xor reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::XORIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // xor reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::XORMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("xor", ir),
+                    }
+                }
                 // TODO: 今はレジスタに対するnegしかしない
                 X64IRKind::NEGATIVE(inner_op) => {
                     match &inner_op.kind {
@@ -137,6 +281,24 @@ impl X64Optimizer {
                         _ => panic!("not implemented in negative selection"),
                     }
                 }
+                X64IRKind::EXTEND(inner_op) => {
+                    match &inner_op.kind {
+                        // movsx reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::EXTENDREG(inner_op.clone());
+                        }
+                        _ => self.not_selection_panic("extend", ir),
+                    }
+                }
+                X64IRKind::NOT(inner_op) => {
+                    match &inner_op.kind {
+                        // not reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::NOTREG(inner_op.clone());
+                        }
+                        _ => self.not_selection_panic("not", ir),
+                    }
+                }
                 // ret
                 X64IRKind::RET(return_op) => {
                     match &return_op.kind {
@@ -155,15 +317,34 @@ impl X64Optimizer {
                             ir.kind = X64IRKind::RETMEM(return_op.clone());
                         }
 
-                        // return call
-                        X64OpeKind::CALL(_name) => {
-                            ir.kind = X64IRKind::RETCALL(return_op.clone());
-                        }
                         _ => panic!("not implemented in ret selection"),
                     }
                 }
 
                 // store
+                // ポインタの指す先に書き込む場合
+                X64IRKind::STORE(dst_op, src_op)
+                    if matches!(dst_op.kind, X64OpeKind::DEREFERENCE(_)) =>
+                {
+                    match &src_op.kind {
+                        // store reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::STOREREGTOPTR(dst_op.clone(), src_op.clone());
+                        }
+
+                        // store imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::STOREIMMTOPTR(dst_op.clone(), src_op.clone());
+                        }
+
+                        // store var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::STOREMEMTOPTR(dst_op.clone(), src_op.clone());
+                        }
+
+                        _ => self.not_selection_panic("store", ir),
+                    }
+                }
                 X64IRKind::STORE(dst_op, src_op) => {
                     match &src_op.kind {
                         // store reg
@@ -184,6 +365,20 @@ impl X64Optimizer {
                         _ => panic!("not implemented in store selection"),
                     }
                 }
+                // lea reg, var
+                X64IRKind::LEA(dst, src) => match &src.kind {
+                    X64OpeKind::AUTOVAR(_name, _offset) => {
+                        ir.kind = X64IRKind::LEAMEMTOREG(dst.clone(), src.clone());
+                    }
+                    X64OpeKind::GLOBALVAR(_name) => {
+                        ir.kind = X64IRKind::LEAGLOBALTOREG(dst.clone(), src.clone());
+                    }
+                    _ => self.not_selection_panic("lea", ir),
+                },
+                // mov reg, [reg]
+                X64IRKind::LOAD(dst, src) => {
+                    ir.kind = X64IRKind::LOADREG(dst.clone(), src.clone());
+                }
                 // cmpzero
                 X64IRKind::CMPZERO(cmp_op) => {
                     match &cmp_op.kind {
@@ -205,6 +400,36 @@ impl X64Optimizer {
                         _ => panic!("not implemented in cmpzero selection"),
                     }
                 }
+                // cmp
+                X64IRKind::CMP(dst, src) => {
+                    match &src.kind {
+                        // cmp reg, reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::CMPREGTOREG(dst.clone(), src.clone());
+                        }
+
+                        // cmp reg, imm
+                        X64OpeKind::INTLIT(_value) => {
+                            ir.kind = X64IRKind::CMPIMMTOREG(dst.clone(), src.clone());
+                        }
+
+                        // cmp reg, var
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::CMPMEMTOREG(dst.clone(), src.clone());
+                        }
+                        _ => self.not_selection_panic("cmp", ir),
+                    }
+                }
+                // setcc
+                X64IRKind::SET(cond, dst) => {
+                    match &dst.kind {
+                        // setcc reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::SETREG(cond.clone(), dst.clone());
+                        }
+                        _ => self.not_selection_panic("setcc", ir),
+                    }
+                }
                 // params
                 X64IRKind::GENPARAM(reg_num, gen_op) => {
                     match &gen_op.kind {
@@ -212,6 +437,14 @@ impl X64Optimizer {
                         X64OpeKind::INTLIT(_value) => {
                             ir.kind = X64IRKind::GENPARAMIMM(reg_num.clone(), gen_op.clone());
                         }
+                        // genparam reg
+                        X64OpeKind::REG => {
+                            ir.kind = X64IRKind::GENPARAMREG(*reg_num, gen_op.clone());
+                        }
+                        // genparam mem
+                        X64OpeKind::AUTOVAR(_name, _offset) => {
+                            ir.kind = X64IRKind::GENPARAMMEM(*reg_num, gen_op.clone());
+                        }
                         _ => panic!("not implemented in genparam selection"),
                     }
                 }
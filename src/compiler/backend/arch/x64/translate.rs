@@ -1,15 +1,18 @@
-use crate::compiler::backend::arch::x64::optimizer::X64Optimizer;
+use crate::compiler::backend::arch::x64::{optimizer::X64Optimizer, registers};
 use crate::compiler::backend::high_optimizer::HighOptimizer;
 use crate::compiler::ir::arch::x64::{
     basicblock::X64BasicBlock,
     function::X64Function,
     ir::X64IR,
-    ir_kind::{X64IRKind, X64OpeKind, X64Operand},
+    ir_kind::{X64CondKind, X64IRKind, X64OpeKind, X64Operand, REGISTER_ARGS},
 };
 use crate::compiler::ir::three_address_code as tac;
 use tac::tac_kind;
 use tac::{basicblock::BasicBlock, function::IRFunction};
 
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
 impl HighOptimizer {
     // ここでは抽象的なIRにしておく.
     pub fn translate_tacs_to_x64(high_opt: Self) -> X64Optimizer {
@@ -20,7 +23,7 @@ impl HighOptimizer {
             x64_funcs.push(x64_func);
         }
 
-        X64Optimizer::new(x64_funcs)
+        X64Optimizer::new(x64_funcs, high_opt.globals, high_opt.string_literals)
     }
     fn translate_meta_func_to_x64(meta_func: IRFunction) -> X64Function {
         let mut x64_blocks: Vec<X64BasicBlock> = Vec::new();
@@ -30,16 +33,41 @@ impl HighOptimizer {
             x64_blocks.push(x64_block);
         }
 
-        X64Function::new(meta_func.name.to_string(), x64_blocks, meta_func.frame_size)
+        let mut x64_func =
+            X64Function::new(meta_func.name.to_string(), x64_blocks, meta_func.frame_size);
+        x64_func.callee_saved = Self::callee_saved_registers(&meta_func);
+        x64_func.is_static = meta_func.is_static;
+        x64_func
+    }
+    // 関数内で値を書き込む callee-saved なレジスタ
+    fn callee_saved_registers(meta_func: &IRFunction) -> Vec<usize> {
+        let mut defined: BTreeSet<usize> = BTreeSet::new();
+        for t in meta_func.blocks.iter().flat_map(|bb| bb.tacs.iter()) {
+            match &t.kind {
+                tac_kind::TacKind::EXPR(var, _, _, _)
+                | tac_kind::TacKind::UNARYEXPR(var, _, _)
+                | tac_kind::TacKind::CALL(var, _, _, _, _) => {
+                    defined.insert(var.phys);
+                }
+                _ => (),
+            }
+        }
+        let description = registers::register_description();
+        defined
+            .into_iter()
+            .filter(|phys| description.is_callee_saved(*phys))
+            .collect()
     }
     fn translate_meta_bb_to_x64(meta_bb: &BasicBlock) -> X64BasicBlock {
         let mut low_irs: Vec<X64IR> = Vec::new();
         // TAC列のイテレーション
         for t in meta_bb.tacs.iter() {
             match t.kind.clone() {
-                tac_kind::TacKind::GENPARAM(reg_num, op) => {
-                    let gen_op = Self::tac_operand_to_x64(op);
-                    low_irs.push(X64IR::new_genparam(reg_num, gen_op));
+                tac_kind::TacKind::CALL(return_op, callee, args, is_variadic, saved) => {
+                    let dst = Self::tac_operand_to_x64(return_op);
+                    let callee = Self::tac_operand_to_x64(callee);
+                    let args = args.into_iter().map(Self::tac_operand_to_x64).collect();
+                    Self::add_call_sequence(&mut low_irs, dst, callee, args, is_variadic, saved);
                 }
                 tac_kind::TacKind::PUSHPARAM(reg_num, offset) => {
                     low_irs.push(X64IR::new_pushparam(reg_num, offset));
@@ -50,14 +78,22 @@ impl HighOptimizer {
                 }
                 tac_kind::TacKind::IFF(op, label_name) => {
                     let cmp_op = Self::tac_operand_to_x64(op);
-                    low_irs.push(X64IR::new_cmpzero(cmp_op));
-                    low_irs.push(X64IR::new_jumpzero(label_name));
+                    Self::add_conditional_jump(&mut low_irs, cmp_op, label_name, false);
+                }
+                tac_kind::TacKind::IFT(op, label_name) => {
+                    let cmp_op = Self::tac_operand_to_x64(op);
+                    Self::add_conditional_jump(&mut low_irs, cmp_op, label_name, true);
                 }
                 tac_kind::TacKind::GOTO(label_name) => {
                     low_irs.push(X64IR::new_jump(label_name));
                 }
+                tac_kind::TacKind::JUMPTABLE(index, table_label, targets) => {
+                    let index_op = Self::tac_operand_to_x64(index);
+                    low_irs.push(X64IR::new_jump_table(index_op, table_label, targets));
+                }
                 tac_kind::TacKind::ASSIGN(lv_bf, rv_bf) => {
                     let src_op = Self::tac_operand_to_x64(rv_bf);
+                    let src_op = Self::load_wide_immediate(&mut low_irs, src_op);
                     let dst_op = Self::tac_operand_to_x64(lv_bf);
                     low_irs.push(X64IR::new_store(dst_op, src_op));
                 }
@@ -69,87 +105,77 @@ impl HighOptimizer {
                 tac_kind::TacKind::UNARYEXPR(var_bf, operator_bf, inner_bf) => {
                     // 各構成要素を変換
                     let inner = Self::tac_operand_to_x64(inner_bf);
-                    let opcode: X64IRKind = Self::unary_opcode_from_operator(operator_bf);
                     let dst = Self::tac_operand_to_x64(var_bf);
 
+                    // アドレスはロードせずにleaで求める
+                    if let tac_kind::Operator::AMPERSAND = operator_bf {
+                        low_irs.push(X64IR::new_lea(dst, inner));
+                        continue;
+                    }
+                    let opcode: X64IRKind = Self::unary_opcode_from_operator(operator_bf);
+
                     // movしてから演算
-                    let load_ir = X64IR::new_mov(dst.clone(), inner);
-                    low_irs.push(load_ir);
+                    Self::add_move(&mut low_irs, dst.clone(), inner);
 
-                    Self::add_unary_ir_matching_opcode(&mut low_irs, opcode, dst);
+                    let should_extend = Self::may_break_extension(&opcode, &dst);
+                    Self::add_unary_ir_matching_opcode(&mut low_irs, opcode, dst.clone());
+                    if should_extend {
+                        low_irs.push(X64IR::new_extend(dst));
+                    }
                 }
                 tac_kind::TacKind::EXPR(var_bf, operator_bf, left_bf, right_bf) => {
                     // 各構成要素を変換
                     let left = Self::tac_operand_to_x64(left_bf);
                     let right = Self::tac_operand_to_x64(right_bf);
-                    let opcode: X64IRKind = Self::binary_opcode_from_operator(operator_bf);
                     let dst = Self::tac_operand_to_x64(var_bf);
+                    let right = match operator_bf {
+                        // 除算は即値を rcx に mov し,シフト量は8bitに収まるので,そのまま使う
+                        tac_kind::Operator::SLASH
+                        | tac_kind::Operator::PERCENT
+                        | tac_kind::Operator::LSHIFT
+                        | tac_kind::Operator::RSHIFT => right,
+                        _ => Self::load_wide_immediate(&mut low_irs, right),
+                    };
+
+                    // 左オペランドをdstにロードしてから,dstに対して演算する.
+                    // 左オペランドが変数でも書き換えずに済む.
+                    // e.g. t1 <- x + y
+                    // -----------------
+                    // t1 <- x
+                    // t1 <- t1 + y
+                    // 右オペランドとdstに同じレジスタが割り当てられている場合は,
+                    // ロードで右オペランドを壊さないようにraxで演算する.
+                    let work = if Self::is_same_register(&dst, &right) {
+                        X64Operand::new_rax().with_type_of(&dst)
+                    } else {
+                        dst.clone()
+                    };
+                    let is_unsigned_operation = left.is_unsigned;
+                    Self::add_move(&mut low_irs, work.clone(), left);
 
-                    // 左が数値リテラル -> (右がレジスタであれば) raxにロードしてから演算
-                    //                  -> (そうでなければ)先にdstにロードしてから,演算
-                    // それ以外         -> 演算してからdstにロード
-                    match &left.kind {
-                        // 左がレジスタ
-                        X64OpeKind::REG => {
-                            Self::add_binary_ir_matching_opcode(
-                                &mut low_irs,
-                                opcode,
-                                left.clone(),
-                                right,
-                            );
-
-                            let load_ir = X64IR::new_mov(dst, left);
-                            low_irs.push(load_ir);
-                        }
-                        X64OpeKind::AUTOVAR(_name, _offset) => {
-                            Self::add_binary_ir_matching_opcode(
-                                &mut low_irs,
-                                opcode,
-                                left.clone(),
-                                right,
-                            );
-                            let load_ir = X64IR::new_mov(dst, left.clone());
-                            low_irs.push(load_ir);
-                        }
-                        // 左が整数値
-                        X64OpeKind::INTLIT(_) => {
-                            match &right.kind {
-                                X64OpeKind::REG => {
-                                    // r.g. t1 <- 2 + t1
-                                    // -----------------
-                                    // rax <- 2
-                                    // rax <- rax + t1
-                                    // t1 <- rax
-                                    let load_ir =
-                                        X64IR::new_mov(X64Operand::new_rax(), left.clone());
-                                    low_irs.push(load_ir);
-
-                                    // 演算命令
-                                    Self::add_binary_ir_matching_opcode(
-                                        &mut low_irs,
-                                        opcode,
-                                        X64Operand::new_rax(),
-                                        right,
-                                    );
-
-                                    let load_ir = X64IR::new_mov(dst, X64Operand::new_rax());
-                                    low_irs.push(load_ir);
-                                }
-                                _ => {
-                                    let load_ir = X64IR::new_mov(dst.clone(), left.clone());
-                                    low_irs.push(load_ir);
-
-                                    // 演算命令
-                                    Self::add_binary_ir_matching_opcode(
-                                        &mut low_irs,
-                                        opcode,
-                                        dst,
-                                        right,
-                                    );
-                                }
-                            }
-                        }
-                        _ => panic!("got invalid operand"),
+                    // 比較演算 -> cmpした結果をsetccで取り出す
+                    let mut should_extend = false;
+                    if let Some(cond) =
+                        Self::condition_from_operator(&operator_bf, is_unsigned_operation)
+                    {
+                        low_irs.push(X64IR::new_cmp(work.clone(), right));
+                        low_irs.push(X64IR::new_set(cond, work.clone()));
+                    } else {
+                        let opcode: X64IRKind = Self::binary_opcode_from_operator(operator_bf);
+                        should_extend = Self::may_break_extension(&opcode, &dst);
+                        Self::add_binary_ir_matching_opcode(
+                            &mut low_irs,
+                            opcode,
+                            work.clone(),
+                            right,
+                        );
+                    }
+
+                    if !Self::is_same_register(&dst, &work) {
+                        low_irs.push(X64IR::new_mov(dst.clone(), work));
+                    }
+                    if should_extend {
+                        low_irs.push(X64IR::new_extend(dst));
                     }
                 }
             }
@@ -165,9 +191,50 @@ impl HighOptimizer {
             X64IRKind::NEGATIVE(_) => {
                 low_irs.push(X64IR::new_neg(inner));
             }
+            X64IRKind::NOT(_) => {
+                low_irs.push(X64IR::new_not(inner));
+            }
+            // アドレスをロードしたレジスタから,指す先の値を読み出す
+            X64IRKind::LOAD(_, _) => {
+                low_irs.push(X64IR::new_load(inner.clone(), inner));
+            }
+            // 型変換 -> 切り詰めてから拡張し直す
+            X64IRKind::EXTEND(_) => {
+                low_irs.push(X64IR::new_extend(inner));
+            }
+            // !x -> x == 0
+            X64IRKind::SET(cond, _) => {
+                low_irs.push(X64IR::new_cmp(
+                    inner.clone(),
+                    X64Operand::new(X64OpeKind::INTLIT(0), 0, 0),
+                ));
+                low_irs.push(X64IR::new_set(cond, inner));
+            }
             _ => {}
         }
     }
+    // 0と比較して条件ジャンプする.
+    // 即値であればコンパイル時に分岐先が決まる.
+    fn add_conditional_jump(
+        low_irs: &mut Vec<X64IR>,
+        cmp_op: X64Operand,
+        label_name: String,
+        jump_if_true: bool,
+    ) {
+        if let X64OpeKind::INTLIT(value) = cmp_op.kind {
+            if (value != 0) == jump_if_true {
+                low_irs.push(X64IR::new_jump(label_name));
+            }
+            return;
+        }
+
+        low_irs.push(X64IR::new_cmpzero(cmp_op));
+        if jump_if_true {
+            low_irs.push(X64IR::new_jumpnotzero(label_name));
+        } else {
+            low_irs.push(X64IR::new_jumpzero(label_name));
+        }
+    }
     fn add_binary_ir_matching_opcode(
         low_irs: &mut Vec<X64IR>,
         opcode: X64IRKind,
@@ -187,6 +254,24 @@ impl HighOptimizer {
             X64IRKind::DIV(_, _) => {
                 low_irs.push(X64IR::new_div(left, right));
             }
+            X64IRKind::MOD(_, _) => {
+                low_irs.push(X64IR::new_mod(left, right));
+            }
+            X64IRKind::SHL(_, _) => {
+                low_irs.push(X64IR::new_shl(left, right));
+            }
+            X64IRKind::SAR(_, _) => {
+                low_irs.push(X64IR::new_sar(left, right));
+            }
+            X64IRKind::AND(_, _) => {
+                low_irs.push(X64IR::new_and(left, right));
+            }
+            X64IRKind::OR(_, _) => {
+                low_irs.push(X64IR::new_or(left, right));
+            }
+            X64IRKind::XOR(_, _) => {
+                low_irs.push(X64IR::new_xor(left, right));
+            }
             _ => {}
         }
     }
@@ -194,6 +279,16 @@ impl HighOptimizer {
         // 返すIRKindの中身は全てINVALID
         match operator {
             tac_kind::Operator::MINUS => X64IRKind::NEGATIVE(X64Operand::new_inv()),
+            tac_kind::Operator::BANG => X64IRKind::SET(X64CondKind::EQUAL, X64Operand::new_inv()),
+            tac_kind::Operator::CAST => X64IRKind::EXTEND(X64Operand::new_inv()),
+            tac_kind::Operator::TILDE => X64IRKind::NOT(X64Operand::new_inv()),
+            tac_kind::Operator::ASTERISK => {
+                X64IRKind::LOAD(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            // +x はロードするだけ
+            tac_kind::Operator::PLUS => {
+                X64IRKind::MOV(X64Operand::new_inv(), X64Operand::new_inv())
+            }
             _ => panic!("can't traslate opcode from operator"),
         }
     }
@@ -212,11 +307,151 @@ impl HighOptimizer {
             tac_kind::Operator::SLASH => {
                 X64IRKind::DIV(X64Operand::new_inv(), X64Operand::new_inv())
             }
+            tac_kind::Operator::PERCENT => {
+                X64IRKind::MOD(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            tac_kind::Operator::LSHIFT => {
+                X64IRKind::SHL(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            tac_kind::Operator::RSHIFT => {
+                X64IRKind::SAR(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            tac_kind::Operator::AMPERSAND => {
+                X64IRKind::AND(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            tac_kind::Operator::PIPE => X64IRKind::OR(X64Operand::new_inv(), X64Operand::new_inv()),
+            tac_kind::Operator::CARET => {
+                X64IRKind::XOR(X64Operand::new_inv(), X64Operand::new_inv())
+            }
+            _ => panic!("can't traslate opcode from operator"),
+        }
+    }
+    // 直前の演算で,8バイト未満の値の上位ビットが型と合わなくなる可能性があるか
+    // e.g. int同士の加算は32bitを超えうるので,結果を符号拡張し直す
+    fn may_break_extension(opcode: &X64IRKind, dst: &X64Operand) -> bool {
+        let is_breaking_op = matches!(
+            opcode,
+            X64IRKind::ADD(_, _)
+                | X64IRKind::SUB(_, _)
+                | X64IRKind::MUL(_, _)
+                | X64IRKind::SHL(_, _)
+                | X64IRKind::NEGATIVE(_)
+                | X64IRKind::NOT(_)
+        );
+        is_breaking_op && dst.size < 8
+    }
+    fn condition_from_operator(
+        operator: &tac_kind::Operator,
+        is_unsigned: bool,
+    ) -> Option<X64CondKind> {
+        match operator {
+            tac_kind::Operator::LESS if is_unsigned => Some(X64CondKind::BELOW),
+            tac_kind::Operator::LESSEQ if is_unsigned => Some(X64CondKind::BELOWEQ),
+            tac_kind::Operator::GREATER if is_unsigned => Some(X64CondKind::ABOVE),
+            tac_kind::Operator::GREATEREQ if is_unsigned => Some(X64CondKind::ABOVEEQ),
+            tac_kind::Operator::LESS => Some(X64CondKind::LESS),
+            tac_kind::Operator::LESSEQ => Some(X64CondKind::LESSEQ),
+            tac_kind::Operator::GREATER => Some(X64CondKind::GREATER),
+            tac_kind::Operator::GREATEREQ => Some(X64CondKind::GREATEREQ),
+            tac_kind::Operator::EQUAL => Some(X64CondKind::EQUAL),
+            tac_kind::Operator::NOTEQUAL => Some(X64CondKind::NOTEQUAL),
+            _ => None,
+        }
+    }
+    // 64bitの演算やメモリへの mov の即値は32bitから符号拡張されるので,
+    // 収まらない即値は rax に置いてから使う
+    // e.g. cmp r10, 4294967295 -> mov rax, 4294967295 ; cmp r10, rax
+    fn load_wide_immediate(low_irs: &mut Vec<X64IR>, op: X64Operand) -> X64Operand {
+        match op.kind {
+            X64OpeKind::INTLIT(value) if i32::try_from(value).is_err() => {
+                let rax = X64Operand::new_rax().with_type_of(&op);
+                low_irs.push(X64IR::new_mov(rax.clone(), op));
+                rax
+            }
+            _ => op,
+        }
+    }
+    // 同じレジスタ同士の mov は何もしないので出力しない
+    // (レジスタ割付で合併した値の読み込み)
+    fn add_move(low_irs: &mut Vec<X64IR>, dst: X64Operand, src: X64Operand) {
+        if !Self::is_same_register(&dst, &src) {
+            low_irs.push(X64IR::new_mov(dst, src));
+        }
+    }
+    fn is_same_register(a: &X64Operand, b: &X64Operand) -> bool {
+        match (&a.kind, &b.kind) {
+            (X64OpeKind::REG, X64OpeKind::REG) => a.phys == b.phys,
+            _ => false,
+        }
+    }
+    // System V ABI に従った関数呼び出し
+    // 1. 呼び出し後も使う caller-saved なレジスタを push で退避
+    // 2. 7番目以降の引数を後ろから push し, 6番目までを引数レジスタに置く
+    //    call 命令の直前で rsp が16バイト境界に揃うよう,必要なら8バイト空ける
+    //    可変長引数の関数には al でベクタレジスタの数を渡す(浮動小数点数は無いので常に0)
+    //    関数ポインタを経由する場合は,それを置いたレジスタを call する
+    // 3. 返り値を rax から受け取り,型に合わせて拡張し直す
+    // 4. 積んだ引数を捨て,退避したレジスタを戻す
+    fn add_call_sequence(
+        low_irs: &mut Vec<X64IR>,
+        dst: X64Operand,
+        callee: X64Operand,
+        args: Vec<X64Operand>,
+        is_variadic: bool,
+        saved: Vec<usize>,
+    ) {
+        let saved_regs: Vec<X64Operand> = saved
+            .iter()
+            .map(|phys| X64Operand::new(X64OpeKind::REG, 0, *phys))
+            .collect();
+        for reg in saved_regs.iter() {
+            low_irs.push(X64IR::new_push(reg.clone()));
+        }
+
+        let stack_args = args.len().saturating_sub(REGISTER_ARGS);
+        let padding = if (saved_regs.len() + stack_args) % 2 == 1 {
+            8
+        } else {
+            0
+        };
+        if padding != 0 {
+            low_irs.push(X64IR::new_alloc_stack(padding));
+        }
+        for (reg_num, arg) in args.into_iter().enumerate().rev() {
+            low_irs.push(X64IR::new_genparam(reg_num, arg));
+        }
+
+        if is_variadic {
+            let vector_count = X64Operand::new(X64OpeKind::INTLIT(0), 0, 0);
+            low_irs.push(X64IR::new_mov(X64Operand::new_rax(), vector_count));
+        }
+        match callee.kind {
+            X64OpeKind::GLOBALVAR(name) => low_irs.push(X64IR::new_call(name)),
+            _ => low_irs.push(X64IR::new_call_reg(callee)),
+        }
+
+        let pushed_size = stack_args * 8 + padding;
+        if pushed_size != 0 {
+            low_irs.push(X64IR::new_free_stack(pushed_size));
+        }
+        low_irs.push(X64IR::new_mov(
+            dst.clone(),
+            X64Operand::new_rax().with_type_of(&dst),
+        ));
+        if dst.size < 8 {
+            low_irs.push(X64IR::new_extend(dst));
+        }
+
+        for reg in saved_regs.into_iter().rev() {
+            low_irs.push(X64IR::new_pop(reg));
         }
     }
     fn tac_operand_to_x64(op: tac_kind::Operand) -> X64Operand {
         let kind = Self::tac_opekind_to_x64(op.kind);
-        X64Operand::new(kind, op.virt, op.phys)
+        let mut x64_op = X64Operand::new(kind, op.virt, op.phys);
+        x64_op.size = op.size;
+        x64_op.is_unsigned = op.is_unsigned;
+        x64_op
     }
     fn tac_opekind_to_x64(kind: tac_kind::OpeKind) -> X64OpeKind {
         match kind {
@@ -224,8 +459,9 @@ impl HighOptimizer {
             tac_kind::OpeKind::AUTOVARIABLE(name, offset) => {
                 X64OpeKind::AUTOVAR(name.to_string(), offset)
             }
+            tac_kind::OpeKind::GLOBALVARIABLE(name) => X64OpeKind::GLOBALVAR(name),
             tac_kind::OpeKind::REG => X64OpeKind::REG,
-            tac_kind::OpeKind::CALL(name) => X64OpeKind::CALL(name),
+            tac_kind::OpeKind::DEREFERENCE(offset) => X64OpeKind::DEREFERENCE(offset),
             tac_kind::OpeKind::INVALID => X64OpeKind::INVALID,
         }
     }
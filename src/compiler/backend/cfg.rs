@@ -6,6 +6,11 @@ use crate::compiler::ir::three_address_code::{
 };
 
 type RegisterNumber = usize;
+type BlockNumber = usize;
+type TacNumber = usize;
+// ラベル名 -> (ラベルを持つブロック, ブロック内の位置)
+type LabelMap = BTreeMap<String, (BlockNumber, TacNumber)>;
+
 #[allow(dead_code)]
 #[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct ControlFlowGraphInBB {
@@ -25,6 +30,108 @@ impl ControlFlowGraphInBB {
     }
 }
 
+// 関数全体のCFG
+// ノードは各ベーシックブロックで,その後ろに出口ノードを1つ置く
+// 入口ノードは常に先頭のブロック
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone)]
+pub struct ControlFlowGraph {
+    pub succ: Vec<BTreeSet<BlockNumber>>,
+    pub prev: Vec<BTreeSet<BlockNumber>>,
+    // ブロックの途中にもジャンプがあるので,命令ごとの飛び先も持っておく
+    pub jumps: BTreeMap<(BlockNumber, TacNumber), BTreeSet<BlockNumber>>,
+    // 末尾まで実行した時に進むブロック(出口ノードを含む)
+    pub fall_through: Vec<Option<BlockNumber>>,
+}
+impl ControlFlowGraph {
+    pub const ENTRY: BlockNumber = 0;
+
+    pub fn new(block_number: usize) -> Self {
+        Self {
+            succ: vec![BTreeSet::new(); block_number + 1],
+            prev: vec![BTreeSet::new(); block_number + 1],
+            jumps: BTreeMap::new(),
+            fall_through: vec![None; block_number],
+        }
+    }
+    pub fn exit(&self) -> BlockNumber {
+        self.succ.len() - 1
+    }
+    pub fn block_number(&self) -> usize {
+        self.exit()
+    }
+    pub fn successors(&self, blk: BlockNumber) -> &BTreeSet<BlockNumber> {
+        &self.succ[blk]
+    }
+    fn add_edge(&mut self, from: BlockNumber, to: BlockNumber) {
+        self.succ[from].insert(to);
+        self.prev[to].insert(from);
+    }
+    // 入口から到達可能なブロックの集合(出口ノードは含まない)
+    pub fn reachable(&self) -> BTreeSet<BlockNumber> {
+        self.postorder().into_iter().collect()
+    }
+    // 入口から辿れるブロックの逆後順
+    // 前向きのデータフロー解析はこの順で回すと早く収束する
+    pub fn reverse_postorder(&self) -> Vec<BlockNumber> {
+        let mut order = self.postorder();
+        order.reverse();
+        order
+    }
+    pub fn postorder(&self) -> Vec<BlockNumber> {
+        let mut visited = vec![false; self.succ.len()];
+        let mut order = Vec::new();
+        if self.block_number() != 0 {
+            self.visit_postorder(Self::ENTRY, &mut visited, &mut order);
+        }
+        order
+    }
+    fn visit_postorder(
+        &self,
+        blk: BlockNumber,
+        visited: &mut Vec<bool>,
+        order: &mut Vec<BlockNumber>,
+    ) {
+        visited[blk] = true;
+        for s in self.successors(blk).iter() {
+            if !visited[*s] {
+                self.visit_postorder(*s, visited, order);
+            }
+        }
+        if blk != self.exit() {
+            order.push(blk);
+        }
+    }
+    // ブロックが閉路(ループ)の中にあるか
+    pub fn in_loop(&self, blk: BlockNumber) -> bool {
+        let mut visited = vec![false; self.succ.len()];
+        let mut stack: Vec<BlockNumber> = self.successors(blk).iter().copied().collect();
+        while let Some(next) = stack.pop() {
+            if next == blk {
+                return true;
+            }
+            if visited[next] {
+                continue;
+            }
+            visited[next] = true;
+            stack.extend(self.successors(next).iter().copied());
+        }
+        false
+    }
+    pub fn dump_to_stderr(&self, labels: Vec<&String>) {
+        for (blk, label) in labels.iter().enumerate() {
+            let succ: Vec<String> = self
+                .successors(blk)
+                .iter()
+                .map(|s| match labels.get(*s) {
+                    Some(succ_label) => succ_label.to_string(),
+                    None => "exit".to_string(),
+                })
+                .collect();
+            eprintln!("  {} -> {}", label, succ.join(", "));
+        }
+    }
+}
+
 impl HighOptimizer {
     pub fn build_cfg(&mut self) {
         // 各関数に対しCFG構築を行う
@@ -36,16 +143,111 @@ impl HighOptimizer {
 
         self.functions = functions;
     }
-    fn build_cfg_with_func(&mut self, func: &mut IRFunction) {
+    pub fn build_cfg_with_func(&mut self, func: &mut IRFunction) {
+        // ラベルは関数内のどのブロックにもありうるので,関数単位で集める
+        let label_map = self.build_labelmap(func);
+
         let block_number = func.blocks.len();
         for blk_idx in 0..block_number {
-            let cfg_inbb = self.build_cfg_with_bb(func.blocks[blk_idx].tacs.clone());
+            let cfg_inbb =
+                self.build_cfg_with_bb(func.blocks[blk_idx].tacs.clone(), &label_map, blk_idx);
             func.blocks[blk_idx].cfg_inbb = cfg_inbb;
         }
+
+        func.cfg = self.build_cfg_between_bbs(func, &label_map);
+    }
+    fn build_cfg_between_bbs(
+        &mut self,
+        func: &IRFunction,
+        label_map: &LabelMap,
+    ) -> ControlFlowGraph {
+        let mut cfg = ControlFlowGraph::new(func.blocks.len());
+        let exit = cfg.exit();
+
+        for (blk_idx, bb) in func.blocks.iter().enumerate() {
+            // ブロックの途中で無条件に抜けていれば,末尾へは到達しない
+            let mut reaches_end = true;
+
+            for (i, t) in bb.tacs.iter().enumerate() {
+                let targets: Vec<&String> = match &t.kind {
+                    TacKind::GOTO(label) => {
+                        reaches_end = false;
+                        vec![label]
+                    }
+                    TacKind::IFF(_, label) | TacKind::IFT(_, label) => vec![label],
+                    TacKind::JUMPTABLE(_, _, targets) => {
+                        reaches_end = false;
+                        targets.iter().collect()
+                    }
+                    TacKind::RET(_) => {
+                        reaches_end = false;
+                        cfg.add_edge(blk_idx, exit);
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                for label in targets {
+                    if let Some((target_blk, _)) = label_map.get(label) {
+                        cfg.add_edge(blk_idx, *target_blk);
+                        cfg.jumps
+                            .entry((blk_idx, i))
+                            .or_default()
+                            .insert(*target_blk);
+                    }
+                }
+            }
+
+            // 次のブロックへのフォールスルー(最後のブロックなら出口へ)
+            if reaches_end {
+                let next = blk_idx + 1;
+                cfg.add_edge(blk_idx, next);
+                cfg.fall_through[blk_idx] = Some(next);
+            }
+        }
+
+        cfg
+    }
+    // 入口から到達できないブロックを取り除き,CFGを作り直す
+    pub fn remove_unreachable_blocks(&mut self) {
+        let mut functions = self.functions.clone();
+        for func in functions.iter_mut() {
+            let reachable = func.cfg.reachable();
+            if reachable.len() == func.blocks.len() {
+                continue;
+            }
+
+            let blocks = std::mem::take(&mut func.blocks);
+            func.blocks = blocks
+                .into_iter()
+                .enumerate()
+                .filter(|(blk_idx, _)| reachable.contains(blk_idx))
+                .map(|(_, bb)| bb)
+                .collect();
+            self.build_cfg_with_func(func);
+        }
+        self.functions = functions;
+    }
+    pub fn dump_cfg_to_stderr(&self) {
+        for func in self.functions.iter() {
+            eprintln!("{}'s control-flow-graph:", func.name);
+            let labels = func.blocks.iter().map(|bb| &bb.label).collect();
+            func.cfg.dump_to_stderr(labels);
+        }
     }
-    fn build_cfg_with_bb(&mut self, tacs: Vec<ThreeAddressCode>) -> ControlFlowGraphInBB {
+    fn build_cfg_with_bb(
+        &mut self,
+        tacs: Vec<ThreeAddressCode>,
+        label_map: &LabelMap,
+        blk_idx: usize,
+    ) -> ControlFlowGraphInBB {
         // jump-statement系にエッジを追加するときのために利用
-        let label_map: BTreeMap<String, usize> = self.build_labelmap(&tacs);
+        // 他のブロックへのエッジは関数全体のCFGで扱う
+        let label_map: BTreeMap<&String, usize> = label_map
+            .iter()
+            .filter(|(_, (label_blk, _))| *label_blk == blk_idx)
+            .map(|(label, (_, tac_idx))| (label, *tac_idx))
+            .collect();
 
         // 各ベーシックブロックに対応したCFG
         let mut cfg_inbb = ControlFlowGraphInBB::new(tacs.len());
@@ -75,7 +277,7 @@ impl HighOptimizer {
                         self.add_prev(&mut cfg_inbb, i, i - 1);
                     }
                 }
-                TacKind::IFF(_lv, label) => {
+                TacKind::IFF(_lv, label) | TacKind::IFT(_lv, label) => {
                     self.add_succ(&mut cfg_inbb, tacs.len(), i, i + 1);
 
                     if i != 0 && !prev_inst_is_goto {
@@ -98,7 +300,7 @@ impl HighOptimizer {
                         self.add_prev(&mut cfg_inbb, i, i - 1);
                     }
                 }
-                TacKind::GENPARAM(_, _) => {
+                TacKind::CALL(_, _, _, _, _) => {
                     self.add_succ(&mut cfg_inbb, tacs.len(), i, i + 1);
 
                     if i != 0 && !prev_inst_is_goto {
@@ -119,6 +321,22 @@ impl HighOptimizer {
                         self.add_prev(&mut cfg_inbb, i, i - 1);
                     }
                 }
+                // テーブルに並ぶ全てのラベルへ飛びうる
+                TacKind::JUMPTABLE(_index, _table, targets) => {
+                    if i != 0 && !prev_inst_is_goto {
+                        self.add_prev(&mut cfg_inbb, i, i - 1);
+                    }
+
+                    for target in targets.iter() {
+                        if let Some(label_idx) = label_map.get(target) {
+                            self.add_succ(&mut cfg_inbb, tacs.len(), i, *label_idx);
+                            self.add_prev(&mut cfg_inbb, *label_idx, i);
+                        }
+                    }
+
+                    prev_inst_is_goto = true;
+                    continue;
+                }
                 TacKind::GOTO(label_name) => {
                     if i != 0 && !prev_inst_is_goto {
                         self.add_prev(&mut cfg_inbb, i, i - 1);
@@ -142,11 +360,13 @@ impl HighOptimizer {
         }
         cfg_inbb
     }
-    fn build_labelmap(&mut self, tacs: &Vec<ThreeAddressCode>) -> BTreeMap<String, usize> {
-        let mut label_map: BTreeMap<String, usize> = BTreeMap::new();
-        for (i, t) in tacs.iter().enumerate() {
-            if let TacKind::LABEL(label_name) = t.kind.clone() {
-                label_map.insert(label_name.to_string(), i);
+    fn build_labelmap(&mut self, func: &IRFunction) -> LabelMap {
+        let mut label_map = LabelMap::new();
+        for (blk_idx, bb) in func.blocks.iter().enumerate() {
+            for (i, t) in bb.tacs.iter().enumerate() {
+                if let TacKind::LABEL(label_name) = &t.kind {
+                    label_map.insert(label_name.to_string(), (blk_idx, i));
+                }
             }
         }
 
@@ -173,4 +393,111 @@ impl HighOptimizer {
 }
 
 #[cfg(test)]
-mod build_cfg_tests {}
+mod build_cfg_tests {
+    use super::*;
+    use crate::compiler::ir::three_address_code::{basicblock::BasicBlock, tac_kind::Operand};
+
+    // entry -> (.L0 | .L1), .L0 -> .L2, .L2 -> (.L0 | exit), .L1 -> exit
+    // .L3 はどこからも飛んでこない
+    fn new_function() -> IRFunction {
+        let cond = Operand::new_virtreg(0);
+        let block = |label: &str, tacs: Vec<ThreeAddressCode>| {
+            let mut bb = BasicBlock::new(label.to_string());
+            bb.tacs = tacs;
+            bb
+        };
+        let label = |name: &str| ThreeAddressCode::new_label(name.to_string());
+        let goto = |name: &str| ThreeAddressCode::new_goto(name.to_string());
+
+        let mut func = IRFunction::new("f".to_string());
+        func.blocks = vec![
+            block(
+                "entry",
+                vec![
+                    ThreeAddressCode::new_assign_code(cond.clone(), Operand::new_int_literal(1)),
+                    ThreeAddressCode::new_iff(cond.clone(), ".L1".to_string()),
+                ],
+            ),
+            block(".L0", vec![label(".L0"), goto(".L2")]),
+            block(".L3", vec![goto(".L0")]),
+            block(
+                ".L1",
+                vec![
+                    label(".L1"),
+                    ThreeAddressCode::new_return(Operand::new_int_literal(0)),
+                ],
+            ),
+            block(
+                ".L2",
+                vec![
+                    label(".L2"),
+                    ThreeAddressCode::new_ift(cond, ".L0".to_string()),
+                ],
+            ),
+        ];
+        func
+    }
+
+    fn build_cfg_input(func: IRFunction) -> HighOptimizer {
+        let mut high_opt = HighOptimizer::new(vec![func], BTreeMap::new(), Vec::new());
+        high_opt.build_cfg();
+        high_opt
+    }
+
+    #[test]
+    fn test_build_cfg_between_bbs() {
+        let high_opt = build_cfg_input(new_function());
+        let cfg = &high_opt.functions[0].cfg;
+        let set = |blocks: &[usize]| blocks.iter().copied().collect::<BTreeSet<usize>>();
+
+        assert_eq!(5, cfg.exit());
+        assert_eq!(set(&[1, 3]), cfg.succ[0]);
+        assert_eq!(set(&[4]), cfg.succ[1]);
+        assert_eq!(set(&[1]), cfg.succ[2]);
+        assert_eq!(set(&[5]), cfg.succ[3]);
+        // 最後のブロックは出口へフォールスルーする
+        assert_eq!(set(&[1, 5]), cfg.succ[4]);
+
+        assert_eq!(set(&[0, 2, 4]), cfg.prev[1]);
+        assert_eq!(set(&[3, 4]), cfg.prev[5]);
+
+        assert_eq!(Some(&set(&[3])), cfg.jumps.get(&(0, 1)));
+        assert_eq!(Some(&set(&[1])), cfg.jumps.get(&(4, 1)));
+        assert_eq!(vec![Some(1), None, None, None, Some(5)], cfg.fall_through);
+
+        // 他のブロックへのジャンプは,ブロック内のCFGには現れない
+        let entry_cfg = &high_opt.functions[0].blocks[0].cfg_inbb;
+        assert!(entry_cfg.succ[1].is_empty());
+    }
+
+    #[test]
+    fn test_reachability_and_reverse_postorder() {
+        let high_opt = build_cfg_input(new_function());
+        let cfg = &high_opt.functions[0].cfg;
+
+        assert_eq!(
+            vec![0, 1, 3, 4],
+            cfg.reachable().into_iter().collect::<Vec<usize>>()
+        );
+        assert_eq!(vec![4, 1, 3, 0], cfg.postorder());
+        assert_eq!(vec![0, 3, 1, 4], cfg.reverse_postorder());
+    }
+
+    #[test]
+    fn test_remove_unreachable_blocks() {
+        let mut high_opt = build_cfg_input(new_function());
+        high_opt.remove_unreachable_blocks();
+
+        let func = &high_opt.functions[0];
+        let labels: Vec<&str> = func.blocks.iter().map(|bb| bb.label.as_str()).collect();
+        assert_eq!(vec!["entry", ".L0", ".L1", ".L2"], labels);
+
+        // ブロック番号が詰められた後のCFGになっている
+        assert_eq!(4, func.cfg.exit());
+        assert_eq!(
+            vec![3],
+            func.cfg.succ[1].iter().copied().collect::<Vec<usize>>()
+        );
+        assert_eq!(4, func.cfg.reachable().len());
+    }
+}
@@ -0,0 +1,435 @@
+use crate::compiler::backend::cfg::ControlFlowGraph;
+use crate::compiler::backend::high_optimizer::HighOptimizer;
+use crate::compiler::backend::register_description::RegisterDescription;
+use crate::compiler::ir::three_address_code::{
+    function::IRFunction,
+    tac_kind::{Operator, TacKind},
+};
+use crate::error::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+type RegisterNumber = usize;
+
+// 干渉グラフ
+// 合併した仮想レジスタは,代表の番号を持つ1つのノードとして扱う
+#[derive(Debug, Default)]
+struct InterferenceGraph {
+    adjacency: BTreeMap<RegisterNumber, BTreeSet<RegisterNumber>>,
+    // 代表 -> 合併した仮想レジスタ(自身を含む)
+    members: BTreeMap<RegisterNumber, BTreeSet<RegisterNumber>>,
+    // 合併された仮想レジスタ -> 代表
+    alias: BTreeMap<RegisterNumber, RegisterNumber>,
+    // 固定レジスタの制約で置けないレジスタ
+    forbidden: BTreeMap<RegisterNumber, BTreeSet<RegisterNumber>>,
+    crosses_call: BTreeSet<RegisterNumber>,
+    // mov dst, src を生む (dst, src) の組
+    moves: Vec<(RegisterNumber, RegisterNumber)>,
+}
+
+impl InterferenceGraph {
+    fn add_node(&mut self, virt: RegisterNumber) {
+        self.adjacency.entry(virt).or_default();
+        self.members
+            .entry(virt)
+            .or_insert_with(|| Some(virt).into_iter().collect());
+        self.forbidden.entry(virt).or_default();
+    }
+    fn add_edge(&mut self, a: RegisterNumber, b: RegisterNumber) {
+        if a == b {
+            return;
+        }
+        self.adjacency.entry(a).or_default().insert(b);
+        self.adjacency.entry(b).or_default().insert(a);
+    }
+    fn find(&self, virt: RegisterNumber) -> RegisterNumber {
+        let mut node = virt;
+        while let Some(representative) = self.alias.get(&node) {
+            node = *representative;
+        }
+        node
+    }
+    fn interferes(&self, a: RegisterNumber, b: RegisterNumber) -> bool {
+        self.adjacency[&a].contains(&b)
+    }
+    // そのノードに使えるレジスタの数
+    fn colors(&self, node: RegisterNumber, allocatable: &[RegisterNumber]) -> usize {
+        allocatable
+            .iter()
+            .filter(|phys| !self.forbidden[&node].contains(phys))
+            .count()
+    }
+    // b を a に合併する
+    fn merge(&mut self, a: RegisterNumber, b: RegisterNumber) {
+        let neighbors = self.adjacency.remove(&b).unwrap_or_default();
+        for neighbor in neighbors.iter() {
+            if let Some(adjacency) = self.adjacency.get_mut(neighbor) {
+                adjacency.remove(&b);
+            }
+            self.add_edge(a, *neighbor);
+        }
+
+        let members = self.members.remove(&b).unwrap_or_default();
+        self.members.entry(a).or_default().extend(members);
+        let forbidden = self.forbidden.remove(&b).unwrap_or_default();
+        self.forbidden.entry(a).or_default().extend(forbidden);
+        if self.crosses_call.remove(&b) {
+            self.crosses_call.insert(a);
+        }
+        self.alias.insert(b, a);
+    }
+}
+
+impl HighOptimizer {
+    // Chaitin/Briggs 方式のグラフ彩色によるレジスタ割付
+    // 干渉グラフを作り,保守的に mov を合併してから彩色する
+    // 彩色できなかったノードはスピルし,グラフを作り直す
+    pub fn graph_coloring_for_virtual_registers(&mut self, description: &RegisterDescription) {
+        let mut functions = self.functions.clone();
+        for func in functions.iter_mut() {
+            self.graph_coloring_for_func(func, description);
+        }
+        self.functions = functions;
+    }
+    fn graph_coloring_for_func(
+        &mut self,
+        func: &mut IRFunction,
+        description: &RegisterDescription,
+    ) {
+        let allocatable = description.allocatable_registers();
+        // スピルで作ったレジスタは生存区間が短いので,再びスピルしても意味がない
+        let mut unspillable: BTreeSet<RegisterNumber> = BTreeSet::new();
+
+        loop {
+            let (graph, live_out) = self.build_interference_graph(func, description);
+            let graph = Self::coalesce_moves(graph, &allocatable);
+            let stack = Self::simplify(func, &graph, &allocatable, &unspillable);
+            let (colors, spilled) = Self::select_colors(&graph, stack, description);
+
+            if spilled.is_empty() {
+                let mut assignment = BTreeMap::new();
+                for (node, members) in graph.members.iter() {
+                    for virt in members.iter() {
+                        assignment.insert(*virt, colors[node]);
+                    }
+                }
+                Self::save_registers_across_calls(func, &live_out, &assignment, description);
+                Self::write_physical_registers(func, &assignment);
+                return;
+            }
+
+            let spilled: BTreeSet<RegisterNumber> = spilled
+                .iter()
+                .filter(|node| Self::is_spillable(&graph, **node, &unspillable))
+                .flat_map(|node| graph.members[node].iter().copied())
+                .collect();
+            if spilled.is_empty() {
+                let err = Error::new(
+                    ErrorKind::RegAlloc,
+                    (0, 0),
+                    ErrorMsg::CantUseNoMoreRegisters,
+                );
+                err.compile_error();
+                return;
+            }
+
+            // 命令の位置が変わるので,CFGと生存情報を作り直す
+            Self::insert_spill_code(func, &spilled, &mut unspillable);
+            self.build_cfg_with_func(func);
+            self.liveness_analyze_func(func);
+        }
+    }
+    // 命令が定義するレジスタと,その直後に生きているレジスタを干渉させる
+    // 関数の先頭で(定義されずに)生きているレジスタ同士も干渉させる
+    fn build_interference_graph(
+        &mut self,
+        func: &IRFunction,
+        description: &RegisterDescription,
+    ) -> (InterferenceGraph, Vec<Vec<BTreeSet<RegisterNumber>>>) {
+        let (live_in, live_out) = self.liveness_analysis(func);
+        let mut graph = InterferenceGraph::default();
+
+        for (blk_idx, bb) in func.blocks.iter().enumerate() {
+            for (idx, t) in bb.tacs.iter().enumerate() {
+                let def = &bb.cfg_inbb.def[idx];
+                let used = &bb.cfg_inbb.used[idx];
+                let out = &live_out[blk_idx][idx];
+                let living = def | &(&live_in[blk_idx][idx] | out);
+                for virt in living.iter() {
+                    graph.add_node(*virt);
+                }
+
+                for defined in def.iter() {
+                    for live in out.iter() {
+                        graph.add_edge(*defined, *live);
+                    }
+                }
+
+                let constraint = description.constraint(&t.kind);
+                for virt in living.iter() {
+                    graph
+                        .forbidden
+                        .entry(*virt)
+                        .or_default()
+                        .extend(constraint.clobbered.iter());
+                }
+                for virt in used.iter() {
+                    graph
+                        .forbidden
+                        .entry(*virt)
+                        .or_default()
+                        .extend(constraint.operands.iter());
+                }
+                if let TacKind::CALL(_, _, _, _, _) = t.kind {
+                    graph.crosses_call.extend(out - def);
+                }
+                if let Some(pair) = Self::move_pair(&t.kind) {
+                    graph.moves.push(pair);
+                }
+            }
+        }
+
+        if let Some(entry) = live_in[ControlFlowGraph::ENTRY].first() {
+            for a in entry.iter() {
+                for b in entry.iter() {
+                    graph.add_edge(*a, *b);
+                }
+            }
+        }
+
+        (graph, live_out)
+    }
+    // 定義先に読み込んでから演算する命令は,読み込み元と同じレジスタなら mov が要らない
+    // e.g. t1 <- t0 + 1 => mov t1, t0; add t1, 1
+    fn move_pair(kind: &TacKind) -> Option<(RegisterNumber, RegisterNumber)> {
+        match kind {
+            TacKind::UNARYEXPR(var_op, operator, src_op) if *operator != Operator::AMPERSAND => {
+                if var_op.is_register() && src_op.is_register() {
+                    return Some((var_op.virt, src_op.virt));
+                }
+                None
+            }
+            TacKind::EXPR(var_op, _, left, _) if var_op.is_register() && left.is_register() => {
+                Some((var_op.virt, left.virt))
+            }
+            _ => None,
+        }
+    }
+    // Briggs の保守的な合併
+    // 合併後のノードの,次数の大きい隣接ノードが使えるレジスタ数より少なければ,
+    // 合併しても彩色できなくなることはない
+    fn coalesce_moves(
+        mut graph: InterferenceGraph,
+        allocatable: &[RegisterNumber],
+    ) -> InterferenceGraph {
+        loop {
+            let mut changed = false;
+            for (dst, src) in graph.moves.clone() {
+                let (a, b) = (graph.find(dst), graph.find(src));
+                if a == b || graph.interferes(a, b) {
+                    continue;
+                }
+
+                let forbidden = &graph.forbidden[&a] | &graph.forbidden[&b];
+                let colors = allocatable
+                    .iter()
+                    .filter(|phys| !forbidden.contains(phys))
+                    .count();
+                let neighbors = &graph.adjacency[&a] | &graph.adjacency[&b];
+                let significant = neighbors
+                    .iter()
+                    .filter(|n| graph.adjacency[n].len() >= graph.colors(**n, allocatable))
+                    .count();
+                if significant < colors {
+                    graph.merge(a, b);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return graph;
+            }
+        }
+    }
+    // 使えるレジスタ数より次数の小さいノードから順にグラフから取り除き,スタックに積む
+    // そのようなノードが無ければ,スピルコストを次数で割った値の最も小さいノードを
+    // スピル候補として(彩色できることを期待して)積む
+    fn simplify(
+        func: &IRFunction,
+        graph: &InterferenceGraph,
+        allocatable: &[RegisterNumber],
+        unspillable: &BTreeSet<RegisterNumber>,
+    ) -> Vec<RegisterNumber> {
+        let costs = Self::spill_costs(func);
+        let mut degrees: BTreeMap<RegisterNumber, usize> = graph
+            .adjacency
+            .iter()
+            .map(|(node, neighbors)| (*node, neighbors.len()))
+            .collect();
+        let mut stack = Vec::new();
+
+        while !degrees.is_empty() {
+            let trivial = degrees
+                .iter()
+                .find(|(node, degree)| **degree < graph.colors(**node, allocatable))
+                .map(|(node, _)| *node);
+            let node = match trivial {
+                Some(node) => node,
+                None => {
+                    let spill_priority = |node: &RegisterNumber| {
+                        let cost: f64 = graph.members[node]
+                            .iter()
+                            .map(|virt| costs.get(virt).copied().unwrap_or(0.0))
+                            .sum();
+                        cost / degrees[node] as f64
+                    };
+                    // 追い出せないノードしか残っていなければ,次数の大きいものから積む
+                    degrees
+                        .keys()
+                        .filter(|node| Self::is_spillable(graph, **node, unspillable))
+                        .min_by(|a, b| spill_priority(a).partial_cmp(&spill_priority(b)).unwrap())
+                        .copied()
+                        .unwrap_or_else(|| *degrees.iter().max_by_key(|(_, d)| **d).unwrap().0)
+                }
+            };
+
+            degrees.remove(&node);
+            for neighbor in graph.adjacency[&node].iter() {
+                if let Some(degree) = degrees.get_mut(neighbor) {
+                    *degree -= 1;
+                }
+            }
+            stack.push(node);
+        }
+
+        stack
+    }
+    // スタックから取り出した順に,隣接ノードと重ならないレジスタを割り当てる
+    // 割り当てられなかったノードを実際にスピルする
+    fn select_colors(
+        graph: &InterferenceGraph,
+        mut stack: Vec<RegisterNumber>,
+        description: &RegisterDescription,
+    ) -> (
+        BTreeMap<RegisterNumber, RegisterNumber>,
+        BTreeSet<RegisterNumber>,
+    ) {
+        let mut colors: BTreeMap<RegisterNumber, RegisterNumber> = BTreeMap::new();
+        let mut spilled = BTreeSet::new();
+
+        while let Some(node) = stack.pop() {
+            let mut free: BTreeSet<RegisterNumber> =
+                description.allocatable_registers().into_iter().collect();
+            for neighbor in graph.adjacency[&node].iter() {
+                if let Some(phys) = colors.get(neighbor) {
+                    free.remove(phys);
+                }
+            }
+
+            let crosses_call = graph.crosses_call.contains(&node);
+            match Self::choose_register(description, &free, &graph.forbidden[&node], crosses_call) {
+                Some(phys) => {
+                    colors.insert(node, phys);
+                }
+                None => {
+                    spilled.insert(node);
+                }
+            }
+        }
+
+        (colors, spilled)
+    }
+    fn is_spillable(
+        graph: &InterferenceGraph,
+        node: RegisterNumber,
+        unspillable: &BTreeSet<RegisterNumber>,
+    ) -> bool {
+        graph.members[&node].is_disjoint(unspillable)
+    }
+    // 呼び出しの後も生きている caller-saved なレジスタは,呼び出しの前後で退避する
+    fn save_registers_across_calls(
+        func: &mut IRFunction,
+        live_out: &[Vec<BTreeSet<RegisterNumber>>],
+        assignment: &BTreeMap<RegisterNumber, RegisterNumber>,
+        description: &RegisterDescription,
+    ) {
+        for (blk_idx, bb) in func.blocks.iter_mut().enumerate() {
+            for (idx, t) in bb.tacs.iter_mut().enumerate() {
+                if let TacKind::CALL(_, _, _, _, ref mut saved) = t.kind {
+                    let across = &live_out[blk_idx][idx] - &bb.cfg_inbb.def[idx];
+                    let registers: BTreeSet<RegisterNumber> = across
+                        .iter()
+                        .filter_map(|virt| assignment.get(virt).copied())
+                        .filter(|phys| description.is_caller_saved(*phys))
+                        .collect();
+                    *saved = registers.into_iter().collect();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod graph_coloring_tests {
+    use super::*;
+    use crate::compiler::backend::arch::x64::registers;
+    use crate::compiler::backend::test_support::{
+        defined_phys, single_block_function, sum_of_live_values,
+    };
+    use crate::compiler::ir::three_address_code::{tac::ThreeAddressCode, tac_kind::Operand};
+
+    fn allocate(func: IRFunction) -> IRFunction {
+        crate::compiler::backend::test_support::allocate(
+            func,
+            &registers::register_description(),
+            HighOptimizer::graph_coloring_for_virtual_registers,
+        )
+    }
+
+    #[test]
+    fn test_coalesce_moves() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let func = allocate(single_block_function(vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, int(3), int(4)),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::MINUS, reg(1), reg(0)),
+            ThreeAddressCode::new_binop_code(reg(3), Operator::ASTERISK, reg(2), int(5)),
+            ThreeAddressCode::new_return(reg(3)),
+        ]));
+
+        // 読み込み元が最後に使われる t1 -> t2 -> t3 は1つのレジスタにまとまる
+        let phys = defined_phys(&func);
+        assert_ne!(phys[0], phys[1]);
+        assert_eq!(phys[1], phys[2]);
+        assert_eq!(phys[2], phys[3]);
+    }
+
+    #[test]
+    fn test_interfering_moves_are_not_coalesced() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let func = allocate(single_block_function(vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, reg(0), int(1)),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, reg(0), reg(1)),
+            ThreeAddressCode::new_return(reg(2)),
+        ]));
+
+        // t1 の定義の後も t0 は生きている
+        let phys = defined_phys(&func);
+        assert_ne!(phys[0], phys[1]);
+    }
+
+    #[test]
+    fn test_spill_uncolorable_nodes() {
+        // t0..t19 を全て定義してから足し合わせる
+        let func = allocate(sum_of_live_values(20));
+
+        // 13個のレジスタに収まらない分がスピルされ,全て使用可能なレジスタに割り付けられる
+        assert!(func.frame_size > 0);
+        let description = registers::register_description();
+        for phys in defined_phys(&func) {
+            assert!(description.registers[phys].is_allocatable);
+        }
+    }
+}
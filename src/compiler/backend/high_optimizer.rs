@@ -1,13 +1,27 @@
+use crate::compiler::frontend::variable::Variable;
 use crate::compiler::ir::three_address_code::function::IRFunction;
 
+use std::collections::BTreeMap;
+
 // 機械独立なバックエンド操作を行う
 pub struct HighOptimizer {
     pub functions: Vec<IRFunction>,
+    // グローバル変数はデータとしてそのまま後段に渡す
+    pub globals: BTreeMap<String, Variable>,
+    pub string_literals: Vec<Vec<u8>>,
 }
 
 impl HighOptimizer {
-    pub fn new(functions: Vec<IRFunction>) -> Self {
-        Self { functions }
+    pub fn new(
+        functions: Vec<IRFunction>,
+        globals: BTreeMap<String, Variable>,
+        string_literals: Vec<Vec<u8>>,
+    ) -> Self {
+        Self {
+            functions,
+            globals,
+            string_literals,
+        }
     }
     pub fn dump_tacs_to_stderr(&self) {
         for func in self.functions.iter() {
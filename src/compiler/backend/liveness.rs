@@ -1,67 +1,46 @@
-use crate::compiler::backend::cfg::ControlFlowGraphInBB;
 use crate::compiler::backend::high_optimizer::HighOptimizer;
 use crate::compiler::ir::three_address_code::{
     basicblock::BasicBlock, function::IRFunction, tac_kind::TacKind,
 };
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 type LiveInMap = Vec<BTreeSet<usize>>;
 type LiveOutMap = Vec<BTreeSet<usize>>;
 
 impl HighOptimizer {
     pub fn setup_liveness_analyze(&mut self) {
-        // 生存解析用の情報収集
-        self.append_liveness_informations();
-
         let mut functions = self.functions.clone();
-
-        // iter_mut() では多段的に変更できないのでインデックス指定で
-        let function_number = functions.len();
-        for func_idx in 0..function_number {
-            let block_number = functions[func_idx].blocks.len();
-
-            for blk_idx in 0..block_number {
-                let ir_number = functions[func_idx].blocks[blk_idx].tacs.len();
-
-                // 生存情報の収集
-                let (live_in, live_out) = self.liveness_analysis(
-                    functions[func_idx].blocks[blk_idx].cfg_inbb.clone(),
-                    ir_number,
-                );
-
-                // 生存情報の反映
-                for (reg_number, range) in functions[func_idx].blocks[blk_idx].living.iter_mut() {
-                    for ir_idx in 0..ir_number {
-                        if !live_in[ir_idx].contains(reg_number)
-                            && live_out[ir_idx].contains(reg_number)
-                        {
-                            range.0 = ir_idx;
-                        }
-                        if live_in[ir_idx].contains(reg_number)
-                            && !live_out[ir_idx].contains(reg_number)
-                        {
-                            range.1 = ir_idx;
-                        }
-                    }
-                }
-            }
+        for func in functions.iter_mut() {
+            self.liveness_analyze_func(func);
         }
 
         self.functions = functions;
     }
-    pub fn append_liveness_informations(&mut self) {
-        let mut functions = self.functions.clone();
-        let functions_number = functions.len();
-        for func_idx in 0..functions_number {
-            self.liveness_analyze_to_func(&mut functions[func_idx]);
+    // CFG構築済みの関数に対して生存解析を行う
+    // スピルコードを挿入した後も,これで解析し直す
+    pub fn liveness_analyze_func(&mut self, func: &mut IRFunction) {
+        // 生存解析用の情報収集
+        self.liveness_analyze_to_func(func);
+
+        // 関数全体で生存情報を収集
+        let (live_in, live_out) = self.liveness_analysis(func);
+
+        // ブロックの入口/出口での生存情報の反映
+        let block_live_in = Self::block_live_in(func, &live_in);
+        for (blk_idx, bb) in func.blocks.iter_mut().enumerate() {
+            bb.live_in = block_live_in[blk_idx].clone();
+            bb.live_out = func.cfg.succ[blk_idx]
+                .iter()
+                .fold(BTreeSet::new(), |out, s| &out | &block_live_in[*s]);
         }
-        self.functions = functions;
+
+        // 生存区間の反映
+        func.living = Self::build_live_ranges(func, &live_in, &live_out);
     }
     fn liveness_analyze_to_func(&mut self, func: &mut IRFunction) {
         let mut blocks = func.blocks.clone();
-        let blocks_number = func.blocks.len();
-        for blk_idx in 0..blocks_number {
+        for blk_idx in func.cfg.postorder() {
             self.liveness_analyze_to_bb(&mut blocks[blk_idx]);
         }
         func.blocks = blocks;
@@ -69,11 +48,18 @@ impl HighOptimizer {
     fn liveness_analyze_to_bb(&mut self, bb: &mut BasicBlock) {
         for (i, t) in bb.tacs.iter().enumerate() {
             match &t.kind {
+                TacKind::UNARYEXPR(var, _operator, inner) => {
+                    if var.is_register() {
+                        bb.cfg_inbb.def[i].insert(var.virt);
+                    }
+                    if inner.is_register() || inner.is_dereference() {
+                        bb.cfg_inbb.used[i].insert(inner.virt);
+                    }
+                }
                 TacKind::EXPR(var, _operator, left, right) => {
                     // 代入されているオペランドがレジスタであれば定義集合に
                     if var.is_register() {
                         bb.cfg_inbb.def[i].insert(var.virt);
-                        bb.living.insert(var.virt, (0, 0));
                     }
 
                     // 左右オペランドがレジスタであれば使用集合に
@@ -84,11 +70,15 @@ impl HighOptimizer {
                         bb.cfg_inbb.used[i].insert(right.virt);
                     }
                 }
-                TacKind::ASSIGN(_dst_op, src_op) => {
+                TacKind::ASSIGN(dst_op, src_op) => {
                     // 使用オペランドがレジスタなら使用集合に
                     if src_op.is_register() {
                         bb.cfg_inbb.used[i].insert(src_op.virt);
                     }
+                    // *t への代入はアドレスのレジスタを使用する
+                    if dst_op.is_dereference() {
+                        bb.cfg_inbb.used[i].insert(dst_op.virt);
+                    }
                 }
                 TacKind::RET(return_op) => {
                     // 返すオペランドがレジスタなら使用集合に
@@ -96,7 +86,18 @@ impl HighOptimizer {
                         bb.cfg_inbb.used[i].insert(return_op.virt);
                     }
                 }
-                TacKind::IFF(op, _label) => {
+                // 関数ポインタと引数に渡すレジスタは使用,返り値を受け取るレジスタは定義
+                TacKind::CALL(return_op, callee, args, _, _) => {
+                    for arg in args
+                        .iter()
+                        .chain(Some(callee))
+                        .filter(|op| op.is_register())
+                    {
+                        bb.cfg_inbb.used[i].insert(arg.virt);
+                    }
+                    bb.cfg_inbb.def[i].insert(return_op.virt);
+                }
+                TacKind::IFF(op, _) | TacKind::IFT(op, _) | TacKind::JUMPTABLE(op, _, _) => {
                     // 返すオペランドがレジスタなら使用集合に
                     if op.is_register() {
                         bb.cfg_inbb.used[i].insert(op.virt);
@@ -106,54 +107,227 @@ impl HighOptimizer {
             }
         }
     }
-    pub fn liveness_analysis(
-        &mut self,
-        cfg_inbb: ControlFlowGraphInBB,
-        tac_length: usize,
-    ) -> (LiveInMap, LiveOutMap) {
+    // 関数全体の命令を対象にした生存解析
+    // ブロック内のエッジに加えて,他のブロックへのジャンプとフォールスルーを辿る
+    pub fn liveness_analysis(&mut self, func: &IRFunction) -> (Vec<LiveInMap>, Vec<LiveOutMap>) {
+        let cfg = &func.cfg;
+
         // in集合, out集合の定義
         // foreach n; in[n] <- {}; out[n] <- {};
-        let mut live_in: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); tac_length];
-        let mut live_out: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); tac_length];
+        let mut live_in: Vec<LiveInMap> = func
+            .blocks
+            .iter()
+            .map(|bb| vec![BTreeSet::new(); bb.tacs.len()])
+            .collect();
+        let mut live_out: Vec<LiveOutMap> = live_in.clone();
 
         // repeat
         'outer: loop {
-            // 変更をチェックするための集合
-            let mut in_dash: Vec<BTreeSet<usize>> = Vec::new();
-            let mut out_dash: Vec<BTreeSet<usize>> = Vec::new();
-
-            // foreach n
-            for idx in 0..tac_length {
-                // 後で変更が無いかチェックする為に保存
-                in_dash.push(live_in[idx].clone());
-                out_dash.push(live_out[idx].clone());
-
-                // out[n] <- U in[s] (where s ∈ succ[n])
-                for s in cfg_inbb.succ[idx].iter() {
-                    live_out[idx] = &live_out[idx] | &live_in[*s];
-                }
+            // 各ブロックの入口で生きているレジスタ(出口ノードは常に空)
+            let block_in = Self::block_live_in(func, &live_in);
+            let mut changed = false;
 
-                live_in[idx] = &cfg_inbb.used[idx] | &(&live_out[idx] - &cfg_inbb.def[idx]);
-            }
+            // 後ろ向きの解析なので,後順にブロックを見て命令は後ろから辿る
+            for blk_idx in cfg.postorder() {
+                let bb = &func.blocks[blk_idx];
+                let tac_length = bb.tacs.len();
 
-            // until in'[n] == in[n] and out'[n] == out[n] for all n
-            let mut chg_flg: bool = true;
-            for idx in 0..live_in.len() {
-                if live_in[idx] != in_dash[idx] {
-                    chg_flg = false;
-                }
-                if live_out[idx] != out_dash[idx] {
-                    chg_flg = false;
+                for idx in (0..tac_length).rev() {
+                    // out[n] <- U in[s] (where s ∈ succ[n])
+                    let mut out = BTreeSet::new();
+                    for s in bb.cfg_inbb.succ[idx].iter() {
+                        out = &out | &live_in[blk_idx][*s];
+                    }
+                    if let Some(targets) = cfg.jumps.get(&(blk_idx, idx)) {
+                        for target in targets.iter() {
+                            out = &out | &block_in[*target];
+                        }
+                    }
+                    if idx + 1 == tac_length {
+                        if let Some(next) = cfg.fall_through[blk_idx] {
+                            out = &out | &block_in[next];
+                        }
+                    }
+
+                    // in[n] <- use[n] U (out[n] - def[n])
+                    let in_ = &bb.cfg_inbb.used[idx] | &(&out - &bb.cfg_inbb.def[idx]);
+
+                    if in_ != live_in[blk_idx][idx] || out != live_out[blk_idx][idx] {
+                        changed = true;
+                        live_in[blk_idx][idx] = in_;
+                        live_out[blk_idx][idx] = out;
+                    }
                 }
             }
-            if chg_flg {
+
+            // until in'[n] == in[n] and out'[n] == out[n] for all n
+            if !changed {
                 break 'outer;
             }
         }
 
         (live_in, live_out)
     }
+    // ブロックの入口で生きているレジスタ
+    // 空のブロックは後続のブロックの入口と同じになる
+    fn block_live_in(func: &IRFunction, live_in: &[LiveInMap]) -> Vec<BTreeSet<usize>> {
+        let cfg = &func.cfg;
+        let mut block_in = vec![BTreeSet::new(); cfg.exit() + 1];
+
+        // 空のブロックが連なっても収束するまで繰り返す
+        loop {
+            let mut changed = false;
+            for blk_idx in cfg.postorder() {
+                let entry = match live_in[blk_idx].first() {
+                    Some(in_) => in_.clone(),
+                    None => cfg.succ[blk_idx]
+                        .iter()
+                        .fold(BTreeSet::new(), |entry, s| &entry | &block_in[*s]),
+                };
+                if entry != block_in[blk_idx] {
+                    block_in[blk_idx] = entry;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return block_in;
+            }
+        }
+    }
+    // CFGの逆後順に命令を並べ,各レジスタが定義/生存している位置の最初と最後を区間とする
+    fn build_live_ranges(
+        func: &IRFunction,
+        live_in: &[LiveInMap],
+        live_out: &[LiveOutMap],
+    ) -> BTreeMap<usize, (usize, usize)> {
+        let mut living = BTreeMap::new();
+
+        for (position, (blk_idx, idx)) in Self::linear_positions(func).into_iter().enumerate() {
+            let regs = func.blocks[blk_idx].cfg_inbb.def[idx]
+                .iter()
+                .chain(live_in[blk_idx][idx].iter())
+                .chain(live_out[blk_idx][idx].iter());
+            for reg_number in regs {
+                let range = living.entry(*reg_number).or_insert((position, position));
+                range.1 = position;
+            }
+        }
+
+        living
+    }
+    // 生存区間の位置に対応する (ブロック番号, ブロック内の命令番号)
+    // CFGの逆後順にブロックを並べる
+    pub fn linear_positions(func: &IRFunction) -> Vec<(usize, usize)> {
+        func.cfg
+            .reverse_postorder()
+            .into_iter()
+            .flat_map(|blk_idx| (0..func.blocks[blk_idx].tacs.len()).map(move |idx| (blk_idx, idx)))
+            .collect()
+    }
 }
 
 #[cfg(test)]
-mod liveness_tests {}
+mod liveness_tests {
+    use super::*;
+    use crate::compiler::ir::three_address_code::{
+        tac::ThreeAddressCode,
+        tac_kind::{Operand, Operator},
+    };
+
+    fn block(label: &str, tacs: Vec<ThreeAddressCode>) -> BasicBlock {
+        let mut bb = BasicBlock::new(label.to_string());
+        bb.tacs = tacs;
+        bb
+    }
+    fn reg(virt: usize) -> Operand {
+        Operand::new_virtreg(virt)
+    }
+    fn int(value: i128) -> Operand {
+        Operand::new_int_literal(value)
+    }
+    fn analyze(blocks: Vec<BasicBlock>) -> IRFunction {
+        let mut func = IRFunction::new("f".to_string());
+        func.blocks = blocks;
+        let mut high_opt = HighOptimizer::new(vec![func], BTreeMap::new(), Vec::new());
+        high_opt.build_cfg();
+        high_opt.setup_liveness_analyze();
+        high_opt.functions.remove(0)
+    }
+    fn set(regs: &[usize]) -> BTreeSet<usize> {
+        regs.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_liveness_across_blocks() {
+        // t1 は entry で定義され, .L1 で使われる
+        let func = analyze(vec![
+            block(
+                "entry",
+                vec![
+                    ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+                    ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, int(3), int(4)),
+                    ThreeAddressCode::new_iff(reg(0), ".L1".to_string()),
+                    ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, reg(1), int(1)),
+                ],
+            ),
+            block(
+                ".L1",
+                vec![
+                    ThreeAddressCode::new_label(".L1".to_string()),
+                    ThreeAddressCode::new_binop_code(reg(3), Operator::ASTERISK, reg(1), int(2)),
+                    ThreeAddressCode::new_return(reg(3)),
+                ],
+            ),
+        ]);
+
+        assert_eq!(set(&[]), func.blocks[0].live_in);
+        assert_eq!(set(&[1]), func.blocks[0].live_out);
+        assert_eq!(set(&[1]), func.blocks[1].live_in);
+        assert_eq!(set(&[]), func.blocks[1].live_out);
+
+        assert_eq!(Some(&(0, 2)), func.living.get(&0));
+        assert_eq!(Some(&(1, 5)), func.living.get(&1));
+        // 使われない定義は,その命令だけで死ぬ
+        assert_eq!(Some(&(3, 3)), func.living.get(&2));
+        assert_eq!(Some(&(5, 6)), func.living.get(&3));
+    }
+
+    #[test]
+    fn test_liveness_in_loop() {
+        // ループの後方エッジを通じて, t0 はループの末尾まで生き続ける
+        let func = analyze(vec![
+            block(
+                "entry",
+                vec![ThreeAddressCode::new_binop_code(
+                    reg(0),
+                    Operator::PLUS,
+                    int(0),
+                    int(10),
+                )],
+            ),
+            block(
+                ".L0",
+                vec![
+                    ThreeAddressCode::new_label(".L0".to_string()),
+                    ThreeAddressCode::new_iff(reg(0), ".L1".to_string()),
+                    ThreeAddressCode::new_binop_code(reg(1), Operator::MINUS, reg(0), int(1)),
+                    ThreeAddressCode::new_goto(".L0".to_string()),
+                ],
+            ),
+            block(
+                ".L1",
+                vec![
+                    ThreeAddressCode::new_label(".L1".to_string()),
+                    ThreeAddressCode::new_return(int(0)),
+                ],
+            ),
+        ]);
+
+        assert_eq!(set(&[0]), func.blocks[1].live_in);
+        assert_eq!(set(&[0]), func.blocks[1].live_out);
+        assert_eq!(set(&[]), func.blocks[2].live_in);
+
+        assert_eq!(Some(&(0, 4)), func.living.get(&0));
+        assert_eq!(Some(&(3, 3)), func.living.get(&1));
+    }
+}
@@ -2,47 +2,63 @@ extern crate clap;
 
 pub mod arch;
 pub mod cfg;
+pub mod graph_coloring;
 pub mod high_optimizer;
 pub mod liveness;
 pub mod regalloc;
+pub mod register_description;
+pub mod spill;
+#[cfg(test)]
+mod test_support;
 pub mod translate_ir;
 
+use crate::compiler::backend::register_description::RegisterDescription;
+use crate::compiler::frontend::variable::Variable;
 use crate::compiler::ir::three_address_code::function::IRFunction;
 use crate::error::Error;
 use crate::target::*;
 use crate::util;
 
+use std::collections::BTreeMap;
+
 pub fn backend_process(
     matches: &clap::ArgMatches,
     functions: Vec<IRFunction>,
+    globals: BTreeMap<String, Variable>,
+    string_literals: Vec<Vec<u8>>,
     target: &Target,
 ) -> String {
-    let mut high_opt = high_optimizer::HighOptimizer::new(functions);
+    let mut high_opt = high_optimizer::HighOptimizer::new(functions, globals, string_literals);
 
     // 制御フローグラフ構築
     high_opt.build_cfg();
 
     if matches.is_present("d-controlflow") {
-        // util::colored_message_to_stderr("dump control-flow-graph to cfg.dot...");
-        // high_opt.dump_cfg_to_file();
-        // util::colored_message_to_stderr("done!");
+        util::colored_prefix_to_stderr("dump control-flow-graph");
+        high_opt.dump_cfg_to_stderr();
     }
 
+    // どこからも飛んでこないブロックはコードを生成しない
+    high_opt.remove_unreachable_blocks();
+
     // データフローグラフ構築(レジスタ割付用の生存解析)
     high_opt.setup_liveness_analyze();
 
     if matches.is_present("d-liveness-info") {
         util::colored_prefix_to_stderr("dump liveness analysis informations");
         for func in high_opt.functions.iter() {
-            for bb in func.blocks.iter() {
-                bb.dump_liveness();
-            }
+            func.dump_liveness();
         }
     }
 
     // レジスタ割付( 仮想レジスタ専用 )
-    let available_registers = find_available_registers_each_archs(target);
-    high_opt.register_allocation_for_virtual_registers(available_registers);
+    // -O2 ではグラフ彩色で,それ以外は線形走査で割り付ける
+    let description = find_register_description_each_archs(target);
+    if matches.value_of("optimize") == Some("2") {
+        high_opt.graph_coloring_for_virtual_registers(&description);
+    } else {
+        high_opt.register_allocation_for_virtual_registers(&description);
+    }
 
     if matches.is_present("d-higher-ir-regalloced") {
         util::colored_prefix_to_stderr("dump three address code( after register-allocation )");
@@ -60,13 +76,13 @@ pub fn backend_process(
     }
 }
 
-// レジスタ割付で使用可能なレジスタ数をチェック
-fn find_available_registers_each_archs(target: &Target) -> usize {
+// レジスタ割付に使うターゲットのレジスタ記述
+fn find_register_description_each_archs(target: &Target) -> RegisterDescription {
     match target.arch {
-        Architecture::X86_64 => 9,
+        Architecture::X86_64 => arch::x64::registers::register_description(),
         _ => {
             Error::found_cant_support_architecture();
-            0
+            RegisterDescription::new(Vec::new(), |_| Default::default())
         }
     }
 }
@@ -1,155 +1,651 @@
+// 線形走査によるレジスタ割付
+// 生存区間は関数呼び出しの位置で分割する.呼び出しを跨ぐ値は呼び出しの直前で新しい仮想レジスタに写し,
+// 呼び出しより前の部分と,呼び出しを跨ぐ部分を別々に割り付ける
+// 跨ぐ部分は callee-saved なレジスタを優先し,空いていなければスピル領域か,
+// caller-saved なレジスタに置いて呼び出しの前後で push/pop する (CALL の退避レジスタに記録する)
 use crate::compiler::backend::high_optimizer::HighOptimizer;
+use crate::compiler::backend::register_description::{RegisterConstraint, RegisterDescription};
 use crate::compiler::ir::three_address_code::{
-    basicblock::BasicBlock,
     function::IRFunction,
-    tac_kind::{OpeKind, TacKind},
+    tac::ThreeAddressCode,
+    tac_kind::{OpeKind, Operand, Operator, TacKind},
 };
 use crate::error::*;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+type RegisterNumber = usize;
+
+// 関数を線形に並べた時の,仮想レジスタの生存区間
+#[derive(Debug, Clone, Copy)]
+struct LiveInterval {
+    virt: RegisterNumber,
+    start: usize,
+    end: usize,
+}
+
+// (ブロック番号, ブロック内の命令番号) -> 呼び出しを跨いで生きる仮想レジスタ
+type ValuesAcrossCalls = BTreeMap<(usize, usize), BTreeSet<RegisterNumber>>;
+
+// 割付の間に参照する,線形に並べた関数の情報
+struct LinearFunction {
+    // 各位置の (ブロック番号, ブロック内の命令番号)
+    positions: Vec<(usize, usize)>,
+    constraints: Vec<RegisterConstraint>,
+    // 呼び出しの位置 -> 呼び出しを跨いで生きる仮想レジスタ
+    calls: BTreeMap<usize, BTreeSet<RegisterNumber>>,
+}
 
 impl HighOptimizer {
-    pub fn register_allocation_for_virtual_registers(&mut self, available_registers: usize) {
+    pub fn register_allocation_for_virtual_registers(&mut self, description: &RegisterDescription) {
         // 各関数に対しレジスタ割付を行う
         let mut functions = self.functions.clone();
         let functions_number = functions.len();
 
         for func_idx in 0..functions_number {
-            self.register_allocation_for_func(&mut functions[func_idx], available_registers);
+            self.register_allocation_for_func(&mut functions[func_idx], description);
         }
         self.functions = functions;
     }
-    fn register_allocation_for_func(&mut self, func: &mut IRFunction, avreg: usize) {
-        let mut regalloced_blocks = Vec::new();
-        let blocks = func.blocks.clone();
-        for block in blocks {
-            let regalloced_block = self.register_allocation_for_bb(block, avreg);
+    fn register_allocation_for_func(
+        &mut self,
+        func: &mut IRFunction,
+        description: &RegisterDescription,
+    ) {
+        let mut unspillable: BTreeSet<RegisterNumber> = BTreeSet::new();
+
+        self.split_at_calls(func);
 
-            regalloced_blocks.push(regalloced_block);
+        loop {
+            // 同時に生きるレジスタが使用可能な数に収まるよう,先にスピルしておく
+            self.spill_registers(func, description, &mut unspillable);
+
+            let linear = self.linearize(func, description);
+            match Self::linear_scan(func, &linear, description, &unspillable) {
+                Ok(assignment) => {
+                    Self::assign_physical_registers(func, &linear, &assignment, description);
+                    return;
+                }
+                // 固定レジスタの制約で置き場所が無かったので,1つ追い出してやり直す
+                Err(Some(victim)) => {
+                    let spilled = Some(victim).into_iter().collect();
+                    Self::insert_spill_code(func, &spilled, &mut unspillable);
+                    self.build_cfg_with_func(func);
+                    self.liveness_analyze_func(func);
+                }
+                Err(None) => {
+                    let err = Error::new(
+                        ErrorKind::RegAlloc,
+                        (0, 0),
+                        ErrorMsg::CantUseNoMoreRegisters,
+                    );
+                    err.compile_error();
+                    return;
+                }
+            }
         }
-        func.blocks = regalloced_blocks;
     }
-    pub fn register_allocation_for_bb(
-        &mut self,
-        mut block: BasicBlock,
-        available_registers: usize,
-    ) -> BasicBlock {
-        // レジスタの使用を管理するマップ
-        // virtual_register_number -> physical_register_number
-        let mut register_map: BTreeMap<usize, usize> = BTreeMap::new();
-
-        let living = block.living.clone();
-
-        // 各IRのレジスタに物理レジスタを割り当てる
-        for (now_looking, t) in block.tacs.iter_mut().enumerate() {
-            match t.kind {
-                TacKind::UNARYEXPR(ref mut var_op, ref mut _operator, ref mut inner) => {
-                    // オペランド
-                    if let OpeKind::REG = inner.kind {
-                        if let Some(allocated_number) = register_map.get(&inner.virt) {
-                            inner.phys = *allocated_number;
-                        } else {
-                            panic!("spill occured!(not implemented)");
-                        }
-                    }
+    // 呼び出しを跨いで生きる値を,呼び出しの直前で新しい仮想レジスタに写す
+    // 呼び出しより後のブロック内の使用は写した先を読み,元のレジスタは呼び出しの前で生存区間を終える
+    // 他のブロックは元のレジスタを読むので,ブロックを出る前に書き戻す
+    fn split_at_calls(&mut self, func: &mut IRFunction) {
+        let across_calls = self.values_across_calls(func);
+        if across_calls.values().all(|values| values.is_empty()) {
+            return;
+        }
 
-                    // レジスタ数の削減
-                    Self::reduce_register_number(&living, &mut register_map, now_looking);
+        let block_live_in: Vec<BTreeSet<RegisterNumber>> =
+            func.blocks.iter().map(|bb| bb.live_in.clone()).collect();
+        let live_in_of = |blk: &usize| block_live_in.get(*blk).cloned().unwrap_or_default();
 
-                    // 実際の割付
-                    var_op.phys = register_map.len();
-                    register_map.insert(var_op.virt, var_op.phys);
-                }
-                TacKind::EXPR(ref mut var_op, ref mut _operator, ref mut left, ref mut right) => {
-                    // 左オペランド
-                    if let OpeKind::REG = left.kind {
-                        if let Some(allocated_number) = register_map.get(&left.virt) {
-                            left.phys = *allocated_number;
-                        } else {
-                            panic!("spill occured!(not implemented)");
-                        }
+        let mut next_virt = Self::max_virtual_register(func) + 1;
+        for (blk_idx, bb) in func.blocks.iter_mut().enumerate() {
+            let tacs = std::mem::take(&mut bb.tacs);
+            let last = tacs.len().saturating_sub(1);
+
+            // 元の仮想レジスタ -> 今その値を持っている仮想レジスタ
+            let mut renamed: BTreeMap<RegisterNumber, RegisterNumber> = BTreeMap::new();
+            for (idx, mut t) in tacs.into_iter().enumerate() {
+                for op in Self::used_registers_mut(&mut t.kind) {
+                    if let Some(current) = renamed.get(&op.virt) {
+                        op.virt = *current;
                     }
+                }
 
-                    // レジスタ数の削減
-                    Self::reduce_register_number(&living, &mut register_map, now_looking);
+                // ブロックを出る先で生きている値は,元のレジスタに書き戻す
+                let mut exits: BTreeSet<usize> = func
+                    .cfg
+                    .jumps
+                    .get(&(blk_idx, idx))
+                    .cloned()
+                    .unwrap_or_default();
+                if idx == last {
+                    exits.extend(func.cfg.fall_through[blk_idx]);
+                }
+                let exit_live: BTreeSet<RegisterNumber> =
+                    exits.iter().flat_map(live_in_of).collect();
+                let write_backs = |renamed: &BTreeMap<RegisterNumber, RegisterNumber>| {
+                    renamed
+                        .iter()
+                        .filter(|(original, _)| exit_live.contains(original))
+                        .map(|(original, current)| Self::copy_code(*original, *current))
+                        .collect::<Vec<ThreeAddressCode>>()
+                };
+                let is_jump = matches!(
+                    t.kind,
+                    TacKind::GOTO(_)
+                        | TacKind::IFF(_, _)
+                        | TacKind::IFT(_, _)
+                        | TacKind::JUMPTABLE(_, _, _)
+                );
+                if is_jump {
+                    bb.tacs.extend(write_backs(&renamed));
+                }
 
-                    // 右オペランド
-                    if let OpeKind::REG = right.kind {
-                        if let Some(allocated_number) = register_map.get(&right.virt) {
-                            right.phys = *allocated_number;
-                        } else {
-                            panic!("spill occured!(not implemented)");
-                        }
+                if let Some(values) = across_calls.get(&(blk_idx, idx)) {
+                    for original in values.iter() {
+                        let current = renamed.get(original).copied().unwrap_or(*original);
+                        bb.tacs.push(Self::copy_code(next_virt, current));
+                        renamed.insert(*original, next_virt);
+                        next_virt += 1;
                     }
+                }
 
-                    // レジスタ数の削減
-                    Self::reduce_register_number(&living, &mut register_map, now_looking);
+                // 定義し直された値は,以降は元のレジスタを読めばよい
+                if let Some(def_op) = Self::defined_register(&t.kind) {
+                    renamed.remove(&def_op.virt);
+                }
 
-                    // 実際の割付
-                    var_op.phys = register_map.len();
-                    register_map.insert(var_op.virt, var_op.phys);
+                bb.tacs.push(t);
+                // 末尾から次のブロックへ進む場合
+                if !is_jump {
+                    bb.tacs.extend(write_backs(&renamed));
                 }
-                TacKind::ASSIGN(ref mut _var_op, ref mut src_op) => {
-                    if let OpeKind::REG = src_op.kind {
-                        if let Some(allocated_number) = register_map.get(&src_op.virt) {
-                            src_op.phys = *allocated_number;
-                        } else {
-                            eprintln!("before spill -> {:?}", src_op);
-                            panic!("spill occured!(not implemented)");
-                        }
-                    }
+            }
+        }
+
+        // 命令の位置が変わるので,CFGと生存情報を作り直す
+        self.build_cfg_with_func(func);
+        self.liveness_analyze_func(func);
+    }
+    // dst <- src の8バイトのコピー
+    // 拡張済みのレジスタの値をそのまま写す
+    fn copy_code(dst: RegisterNumber, src: RegisterNumber) -> ThreeAddressCode {
+        ThreeAddressCode::new_unop_code(
+            Operand::new_virtreg(dst),
+            Operator::PLUS,
+            Operand::new_virtreg(src),
+        )
+    }
+    // 各呼び出しの後も生きている値
+    // 呼び出しで定義される返り値は含まない
+    fn values_across_calls(&mut self, func: &IRFunction) -> ValuesAcrossCalls {
+        let (_, live_out) = self.liveness_analysis(func);
+        let mut across_calls = BTreeMap::new();
+        for (blk_idx, bb) in func.blocks.iter().enumerate() {
+            for (idx, t) in bb.tacs.iter().enumerate() {
+                if let TacKind::CALL(_, _, _, _, _) = t.kind {
+                    let values = &live_out[blk_idx][idx] - &bb.cfg_inbb.def[idx];
+                    across_calls.insert((blk_idx, idx), values);
                 }
-                TacKind::RET(ref mut return_op) => {
-                    if let OpeKind::REG = return_op.kind {
-                        if let Some(allocated_number) = register_map.get(&return_op.virt) {
-                            return_op.phys = *allocated_number;
-                        } else {
-                            panic!("spill occured!(not implemented)");
-                        }
-                    }
+            }
+        }
+        across_calls
+    }
+    fn linearize(
+        &mut self,
+        func: &IRFunction,
+        description: &RegisterDescription,
+    ) -> LinearFunction {
+        let positions = Self::linear_positions(func);
+        let constraints = positions
+            .iter()
+            .map(|(blk_idx, idx)| description.constraint(&func.blocks[*blk_idx].tacs[*idx].kind))
+            .collect();
+        let mut across_calls = self.values_across_calls(func);
+        let calls = positions
+            .iter()
+            .enumerate()
+            .filter_map(|(position, place)| {
+                across_calls.remove(place).map(|values| (position, values))
+            })
+            .collect();
+        LinearFunction {
+            positions,
+            constraints,
+            calls,
+        }
+    }
+    // 生存区間を開始位置の順に走査し,空いている物理レジスタを割り付ける(線形走査)
+    // 割り付けられなければ,追い出す仮想レジスタを返す
+    // virtual_register_number -> physical_register_number
+    fn linear_scan(
+        func: &IRFunction,
+        linear: &LinearFunction,
+        description: &RegisterDescription,
+        unspillable: &BTreeSet<RegisterNumber>,
+    ) -> Result<BTreeMap<RegisterNumber, RegisterNumber>, Option<RegisterNumber>> {
+        let mut intervals: Vec<LiveInterval> = func
+            .living
+            .iter()
+            .map(|(virt, (start, end))| LiveInterval {
+                virt: *virt,
+                start: *start,
+                end: *end,
+            })
+            .collect();
+        intervals.sort_by_key(|interval| (interval.start, interval.end));
+
+        let mut free: BTreeSet<RegisterNumber> =
+            description.allocatable_registers().into_iter().collect();
+        let mut active: Vec<(LiveInterval, RegisterNumber)> = Vec::new();
+        let mut assignment = BTreeMap::new();
+
+        for interval in intervals {
+            // 区間の終わった値のレジスタを空きに戻す
+            // 定義する命令で最後に使われる値のレジスタは,定義先に使ってよい
+            let reusable = Self::is_defined_in_middle_of_block(func, linear, &interval);
+            active.retain(|(other, phys)| {
+                let is_expired =
+                    other.end < interval.start || (reusable && other.end == interval.start);
+                if is_expired {
+                    free.insert(*phys);
                 }
-                TacKind::IFF(ref mut op, ref mut _label) => {
-                    if let OpeKind::REG = op.kind {
-                        if let Some(allocated_number) = register_map.get(&op.virt) {
-                            op.phys = *allocated_number;
-                        } else {
-                            panic!("spill occured!(not implemented)");
-                        }
-                    }
+                !is_expired
+            });
+
+            let forbidden = Self::forbidden_registers(func, linear, &interval);
+            let crosses_call = linear
+                .calls
+                .values()
+                .any(|values| values.contains(&interval.virt));
+
+            // 分割で写した値は,写し元と同じ種類のレジスタで済むなら写し元に置き,コピーを消す
+            let chosen = Self::choose_register(description, &free, &forbidden, crosses_call);
+            let copy_source = Self::copy_source(func, linear, &interval)
+                .and_then(|src| assignment.get(&src).copied())
+                .filter(|phys| {
+                    free.contains(phys)
+                        && !forbidden.contains(phys)
+                        && chosen.is_none_or(|chosen| {
+                            description.is_callee_saved(chosen)
+                                == description.is_callee_saved(*phys)
+                        })
+                });
+
+            match copy_source.or(chosen) {
+                Some(phys) => {
+                    free.remove(&phys);
+                    active.push((interval, phys));
+                    assignment.insert(interval.virt, phys);
+                }
+                None => {
+                    return Err(Self::choose_spill_victim(
+                        func,
+                        &interval,
+                        &active,
+                        &forbidden,
+                        unspillable,
+                    ))
                 }
-                _ => (),
             }
+        }
 
-            // レジスタ数の削減
-            Self::reduce_register_number(&living, &mut register_map, now_looking);
+        Ok(assignment)
+    }
+    // 呼び出しを跨ぐ値は callee-saved なレジスタに置き,呼び出しの度に退避しなくて済むようにする
+    // それ以外の値は,プロローグで退避しなくて済む caller-saved なレジスタを優先する
+    pub fn choose_register(
+        description: &RegisterDescription,
+        free: &BTreeSet<RegisterNumber>,
+        forbidden: &BTreeSet<RegisterNumber>,
+        crosses_call: bool,
+    ) -> Option<RegisterNumber> {
+        let candidates: Vec<RegisterNumber> = description
+            .allocatable_registers()
+            .into_iter()
+            .filter(|phys| free.contains(phys) && !forbidden.contains(phys))
+            .collect();
+        candidates
+            .iter()
+            .find(|phys| description.is_callee_saved(**phys) == crosses_call)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+    // 区間内の命令が壊すレジスタと,オペランドとして読まれる命令が読めないレジスタ
+    fn forbidden_registers(
+        func: &IRFunction,
+        linear: &LinearFunction,
+        interval: &LiveInterval,
+    ) -> BTreeSet<RegisterNumber> {
+        let mut forbidden = BTreeSet::new();
+        for position in interval.start..=interval.end {
+            let constraint = &linear.constraints[position];
+            forbidden.extend(constraint.clobbered.iter().copied());
 
-            // もし使用可能なレジスタ数を超えていたら
-            // (仮想レジスタに関しては起きないと思うけど)
-            if available_registers <= register_map.len() {
-                let err = Error::new(
-                    ErrorKind::RegAlloc,
-                    (0, 0),
-                    ErrorMsg::CantUseNoMoreRegisters,
-                );
-                err.compile_error();
+            let (blk_idx, idx) = linear.positions[position];
+            if func.blocks[blk_idx].cfg_inbb.used[idx].contains(&interval.virt) {
+                forbidden.extend(constraint.operands.iter().copied());
+            }
+        }
+        forbidden
+    }
+    // 区間の先頭が,別のレジスタからのコピーであればその仮想レジスタ
+    fn copy_source(
+        func: &IRFunction,
+        linear: &LinearFunction,
+        interval: &LiveInterval,
+    ) -> Option<RegisterNumber> {
+        let (blk_idx, idx) = linear.positions[interval.start];
+        match &func.blocks[blk_idx].tacs[idx].kind {
+            TacKind::UNARYEXPR(dst, Operator::PLUS, src)
+                if dst.is_register() && dst.virt == interval.virt && src.is_register() =>
+            {
+                Some(src.virt)
             }
+            _ => None,
         }
+    }
+    // 区間の先頭で定義され,次の命令へそのまま進む値か
+    // この時,先頭の命令で最後に使われる値は,定義の後では生きていない
+    fn is_defined_in_middle_of_block(
+        func: &IRFunction,
+        linear: &LinearFunction,
+        interval: &LiveInterval,
+    ) -> bool {
+        let (blk_idx, idx) = linear.positions[interval.start];
+        let bb = &func.blocks[blk_idx];
+        bb.cfg_inbb.def[idx].contains(&interval.virt) && idx + 1 < bb.tacs.len()
+    }
+    // 割り付けられなかった値か,それと重なる値のうちスピルコストの最も低いもの
+    // 追い出しても置けないレジスタを使っている値は選ばない
+    fn choose_spill_victim(
+        func: &IRFunction,
+        interval: &LiveInterval,
+        active: &[(LiveInterval, RegisterNumber)],
+        forbidden: &BTreeSet<RegisterNumber>,
+        unspillable: &BTreeSet<RegisterNumber>,
+    ) -> Option<RegisterNumber> {
+        let costs = Self::spill_costs(func);
+        active
+            .iter()
+            .filter(|(_, phys)| !forbidden.contains(phys))
+            .map(|(other, _)| other)
+            .chain(Some(interval))
+            .filter(|candidate| !unspillable.contains(&candidate.virt))
+            .min_by(|a, b| {
+                costs[&a.virt]
+                    .partial_cmp(&costs[&b.virt])
+                    .unwrap()
+                    .then(b.end.cmp(&a.end))
+            })
+            .map(|victim| victim.virt)
+    }
+    // 割付の結果を各オペランドに書き込む
+    // 呼び出しを跨いで生きる caller-saved なレジスタは,呼び出しの前後で退避する
+    fn assign_physical_registers(
+        func: &mut IRFunction,
+        linear: &LinearFunction,
+        assignment: &BTreeMap<RegisterNumber, RegisterNumber>,
+        description: &RegisterDescription,
+    ) {
+        for (call, values) in linear.calls.iter() {
+            let saved: Vec<RegisterNumber> = values
+                .iter()
+                .filter_map(|virt| assignment.get(virt).copied())
+                .filter(|phys| description.is_caller_saved(*phys))
+                .collect();
 
-        block
+            let (blk_idx, idx) = linear.positions[*call];
+            if let TacKind::CALL(_, _, _, _, ref mut call_saved) =
+                func.blocks[blk_idx].tacs[idx].kind
+            {
+                *call_saved = saved;
+            }
+        }
+
+        Self::write_physical_registers(func, assignment);
     }
-    fn reduce_register_number(
-        living: &BTreeMap<usize, (usize, usize)>,
-        register_map: &mut BTreeMap<usize, usize>,
-        now_looking: usize,
+    pub fn write_physical_registers(
+        func: &mut IRFunction,
+        assignment: &BTreeMap<RegisterNumber, RegisterNumber>,
     ) {
-        // 生存情報をループ
-        // すでに死んでるレジスタがいればマップから取り除く
-        for (virt_num, (_live_in, live_out)) in living.iter() {
-            if register_map.contains_key(virt_num) {
-                if *live_out < now_looking {
-                    register_map.remove(virt_num);
+        for t in func.blocks.iter_mut().flat_map(|bb| bb.tacs.iter_mut()) {
+            for op in Self::register_operands_mut(&mut t.kind) {
+                if let Some(phys) = assignment.get(&op.virt) {
+                    op.phys = *phys;
                 }
             }
         }
     }
+    // 仮想レジスタを持つオペランド (*t の t も含む)
+    fn register_operands_mut(kind: &mut TacKind) -> Vec<&mut Operand> {
+        let operands = match kind {
+            TacKind::EXPR(var_op, _, left, right) => vec![var_op, left, right],
+            TacKind::UNARYEXPR(var_op, _, inner) => vec![var_op, inner],
+            TacKind::ASSIGN(dst_op, src_op) => vec![dst_op, src_op],
+            TacKind::CALL(return_op, callee, args, _, _) => {
+                args.iter_mut().chain(vec![return_op, callee]).collect()
+            }
+            TacKind::RET(op)
+            | TacKind::IFF(op, _)
+            | TacKind::IFT(op, _)
+            | TacKind::JUMPTABLE(op, _, _) => vec![op],
+            _ => Vec::new(),
+        };
+        operands
+            .into_iter()
+            .filter(|op| matches!(op.kind, OpeKind::REG | OpeKind::DEREFERENCE(_)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod regalloc_tests {
+    use super::*;
+    use crate::compiler::backend::arch::x64::registers;
+    use crate::compiler::backend::test_support::{
+        allocate, caller_saved_description, defined_phys, single_block_function, Allocator,
+    };
+    use crate::compiler::ir::three_address_code::basicblock::BasicBlock;
+    use crate::compiler::ir::three_address_code::{
+        tac::ThreeAddressCode,
+        tac_kind::{Operand, Operator},
+    };
+
+    const LINEAR_SCAN: Allocator = HighOptimizer::register_allocation_for_virtual_registers;
+
+    #[test]
+    fn test_allocation_across_blocks() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let mut entry = BasicBlock::new("entry".to_string());
+        entry.tacs = vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_iff(reg(0), ".L1".to_string()),
+            ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, int(3), int(4)),
+            ThreeAddressCode::new_iff(reg(1), ".L1".to_string()),
+        ];
+        let mut labeled = BasicBlock::new(".L1".to_string());
+        labeled.tacs = vec![
+            ThreeAddressCode::new_label(".L1".to_string()),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, reg(0), reg(1)),
+            ThreeAddressCode::new_return(reg(2)),
+        ];
+
+        let mut func = IRFunction::new("f".to_string());
+        func.blocks = vec![entry, labeled];
+        let func = allocate(func, &registers::register_description(), LINEAR_SCAN);
+
+        // ブロックを跨いで生きる t0, t1 は,定義と使用で同じ物理レジスタになる
+        let blocks = &func.blocks;
+        let (t0, t1) = match (&blocks[0].tacs[0].kind, &blocks[0].tacs[2].kind) {
+            (TacKind::EXPR(t0, _, _, _), TacKind::EXPR(t1, _, _, _)) => (t0.phys, t1.phys),
+            _ => panic!("expected binary expressions"),
+        };
+        assert_ne!(t0, t1);
+        match &blocks[1].tacs[1].kind {
+            TacKind::EXPR(_, _, left, right) => assert_eq!((t0, t1), (left.phys, right.phys)),
+            _ => panic!("expected binary expression"),
+        }
+    }
+
+    #[test]
+    fn test_reuse_freed_registers() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let tacs = vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, reg(0), int(1)),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, int(3), int(4)),
+            ThreeAddressCode::new_binop_code(reg(3), Operator::PLUS, reg(1), reg(2)),
+            ThreeAddressCode::new_return(reg(3)),
+        ];
+        let func = allocate(
+            single_block_function(tacs),
+            &registers::register_description(),
+            LINEAR_SCAN,
+        );
+
+        // 最後に使われた値のレジスタを,定義先が引き継ぐ
+        let phys = defined_phys(&func);
+        assert_eq!(phys[0], phys[1]);
+        assert_ne!(phys[1], phys[2]);
+        assert_eq!(phys[1], phys[3]);
+    }
+
+    #[test]
+    fn test_fixed_register_constraints() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let tacs = vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_binop_code(reg(1), Operator::PLUS, int(3), int(4)),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, int(5), int(6)),
+            ThreeAddressCode::new_binop_code(reg(3), Operator::SLASH, reg(1), reg(2)),
+            ThreeAddressCode::new_binop_code(reg(4), Operator::LSHIFT, reg(3), reg(0)),
+            ThreeAddressCode::new_return(reg(4)),
+        ];
+        let description = registers::register_description();
+        let func = allocate(single_block_function(tacs), &description, LINEAR_SCAN);
+
+        // 除算の間に生きている値は rdx/rcx に, シフト量は rcx に置かない
+        let names: Vec<&str> = defined_phys(&func)
+            .into_iter()
+            .map(|phys| description.registers[phys].name.as_str())
+            .collect();
+        for name in names[..3].iter() {
+            assert!(!["rax", "rcx", "rdx"].contains(name));
+        }
+    }
+
+    #[test]
+    fn test_allocation_across_call() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let tacs = vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_call(
+                reg(1),
+                Operand::new_global_var("g".to_string()),
+                vec![int(3)],
+                false,
+            ),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, reg(0), reg(1)),
+            ThreeAddressCode::new_return(reg(2)),
+        ];
+
+        // 呼び出しの直前で t0 を写し,呼び出しを跨ぐ部分だけを callee-saved なレジスタに置く
+        let description = registers::register_description();
+        let func = allocate(
+            single_block_function(tacs.clone()),
+            &description,
+            LINEAR_SCAN,
+        );
+        let phys = defined_phys(&func);
+        assert!(!description.is_callee_saved(phys[0]));
+        let across = copied_phys(&func);
+        assert_eq!(phys[0], across.1);
+        assert!(description.is_callee_saved(across.0));
+        match &func.blocks[0].tacs[2].kind {
+            TacKind::CALL(_, _, _, _, saved) => assert!(saved.is_empty()),
+            _ => panic!("expected call"),
+        }
+
+        // callee-saved なレジスタが無ければ,写し元のまま呼び出しの前後で退避する
+        let description = caller_saved_description(3);
+        let func = allocate(single_block_function(tacs), &description, LINEAR_SCAN);
+        let (across, original) = copied_phys(&func);
+        assert_eq!(original, across);
+        match &func.blocks[0].tacs[2].kind {
+            TacKind::CALL(_, _, _, _, saved) => assert_eq!(&vec![across], saved),
+            _ => panic!("expected call"),
+        }
+    }
+
+    // 呼び出しの直前に入れたコピーの (写し先, 写し元) の物理レジスタ番号
+    fn copied_phys(func: &IRFunction) -> (RegisterNumber, RegisterNumber) {
+        match &func.blocks[0].tacs[1].kind {
+            TacKind::UNARYEXPR(dst, Operator::PLUS, src) => (dst.phys, src.phys),
+            _ => panic!("expected copy"),
+        }
+    }
+
+    #[test]
+    fn test_split_interval_at_call_across_blocks() {
+        let reg = Operand::new_virtreg;
+        let int = Operand::new_int_literal;
+        let mut entry = BasicBlock::new("entry".to_string());
+        entry.tacs = vec![
+            ThreeAddressCode::new_binop_code(reg(0), Operator::PLUS, int(1), int(2)),
+            ThreeAddressCode::new_call(
+                reg(1),
+                Operand::new_global_var("g".to_string()),
+                Vec::new(),
+                false,
+            ),
+            ThreeAddressCode::new_iff(reg(1), ".L1".to_string()),
+            ThreeAddressCode::new_binop_code(reg(2), Operator::PLUS, reg(0), reg(1)),
+            ThreeAddressCode::new_return(reg(2)),
+        ];
+        let mut labeled = BasicBlock::new(".L1".to_string());
+        labeled.tacs = vec![
+            ThreeAddressCode::new_label(".L1".to_string()),
+            ThreeAddressCode::new_return(reg(0)),
+        ];
+        let mut func = IRFunction::new("f".to_string());
+        func.blocks = vec![entry, labeled];
+        let description = registers::register_description();
+        let func = allocate(func, &description, LINEAR_SCAN);
+
+        // t0 = 1 + 2; t3 = t0; t1 = g(); t0 = t3; iff t1; t2 = t3 + t1; ret t2
+        let copies: Vec<(RegisterNumber, RegisterNumber)> = func.blocks[0]
+            .tacs
+            .iter()
+            .filter_map(|t| match &t.kind {
+                TacKind::UNARYEXPR(dst, Operator::PLUS, src) => Some((dst.phys, src.phys)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, copies.len());
+        let (across, original) = copies[0];
+        assert!(description.is_callee_saved(across));
+        assert!(!description.is_callee_saved(original));
+
+        // .L1 は元のレジスタを読むので,ブロックを出る前に書き戻す
+        assert_eq!((original, across), copies[1]);
+        assert!(matches!(func.blocks[0].tacs[4].kind, TacKind::IFF(_, _)));
+        match &func.blocks[0].tacs[5].kind {
+            TacKind::EXPR(_, _, left, _) => assert_eq!(across, left.phys),
+            _ => panic!("expected binary expression"),
+        }
+        match &func.blocks[1].tacs[1].kind {
+            TacKind::RET(op) => assert_eq!(original, op.phys),
+            _ => panic!("expected return"),
+        }
+        match &func.blocks[0].tacs[2].kind {
+            TacKind::CALL(_, _, _, _, saved) => assert!(saved.is_empty()),
+            _ => panic!("expected call"),
+        }
+    }
 }
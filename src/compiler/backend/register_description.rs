@@ -0,0 +1,62 @@
+use crate::compiler::ir::three_address_code::tac_kind::TacKind;
+
+use std::collections::BTreeSet;
+
+type RegisterNumber = usize;
+
+// レジスタ割付から見た,ターゲットの物理レジスタの記述
+// IR上の物理レジスタ番号は registers の添字で,割付ではこの順に空きを探す
+#[derive(Clone)]
+pub struct RegisterDescription {
+    pub registers: Vec<PhysicalRegister>,
+    // 命令ごとの固定レジスタの制約
+    constraint_of: fn(&TacKind) -> RegisterConstraint,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PhysicalRegister {
+    pub name: String,
+    // コード生成で作業用に使うレジスタは割り付けない
+    pub is_allocatable: bool,
+    pub is_callee_saved: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct RegisterConstraint {
+    // 命令の途中で書き換えられるので,その位置で生きている値を置けないレジスタ
+    pub clobbered: BTreeSet<RegisterNumber>,
+    // 命令が読むオペランドを置けないレジスタ
+    pub operands: BTreeSet<RegisterNumber>,
+}
+
+impl RegisterDescription {
+    pub fn new(
+        registers: Vec<PhysicalRegister>,
+        constraint_of: fn(&TacKind) -> RegisterConstraint,
+    ) -> Self {
+        Self {
+            registers,
+            constraint_of,
+        }
+    }
+    // 割付に使えるレジスタを,優先する順に
+    pub fn allocatable_registers(&self) -> Vec<RegisterNumber> {
+        self.registers
+            .iter()
+            .enumerate()
+            .filter(|(_, reg)| reg.is_allocatable)
+            .map(|(phys, _)| phys)
+            .collect()
+    }
+    // 関数呼び出しで壊されるので,呼び出し側で退避が必要なレジスタ
+    pub fn is_caller_saved(&self, phys: RegisterNumber) -> bool {
+        matches!(self.registers.get(phys), Some(reg) if reg.is_allocatable && !reg.is_callee_saved)
+    }
+    // 書き込むなら,呼び出された側がプロローグで退避するレジスタ
+    pub fn is_callee_saved(&self, phys: RegisterNumber) -> bool {
+        matches!(self.registers.get(phys), Some(reg) if reg.is_callee_saved)
+    }
+    pub fn constraint(&self, kind: &TacKind) -> RegisterConstraint {
+        (self.constraint_of)(kind)
+    }
+}
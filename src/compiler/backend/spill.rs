@@ -0,0 +1,322 @@
+use crate::compiler::backend::high_optimizer::HighOptimizer;
+use crate::compiler::backend::register_description::RegisterDescription;
+use crate::compiler::ir::three_address_code::{
+    function::IRFunction,
+    tac::ThreeAddressCode,
+    tac_kind::{Operand, Operator, TacKind},
+};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+type RegisterNumber = usize;
+
+// ループ内の参照は,ループ外の参照よりこれだけ重く見積もる
+const LOOP_WEIGHT: usize = 10;
+
+impl HighOptimizer {
+    // 同時に生きる仮想レジスタが使用可能なレジスタ数を超える間,
+    // いくつかをスタック上のスピル領域に追い出す
+    // 追い出したレジスタは定義直後にストアし,使用直前に新しいレジスタへ読み直す
+    // スピルで作ったレジスタは生存区間が短いので,再びスピルしても意味がない (unspillable に加える)
+    pub fn spill_registers(
+        &mut self,
+        func: &mut IRFunction,
+        description: &RegisterDescription,
+        unspillable: &mut BTreeSet<RegisterNumber>,
+    ) {
+        loop {
+            let spilled = Self::select_spilled_registers(func, description, unspillable);
+            if spilled.is_empty() {
+                return;
+            }
+
+            Self::insert_spill_code(func, &spilled, unspillable);
+
+            // 命令の位置が変わるので,CFGと生存情報を作り直す
+            self.build_cfg_with_func(func);
+            self.liveness_analyze_func(func);
+        }
+    }
+    // 生存区間を先頭から走査し,レジスタが足りない位置ではスピルコストの低いものを選ぶ
+    // 命令が壊すレジスタは,その位置では使用可能な数から除く
+    fn select_spilled_registers(
+        func: &IRFunction,
+        description: &RegisterDescription,
+        unspillable: &BTreeSet<RegisterNumber>,
+    ) -> BTreeSet<RegisterNumber> {
+        let costs = Self::spill_costs(func);
+        let allocatable = description.allocatable_registers();
+        let mut spilled = BTreeSet::new();
+
+        for (position, (blk_idx, idx)) in Self::linear_positions(func).into_iter().enumerate() {
+            let clobbered = description
+                .constraint(&func.blocks[blk_idx].tacs[idx].kind)
+                .clobbered;
+            let available_registers = allocatable
+                .iter()
+                .filter(|phys| !clobbered.contains(phys))
+                .count();
+
+            let mut active: Vec<(RegisterNumber, usize)> = func
+                .living
+                .iter()
+                .filter(|(virt, (live_in, live_out))| {
+                    *live_in <= position && position <= *live_out && !spilled.contains(*virt)
+                })
+                .map(|(virt, (_live_in, live_out))| (*virt, *live_out))
+                .collect();
+
+            while available_registers < active.len() {
+                // コストが同じなら,生存区間がより先まで続くものを追い出す
+                let victim = active
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (virt, _))| !unspillable.contains(virt))
+                    .min_by(|(_, (a, a_end)), (_, (b, b_end))| {
+                        costs[a]
+                            .partial_cmp(&costs[b])
+                            .unwrap()
+                            .then(b_end.cmp(a_end))
+                    })
+                    .map(|(idx, _)| idx);
+
+                // 追い出せるレジスタが無ければ,レジスタ割付時のエラーに任せる
+                match victim {
+                    Some(idx) => {
+                        let (virt, _) = active.remove(idx);
+                        spilled.insert(virt);
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        spilled
+    }
+    // スピルコスト = (ループの重みを掛けた参照回数) / (生存区間の長さ)
+    // 参照が少なく,長く生きているレジスタほど追い出しやすい
+    pub fn spill_costs(func: &IRFunction) -> BTreeMap<RegisterNumber, f64> {
+        let mut references: BTreeMap<RegisterNumber, usize> = BTreeMap::new();
+        for (blk_idx, bb) in func.blocks.iter().enumerate() {
+            let weight = if func.cfg.in_loop(blk_idx) {
+                LOOP_WEIGHT
+            } else {
+                1
+            };
+            for (idx, _) in bb.tacs.iter().enumerate() {
+                let regs = bb.cfg_inbb.used[idx]
+                    .iter()
+                    .chain(bb.cfg_inbb.def[idx].iter());
+                for virt in regs {
+                    *references.entry(*virt).or_insert(0) += weight;
+                }
+            }
+        }
+
+        func.living
+            .iter()
+            .map(|(virt, (live_in, live_out))| {
+                let refs = references.get(virt).copied().unwrap_or(0);
+                (*virt, refs as f64 / (live_out - live_in + 1) as f64)
+            })
+            .collect()
+    }
+    pub fn insert_spill_code(
+        func: &mut IRFunction,
+        spilled: &BTreeSet<RegisterNumber>,
+        unspillable: &mut BTreeSet<RegisterNumber>,
+    ) {
+        // スピル領域は8バイトで,拡張済みのレジスタの値をそのまま置く
+        let mut slots: BTreeMap<RegisterNumber, Operand> = BTreeMap::new();
+        for virt in spilled.iter() {
+            func.frame_size += 8;
+            let offset = func.frame_size;
+            slots.insert(
+                *virt,
+                Operand::new_auto_var(format!(".spill{}", offset), offset),
+            );
+        }
+
+        let mut next_virt = Self::max_virtual_register(func) + 1;
+        for bb in func.blocks.iter_mut() {
+            let tacs = std::mem::take(&mut bb.tacs);
+            for mut t in tacs.into_iter() {
+                // 同じ命令で複数回使われても,読み直すのは1回だけ
+                let mut reloaded: BTreeMap<RegisterNumber, RegisterNumber> = BTreeMap::new();
+                for op in Self::used_registers_mut(&mut t.kind) {
+                    let slot = match slots.get(&op.virt) {
+                        Some(slot) => slot,
+                        None => continue,
+                    };
+                    if let Some(reload_virt) = reloaded.get(&op.virt) {
+                        op.virt = *reload_virt;
+                        continue;
+                    }
+
+                    // アドレスとして使うレジスタは常に8バイト
+                    let mut reload_reg = Operand::new_virtreg(next_virt);
+                    if op.is_register() {
+                        reload_reg.size = op.size;
+                        reload_reg.is_unsigned = op.is_unsigned;
+                    }
+                    bb.tacs.push(ThreeAddressCode::new_unop_code(
+                        reload_reg,
+                        Operator::PLUS,
+                        slot.clone(),
+                    ));
+
+                    reloaded.insert(op.virt, next_virt);
+                    unspillable.insert(next_virt);
+                    op.virt = next_virt;
+                    next_virt += 1;
+                }
+
+                let store_code = match Self::defined_register(&t.kind) {
+                    Some(def_op) => slots.get(&def_op.virt).map(|slot| {
+                        unspillable.insert(def_op.virt);
+                        ThreeAddressCode::new_assign_code(slot.clone(), def_op.clone())
+                    }),
+                    None => None,
+                };
+
+                bb.tacs.push(t);
+                if let Some(store_code) = store_code {
+                    bb.tacs.push(store_code);
+                }
+            }
+        }
+    }
+    // 物理レジスタを割り付ける時に,割付済みであることを要求するオペランド
+    // *t の t も含む
+    pub fn used_registers_mut(kind: &mut TacKind) -> Vec<&mut Operand> {
+        let is_address = |op: &Operand| op.is_register() || op.is_dereference();
+        match kind {
+            TacKind::UNARYEXPR(_, _, inner) if is_address(inner) => vec![inner],
+            TacKind::ASSIGN(dst_op, src_op) => {
+                let mut operands = Vec::new();
+                if dst_op.is_dereference() {
+                    operands.push(dst_op);
+                }
+                if src_op.is_register() {
+                    operands.push(src_op);
+                }
+                operands
+            }
+            TacKind::EXPR(_, _, left, right) => vec![left, right]
+                .into_iter()
+                .filter(|op| op.is_register())
+                .collect(),
+            TacKind::CALL(_, callee, args, _, _) => args
+                .iter_mut()
+                .chain(Some(callee))
+                .filter(|op| op.is_register())
+                .collect(),
+            TacKind::RET(op)
+            | TacKind::IFF(op, _)
+            | TacKind::IFT(op, _)
+            | TacKind::JUMPTABLE(op, _, _)
+                if op.is_register() =>
+            {
+                vec![op]
+            }
+            _ => Vec::new(),
+        }
+    }
+    pub fn defined_register(kind: &TacKind) -> Option<&Operand> {
+        match kind {
+            TacKind::UNARYEXPR(var_op, _, _)
+            | TacKind::EXPR(var_op, _, _, _)
+            | TacKind::CALL(var_op, _, _, _, _)
+                if var_op.is_register() =>
+            {
+                Some(var_op)
+            }
+            _ => None,
+        }
+    }
+    pub fn max_virtual_register(func: &IRFunction) -> RegisterNumber {
+        func.blocks
+            .iter()
+            .flat_map(|bb| bb.cfg_inbb.used.iter().chain(bb.cfg_inbb.def.iter()))
+            .flat_map(|regs| regs.iter())
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod spill_tests {
+    use super::*;
+    use crate::compiler::backend::test_support::{
+        allocate, caller_saved_description, sum_of_live_values,
+    };
+
+    fn stored_registers(func: &IRFunction) -> BTreeSet<RegisterNumber> {
+        func.blocks[0]
+            .tacs
+            .iter()
+            .filter_map(|t| match &t.kind {
+                TacKind::ASSIGN(_, src_op) => Some(src_op.virt),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_spill_registers() {
+        let func = allocate(
+            sum_of_live_values(12),
+            &caller_saved_description(6),
+            HighOptimizer::register_allocation_for_virtual_registers,
+        );
+
+        // 追い出した値ごとにスピル領域が確保される
+        let spilled = stored_registers(&func);
+        assert!(!spilled.is_empty());
+        assert_eq!(spilled.len() * 8, func.frame_size);
+
+        // スピル後は,どの位置でも同時に生きるレジスタが6個以下になる
+        let position_number = func.blocks[0].tacs.len();
+        for position in 0..position_number {
+            let active = func
+                .living
+                .values()
+                .filter(|(live_in, live_out)| *live_in <= position && position <= *live_out)
+                .count();
+            assert!(active <= 6);
+        }
+
+        // 全てのオペランドに,使用可能な範囲の物理レジスタが割り付けられる
+        for t in func.blocks[0].tacs.iter() {
+            if let TacKind::EXPR(var_op, _, left, right) = &t.kind {
+                for op in [var_op, left, right].iter() {
+                    assert!(op.phys < 6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spill_cost() {
+        let mut func = sum_of_live_values(10);
+        let mut high_opt = HighOptimizer::new(Vec::new(), BTreeMap::new(), Vec::new());
+        high_opt.build_cfg_with_func(&mut func);
+        high_opt.liveness_analyze_func(&mut func);
+
+        // 最初の加算では t0..t9 と結果の11個が同時に生きている
+        // 参照回数が同じなら,生存区間の長い(先に定義された)値から追い出す
+        let description = caller_saved_description(10);
+        let spilled =
+            HighOptimizer::select_spilled_registers(&func, &description, &BTreeSet::new());
+        assert_eq!(
+            vec![0],
+            spilled.into_iter().collect::<Vec<RegisterNumber>>()
+        );
+
+        // スピルで作ったレジスタは選ばれない
+        let unspillable = (0..20).collect();
+        let spilled = HighOptimizer::select_spilled_registers(&func, &description, &unspillable);
+        assert!(spilled.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+// レジスタ割付のテストで共有する,関数とターゲットの組み立て
+use crate::compiler::backend::high_optimizer::HighOptimizer;
+use crate::compiler::backend::register_description::{PhysicalRegister, RegisterDescription};
+use crate::compiler::ir::three_address_code::{
+    basicblock::BasicBlock,
+    function::IRFunction,
+    tac::ThreeAddressCode,
+    tac_kind::{Operand, Operator, TacKind},
+};
+
+use std::collections::BTreeMap;
+
+type RegisterNumber = usize;
+
+// 線形走査(HighOptimizer::register_allocation_for_virtual_registers)か,
+// グラフ彩色(HighOptimizer::graph_coloring_for_virtual_registers)のどちらか
+pub type Allocator = fn(&mut HighOptimizer, &RegisterDescription);
+
+// entry ブロックだけを持つ関数 f
+pub fn single_block_function(tacs: Vec<ThreeAddressCode>) -> IRFunction {
+    let mut entry = BasicBlock::new("entry".to_string());
+    entry.tacs = tacs;
+    let mut func = IRFunction::new("f".to_string());
+    func.blocks.push(entry);
+    func
+}
+
+// t0..t(n-1) を全て定義してから,足し合わせて返す
+// 足し終えるまで n 個の値が同時に生きている
+pub fn sum_of_live_values(live_values: usize) -> IRFunction {
+    let mut tacs = Vec::new();
+    for virt in 0..live_values {
+        tacs.push(ThreeAddressCode::new_binop_code(
+            Operand::new_virtreg(virt),
+            Operator::PLUS,
+            Operand::new_int_literal(virt as i128),
+            Operand::new_int_literal(1),
+        ));
+    }
+    let mut sum = Operand::new_virtreg(0);
+    for virt in 1..live_values {
+        let next = Operand::new_virtreg(live_values + virt);
+        tacs.push(ThreeAddressCode::new_binop_code(
+            next.clone(),
+            Operator::PLUS,
+            sum,
+            Operand::new_virtreg(virt),
+        ));
+        sum = next;
+    }
+    tacs.push(ThreeAddressCode::new_return(sum));
+    single_block_function(tacs)
+}
+
+// 制約の無い,n個の caller-saved なレジスタを持つターゲット
+pub fn caller_saved_description(available_registers: usize) -> RegisterDescription {
+    let registers = (0..available_registers)
+        .map(|phys| PhysicalRegister {
+            name: format!("r{}", phys),
+            is_allocatable: true,
+            is_callee_saved: false,
+        })
+        .collect();
+    RegisterDescription::new(registers, |_| Default::default())
+}
+
+// CFG の構築と生存解析を済ませてから割り付ける
+pub fn allocate(
+    func: IRFunction,
+    description: &RegisterDescription,
+    allocator: Allocator,
+) -> IRFunction {
+    let mut high_opt = HighOptimizer::new(vec![func], BTreeMap::new(), Vec::new());
+    high_opt.build_cfg();
+    high_opt.setup_liveness_analyze();
+    allocator(&mut high_opt, description);
+    high_opt.functions.remove(0)
+}
+
+// 先頭のブロックで,各命令が定義するレジスタの物理レジスタ番号
+pub fn defined_phys(func: &IRFunction) -> Vec<RegisterNumber> {
+    func.blocks[0]
+        .tacs
+        .iter()
+        .filter_map(|t| match &t.kind {
+            TacKind::EXPR(var_op, _, _, _) | TacKind::CALL(var_op, _, _, _, _) => Some(var_op.phys),
+            _ => None,
+        })
+        .collect()
+}
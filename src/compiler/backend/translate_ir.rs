@@ -1,14 +1,22 @@
 use crate::compiler::frontend::manager::Manager;
 use crate::compiler::frontend::node::{Function, Node, NodeKind};
+use crate::compiler::frontend::types::{Type, TypeKind};
 use crate::compiler::frontend::variable::VarKind;
 use crate::compiler::ir::three_address_code;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use three_address_code::{
     basicblock::BasicBlock,
     function::IRFunction,
     tac::ThreeAddressCode,
-    tac_kind::{Operand, Operator},
+    tac_kind::{OpeKind, Operand, Operator, TacKind},
 };
 
+// System V ABI の va_list が前提とする,引数レジスタの退避領域
+// ベクタレジスタは退避しないので, fp_offset は使い切った位置にしておく
+const GP_SAVE_AREA_SIZE: usize = 48;
+const FP_SAVE_AREA_END: usize = 176;
+
 impl NodeKind {
     fn to_operator(&self) -> Option<Operator> {
         match self {
@@ -16,7 +24,21 @@ impl NodeKind {
             Self::SUB(_left, _right) => Some(Operator::MINUS),
             Self::MUL(_left, _right) => Some(Operator::ASTERISK),
             Self::DIV(_left, _right) => Some(Operator::SLASH),
+            Self::MOD(_left, _right) => Some(Operator::PERCENT),
+            Self::LSHIFT(_left, _right) => Some(Operator::LSHIFT),
+            Self::RSHIFT(_left, _right) => Some(Operator::RSHIFT),
+            Self::BITAND(_left, _right) => Some(Operator::AMPERSAND),
+            Self::BITOR(_left, _right) => Some(Operator::PIPE),
+            Self::BITXOR(_left, _right) => Some(Operator::CARET),
+            Self::LESSTHAN(_left, _right) => Some(Operator::LESS),
+            Self::LESSTHANEQUAL(_left, _right) => Some(Operator::LESSEQ),
+            Self::GREATERTHAN(_left, _right) => Some(Operator::GREATER),
+            Self::GREATERTHANEQUAL(_left, _right) => Some(Operator::GREATEREQ),
+            Self::EQUAL(_left, _right) => Some(Operator::EQUAL),
+            Self::NOTEQUAL(_left, _right) => Some(Operator::NOTEQUAL),
             Self::NEGATIVE(_left) => Some(Operator::MINUS),
+            Self::LOGICALNOT(_left) => Some(Operator::BANG),
+            Self::BITNOT(_left) => Some(Operator::TILDE),
             _ => None,
         }
     }
@@ -35,7 +57,7 @@ impl Manager {
             // 関数内ASTからIRを生成
             self.init_info_for_genir();
 
-            for (i, (name, _param)) in ast_func.params.iter().enumerate() {
+            for (i, name) in ast_func.param_names.iter().enumerate() {
                 if let Some(arg_symbol) = self.params.get(name) {
                     let param_offset = arg_symbol.get_local_offset();
                     self.add_ir_to_current_bb(
@@ -44,9 +66,21 @@ impl Manager {
                     );
                 }
             }
+            self.va_save_area = None;
+            if ast_func.is_variadic {
+                self.gen_register_save_area(idx, ast_func.param_names.len());
+            }
             for stmt in ast_func.stmts.iter() {
                 self.gen_stmt(idx, stmt.clone());
             }
+
+            // return 文に到達せず末尾まで実行した場合もエピローグを通って戻る
+            if !self.ends_with_return(idx) {
+                self.add_ir_to_current_bb(
+                    idx,
+                    ThreeAddressCode::new_return(Operand::new_int_literal(0)),
+                );
+            }
             self.var_map.clear();
             self.params.clear();
         }
@@ -55,15 +89,24 @@ impl Manager {
         match stmt.kind.clone() {
             NodeKind::GOTOSTMT(label_name) => {
                 let succ_label = format!(".L{}", label_name);
-                self.add_ir_to_current_bb(
-                    func_idx,
-                    ThreeAddressCode::new_goto(succ_label.to_string()),
-                );
-
-                // 新しいベーシックブロックに向ける
-                let goto_bb = BasicBlock::new(succ_label);
-                self.ir_funcs[func_idx].blocks.push(goto_bb);
-                self.cur_bb += 1;
+                self.gen_goto(func_idx, succ_label);
+            }
+            NodeKind::BREAKSTMT => {
+                // ループ/switch の外の break は意味解析で報告済み
+                if let Some((break_label, _)) = self.jump_labels.last() {
+                    let break_label = break_label.clone();
+                    self.gen_goto(func_idx, break_label);
+                }
+            }
+            NodeKind::CONTINUESTMT => {
+                let continue_label = self
+                    .jump_labels
+                    .iter()
+                    .rev()
+                    .find_map(|(_, continue_label)| continue_label.clone());
+                if let Some(continue_label) = continue_label {
+                    self.gen_goto(func_idx, continue_label);
+                }
             }
             NodeKind::COMPOUNDSTMT(stmts) => {
                 for st in stmts.iter() {
@@ -71,7 +114,11 @@ impl Manager {
                 }
             }
             NodeKind::RETURNSTMT(child) => {
-                let return_operand = self.gen_expr(func_idx, *child);
+                // 値を返さない場合も rax を 0 にして戻る
+                let return_operand = match child.kind {
+                    NodeKind::NOP => Operand::new_int_literal(0),
+                    _ => self.gen_expr(func_idx, *child),
+                };
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_return(return_operand));
             }
             NodeKind::FORSTMT(cl, ex, ex2, stmt) => {
@@ -89,15 +136,26 @@ impl Manager {
                 );
 
                 // 条件式の翻訳
-                let cond_op = self.gen_expr(func_idx, *ex);
+                // 省略されていれば無限ループ
+                if ex.kind != NodeKind::NOP {
+                    let cond_op = self.gen_expr(func_idx, *ex);
 
-                // ifジャンプの翻訳,body/gotoの翻訳
-                self.add_ir_to_current_bb(
-                    func_idx,
-                    ThreeAddressCode::new_iff(cond_op, fin_label.clone()),
-                );
+                    // ifジャンプの翻訳
+                    self.add_ir_to_current_bb(
+                        func_idx,
+                        ThreeAddressCode::new_iff(cond_op, fin_label.clone()),
+                    );
+                }
 
+                // body/gotoの翻訳
+                // continue は更新式に飛ぶので,更新式から新しいBBを始める
+                let continue_label = format!(".L{}", self.use_current_label());
+                self.jump_labels
+                    .push((fin_label.clone(), Some(continue_label.clone())));
                 self.gen_stmt(func_idx, *stmt);
+                self.jump_labels.pop();
+
+                self.start_labeled_bb(func_idx, continue_label);
                 let _ = self.gen_expr(func_idx, *ex2);
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_goto(loop_label));
 
@@ -121,9 +179,15 @@ impl Manager {
                 );
 
                 // bodyの翻訳
+                let continue_label = format!(".L{}", self.use_current_label());
+                self.jump_labels
+                    .push((fin_label.clone(), Some(continue_label.clone())));
                 self.gen_stmt(func_idx, *stmt);
+                self.jump_labels.pop();
 
                 // 条件式の翻訳
+                // continue は条件式の評価に飛ぶ
+                self.start_labeled_bb(func_idx, continue_label);
                 let cond_op = self.gen_expr(func_idx, *cond_expr);
                 // ifジャンプの翻訳,gotoの翻訳
                 self.add_ir_to_current_bb(
@@ -139,8 +203,6 @@ impl Manager {
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(fin_label));
             }
             NodeKind::WHILESTMT(cond_expr, stmt) => {
-                // 条件式の翻訳
-                let cond_op = self.gen_expr(func_idx, *cond_expr);
                 let loop_label = format!(".L{}", self.use_current_label());
                 let fin_label = format!(".L{}", self.use_current_label());
 
@@ -153,13 +215,19 @@ impl Manager {
                     ThreeAddressCode::new_label(loop_label.to_string()),
                 );
 
+                // 条件式は毎回評価し直すので,ループ内で翻訳する
+                let cond_op = self.gen_expr(func_idx, *cond_expr);
+
                 // ifジャンプの翻訳,body/gotoの翻訳
                 self.add_ir_to_current_bb(
                     func_idx,
                     ThreeAddressCode::new_iff(cond_op, fin_label.clone()),
                 );
 
+                self.jump_labels
+                    .push((fin_label.clone(), Some(loop_label.clone())));
                 self.gen_stmt(func_idx, *stmt);
+                self.jump_labels.pop();
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_goto(loop_label));
 
                 // while終了後のラベル/BBを生成
@@ -220,37 +288,387 @@ impl Manager {
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(fin_label));
             }
             NodeKind::LABELEDSTMT(label_name, any_stmt) => {
-                // goto時に新しいBasicBlockを向いた状態になっている
+                // ラベル付き文から新しいBasicBlockを始める
                 // IRを生成するのはCFG構築などに必要な為.
                 let ir_label = format!(".L{}", label_name);
+                let labeled_bb = BasicBlock::new(ir_label.clone());
+                self.ir_funcs[func_idx].blocks.push(labeled_bb);
+                self.cur_bb += 1;
                 self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(ir_label));
                 self.gen_stmt(func_idx, *any_stmt);
             }
+            NodeKind::SWITCHSTMT(cond_expr, stmt, cases, has_default) => {
+                let cond_op = self.gen_expr(func_idx, *cond_expr);
+                let cond_reg = self.load_to_register(func_idx, cond_op);
+
+                let mut case_labels: BTreeMap<i128, String> = BTreeMap::new();
+                for value in cases.iter() {
+                    let case_label = format!(".L{}", self.use_current_label());
+                    case_labels.insert(*value, case_label);
+                }
+                let fin_label = format!(".L{}", self.use_current_label());
+                let default_label = if has_default {
+                    format!(".L{}", self.use_current_label())
+                } else {
+                    fin_label.clone()
+                };
+
+                if Self::should_use_jump_table(&case_labels) {
+                    self.gen_jump_table(func_idx, cond_reg, &case_labels, &default_label);
+                } else {
+                    self.gen_compare_chain(func_idx, cond_reg, &case_labels, &default_label);
+                }
+
+                // 最初の case より前の文には到達しないが,別のベーシックブロックに入れておく
+                let body_label = format!(".L{}", self.use_current_label());
+                self.start_labeled_bb(func_idx, body_label);
+
+                self.switch_labels.push((case_labels, default_label));
+                self.jump_labels.push((fin_label.clone(), None));
+                self.gen_stmt(func_idx, *stmt);
+                self.jump_labels.pop();
+                self.switch_labels.pop();
+
+                self.start_labeled_bb(func_idx, fin_label);
+            }
+            NodeKind::CASESTMT(value_expr, any_stmt) => {
+                // switch の外に現れた case は意味解析で報告済みなので,ラベルを置かない
+                let case_label = match (self.switch_labels.last(), &value_expr.kind) {
+                    (Some((case_labels, _)), NodeKind::INTEGER(value)) => {
+                        case_labels.get(value).cloned()
+                    }
+                    _ => None,
+                };
+                if let Some(case_label) = case_label {
+                    self.start_labeled_bb(func_idx, case_label);
+                }
+                self.gen_stmt(func_idx, *any_stmt);
+            }
+            NodeKind::DEFAULTSTMT(any_stmt) => {
+                let default_label = self.switch_labels.last().map(|(_, label)| label.clone());
+                if let Some(default_label) = default_label {
+                    self.start_labeled_bb(func_idx, default_label);
+                }
+                self.gen_stmt(func_idx, *any_stmt);
+            }
             NodeKind::EXPRSTMT(child) => {
                 let _ = self.gen_expr(func_idx, *child);
             }
+            // 平坦化した初期化子の要素を,先頭から順に変数の領域へ書き込む
+            NodeKind::DECLARATION(var_name, _var_type, Some(initializer)) => {
+                let var_offset = match self.var_map.get(&var_name) {
+                    Some(var) => var.get_local_offset(),
+                    None => return,
+                };
+                if let NodeKind::FLATINIT(values) = initializer.kind {
+                    let var_op = Operand::new_auto_var(var_name, var_offset);
+                    for (offset, value) in values {
+                        let value_type = value.ctype.clone();
+                        let element_op = var_op.offset_by(offset).with_type(&value_type);
+                        if value_type.is_record() {
+                            let value_op = self.gen_lvalue(func_idx, value);
+                            let size = value_type.byte_size;
+                            self.gen_record_copy(func_idx, &element_op, &value_op, size);
+                        } else {
+                            let value_op = self.gen_expr(func_idx, value);
+                            let assign_code =
+                                ThreeAddressCode::new_assign_code(element_op, value_op);
+                            self.add_ir_to_current_bb(func_idx, assign_code);
+                        }
+                    }
+                }
+            }
             _ => (),
         }
     }
-    #[allow(irrefutable_let_patterns)]
+    // case の値が密に並んでいれば,表引きの方が比較を繰り返すより速い
+    // 添字の計算で即値を使うので,値は32bitに収まるものに限る
+    fn should_use_jump_table(case_labels: &BTreeMap<i128, String>) -> bool {
+        let (min, max) = match (case_labels.keys().next(), case_labels.keys().last()) {
+            (Some(min), Some(max)) => (*min, *max),
+            _ => return false,
+        };
+        let fits_in_imm32 = i32::try_from(min).is_ok() && i32::try_from(max).is_ok();
+        let table_length = max - min + 1;
+        fits_in_imm32 && case_labels.len() >= 4 && table_length <= case_labels.len() as i128 * 3
+    }
+    // t1 <- cond - min
+    // if true t1 > max - min goto default
+    // goto table[t1]
+    // 差を符号無しで比べれば,min より小さい値も一度の比較で default に送れる
+    fn gen_jump_table(
+        &mut self,
+        func_idx: usize,
+        cond_reg: Operand,
+        case_labels: &BTreeMap<i128, String>,
+        default_label: &str,
+    ) {
+        let min = *case_labels.keys().next().unwrap();
+        let max = *case_labels.keys().last().unwrap();
+        let index_type = Type::new_long().to_unsigned();
+
+        let index_reg = self.use_current_virt_reg().with_type(&index_type);
+        let sub_code = ThreeAddressCode::new_binop_code(
+            index_reg.clone(),
+            Operator::MINUS,
+            cond_reg.with_type(&index_type),
+            Operand::new_int_literal(min),
+        );
+        self.add_ir_to_current_bb(func_idx, sub_code);
+
+        let out_of_range = self.use_current_virt_reg();
+        let cmp_code = ThreeAddressCode::new_binop_code(
+            out_of_range.clone(),
+            Operator::GREATER,
+            index_reg.clone(),
+            Operand::new_int_literal(max - min),
+        );
+        self.add_ir_to_current_bb(func_idx, cmp_code);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_ift(out_of_range, default_label.to_string()),
+        );
+
+        let targets = (min..=max)
+            .map(|value| match case_labels.get(&value) {
+                Some(case_label) => case_label.to_string(),
+                None => default_label.to_string(),
+            })
+            .collect::<Vec<String>>();
+        let table_label = format!(".L{}", self.use_current_label());
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_jump_table(index_reg, table_label, targets),
+        );
+    }
+    // if true cond == v1 goto case1
+    // if true cond == v2 goto case2
+    // ...
+    // goto default
+    fn gen_compare_chain(
+        &mut self,
+        func_idx: usize,
+        cond_reg: Operand,
+        case_labels: &BTreeMap<i128, String>,
+        default_label: &str,
+    ) {
+        for (value, case_label) in case_labels.iter() {
+            let matched = self.use_current_virt_reg();
+            let cmp_code = ThreeAddressCode::new_binop_code(
+                matched.clone(),
+                Operator::EQUAL,
+                cond_reg.clone(),
+                Operand::new_int_literal(*value),
+            );
+            self.add_ir_to_current_bb(func_idx, cmp_code);
+            self.add_ir_to_current_bb(
+                func_idx,
+                ThreeAddressCode::new_ift(matched, case_label.to_string()),
+            );
+        }
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_goto(default_label.to_string()),
+        );
+    }
+    // 無条件ジャンプの後の文は別のベーシックブロックに入れる
+    // (ジャンプ先のラベルが直後にあるとは限らない)
+    fn gen_goto(&mut self, func_idx: usize, label: String) {
+        self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_goto(label));
+
+        let next_label = format!(".L{}", self.use_current_label());
+        let next_bb = BasicBlock::new(next_label);
+        self.ir_funcs[func_idx].blocks.push(next_bb);
+        self.cur_bb += 1;
+    }
+    // ラベルから新しいBasicBlockを始める
+    fn start_labeled_bb(&mut self, func_idx: usize, label: String) {
+        let labeled_bb = BasicBlock::new(label.clone());
+        self.ir_funcs[func_idx].blocks.push(labeled_bb);
+        self.cur_bb += 1;
+        self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(label));
+    }
     fn gen_expr(&mut self, func_idx: usize, n: Node) -> Operand {
         match n.kind.clone() {
+            // 構造体/共用体の代入はメモリの内容をコピーする
+            // 結果は代入先の領域そのもの
+            NodeKind::ASSIGN(lv, rv) if n.ctype.is_record() => {
+                let right_op = self.gen_lvalue(func_idx, *rv);
+                let left_op = self.gen_lvalue(func_idx, *lv);
+                self.gen_record_copy(func_idx, &left_op, &right_op, n.ctype.byte_size);
+                left_op
+            }
             NodeKind::ASSIGN(lv, rv) => {
                 // 左右の子ノードを変換
                 let right_op = self.gen_expr(func_idx, *rv);
-                let left_op = self.gen_expr(func_idx, *lv);
+                let left_op = self.gen_lvalue(func_idx, *lv);
 
                 let assign_code = ThreeAddressCode::new_assign_code(left_op, right_op.clone());
 
                 self.add_ir_to_current_bb(func_idx, assign_code);
                 right_op
             }
+            // a op= b -> t <- a op b ; a <- t
+            // 左辺は一度しか評価しない
+            // 演算は二項演算の型で行い,結果を左辺の型に戻して書き込む
+            NodeKind::COMPOUNDASSIGN(binary) => {
+                let (_operator, lv, rv) = binary.split_binary();
+                let (lv, operation_type) = match &lv.kind {
+                    NodeKind::CAST(inner) => (*inner.clone(), Some(lv.ctype.clone())),
+                    _ => (lv.clone(), None),
+                };
+                let right_op = self.gen_expr(func_idx, rv.clone());
+                let left_op = self.gen_lvalue(func_idx, lv);
+                let mut left_value = self.load_lvalue(func_idx, &left_op);
+                if let Some(operation_type) = operation_type {
+                    left_value = self.gen_cast(func_idx, left_value, &operation_type);
+                }
+
+                let variable_reg = self.use_current_virt_reg().with_type(&binary.ctype);
+                let binary_code = ThreeAddressCode::new_binop_code(
+                    variable_reg.clone(),
+                    binary.kind.to_operator().unwrap(),
+                    left_value,
+                    right_op,
+                );
+                self.add_ir_to_current_bb(func_idx, binary_code);
+
+                let result_op = self.gen_cast(func_idx, variable_reg, &n.ctype);
+                let assign_code = ThreeAddressCode::new_assign_code(left_op, result_op.clone());
+                self.add_ir_to_current_bb(func_idx, assign_code);
+                result_op
+            }
+            // 整数型の変換
+            NodeKind::CAST(inner) => {
+                let inner_op = self.gen_expr(func_idx, *inner);
+                self.gen_cast(func_idx, inner_op, &n.ctype)
+            }
+            // a++ -> t1 <- a + 1 ; a <- t1 ; t2 <- t1 - 1
+            // 更新前の値は,更新後の値から戻して求める
+            // ポインタであれば要素のサイズだけ進める
+            NodeKind::POSTINCREMENT(inner) | NodeKind::POSTDECREMENT(inner) => {
+                let (update, restore) = match n.kind {
+                    NodeKind::POSTINCREMENT(_) => (Operator::PLUS, Operator::MINUS),
+                    _ => (Operator::MINUS, Operator::PLUS),
+                };
+                let step = match &n.ctype.kind {
+                    TypeKind::POINTER(base) => base.byte_size as i128,
+                    _ => 1,
+                };
+                let inner_op = self.gen_lvalue(func_idx, *inner);
+                let inner_value = self.load_lvalue(func_idx, &inner_op);
+
+                let updated_reg = self.use_current_virt_reg().with_type(&n.ctype);
+                let update_code = ThreeAddressCode::new_binop_code(
+                    updated_reg.clone(),
+                    update,
+                    inner_value,
+                    Operand::new_int_literal(step),
+                );
+                self.add_ir_to_current_bb(func_idx, update_code);
+
+                let assign_code = ThreeAddressCode::new_assign_code(inner_op, updated_reg.clone());
+                self.add_ir_to_current_bb(func_idx, assign_code);
+
+                let original_reg = self.use_current_virt_reg().with_type(&n.ctype);
+                let restore_code = ThreeAddressCode::new_binop_code(
+                    original_reg.clone(),
+                    restore,
+                    updated_reg,
+                    Operand::new_int_literal(step),
+                );
+                self.add_ir_to_current_bb(func_idx, restore_code);
+                original_reg
+            }
+            // 条件演算子
+            // 2つのBBで求めた値を合流させるので,結果はスタック上の一時領域に置く
+            NodeKind::CONDITIONAL(cond, then, alter) => {
+                let result_op = self.use_anonymous_variable(func_idx, n.ctype.is_unsigned);
+                let fin_label = format!(".L{}", self.use_current_label());
+                let else_label = format!(".L{}", self.use_current_label());
+
+                let cond_op = self.gen_expr(func_idx, *cond);
+                self.add_ir_to_current_bb(
+                    func_idx,
+                    ThreeAddressCode::new_iff(cond_op, else_label.clone()),
+                );
+
+                let then_op = self.gen_expr(func_idx, *then);
+                self.add_ir_to_current_bb(
+                    func_idx,
+                    ThreeAddressCode::new_assign_code(result_op.clone(), then_op),
+                );
+                self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_goto(fin_label.clone()));
+
+                // elseブロック
+                let else_bb = BasicBlock::new(else_label.clone());
+                self.ir_funcs[func_idx].blocks.push(else_bb);
+                self.cur_bb += 1;
+                self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(else_label));
+
+                let else_op = self.gen_expr(func_idx, *alter);
+                self.add_ir_to_current_bb(
+                    func_idx,
+                    ThreeAddressCode::new_assign_code(result_op.clone(), else_op),
+                );
+
+                // 合流先のラベル/BBを生成
+                let succ_bb = BasicBlock::new(fin_label.clone());
+                self.ir_funcs[func_idx].blocks.push(succ_bb);
+                self.cur_bb += 1;
+                self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(fin_label));
+
+                result_op
+            }
+            // &x -> t <- & x
+            // &*p は p そのもの, &p->a は p にメンバのオフセットを足したもの
+            NodeKind::ADDRESSOF(inner) => match inner.kind {
+                NodeKind::DEREFERENCE(pointer) => self.gen_expr(func_idx, *pointer),
+                _ => {
+                    let var_op = self.gen_lvalue(func_idx, *inner);
+                    let address_reg = self.use_current_virt_reg().with_type(&n.ctype);
+                    if let OpeKind::DEREFERENCE(offset) = var_op.kind {
+                        let address_code = ThreeAddressCode::new_binop_code(
+                            address_reg.clone(),
+                            Operator::PLUS,
+                            Operand::new_virtreg(var_op.virt),
+                            Operand::new_int_literal(offset as i128),
+                        );
+                        self.add_ir_to_current_bb(func_idx, address_code);
+                        return address_reg;
+                    }
+                    let address_code = ThreeAddressCode::new_unop_code(
+                        address_reg.clone(),
+                        Operator::AMPERSAND,
+                        var_op,
+                    );
+                    self.add_ir_to_current_bb(func_idx, address_code);
+                    address_reg
+                }
+            },
+            // *p -> t <- * p
+            NodeKind::DEREFERENCE(inner) => {
+                let address_op = self.gen_expr(func_idx, *inner);
+                let value_reg = self.use_current_virt_reg().with_type(&n.ctype);
+                let load_code = ThreeAddressCode::new_unop_code(
+                    value_reg.clone(),
+                    Operator::ASTERISK,
+                    address_op,
+                );
+                self.add_ir_to_current_bb(func_idx, load_code);
+                value_reg
+            }
+            // 左辺の値は捨てる
+            NodeKind::COMMA(left, right) => {
+                let _ = self.gen_expr(func_idx, *left);
+                self.gen_expr(func_idx, *right)
+            }
             // 単項演算
-            NodeKind::NEGATIVE(inner) => {
+            NodeKind::NEGATIVE(inner) | NodeKind::LOGICALNOT(inner) | NodeKind::BITNOT(inner) => {
                 let inner_op = self.gen_expr(func_idx, *inner);
 
                 // 次に作るべき番号を持つ仮想レジスタを作成
-                let variable_reg = self.use_current_virt_reg();
+                let variable_reg = self.use_current_virt_reg().with_type(&n.ctype);
 
                 let unary_code = ThreeAddressCode::new_unop_code(
                     variable_reg.clone(),
@@ -264,13 +682,26 @@ impl Manager {
             NodeKind::ADD(left, right)
             | NodeKind::SUB(left, right)
             | NodeKind::MUL(left, right)
-            | NodeKind::DIV(left, right) => {
+            | NodeKind::DIV(left, right)
+            | NodeKind::MOD(left, right)
+            | NodeKind::LSHIFT(left, right)
+            | NodeKind::RSHIFT(left, right)
+            | NodeKind::BITAND(left, right)
+            | NodeKind::BITOR(left, right)
+            | NodeKind::BITXOR(left, right)
+            | NodeKind::LESSTHAN(left, right)
+            | NodeKind::LESSTHANEQUAL(left, right)
+            | NodeKind::GREATERTHAN(left, right)
+            | NodeKind::GREATERTHANEQUAL(left, right)
+            | NodeKind::EQUAL(left, right)
+            | NodeKind::NOTEQUAL(left, right) => {
                 // 左右の子ノードを変換
+                // 右辺で短絡評価のBBが作られても,左辺のレジスタはそのまま生き続ける
                 let left_op = self.gen_expr(func_idx, *left);
                 let right_op = self.gen_expr(func_idx, *right);
 
                 // 次に作るべき番号を持つ仮想レジスタを作成
-                let variable_reg = self.use_current_virt_reg();
+                let variable_reg = self.use_current_virt_reg().with_type(&n.ctype);
 
                 // 二項演算コード生成
                 let binary_code = ThreeAddressCode::new_binop_code(
@@ -284,43 +715,450 @@ impl Manager {
                 // 式が代入されたレジスタを上位に返す
                 variable_reg
             }
-            NodeKind::INTEGER(val) => Operand::new_int_literal(val),
-            NodeKind::IDENTIFIER(name) => {
-                if let Some(var) = self.var_map.get(&name) {
-                    if let VarKind::LOCAL(offset) = var.kind {
-                        return Operand::new_auto_var(name.to_string(), offset);
-                    }
+            // 論理演算(短絡評価)
+            NodeKind::LOGICALAND(left, right) | NodeKind::LOGICALOR(left, right) => {
+                let is_and = matches!(n.kind, NodeKind::LOGICALAND(_, _));
+
+                // 値は基本ブロックを跨いで合流するので,
+                // レジスタではなくスタック上の一時領域に置く
+                let result_op = self.use_anonymous_variable(func_idx, false);
+                let fin_label = format!(".L{}", self.use_current_label());
+
+                // && は偽, || は真のオペランドが見つかった時点で結果が確定する
+                let (decided, otherwise) = if is_and { (0, 1) } else { (1, 0) };
+                self.add_ir_to_current_bb(
+                    func_idx,
+                    ThreeAddressCode::new_assign_code(
+                        result_op.clone(),
+                        Operand::new_int_literal(decided),
+                    ),
+                );
+
+                for operand in [*left, *right] {
+                    let cond_op = self.gen_expr(func_idx, operand);
+                    let jump_code = if is_and {
+                        ThreeAddressCode::new_iff(cond_op, fin_label.clone())
+                    } else {
+                        ThreeAddressCode::new_ift(cond_op, fin_label.clone())
+                    };
+                    self.add_ir_to_current_bb(func_idx, jump_code);
                 }
-                if let Some(var) = self.params.get(&name) {
-                    if let VarKind::LOCAL(offset) = var.kind {
-                        return Operand::new_auto_var(name.to_string(), offset);
+
+                self.add_ir_to_current_bb(
+                    func_idx,
+                    ThreeAddressCode::new_assign_code(
+                        result_op.clone(),
+                        Operand::new_int_literal(otherwise),
+                    ),
+                );
+
+                // 合流先のラベル/BBを生成
+                let succ_bb = BasicBlock::new(fin_label.clone());
+                self.ir_funcs[func_idx].blocks.push(succ_bb);
+                self.cur_bb += 1;
+                self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(fin_label));
+
+                result_op
+            }
+            NodeKind::INTEGER(val) => Operand::new_int_literal(val).with_type(&n.ctype),
+            // 8バイト未満の変数は,拡張しながらレジスタに読み込んでおく
+            NodeKind::IDENTIFIER(_) | NodeKind::MEMBER(_, _) => {
+                let var_op = self.gen_lvalue(func_idx, n);
+                self.load_lvalue(func_idx, &var_op)
+            }
+
+            // 引数の中に関数呼び出しがあっても引数レジスタを壊さないよう,
+            // 全ての引数を評価し終えてから引数レジスタに並べる
+            // 評価した値は一時領域に置き,引数の数だけレジスタを使い切ったり,
+            // 後ろの引数の短絡評価で作られたBBをレジスタが跨いだりしないようにする
+            NodeKind::CALL(callee, args) => {
+                // 可変長引数の関数と,引数並びの分からない関数は al を受け取る
+                let signature = callee
+                    .ctype
+                    .signature()
+                    .or_else(|| callee.ctype.pointed_signature());
+                let is_variadic = match signature {
+                    Some(sig) => sig.is_variadic || sig.param_types.is_none(),
+                    None => true,
+                };
+
+                // 関数ポインタも引数と同じく,先に評価して一時領域に置く
+                let callee_op = match callee.ctype.kind {
+                    TypeKind::FUNCTION(_) => Operand::new_global_var(callee.ident_name()),
+                    _ => {
+                        let pointer_op = self.gen_expr(func_idx, *callee);
+                        let pointer_reg = self.load_to_register(func_idx, pointer_op);
+                        let tmp_op = self.use_anonymous_variable(func_idx, true);
+                        self.add_ir_to_current_bb(
+                            func_idx,
+                            ThreeAddressCode::new_assign_code(tmp_op.clone(), pointer_reg),
+                        );
+                        tmp_op
+                    }
+                };
+
+                let mut arg_ops = Vec::new();
+                for arg in args.into_iter() {
+                    let arg_op = self.gen_expr(func_idx, arg);
+                    if let OpeKind::INTLIT(_) = arg_op.kind {
+                        arg_ops.push(arg_op);
+                        continue;
                     }
+                    let arg_reg = self.load_to_register(func_idx, arg_op);
+                    let tmp_op = self.use_anonymous_variable(func_idx, arg_reg.is_unsigned);
+                    self.add_ir_to_current_bb(
+                        func_idx,
+                        ThreeAddressCode::new_assign_code(tmp_op.clone(), arg_reg),
+                    );
+                    arg_ops.push(tmp_op);
                 }
-                eprintln!("not found such an var -> {}", name);
+
+                // 関数ポインタは呼び出しの直前にレジスタへ読み直す
+                let callee_op = match callee_op.kind {
+                    OpeKind::AUTOVARIABLE(_, _) => self.load_to_register(func_idx, callee_op),
+                    _ => callee_op,
+                };
+
+                let return_reg = self.use_current_virt_reg().with_type(&n.ctype);
+                let call_code =
+                    ThreeAddressCode::new_call(return_reg.clone(), callee_op, arg_ops, is_variadic);
+                self.add_ir_to_current_bb(func_idx, call_code);
+                return_reg
+            }
+            NodeKind::VASTART(ap) => {
+                self.gen_va_start(func_idx, *ap);
                 Operand::new_invalid()
             }
-
-            NodeKind::CALL(ident, args) => {
-                for (i, param) in args.iter().enumerate() {
-                    let param_op = self.gen_expr(func_idx, param.clone());
-                    let param_code = ThreeAddressCode::new_genparam(i, param_op);
-                    self.add_ir_to_current_bb(func_idx, param_code);
-                }
-                Operand::new_call(ident.ident_name())
+            NodeKind::VAARG(ap) => self.gen_va_arg(func_idx, *ap, &n.ctype),
+            NodeKind::VAEND(ap) => {
+                let _ = self.gen_expr(func_idx, *ap);
+                Operand::new_invalid()
             }
             // NodeKind::INVALID => Operand::new_invalid(),
             _ => Operand::new_invalid(),
         }
     }
+    // 代入先のオペランド
+    // *p であればアドレスをレジスタに置き,それを間接参照するオペランドを返す
+    fn gen_lvalue(&mut self, func_idx: usize, n: Node) -> Operand {
+        match n.kind {
+            NodeKind::DEREFERENCE(inner) => {
+                let address_op = self.gen_expr(func_idx, *inner);
+                let address_reg = self.load_to_register(func_idx, address_op);
+                Operand::new_dereference(address_reg.virt, 0).with_type(&n.ctype)
+            }
+            // s.a -> s の領域を,aのオフセットだけずらしたもの
+            NodeKind::MEMBER(record, member_name) => {
+                let member = record.ctype.find_member(&member_name);
+                let record_op = self.gen_lvalue(func_idx, *record);
+                match (member, &record_op.kind) {
+                    (Some(member), OpeKind::AUTOVARIABLE(_, _))
+                    | (Some(member), OpeKind::DEREFERENCE(_)) => {
+                        record_op.offset_by(member.offset).with_type(&n.ctype)
+                    }
+                    _ => Operand::new_invalid(),
+                }
+            }
+            NodeKind::IDENTIFIER(name) => {
+                let var = self
+                    .var_map
+                    .get(&name)
+                    .or_else(|| self.params.get(&name))
+                    .or_else(|| self.global_map.get(&name))
+                    .cloned();
+                if let Some(var) = var {
+                    match var.kind {
+                        VarKind::LOCAL(offset) => {
+                            return Operand::new_auto_var(name.to_string(), offset)
+                                .with_type(&var.ctype);
+                        }
+                        VarKind::GLOBAL(_) | VarKind::EXTERN => {
+                            return self.gen_global_lvalue(func_idx, name, &var.ctype);
+                        }
+                    }
+                }
+                // 関数名は関数の先頭を指す
+                if let TypeKind::FUNCTION(_) = n.ctype.kind {
+                    return self.gen_global_lvalue(func_idx, name, &n.ctype);
+                }
+                eprintln!("not found such an var -> {}", name);
+                Operand::new_invalid()
+            }
+            // 文字列リテラルは .rodata 上の無名オブジェクト
+            NodeKind::STRLIT(idx) => {
+                self.gen_global_lvalue(func_idx, format!(".LC{}", idx), &n.ctype)
+            }
+            _ => self.gen_expr(func_idx, n),
+        }
+    }
+    // 静的な領域はアドレスを求めてから間接参照する
+    // x -> t <- &x ; *t
+    fn gen_global_lvalue(&mut self, func_idx: usize, name: String, ctype: &Type) -> Operand {
+        let address_reg = self.use_current_virt_reg();
+        let address_code = ThreeAddressCode::new_unop_code(
+            address_reg.clone(),
+            Operator::AMPERSAND,
+            Operand::new_global_var(name),
+        );
+        self.add_ir_to_current_bb(func_idx, address_code);
+        Operand::new_dereference(address_reg.virt, 0).with_type(ctype)
+    }
+    // 左辺値の現在の値.間接参照ならメモリからロードする.
+    // 8バイト未満の変数もレジスタに読み込む
+    fn load_lvalue(&mut self, func_idx: usize, lvalue: &Operand) -> Operand {
+        if lvalue.is_dereference() || lvalue.size < 8 {
+            return self.load_to_register(func_idx, lvalue.clone());
+        }
+        lvalue.clone()
+    }
+    // 構造体をsizeバイト分コピーする(memcpy相当)
+    // 8/4/2/1バイトずつ,レジスタを経由して読み書きする
+    fn gen_record_copy(&mut self, func_idx: usize, dst: &Operand, src: &Operand, size: usize) {
+        let mut copied = 0;
+        while copied < size {
+            let chunk = [8, 4, 2, 1]
+                .iter()
+                .copied()
+                .find(|chunk| *chunk <= size - copied)
+                .unwrap();
+            let mut src_chunk = src.offset_by(copied);
+            let mut dst_chunk = dst.offset_by(copied);
+            for chunk_op in [&mut src_chunk, &mut dst_chunk] {
+                chunk_op.size = chunk;
+                chunk_op.is_unsigned = true;
+            }
+
+            let value_reg = self.load_to_register(func_idx, src_chunk);
+            self.add_ir_to_current_bb(
+                func_idx,
+                ThreeAddressCode::new_assign_code(dst_chunk, value_reg),
+            );
+            copied += chunk;
+        }
+    }
+    // 値を別の整数型に変換する
+    // 8バイトへの変換や値が変わらない拡張は,拡張済みの値をそのまま使えるので型を付け替えるだけ
+    // それ以外は t <- (cast) x として切り詰めて拡張し直す
+    fn gen_cast(&mut self, func_idx: usize, op: Operand, ty: &Type) -> Operand {
+        let cast_op = op.clone().with_type(ty);
+        let is_same_type = cast_op.size == op.size && cast_op.is_unsigned == op.is_unsigned;
+        let keeps_value = cast_op.size > op.size && (op.is_unsigned || !cast_op.is_unsigned);
+        if is_same_type || keeps_value || cast_op.size == 8 {
+            return cast_op;
+        }
+        let reg = self.use_current_virt_reg().with_type(ty);
+        let cast_code = ThreeAddressCode::new_unop_code(reg.clone(), Operator::CAST, op);
+        self.add_ir_to_current_bb(func_idx, cast_code);
+        reg
+    }
+    // 可変長引数の関数は,引数レジスタを全て退避領域に並べておき, va_arg で順に取り出す
+    fn gen_register_save_area(&mut self, func_idx: usize, named_params: usize) {
+        let frame_size = &mut self.ir_funcs[func_idx].frame_size;
+        *frame_size = ((*frame_size + 7) & !7) + GP_SAVE_AREA_SIZE;
+        let save_offset = *frame_size;
+
+        for reg_num in 0..GP_SAVE_AREA_SIZE / 8 {
+            self.add_ir_to_current_bb(
+                func_idx,
+                ThreeAddressCode::new_pushparam(reg_num, save_offset - reg_num * 8),
+            );
+        }
+        self.va_save_area = Some((save_offset, named_params));
+    }
+    // va_list の各メンバを,名前付き引数の直後を指すように初期化する
+    fn gen_va_start(&mut self, func_idx: usize, ap: Node) {
+        let (save_offset, named_params) = self.va_save_area.unwrap_or((0, 0));
+        let ap_op = self.gen_expr(func_idx, ap);
+        let ap_reg = self.load_to_register(func_idx, ap_op);
+        let offset_type = Type::new_integer().to_unsigned();
+        let area_type = Type::pointer_to(Type::new_void());
+
+        // gp_offset: 次に取り出す引数の,退避領域の先頭からの位置
+        let gp_offset = (named_params * 8).min(GP_SAVE_AREA_SIZE);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(
+                Operand::new_dereference(ap_reg.virt, 0).with_type(&offset_type),
+                Operand::new_int_literal(gp_offset as i128),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(
+                Operand::new_dereference(ap_reg.virt, 4).with_type(&offset_type),
+                Operand::new_int_literal(FP_SAVE_AREA_END as i128),
+            ),
+        );
+
+        // reg_save_area
+        let area_reg = self.use_current_virt_reg().with_type(&area_type);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_unop_code(
+                area_reg.clone(),
+                Operator::AMPERSAND,
+                Operand::new_auto_var(".va_save_area".to_string(), save_offset),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(
+                Operand::new_dereference(ap_reg.virt, 16).with_type(&area_type),
+                area_reg.clone(),
+            ),
+        );
+
+        // overflow_arg_area: 呼び出し元がスタックに積んだ,名前付きでない最初の引数
+        // 退避領域の末尾が rbp なので,リターンアドレスと rbp の分を足した先になる
+        let stack_named = named_params.saturating_sub(GP_SAVE_AREA_SIZE / 8);
+        let overflow_reg = self.use_current_virt_reg().with_type(&area_type);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_binop_code(
+                overflow_reg.clone(),
+                Operator::PLUS,
+                area_reg,
+                Operand::new_int_literal((save_offset + 16 + stack_named * 8) as i128),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(
+                Operand::new_dereference(ap_reg.virt, 8).with_type(&area_type),
+                overflow_reg,
+            ),
+        );
+    }
+    // 退避領域に残っていればそこから,無ければスタックから次の引数を取り出す
+    // 取り出す位置は基本ブロックを跨いで合流するので,一時領域に置く
+    fn gen_va_arg(&mut self, func_idx: usize, ap: Node, arg_type: &Type) -> Operand {
+        let ap_op = self.gen_expr(func_idx, ap);
+        let ap_reg = self.load_to_register(func_idx, ap_op);
+        let offset_type = Type::new_integer().to_unsigned();
+        let area_type = Type::pointer_to(Type::new_void());
+        let fin_label = format!(".L{}", self.use_current_label());
+        let stack_label = format!(".L{}", self.use_current_label());
+
+        let ap_tmp = self.use_anonymous_variable(func_idx, true);
+        let address_tmp = self.use_anonymous_variable(func_idx, true);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(ap_tmp.clone(), ap_reg.clone()),
+        );
+
+        // gp_offset が退避領域の中を指していれば,レジスタで渡された引数
+        let gp_offset = Operand::new_dereference(ap_reg.virt, 0).with_type(&offset_type);
+        let offset_reg = self.load_to_register(func_idx, gp_offset.clone());
+        let in_register = self.use_current_virt_reg().with_type(&Type::new_integer());
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_binop_code(
+                in_register.clone(),
+                Operator::LESS,
+                offset_reg.clone(),
+                Operand::new_int_literal(GP_SAVE_AREA_SIZE as i128),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_iff(in_register, stack_label.clone()),
+        );
+
+        let save_area = Operand::new_dereference(ap_reg.virt, 16).with_type(&area_type);
+        let area_reg = self.load_to_register(func_idx, save_area);
+        let address_reg = self.use_current_virt_reg().with_type(&area_type);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_binop_code(
+                address_reg.clone(),
+                Operator::PLUS,
+                area_reg,
+                offset_reg.clone(),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(address_tmp.clone(), address_reg),
+        );
+        self.gen_advance(func_idx, gp_offset, offset_reg);
+        self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_goto(fin_label.clone()));
+
+        // スタックで渡された引数
+        let stack_bb = BasicBlock::new(stack_label.clone());
+        self.ir_funcs[func_idx].blocks.push(stack_bb);
+        self.cur_bb += 1;
+        self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(stack_label));
+
+        let ap_reg = self.load_to_register(func_idx, ap_tmp);
+        let overflow_area = Operand::new_dereference(ap_reg.virt, 8).with_type(&area_type);
+        let overflow_reg = self.load_to_register(func_idx, overflow_area.clone());
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(address_tmp.clone(), overflow_reg.clone()),
+        );
+        self.gen_advance(func_idx, overflow_area, overflow_reg);
+
+        // 合流先で取り出す
+        let succ_bb = BasicBlock::new(fin_label.clone());
+        self.ir_funcs[func_idx].blocks.push(succ_bb);
+        self.cur_bb += 1;
+        self.add_ir_to_current_bb(func_idx, ThreeAddressCode::new_label(fin_label));
+
+        let address_reg = self.load_to_register(func_idx, address_tmp);
+        let value_reg = self.use_current_virt_reg().with_type(arg_type);
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_unop_code(value_reg.clone(), Operator::ASTERISK, address_reg),
+        );
+        value_reg
+    }
+    // va_list の位置を,引数1つ分(8バイト)進める
+    fn gen_advance(&mut self, func_idx: usize, position: Operand, current: Operand) {
+        let mut next_reg = self.use_current_virt_reg();
+        next_reg.size = current.size;
+        next_reg.is_unsigned = current.is_unsigned;
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_binop_code(
+                next_reg.clone(),
+                Operator::PLUS,
+                current,
+                Operand::new_int_literal(8),
+            ),
+        );
+        self.add_ir_to_current_bb(
+            func_idx,
+            ThreeAddressCode::new_assign_code(position, next_reg),
+        );
+    }
+    // t <- + x としてレジスタに移す
+    fn load_to_register(&mut self, func_idx: usize, op: Operand) -> Operand {
+        if op.is_register() {
+            return op;
+        }
+        let mut reg = self.use_current_virt_reg();
+        reg.size = op.size;
+        reg.is_unsigned = op.is_unsigned;
+        let load_code = ThreeAddressCode::new_unop_code(reg.clone(), Operator::PLUS, op);
+        self.add_ir_to_current_bb(func_idx, load_code);
+        reg
+    }
     fn add_new_ir_func(&mut self, ast_func: &Function, bb: BasicBlock) {
         let mut ir_func = IRFunction::new(ast_func.name.to_string());
         ir_func.blocks.push(bb);
         ir_func.frame_size = ast_func.frame_size;
+        ir_func.is_static = ast_func.is_static;
         self.ir_funcs.push(ir_func);
     }
     fn add_ir_to_current_bb(&mut self, func_idx: usize, ir: ThreeAddressCode) {
         self.ir_funcs[func_idx].blocks[self.cur_bb].tacs.push(ir);
     }
+    fn ends_with_return(&self, func_idx: usize) -> bool {
+        match self.ir_funcs[func_idx].blocks[self.cur_bb].tacs.last() {
+            Some(tac) => matches!(tac.kind, TacKind::RET(_)),
+            None => false,
+        }
+    }
     fn use_current_virt_reg(&mut self) -> Operand {
         let current_reg = self.cur_virt_reg();
         self.virt += 1;
@@ -331,13 +1169,22 @@ impl Manager {
         self.label += 1;
         current_label
     }
+    // 名前を持たない一時領域をスタックフレームに確保する
+    // 領域は常に8バイトで,拡張済みの値をそのまま置く
+    fn use_anonymous_variable(&mut self, func_idx: usize, is_unsigned: bool) -> Operand {
+        self.ir_funcs[func_idx].frame_size += 8;
+        let offset = self.ir_funcs[func_idx].frame_size;
+        let mut tmp_op = Operand::new_auto_var(format!(".tmp{}", offset), offset);
+        tmp_op.is_unsigned = is_unsigned;
+        tmp_op
+    }
     fn cur_virt_reg(&mut self) -> Operand {
         Operand::new_virtreg(self.virt)
     }
     fn init_info_for_genir(&mut self) {
+        // ラベルはファイル内で一意にしたいので初期化しない
         self.cur_bb = 0;
         self.virt = 0;
-        self.label = 0;
     }
 }
 
@@ -1,5 +1,6 @@
 use crate::compiler::frontend::manager::Manager;
-use crate::compiler::frontend::node::{Function, NodeKind};
+use crate::compiler::frontend::node::{Function, Node, NodeKind};
+use crate::compiler::frontend::types::Type;
 use crate::compiler::frontend::variable::VarKind;
 
 impl Manager {
@@ -17,39 +18,90 @@ impl Manager {
         }
         self.functions = functions;
     }
-    #[allow(irrefutable_let_patterns)]
     pub fn alloc_frame_for_function(&mut self, func: &mut Function) {
         // 簡易実装として,DECLARATIONノードを見たら割り当てるように
         let mut stack_offset: usize = 0;
         for (_name, param) in self.params.iter_mut() {
+            // 引数はレジスタの64bitをそのまま退避するので,型によらず8バイト確保する
             if let VarKind::LOCAL(ref mut offset) = param.kind {
-                stack_offset += param.ctype.byte_size;
-                *offset = stack_offset;
+                *offset = Self::alloc_local_area(&mut stack_offset, &Type::new_long());
             }
         }
         for stmt in func.stmts.iter() {
-            match &stmt.kind {
-                NodeKind::DECLARATION(var_name, var_type) => {
-                    if let Some(local_symbol) = self.var_map.get_mut(var_name) {
-                        if let VarKind::LOCAL(ref mut offset) = local_symbol.kind {
-                            stack_offset += var_type.byte_size;
-                            *offset = stack_offset;
-                        }
+            self.alloc_declarations(stmt, &mut stack_offset);
+        }
+        // 後から確保する一時領域が8バイト境界に並ぶように
+        func.frame_size = Self::align_to(stack_offset, 8);
+    }
+    // ブロックの中の宣言も含め,宣言ごとに別の領域を割り当てる
+    fn alloc_declarations(&mut self, stmt: &Node, stack_offset: &mut usize) {
+        match &stmt.kind {
+            NodeKind::DECLARATION(var_name, var_type, _) => {
+                if let Some(local_symbol) = self.var_map.get_mut(var_name) {
+                    if let VarKind::LOCAL(ref mut offset) = local_symbol.kind {
+                        *offset = Self::alloc_local_area(stack_offset, var_type);
                     }
                 }
-                NodeKind::LABELEDSTMT(_label, inner_st) => {
-                    if let NodeKind::DECLARATION(var_name, var_type) = &inner_st.kind {
-                        if let Some(local_symbol) = self.var_map.get_mut(var_name) {
-                            if let VarKind::LOCAL(ref mut offset) = local_symbol.kind {
-                                stack_offset += var_type.byte_size;
-                                *offset = stack_offset;
-                            }
-                        }
-                    }
+            }
+            NodeKind::COMPOUNDSTMT(stmts) => {
+                for st in stmts.iter() {
+                    self.alloc_declarations(st, stack_offset);
                 }
-                _ => {}
             }
+            NodeKind::IFELSESTMT(_cond, stmt, alt_stmt) => {
+                self.alloc_declarations(stmt, stack_offset);
+                self.alloc_declarations(alt_stmt, stack_offset);
+            }
+            NodeKind::LABELEDSTMT(_, inner_st)
+            | NodeKind::CASESTMT(_, inner_st)
+            | NodeKind::DEFAULTSTMT(inner_st)
+            | NodeKind::IFSTMT(_, inner_st)
+            | NodeKind::WHILESTMT(_, inner_st)
+            | NodeKind::DOWHILESTMT(inner_st, _)
+            | NodeKind::FORSTMT(_, _, _, inner_st)
+            | NodeKind::SWITCHSTMT(_, inner_st, _, _) => {
+                self.alloc_declarations(inner_st, stack_offset);
+            }
+            _ => {}
         }
-        func.frame_size = stack_offset;
+    }
+    // 変数の領域を確保し,そのrbpからのオフセットを返す
+    // 配列の先頭要素が最も低いアドレスに来るので,オフセットは領域の下端を指す
+    fn alloc_local_area(stack_offset: &mut usize, ty: &Type) -> usize {
+        *stack_offset = Self::align_to(*stack_offset + ty.byte_size, ty.alignment());
+        *stack_offset
+    }
+    fn align_to(n: usize, align: usize) -> usize {
+        (n + align - 1) & !(align - 1)
+    }
+}
+
+#[cfg(test)]
+mod alloc_frame_tests {
+    use super::*;
+    use crate::compiler::file::SrcFile;
+    use crate::compiler::frontend::lex;
+
+    #[test]
+    fn test_alloc_nested_declarations() {
+        let input = "int main(){ int a; { int a; char c; } if (a) { long b; } return a; }";
+        let src_file = SrcFile {
+            abs_path: "test.c".to_string(),
+            contents: input.to_string(),
+        };
+        let mut manager = Manager::new(src_file);
+        manager.preprocessed = input.to_string();
+        lex::tokenize(&mut manager);
+        manager.parse();
+        manager.alloc_frame();
+
+        // 同じ名前でも宣言ごとに別の領域を持つ
+        let func = &manager.functions[0];
+        let offset = |name: &str| func.local_map.get(name).unwrap().get_local_offset();
+        assert_eq!(4, offset("a"));
+        assert_eq!(8, offset("a.1"));
+        assert_eq!(9, offset("c.1"));
+        assert_eq!(24, offset("b.1"));
+        assert_eq!(24, func.frame_size);
     }
 }
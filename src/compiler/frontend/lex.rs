@@ -1,12 +1,15 @@
 use crate::compiler::frontend;
 use frontend::manager::Manager;
-use frontend::token::{Position, Token, TokenKind};
+use frontend::token::{intern_file_name, Position, Token, TokenKind};
+use frontend::types::Type;
+
+use crate::error::{Error, ErrorKind, ErrorMsg};
 
 use std::collections::BTreeMap;
 pub fn tokenize(manager: &mut Manager) {
     // ソースコードのメモリコピーをするのは,後ほどエラーメッセージでソースコード本体を表示するため.
     // 本体の変更はしたくない.
-    let mut lexer = Lexer::new(manager.src_file.contents.to_string());
+    let mut lexer = Lexer::new(manager.preprocessed.to_string());
 
     // 予約語の構築
     lexer.build_keywords();
@@ -19,6 +22,7 @@ pub fn tokenize(manager: &mut Manager) {
 struct Lexer {
     column: usize,                         // x軸の座標
     row: usize,                            // y軸の座標
+    file_name: &'static str,               // 行標識で与えられた見かけのファイル名
     contents: String, // メモリコピーし, SrcFile構造体の文字列を破壊しないように
     keywords: BTreeMap<String, TokenKind>, // 予約語をO(1)で取り出すためのメンバ
 }
@@ -28,6 +32,7 @@ impl Lexer {
         Self {
             row: 1,
             column: 1,
+            file_name: "",
             contents: contents,
             keywords: BTreeMap::new(),
         }
@@ -64,31 +69,46 @@ impl Lexer {
             // 数字の場合
             number if number.is_ascii_digit() => Some(self.scan_number()),
 
+            // 文字定数/文字列リテラル
+            '\'' => Some(self.scan_char_constant()),
+            '"' => Some(self.scan_string_literal()),
+
             // 記号の場合
-            '+' => Some(self.scan_symbol(TokenKind::PLUS)),
-            '-' => Some(self.scan_symbol(TokenKind::MINUS)),
-            '*' => Some(self.scan_symbol(TokenKind::ASTERISK)),
-            '/' => Some(self.scan_symbol(TokenKind::SLASH)),
+            '~' => Some(self.scan_symbol(TokenKind::TILDE)),
             ':' => Some(self.scan_symbol(TokenKind::COLON)),
+            '?' => Some(self.scan_symbol(TokenKind::QUESTION)),
             ';' => Some(self.scan_symbol(TokenKind::SEMICOLON)),
             '(' => Some(self.scan_symbol(TokenKind::LPAREN)),
             ')' => Some(self.scan_symbol(TokenKind::RPAREN)),
             '{' => Some(self.scan_symbol(TokenKind::LBRACE)),
             '}' => Some(self.scan_symbol(TokenKind::RBRACE)),
-            '=' => Some(self.scan_symbol(TokenKind::ASSIGN)),
+            '[' => Some(self.scan_symbol(TokenKind::LBRACKET)),
+            ']' => Some(self.scan_symbol(TokenKind::RBRACKET)),
             ',' => Some(self.scan_symbol(TokenKind::COMMA)),
+            '.' if self.contents.starts_with("...") => {
+                let cur_position = self.current_position();
+                self.skip_offset(3);
+                Some(Token::new(cur_position, TokenKind::ELLIPSIS))
+            }
+            '.' => Some(self.scan_symbol(TokenKind::DOT)),
+            '+' | '-' | '*' | '/' | '%' | '^' | '=' | '<' | '>' | '!' | '&' | '|' => {
+                self.scan_multi_char_symbol()
+            }
 
             // アルファベットの場合
             c if c.is_ascii_alphabetic() => Some(self.scan_word()),
             '_' => Some(self.scan_word()),
 
+            // 前処理器が出力する行標識
+            '#' if self.column == 1 => Some(self.scan_line_marker()),
+
             // 空白類文字
             ' ' | '\t' => Some(self.skip_whitespace()),
             '\n' => {
                 self.column = 1;
                 self.row += 1;
                 self.contents.drain(..1);
-                Some(Token::new((0, 0), TokenKind::NEWLINE))
+                Some(Token::new(Position::new(0, 0), TokenKind::NEWLINE))
             }
             _ => None,
         }
@@ -116,14 +136,187 @@ impl Lexer {
     }
 
     // 数字を切り取って,整数トークンを返す
+    // 0x で始まれば16進数, 0 で始まれば8進数
+    // 接尾辞 (u, l, ll) が付くか16進/8進表記であれば,型の付いた整数トークンにする
     fn scan_number(&mut self) -> Token {
-        let number_length = Self::count_length(&self.contents, |c| c.is_ascii_digit());
-        let decimal_value = self.contents[..number_length].parse::<i128>().unwrap();
+        let cur_position = self.current_position();
+        let constant = Self::take_conditional_string(&self.contents, |c| c.is_ascii_alphanumeric());
+        self.skip_offset(constant.len());
+
+        let lower = constant.to_ascii_lowercase();
+        let (radix, digits) = match (lower.strip_prefix("0x"), lower.strip_prefix('0')) {
+            (Some(hex), _) => (16, hex),
+            (None, Some(octal)) if !octal.is_empty() => (8, octal),
+            _ => (10, &lower[..]),
+        };
+        let digits_length = digits.chars().take_while(|c| c.is_digit(radix)).count();
+        let (digits, suffix) = digits.split_at(digits_length);
+
+        let value = match digits.len() {
+            // 0u などは8進数の数字を持たない
+            0 if radix == 8 => Some(0),
+            _ => i128::from_str_radix(digits, radix).ok(),
+        };
+        let suffix = match suffix {
+            "" => Some((false, false)),
+            "u" => Some((true, false)),
+            "l" | "ll" => Some((false, true)),
+            "ul" | "ull" | "lu" | "llu" => Some((true, true)),
+            _ => None,
+        };
+        let (value, (is_unsigned, is_long)) = match (value, suffix) {
+            (Some(value), Some(suffix)) if value <= u64::MAX as i128 => (value, suffix),
+            _ => {
+                let err = Error::new(
+                    ErrorKind::Parse,
+                    cur_position,
+                    ErrorMsg::InvalidIntegerConstant(constant),
+                );
+                err.found();
+                return Token::new(cur_position, TokenKind::INTEGER(0));
+            }
+        };
+
+        if radix == 10 && !is_unsigned && !is_long {
+            return Token::new(cur_position, TokenKind::INTEGER(value));
+        }
+        let ctype = Self::integer_constant_type(value, radix == 10, is_unsigned, is_long);
+        Token::new(cur_position, TokenKind::TYPEDINTEGER(value, ctype))
+    }
+    // 値の収まる最初の型.値は unsigned long に収まっている
+    // 10進数は接尾辞 u が無ければ符号無しの型にならない
+    fn integer_constant_type(
+        value: i128,
+        is_decimal: bool,
+        is_unsigned: bool,
+        is_long: bool,
+    ) -> Type {
+        let mut candidates = Vec::new();
+        if !is_long {
+            if !is_unsigned {
+                candidates.push(Type::new_integer());
+            }
+            if is_unsigned || !is_decimal {
+                candidates.push(Type::new_integer().to_unsigned());
+            }
+        }
+        if !is_unsigned {
+            candidates.push(Type::new_long());
+        }
+        candidates.push(Type::new_long().to_unsigned());
+
+        candidates
+            .into_iter()
+            .find(|ty| {
+                let bits = ty.byte_size * 8 - if ty.is_unsigned { 0 } else { 1 };
+                value < 1 << bits
+            })
+            .unwrap()
+    }
 
+    // 'c' -> 整数トークン
+    // char は符号付きなので, '\377' は -1 になる
+    fn scan_char_constant(&mut self) -> Token {
         let cur_position = self.current_position();
-        self.skip_offset(number_length);
+        let body = self.scan_quoted(b'\'');
+        if body.is_empty() {
+            let err = Error::new(ErrorKind::Parse, cur_position, ErrorMsg::EmptyCharConstant);
+            err.found();
+            return Token::new(cur_position, TokenKind::INTEGER(0));
+        }
+
+        // 複数文字の場合は最後の文字を値とする
+        let value = *body.last().unwrap() as i8;
+        Token::new(cur_position, TokenKind::INTEGER(value as i128))
+    }
 
-        Token::new(cur_position, TokenKind::INTEGER(decimal_value))
+    // "..." -> 文字列リテラルトークン
+    // 隣接する文字列リテラルの連結はパーサで行う
+    fn scan_string_literal(&mut self) -> Token {
+        let cur_position = self.current_position();
+        let bytes = self.scan_quoted(b'"');
+        Token::new(cur_position, TokenKind::STRLIT(bytes))
+    }
+
+    // 引用符で囲まれた部分を,エスケープシーケンスを解釈しながら読む
+    fn scan_quoted(&mut self, quote: u8) -> Vec<u8> {
+        let cur_position = self.current_position();
+        let input = self.contents.as_bytes();
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut length = 1;
+        loop {
+            match input.get(length) {
+                Some(c) if *c == quote => {
+                    length += 1;
+                    break;
+                }
+                // 閉じられないまま行末に達した
+                None | Some(b'\n') => {
+                    let err = Error::new(
+                        ErrorKind::Parse,
+                        cur_position,
+                        ErrorMsg::MissingTerminatingCharacter(quote as char),
+                    );
+                    err.found();
+                    break;
+                }
+                Some(b'\\') => {
+                    let (byte, escape_length) = Self::read_escape_sequence(&input[length + 1..]);
+                    bytes.push(byte);
+                    length += escape_length + 1;
+                }
+                Some(c) => {
+                    bytes.push(*c);
+                    length += 1;
+                }
+            }
+        }
+
+        self.skip_offset(length);
+        bytes
+    }
+
+    // \ の直後から読み,エスケープシーケンスの値と長さを返す
+    fn read_escape_sequence(input: &[u8]) -> (u8, usize) {
+        let head = match input.first() {
+            Some(c) => *c,
+            None => return (b'\\', 0),
+        };
+        match head {
+            // \ooo ... 最大3桁の8進数
+            b'0'..=b'7' => {
+                let length = input
+                    .iter()
+                    .take(3)
+                    .take_while(|c| (b'0'..=b'7').contains(*c))
+                    .count();
+                let value = input[..length]
+                    .iter()
+                    .fold(0u32, |acc, c| acc * 8 + (c - b'0') as u32);
+                (value as u8, length)
+            }
+            // \xhh... ... 16進数は続く限り読む
+            b'x' => {
+                let length = input[1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_hexdigit())
+                    .count();
+                let value = input[1..length + 1].iter().fold(0u32, |acc, c| {
+                    acc.wrapping_mul(16) + (*c as char).to_digit(16).unwrap()
+                });
+                (value as u8, length + 1)
+            }
+            b'n' => (b'\n', 1),
+            b't' => (b'\t', 1),
+            b'r' => (b'\r', 1),
+            b'a' => (0x07, 1),
+            b'b' => (0x08, 1),
+            b'f' => (0x0c, 1),
+            b'v' => (0x0b, 1),
+            b'e' => (0x1b, 1),
+            // \\ \' \" \? など,その文字自身を表すもの
+            c => (c, 1),
+        }
     }
 
     // 記号を切り取って,トークンを返す.
@@ -135,6 +328,55 @@ impl Lexer {
         Token::new(cur_position, kind)
     }
 
+    // 複数文字からなりうる記号を最長一致で切り取る.
+    fn scan_multi_char_symbol(&mut self) -> Option<Token> {
+        // 長いものから順に並べる
+        let symbols = [
+            ("<<=", TokenKind::LSHIFTASSIGN),
+            (">>=", TokenKind::RSHIFTASSIGN),
+            ("++", TokenKind::INCREMENT),
+            ("--", TokenKind::DECREMENT),
+            ("->", TokenKind::ARROW),
+            ("+=", TokenKind::PLUSASSIGN),
+            ("-=", TokenKind::MINUSASSIGN),
+            ("*=", TokenKind::ASTERISKASSIGN),
+            ("/=", TokenKind::SLASHASSIGN),
+            ("%=", TokenKind::PERCENTASSIGN),
+            ("&=", TokenKind::AMPERSANDASSIGN),
+            ("|=", TokenKind::PIPEASSIGN),
+            ("^=", TokenKind::CARETASSIGN),
+            ("<<", TokenKind::LSHIFT),
+            (">>", TokenKind::RSHIFT),
+            ("<=", TokenKind::LESSEQ),
+            (">=", TokenKind::GREATEREQ),
+            ("==", TokenKind::EQUAL),
+            ("!=", TokenKind::NOTEQUAL),
+            ("&&", TokenKind::LOGAND),
+            ("||", TokenKind::LOGOR),
+            ("<", TokenKind::LESS),
+            (">", TokenKind::GREATER),
+            ("=", TokenKind::ASSIGN),
+            ("!", TokenKind::BANG),
+            ("&", TokenKind::AMPERSAND),
+            ("|", TokenKind::PIPE),
+            ("+", TokenKind::PLUS),
+            ("-", TokenKind::MINUS),
+            ("*", TokenKind::ASTERISK),
+            ("/", TokenKind::SLASH),
+            ("%", TokenKind::PERCENT),
+            ("^", TokenKind::CARET),
+        ];
+
+        let cur_position = self.current_position();
+        for (symbol, kind) in symbols.iter() {
+            if self.contents.starts_with(symbol) {
+                self.skip_offset(symbol.len());
+                return Some(Token::new(cur_position, kind.clone()));
+            }
+        }
+        None
+    }
+
     // 空白類文字を読み飛ばす.
     fn skip_whitespace(&mut self) -> Token {
         let ws_length = Self::count_length(&self.contents, |c| c.is_whitespace() || c == &'\t');
@@ -142,7 +384,29 @@ impl Lexer {
         self.skip_offset(ws_length);
 
         // トークン列には追加されないのでポジションはDefaultでいい.
-        Token::new((0, 0), TokenKind::BLANK)
+        Token::new(Position::new(0, 0), TokenKind::BLANK)
+    }
+
+    // # <line> "<file>" を読み,次の行の位置を <file> の <line> 行目にする
+    fn scan_line_marker(&mut self) -> Token {
+        let line_length = Self::count_length(&self.contents, |c| c != &'\n');
+        let marker: String = self.contents.drain(..line_length).collect();
+
+        let line_number = marker[1..]
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse::<usize>().ok());
+        if let Some(n) = line_number {
+            // 直後の改行で1つ進む
+            self.row = n.saturating_sub(1);
+        }
+        if let (Some(begin), Some(end)) = (marker.find('"'), marker.rfind('"')) {
+            if begin < end {
+                self.file_name = intern_file_name(&marker[begin + 1..end]);
+            }
+        }
+
+        Token::new(Position::new(0, 0), TokenKind::BLANK)
     }
 
     fn build_keywords(&mut self) {
@@ -150,12 +414,55 @@ impl Lexer {
             .insert("return".to_string(), TokenKind::RETURN);
         self.keywords.insert("int".to_string(), TokenKind::INT);
         self.keywords.insert("void".to_string(), TokenKind::VOID);
+        self.keywords.insert("char".to_string(), TokenKind::CHAR);
+        self.keywords.insert("short".to_string(), TokenKind::SHORT);
+        self.keywords.insert("long".to_string(), TokenKind::LONG);
+        self.keywords
+            .insert("signed".to_string(), TokenKind::SIGNED);
+        self.keywords
+            .insert("unsigned".to_string(), TokenKind::UNSIGNED);
         self.keywords.insert("goto".to_string(), TokenKind::GOTO);
+        self.keywords.insert("break".to_string(), TokenKind::BREAK);
+        self.keywords
+            .insert("continue".to_string(), TokenKind::CONTINUE);
         self.keywords.insert("if".to_string(), TokenKind::IF);
         self.keywords.insert("else".to_string(), TokenKind::ELSE);
         self.keywords.insert("for".to_string(), TokenKind::FOR);
         self.keywords.insert("do".to_string(), TokenKind::DO);
         self.keywords.insert("while".to_string(), TokenKind::WHILE);
+        self.keywords
+            .insert("switch".to_string(), TokenKind::SWITCH);
+        self.keywords.insert("case".to_string(), TokenKind::CASE);
+        self.keywords
+            .insert("default".to_string(), TokenKind::DEFAULT);
+        self.keywords
+            .insert("sizeof".to_string(), TokenKind::SIZEOF);
+        self.keywords
+            .insert("struct".to_string(), TokenKind::STRUCT);
+        self.keywords.insert("union".to_string(), TokenKind::UNION);
+        self.keywords
+            .insert("extern".to_string(), TokenKind::EXTERN);
+        self.keywords
+            .insert("static".to_string(), TokenKind::STATIC);
+        self.keywords.insert("auto".to_string(), TokenKind::AUTO);
+        self.keywords
+            .insert("register".to_string(), TokenKind::REGISTER);
+        self.keywords
+            .insert("typedef".to_string(), TokenKind::TYPEDEF);
+        self.keywords.insert("enum".to_string(), TokenKind::ENUM);
+        self.keywords.insert("const".to_string(), TokenKind::CONST);
+        self.keywords
+            .insert("volatile".to_string(), TokenKind::VOLATILE);
+        self.keywords
+            .insert("restrict".to_string(), TokenKind::RESTRICT);
+        self.keywords
+            .insert("__builtin_va_list".to_string(), TokenKind::VALIST);
+        self.keywords
+            .insert("__builtin_va_start".to_string(), TokenKind::VASTART);
+        self.keywords
+            .insert("__builtin_va_arg".to_string(), TokenKind::VAARG);
+        self.keywords
+            .insert("__builtin_va_end".to_string(), TokenKind::VAEND);
     }
 
     fn skip_offset(&mut self, len: usize) {
@@ -173,7 +480,7 @@ impl Lexer {
 
     // 現在のオフセットを取得
     fn current_position(&mut self) -> Position {
-        (self.row, self.column)
+        Position::new(self.row, self.column).in_file(self.file_name)
     }
 }
 
@@ -185,27 +492,69 @@ mod lexer_tests {
     #[test]
     fn test_build_tokens() {
         let expected_tokens = vec![
-            Token::new((1, 1), TokenKind::INTEGER(12345)),
-            Token::new((1, 7), TokenKind::PLUS),
-            Token::new((1, 9), TokenKind::INTEGER(678910)),
-            Token::new((1, 15), TokenKind::EOF),
+            Token::new(Position::new(1, 1), TokenKind::INTEGER(12345)),
+            Token::new(Position::new(1, 7), TokenKind::PLUS),
+            Token::new(Position::new(1, 9), TokenKind::INTEGER(678910)),
+            Token::new(Position::new(1, 15), TokenKind::EOF),
         ];
         integration_test_lexing("12345 + 678910", expected_tokens);
     }
 
+    #[test]
+    fn test_lex_integer_constants() {
+        let int = Type::new_integer;
+        let long = Type::new_long;
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::TYPEDINTEGER(255, int())),
+            Token::new(Position::new(1, 6), TokenKind::TYPEDINTEGER(8, int())),
+            Token::new(
+                Position::new(1, 10),
+                TokenKind::TYPEDINTEGER(1, int().to_unsigned()),
+            ),
+            Token::new(Position::new(1, 13), TokenKind::TYPEDINTEGER(1, long())),
+            Token::new(
+                Position::new(1, 16),
+                TokenKind::TYPEDINTEGER(1, long().to_unsigned()),
+            ),
+            Token::new(
+                Position::new(1, 21),
+                TokenKind::TYPEDINTEGER(0, int().to_unsigned()),
+            ),
+            Token::new(
+                Position::new(1, 24),
+                TokenKind::TYPEDINTEGER(4294967295, int().to_unsigned()),
+            ),
+            Token::new(
+                Position::new(1, 35),
+                TokenKind::TYPEDINTEGER(4294967295, long()),
+            ),
+            Token::new(Position::new(1, 47), TokenKind::INTEGER(0)),
+            Token::new(Position::new(1, 48), TokenKind::EOF),
+        ];
+
+        // 16進/8進表記の定数は,int に収まらなければ符号無しの型になる
+        integration_test_lexing(
+            "0xff 010 1u 1L 1ULL 0u 0xffffffff 4294967295l 0",
+            expected_tokens,
+        );
+    }
+
     #[test]
     fn test_lex_function_definition() {
         let expected_tokens = vec![
-            Token::new((1, 1), TokenKind::INT),
-            Token::new((1, 5), TokenKind::IDENTIFIER("main".to_string())),
-            Token::new((1, 9), TokenKind::LPAREN),
-            Token::new((1, 10), TokenKind::RPAREN),
-            Token::new((1, 11), TokenKind::LBRACE),
-            Token::new((1, 13), TokenKind::RETURN),
-            Token::new((1, 20), TokenKind::INTEGER(30)),
-            Token::new((1, 22), TokenKind::SEMICOLON),
-            Token::new((1, 24), TokenKind::RBRACE),
-            Token::new((1, 25), TokenKind::EOF),
+            Token::new(Position::new(1, 1), TokenKind::INT),
+            Token::new(
+                Position::new(1, 5),
+                TokenKind::IDENTIFIER("main".to_string()),
+            ),
+            Token::new(Position::new(1, 9), TokenKind::LPAREN),
+            Token::new(Position::new(1, 10), TokenKind::RPAREN),
+            Token::new(Position::new(1, 11), TokenKind::LBRACE),
+            Token::new(Position::new(1, 13), TokenKind::RETURN),
+            Token::new(Position::new(1, 20), TokenKind::INTEGER(30)),
+            Token::new(Position::new(1, 22), TokenKind::SEMICOLON),
+            Token::new(Position::new(1, 24), TokenKind::RBRACE),
+            Token::new(Position::new(1, 25), TokenKind::EOF),
         ];
 
         integration_test_lexing("int main(){ return 30; }", expected_tokens);
@@ -214,14 +563,171 @@ mod lexer_tests {
     #[test]
     fn test_addition_expression() {
         let expected_tokens = vec![
-            Token::new((1, 1), TokenKind::INTEGER(30)),
-            Token::new((1, 4), TokenKind::PLUS),
-            Token::new((1, 6), TokenKind::INTEGER(40)),
-            Token::new((1, 8), TokenKind::EOF),
+            Token::new(Position::new(1, 1), TokenKind::INTEGER(30)),
+            Token::new(Position::new(1, 4), TokenKind::PLUS),
+            Token::new(Position::new(1, 6), TokenKind::INTEGER(40)),
+            Token::new(Position::new(1, 8), TokenKind::EOF),
         ];
 
         integration_test_lexing("30 + 40", expected_tokens);
     }
+    #[test]
+    fn test_lex_relational_and_logical_operators() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::LESSEQ),
+            Token::new(Position::new(1, 3), TokenKind::LESS),
+            Token::new(Position::new(1, 5), TokenKind::EQUAL),
+            Token::new(Position::new(1, 7), TokenKind::ASSIGN),
+            Token::new(Position::new(1, 8), TokenKind::NOTEQUAL),
+            Token::new(Position::new(1, 11), TokenKind::BANG),
+            Token::new(Position::new(1, 12), TokenKind::LOGAND),
+            Token::new(Position::new(1, 14), TokenKind::LOGOR),
+            Token::new(Position::new(1, 16), TokenKind::GREATEREQ),
+            Token::new(Position::new(1, 18), TokenKind::GREATER),
+            Token::new(Position::new(1, 19), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("<=< ===!= !&&||>=>", expected_tokens);
+    }
+    #[test]
+    fn test_lex_bitwise_and_shift_operators() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::LSHIFT),
+            Token::new(Position::new(1, 3), TokenKind::LESS),
+            Token::new(Position::new(1, 5), TokenKind::RSHIFT),
+            Token::new(Position::new(1, 7), TokenKind::AMPERSAND),
+            Token::new(Position::new(1, 9), TokenKind::LOGAND),
+            Token::new(Position::new(1, 12), TokenKind::PIPE),
+            Token::new(Position::new(1, 13), TokenKind::CARET),
+            Token::new(Position::new(1, 14), TokenKind::TILDE),
+            Token::new(Position::new(1, 15), TokenKind::PERCENT),
+            Token::new(Position::new(1, 16), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("<<< >>& && |^~%", expected_tokens);
+    }
+    #[test]
+    fn test_lex_assignment_operators() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::LSHIFTASSIGN),
+            Token::new(Position::new(1, 4), TokenKind::INCREMENT),
+            Token::new(Position::new(1, 6), TokenKind::PLUS),
+            Token::new(Position::new(1, 8), TokenKind::PLUSASSIGN),
+            Token::new(Position::new(1, 10), TokenKind::DECREMENT),
+            Token::new(Position::new(1, 12), TokenKind::MINUSASSIGN),
+            Token::new(Position::new(1, 15), TokenKind::RSHIFTASSIGN),
+            Token::new(Position::new(1, 18), TokenKind::PERCENTASSIGN),
+            Token::new(Position::new(1, 20), TokenKind::CARETASSIGN),
+            Token::new(Position::new(1, 22), TokenKind::PIPEASSIGN),
+            Token::new(Position::new(1, 24), TokenKind::AMPERSANDASSIGN),
+            Token::new(Position::new(1, 26), TokenKind::ASTERISKASSIGN),
+            Token::new(Position::new(1, 28), TokenKind::SLASHASSIGN),
+            Token::new(Position::new(1, 30), TokenKind::COMMA),
+            Token::new(Position::new(1, 31), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("<<=+++ +=---= >>=%=^=|=&=*=/=,", expected_tokens);
+    }
+    #[test]
+    fn test_lex_member_access() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::STRUCT),
+            Token::new(Position::new(1, 8), TokenKind::IDENTIFIER("s".to_string())),
+            Token::new(Position::new(1, 9), TokenKind::DOT),
+            Token::new(Position::new(1, 10), TokenKind::IDENTIFIER("a".to_string())),
+            Token::new(Position::new(1, 11), TokenKind::ARROW),
+            Token::new(Position::new(1, 13), TokenKind::IDENTIFIER("b".to_string())),
+            Token::new(Position::new(1, 14), TokenKind::DECREMENT),
+            Token::new(Position::new(1, 17), TokenKind::UNION),
+            Token::new(Position::new(1, 22), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("struct s.a->b-- union", expected_tokens);
+    }
+
+    #[test]
+    fn test_lex_with_line_marker() {
+        // 行標識の次の行は,標識で指定されたファイルの行番号になる
+        let at = |row, column| Position::new(row, column).in_file("foo.c");
+        let expected_tokens = vec![
+            Token::new(at(10, 3), TokenKind::INTEGER(1)),
+            Token::new(at(11, 1), TokenKind::SEMICOLON),
+            Token::new(
+                Position::new(3, 1).in_file("include/bar.h"),
+                TokenKind::COLON,
+            ),
+            Token::new(Position::new(3, 2).in_file("include/bar.h"), TokenKind::EOF),
+        ];
+
+        integration_test_lexing(
+            "# 10 \"foo.c\"\n  1\n;\n# 3 \"include/bar.h\"\n:",
+            expected_tokens,
+        );
+        assert_eq!("foo.c:10:3", at(10, 3).to_string());
+        assert_eq!("10:3", Position::new(10, 3).to_string());
+    }
+
+    #[test]
+    fn test_lex_char_constant() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::INTEGER(97)),
+            Token::new(Position::new(1, 5), TokenKind::INTEGER(10)),
+            Token::new(Position::new(1, 10), TokenKind::INTEGER(39)),
+            Token::new(Position::new(1, 15), TokenKind::INTEGER(0)),
+            Token::new(Position::new(1, 20), TokenKind::INTEGER(65)),
+            Token::new(Position::new(1, 27), TokenKind::INTEGER(-1)),
+            Token::new(Position::new(1, 33), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("'a' '\\n' '\\'' '\\0' '\\x41' '\\377'", expected_tokens);
+    }
+
+    #[test]
+    fn test_lex_ellipsis() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::INT),
+            Token::new(Position::new(1, 5), TokenKind::IDENTIFIER("n".to_string())),
+            Token::new(Position::new(1, 6), TokenKind::COMMA),
+            Token::new(Position::new(1, 8), TokenKind::ELLIPSIS),
+            Token::new(Position::new(1, 11), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("int n, ...", expected_tokens);
+    }
+
+    #[test]
+    fn test_lex_declaration_specifiers() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::STATIC),
+            Token::new(Position::new(1, 8), TokenKind::CONST),
+            Token::new(Position::new(1, 14), TokenKind::ENUM),
+            Token::new(Position::new(1, 19), TokenKind::IDENTIFIER("e".to_string())),
+            Token::new(Position::new(1, 21), TokenKind::TYPEDEF),
+            Token::new(Position::new(1, 28), TokenKind::EOF),
+        ];
+
+        integration_test_lexing("static const enum e typedef", expected_tokens);
+    }
+
+    #[test]
+    fn test_lex_string_literal() {
+        let expected_tokens = vec![
+            Token::new(Position::new(1, 1), TokenKind::STRLIT(b"hi\n".to_vec())),
+            Token::new(Position::new(1, 8), TokenKind::STRLIT(b"a\"b\\".to_vec())),
+            Token::new(
+                Position::new(1, 17),
+                TokenKind::STRLIT(vec![0x01, b'2', 0x7f, 0x1b]),
+            ),
+            Token::new(Position::new(1, 31), TokenKind::STRLIT(Vec::new())),
+            Token::new(Position::new(1, 33), TokenKind::EOF),
+        ];
+
+        integration_test_lexing(
+            "\"hi\\n\" \"a\\\"b\\\\\" \"\\0012\\x7f\\e\" \"\"",
+            expected_tokens,
+        );
+    }
+
     #[test]
     fn test_count_length() {
         // 数字の範囲
@@ -246,11 +752,11 @@ mod lexer_tests {
         let actual = lexer.scan_number();
 
         assert_eq!(TokenKind::INTEGER(12345), actual.kind);
-        assert_eq!((1, 1), actual.position);
+        assert_eq!(Position::new(1, 1), actual.position);
 
         // scan_number() 内部でオフセットがちゃんと進んでいるか.
         let cur_position = lexer.current_position();
-        assert_eq!((1, 6), cur_position);
+        assert_eq!(Position::new(1, 6), cur_position);
 
         // 文字列が切り取られているか.
         let cur_looking_string = lexer.contents;
@@ -259,7 +765,7 @@ mod lexer_tests {
 
     #[test]
     fn test_skip_whitespace() {
-        let expected_eof = Token::new((1, 6), TokenKind::EOF);
+        let expected_eof = Token::new(Position::new(1, 6), TokenKind::EOF);
 
         let mut lexer = create_lexer("     ");
         let whitespace = lexer.skip_whitespace();
@@ -1,13 +1,15 @@
 use crate::compiler::file;
 use crate::compiler::frontend::node;
 use crate::compiler::frontend::token;
+use crate::compiler::frontend::types;
 use crate::compiler::frontend::variable;
 use crate::compiler::ir::three_address_code::function::IRFunction;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct Manager {
     pub src_file: file::SrcFile,
+    pub preprocessed: String,
     pub tokens: Vec<token::Token>,
 
     // TODO: モジュールを受け取るように変更
@@ -17,30 +19,123 @@ pub struct Manager {
     pub cur_token: usize,
     pub next_token: usize,
     pub params: BTreeMap<String, variable::Variable>,
+    // 関数内の全ての局所変数.宣言ごとに一意な名前で登録する
     pub var_map: BTreeMap<String, variable::Variable>,
+    // ブロックスコープの連鎖.ソース上の名前 -> 名前の指すもの
+    pub scopes: Vec<BTreeMap<String, Binding>>,
+    // ファイルスコープの typedef 名と列挙定数
+    pub file_scope: BTreeMap<String, Binding>,
+    // 局所変数として解決できなかった(グローバルを指す)名前
+    pub outer_names: BTreeSet<String>,
+    // int (*f(int i))(int) のように,括弧でくくった宣言子の中で名前の直後に読んだ引数並び
+    pub declarator_params: Option<ParamList>,
+
+    // 宣言/定義された関数の型
+    pub signatures: BTreeMap<String, node::Signature>,
+    // static で宣言された(内部結合を持つ)関数
+    pub static_functions: BTreeSet<String>,
+
+    // グローバル変数
+    pub global_map: BTreeMap<String, variable::Variable>,
+    // グローバル変数の初期化式.意味解析で定数に畳み込む
+    pub global_initializers: BTreeMap<String, node::Node>,
+    // 文字列リテラルの中身.番号が .rodata 上のラベルになる
+    pub string_literals: Vec<Vec<u8>>,
+
+    // 構造体/共用体/列挙型のタグの名前空間.scopes と同じ単位で積み下ろす
+    pub tag_scopes: Vec<BTreeMap<String, Tag>>,
+    // ファイルスコープのタグ
+    pub file_tags: BTreeMap<String, Tag>,
+    // 構造体/共用体の定義.Recordのidで引く
+    pub records: Vec<types::Type>,
+
+    // 意味解析中の関数の返り値型
+    pub return_type: types::Type,
+    // 意味解析中の関数が可変長引数を取るか
+    pub is_variadic: bool,
+    // 意味解析中のswitch文.入れ子になるのでスタックで持つ
+    pub switch_scopes: Vec<SwitchScope>,
+    // 意味解析中のループの入れ子の深さ.continue が使えるかどうかに使う
+    pub loop_depth: usize,
+    // 意味解析でエラーが見つかったか.見つかればコード生成に進まない
+    pub error_occurred: bool,
 
     // 3番地コード列
     // TODO: モジュールを受け取るように変更
     pub ir_funcs: Vec<IRFunction>,
     pub cur_bb: usize,
+    // 翻訳中のswitch文の,case値から飛び先ラベルへの対応と default のラベル
+    pub switch_labels: Vec<(BTreeMap<i128, String>, String)>,
+    // 翻訳中のループ/switch文の (break の飛び先, continue の飛び先)
+    // switch文は continue の飛び先を持たないので,外側のループを探す
+    pub jump_labels: Vec<(String, Option<String>)>,
+    // 翻訳中の可変長引数関数の,引数レジスタの退避領域のオフセットと名前付き引数の数
+    pub va_save_area: Option<(usize, usize)>,
 
     // レジスタ番号
     pub virt: usize,
     pub label: usize,
 }
 
+// 通常の識別子の名前空間で,名前が指すもの
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    VARIABLE(String), // var_map/global_map 上の名前
+    TYPEDEF(types::Type),
+    ENUMERATOR(i128),
+}
+
+// 引数の名前と型の並び(省略されていれば None),および ... があるか
+pub type ParamList = (Option<Vec<(String, types::Type)>>, bool);
+
+// タグの名前空間で,タグが指すもの
+// 列挙型は int として扱うので,型を持たない
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    RECORD(types::Type),
+    ENUM,
+}
+
+// switch文の制御式の型と,それまでに現れた case/default
+pub struct SwitchScope {
+    pub cond_type: types::Type,
+    pub cases: Vec<i128>,
+    pub has_default: bool,
+}
+
 impl Manager {
     pub fn new(src: file::SrcFile) -> Self {
         Self {
             src_file: src,
+            preprocessed: String::new(),
             tokens: Vec::new(),
             functions: Vec::new(),
             cur_token: 0,
             next_token: 1,
             params: BTreeMap::new(),
             var_map: BTreeMap::new(),
+            scopes: Vec::new(),
+            file_scope: BTreeMap::new(),
+            outer_names: BTreeSet::new(),
+            declarator_params: None,
+            signatures: BTreeMap::new(),
+            static_functions: BTreeSet::new(),
+            global_map: BTreeMap::new(),
+            global_initializers: BTreeMap::new(),
+            string_literals: Vec::new(),
+            tag_scopes: Vec::new(),
+            file_tags: BTreeMap::new(),
+            records: Vec::new(),
+            return_type: types::Type::new_unknown(),
+            is_variadic: false,
+            switch_scopes: Vec::new(),
+            loop_depth: 0,
+            error_occurred: false,
             ir_funcs: Vec::new(),
             cur_bb: 0,
+            switch_labels: Vec::new(),
+            jump_labels: Vec::new(),
+            va_save_area: None,
             virt: 0,
             label: 0,
         }
@@ -5,6 +5,7 @@ pub mod lex;
 pub mod manager;
 pub mod node;
 pub mod parse;
+pub mod preprocess;
 pub mod sema;
 pub mod token;
 pub mod types;
@@ -21,6 +22,22 @@ pub fn frontend_process(
 ) -> manager::Manager {
     let mut manager = manager::Manager::new(source_file);
 
+    // 前処理
+    let include_dirs = match matches.values_of("include-dir") {
+        Some(dirs) => dirs.map(|d| d.to_string()).collect(),
+        None => Vec::new(),
+    };
+    let defines = match matches.values_of("define-macro") {
+        Some(defs) => defs.map(|d| d.to_string()).collect(),
+        None => Vec::new(),
+    };
+    preprocess::preprocess(&mut manager, include_dirs, defines);
+
+    if matches.is_present("d-preprocessed") {
+        util::colored_prefix_to_stderr("dump preprocessed source");
+        eprintln!("{}", manager.preprocessed);
+    }
+
     // 字句解析
     lex::tokenize(&mut manager);
 
@@ -34,6 +51,9 @@ pub fn frontend_process(
 
     // 意味解析
     manager.semantics();
+    if manager.error_occurred {
+        std::process::exit(1);
+    }
 
     // 駆動レコード部
     // 今はx64だけを想定
@@ -12,6 +12,11 @@ pub struct Function {
     pub return_type: Type,
     pub local_map: BTreeMap<String, Variable>,
     pub params: BTreeMap<String, Variable>,
+    // 引数の宣言順.引数レジスタとの対応に使う
+    pub param_names: Vec<String>,
+    pub is_variadic: bool,
+    // static な関数は内部結合を持つ
+    pub is_static: bool,
     pub stmts: Vec<Node>,
 
     pub frame_size: usize,
@@ -25,21 +30,28 @@ impl Function {
             stmts: Vec::new(),
             frame_size: 0,
             params: BTreeMap::new(),
+            param_names: Vec::new(),
+            is_variadic: false,
+            is_static: false,
             local_map: BTreeMap::new(),
             return_type: dec_type,
         }
     }
     pub fn dump_ast(&self) {
         let mut params_string = String::new();
-        for (i, (name, param)) in self.params.iter().enumerate() {
+        for (i, name) in self.param_names.iter().enumerate() {
+            let param = &self.params[name];
             params_string += &(format!("{} {}", param.ctype.to_string(), name).as_str());
-            if i != self.params.len() - 1 {
+            if i != self.param_names.len() - 1 {
                 params_string += ", ";
             }
         }
-        if self.params.is_empty() {
+        if self.param_names.is_empty() {
             params_string = "void".to_string();
         }
+        if self.is_variadic {
+            params_string += ", ...";
+        }
         eprintln!("function {}({}) {{ ", self.name, params_string);
         for st in self.stmts.iter() {
             eprintln!("  {}", st.to_string());
@@ -48,6 +60,30 @@ impl Function {
     }
 }
 
+// 関数の型.プロトタイプと定義で一致している必要がある
+// 引数並びを省略した宣言(int f();)では引数の型を検査しない
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Signature {
+    pub return_type: Type,
+    pub param_types: Option<Vec<Type>>,
+    pub is_variadic: bool, // 名前付き引数の後ろに ... がある
+}
+
+impl Signature {
+    // 引数並びを省略した方は,もう一方の引数並びに合わせられる
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        if self.return_type != other.return_type {
+            return false;
+        }
+        match (&self.param_types, &other.param_types) {
+            (Some(params), Some(other_params)) => {
+                params == other_params && self.is_variadic == other.is_variadic
+            }
+            _ => true,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Node {
     pub position: Position,
@@ -64,7 +100,7 @@ impl Node {
         }
     }
     pub fn new_nop() -> Self {
-        Self::new((0, 0), NodeKind::NOP)
+        Self::new(Position::new(0, 0), NodeKind::NOP)
     }
     pub fn new_labeled(pos: Position, label_name: String, stmt: Node) -> Self {
         Self::new(pos, NodeKind::LABELEDSTMT(label_name, Box::new(stmt)))
@@ -101,14 +137,43 @@ impl Node {
             ),
         )
     }
+    pub fn new_switch(pos: Position, expr: Node, stmt: Node) -> Self {
+        Self::new(
+            pos,
+            NodeKind::SWITCHSTMT(Box::new(expr), Box::new(stmt), Vec::new(), false),
+        )
+    }
+    pub fn new_case(pos: Position, expr: Node, stmt: Node) -> Self {
+        Self::new(pos, NodeKind::CASESTMT(Box::new(expr), Box::new(stmt)))
+    }
+    pub fn new_default(pos: Position, stmt: Node) -> Self {
+        Self::new(pos, NodeKind::DEFAULTSTMT(Box::new(stmt)))
+    }
     pub fn new_goto(pos: Position, label_name: String) -> Self {
         Self::new(pos, NodeKind::GOTOSTMT(label_name))
     }
+    pub fn new_break(pos: Position) -> Self {
+        Self::new(pos, NodeKind::BREAKSTMT)
+    }
+    pub fn new_continue(pos: Position) -> Self {
+        Self::new(pos, NodeKind::CONTINUESTMT)
+    }
     pub fn new_exprstmt(pos: Position, expr: Node) -> Self {
         Self::new(pos, NodeKind::EXPRSTMT(Box::new(expr)))
     }
-    pub fn new_declaration(pos: Position, name: String, ty: Type) -> Self {
-        Self::new(pos, NodeKind::DECLARATION(name, ty))
+    pub fn new_declaration(
+        pos: Position,
+        name: String,
+        ty: Type,
+        initializer: Option<Node>,
+    ) -> Self {
+        Self::new(
+            pos,
+            NodeKind::DECLARATION(name, ty, initializer.map(Box::new)),
+        )
+    }
+    pub fn new_initlist(pos: Position, elements: Vec<(Vec<Designator>, Node)>) -> Self {
+        Self::new(pos, NodeKind::INITLIST(elements))
     }
     pub fn new_assign(pos: Position, lvalue: Node, rvalue: Node) -> Self {
         Self::new(pos, NodeKind::ASSIGN(Box::new(lvalue), Box::new(rvalue)))
@@ -125,18 +190,119 @@ impl Node {
             TokenKind::MINUS => NodeKind::SUB(Box::new(left), Box::new(right)),
             TokenKind::ASTERISK => NodeKind::MUL(Box::new(left), Box::new(right)),
             TokenKind::SLASH => NodeKind::DIV(Box::new(left), Box::new(right)),
+            TokenKind::PERCENT => NodeKind::MOD(Box::new(left), Box::new(right)),
+            TokenKind::LSHIFT => NodeKind::LSHIFT(Box::new(left), Box::new(right)),
+            TokenKind::RSHIFT => NodeKind::RSHIFT(Box::new(left), Box::new(right)),
+            TokenKind::AMPERSAND => NodeKind::BITAND(Box::new(left), Box::new(right)),
+            TokenKind::PIPE => NodeKind::BITOR(Box::new(left), Box::new(right)),
+            TokenKind::CARET => NodeKind::BITXOR(Box::new(left), Box::new(right)),
+            TokenKind::LESS => NodeKind::LESSTHAN(Box::new(left), Box::new(right)),
+            TokenKind::LESSEQ => NodeKind::LESSTHANEQUAL(Box::new(left), Box::new(right)),
+            TokenKind::GREATER => NodeKind::GREATERTHAN(Box::new(left), Box::new(right)),
+            TokenKind::GREATEREQ => NodeKind::GREATERTHANEQUAL(Box::new(left), Box::new(right)),
+            TokenKind::EQUAL => NodeKind::EQUAL(Box::new(left), Box::new(right)),
+            TokenKind::NOTEQUAL => NodeKind::NOTEQUAL(Box::new(left), Box::new(right)),
+            TokenKind::LOGAND => NodeKind::LOGICALAND(Box::new(left), Box::new(right)),
+            TokenKind::LOGOR => NodeKind::LOGICALOR(Box::new(left), Box::new(right)),
+            _ => panic!("not found such an operator"),
+        };
+        Self::new(tok.position, node_kind)
+    }
+    // 複合代入は二項演算を包んで表す.
+    // 左辺は二項演算の左オペランドとして一度だけ評価される.
+    pub fn new_compound_assign(tok: &Token, lvalue: Node, rvalue: Node) -> Self {
+        let operator_kind = match tok.kind {
+            TokenKind::PLUSASSIGN | TokenKind::INCREMENT => TokenKind::PLUS,
+            TokenKind::MINUSASSIGN | TokenKind::DECREMENT => TokenKind::MINUS,
+            TokenKind::ASTERISKASSIGN => TokenKind::ASTERISK,
+            TokenKind::SLASHASSIGN => TokenKind::SLASH,
+            TokenKind::PERCENTASSIGN => TokenKind::PERCENT,
+            TokenKind::LSHIFTASSIGN => TokenKind::LSHIFT,
+            TokenKind::RSHIFTASSIGN => TokenKind::RSHIFT,
+            TokenKind::AMPERSANDASSIGN => TokenKind::AMPERSAND,
+            TokenKind::PIPEASSIGN => TokenKind::PIPE,
+            TokenKind::CARETASSIGN => TokenKind::CARET,
+            _ => panic!("not found such an assignment operator"),
+        };
+        let operator = Token::new(tok.position, operator_kind);
+        let binary = Self::new_binary_node(&operator, lvalue, rvalue);
+        Self::new(tok.position, NodeKind::COMPOUNDASSIGN(Box::new(binary)))
+    }
+    pub fn new_postfix_node(tok: &Token, inner: Node) -> Self {
+        let node_kind = match tok.kind {
+            TokenKind::INCREMENT => NodeKind::POSTINCREMENT(Box::new(inner)),
+            TokenKind::DECREMENT => NodeKind::POSTDECREMENT(Box::new(inner)),
             _ => panic!("not found such an operator"),
         };
         Self::new(tok.position, node_kind)
     }
+    pub fn new_conditional(pos: Position, cond: Node, then: Node, alter: Node) -> Self {
+        Self::new(
+            pos,
+            NodeKind::CONDITIONAL(Box::new(cond), Box::new(then), Box::new(alter)),
+        )
+    }
+    pub fn new_comma(pos: Position, left: Node, right: Node) -> Self {
+        Self::new(pos, NodeKind::COMMA(Box::new(left), Box::new(right)))
+    }
     pub fn new_unary_node(tok: &Token, inner: Node) -> Self {
         let node_kind = match tok.kind {
             TokenKind::MINUS => NodeKind::NEGATIVE(Box::new(inner)),
+            TokenKind::BANG => NodeKind::LOGICALNOT(Box::new(inner)),
+            TokenKind::TILDE => NodeKind::BITNOT(Box::new(inner)),
+            TokenKind::AMPERSAND => NodeKind::ADDRESSOF(Box::new(inner)),
+            TokenKind::ASTERISK => NodeKind::DEREFERENCE(Box::new(inner)),
             _ => panic!("not found such an operator"),
         };
         Self::new(tok.position, node_kind)
     }
+    // a[i] -> *(a + i)
+    pub fn new_subscript(pos: Position, array: Node, index: Node) -> Self {
+        let address = Self::new(pos, NodeKind::ADD(Box::new(array), Box::new(index)));
+        Self::new(pos, NodeKind::DEREFERENCE(Box::new(address)))
+    }
+    // s.a
+    pub fn new_member(pos: Position, record: Node, member: String) -> Self {
+        Self::new(pos, NodeKind::MEMBER(Box::new(record), member))
+    }
+    // p->a -> (*p).a
+    pub fn new_arrow(pos: Position, pointer: Node, member: String) -> Self {
+        let record = Self::new(pos, NodeKind::DEREFERENCE(Box::new(pointer)));
+        Self::new_member(pos, record, member)
+    }
+    pub fn new_sizeof(pos: Position, inner: Node) -> Self {
+        Self::new(pos, NodeKind::SIZEOF(Box::new(inner)))
+    }
 
+    // 複合代入で包まれた二項演算から,演算子の記号と両辺を取り出す
+    pub fn split_binary(&self) -> (&str, &Node, &Node) {
+        match &self.kind {
+            NodeKind::ADD(left, right) => ("+", left, right),
+            NodeKind::SUB(left, right) => ("-", left, right),
+            NodeKind::MUL(left, right) => ("*", left, right),
+            NodeKind::DIV(left, right) => ("/", left, right),
+            NodeKind::MOD(left, right) => ("%", left, right),
+            NodeKind::LSHIFT(left, right) => ("<<", left, right),
+            NodeKind::RSHIFT(left, right) => (">>", left, right),
+            NodeKind::BITAND(left, right) => ("&", left, right),
+            NodeKind::BITOR(left, right) => ("|", left, right),
+            NodeKind::BITXOR(left, right) => ("^", left, right),
+            _ => panic!("not binary operation"),
+        }
+    }
+    // 代入やアドレス演算子の対象になれるか
+    pub fn is_lvalue(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::IDENTIFIER(_)
+                | NodeKind::DEREFERENCE(_)
+                | NodeKind::MEMBER(_, _)
+                | NodeKind::STRLIT(_)
+        )
+    }
+    pub fn is_null_pointer_constant(&self) -> bool {
+        self.kind == NodeKind::INTEGER(0)
+    }
     pub fn ident_name(&self) -> String {
         match &self.kind {
             NodeKind::IDENTIFIER(name) => name.to_string(),
@@ -149,8 +315,11 @@ impl Node {
     pub fn to_string(&self) -> String {
         match &self.kind {
             // statement
+            NodeKind::RETURNSTMT(expr) if expr.kind == NodeKind::NOP => "return;".to_string(),
             NodeKind::RETURNSTMT(expr) => format!("return {};", expr.to_string()),
             NodeKind::GOTOSTMT(label) => format!("goto {};", label),
+            NodeKind::BREAKSTMT => "break;".to_string(),
+            NodeKind::CONTINUESTMT => "continue;".to_string(),
             NodeKind::LABELEDSTMT(label, st) => format!("{}: {}", label, st.to_string()),
             NodeKind::EXPRSTMT(expr) => format!("{};", expr.to_string()),
             NodeKind::FORSTMT(clause, expr_2, expr_3, stmt) => format!(
@@ -184,7 +353,17 @@ impl Node {
                 stmt.to_string(),
                 alt.to_string()
             ),
-            NodeKind::DECLARATION(name, ty) => format!("{} {};", ty.to_string(), name),
+            NodeKind::SWITCHSTMT(expr, stmt, _cases, _has_default) => {
+                format!("switch ( {} ) {}", expr.to_string(), stmt.to_string())
+            }
+            NodeKind::CASESTMT(expr, stmt) => {
+                format!("case {}: {}", expr.to_string(), stmt.to_string())
+            }
+            NodeKind::DEFAULTSTMT(stmt) => format!("default: {}", stmt.to_string()),
+            NodeKind::DECLARATION(name, ty, None) => format!("{} {};", ty.to_string(), name),
+            NodeKind::DECLARATION(name, ty, Some(initializer)) => {
+                format!("{} {} = {};", ty.to_string(), name, initializer.to_string())
+            }
 
             // expression
             NodeKind::CALL(ident, params) => {
@@ -198,13 +377,103 @@ impl Node {
                 format!("{}({})", ident.to_string(), params_string)
             }
             NodeKind::ASSIGN(lv, rv) => format!("{} = {}", lv.to_string(), rv.to_string()),
+            NodeKind::COMPOUNDASSIGN(binary) => {
+                let (operator, lv, rv) = binary.split_binary();
+                format!("{} {}= {}", lv.to_string(), operator, rv.to_string())
+            }
+            NodeKind::CONDITIONAL(cond, then, alter) => format!(
+                "{} ? {} : {}",
+                cond.to_string(),
+                then.to_string(),
+                alter.to_string()
+            ),
+            NodeKind::COMMA(left, right) => {
+                format!("{}, {}", left.to_string(), right.to_string())
+            }
             NodeKind::ADD(left, right) => format!("{} + {}", left.to_string(), right.to_string()),
             NodeKind::SUB(left, right) => format!("{} - {}", left.to_string(), right.to_string()),
             NodeKind::MUL(left, right) => format!("{} * {}", left.to_string(), right.to_string()),
             NodeKind::DIV(left, right) => format!("{} / {}", left.to_string(), right.to_string()),
+            NodeKind::MOD(left, right) => format!("{} % {}", left.to_string(), right.to_string()),
+            NodeKind::LSHIFT(left, right) => {
+                format!("{} << {}", left.to_string(), right.to_string())
+            }
+            NodeKind::RSHIFT(left, right) => {
+                format!("{} >> {}", left.to_string(), right.to_string())
+            }
+            NodeKind::BITAND(left, right) => {
+                format!("{} & {}", left.to_string(), right.to_string())
+            }
+            NodeKind::BITOR(left, right) => {
+                format!("{} | {}", left.to_string(), right.to_string())
+            }
+            NodeKind::BITXOR(left, right) => {
+                format!("{} ^ {}", left.to_string(), right.to_string())
+            }
+            NodeKind::LESSTHAN(left, right) => {
+                format!("{} < {}", left.to_string(), right.to_string())
+            }
+            NodeKind::LESSTHANEQUAL(left, right) => {
+                format!("{} <= {}", left.to_string(), right.to_string())
+            }
+            NodeKind::GREATERTHAN(left, right) => {
+                format!("{} > {}", left.to_string(), right.to_string())
+            }
+            NodeKind::GREATERTHANEQUAL(left, right) => {
+                format!("{} >= {}", left.to_string(), right.to_string())
+            }
+            NodeKind::EQUAL(left, right) => {
+                format!("{} == {}", left.to_string(), right.to_string())
+            }
+            NodeKind::NOTEQUAL(left, right) => {
+                format!("{} != {}", left.to_string(), right.to_string())
+            }
+            NodeKind::LOGICALAND(left, right) => {
+                format!("{} && {}", left.to_string(), right.to_string())
+            }
+            NodeKind::LOGICALOR(left, right) => {
+                format!("{} || {}", left.to_string(), right.to_string())
+            }
             NodeKind::NEGATIVE(ex) => format!("- {}", ex.to_string()),
+            NodeKind::LOGICALNOT(ex) => format!("!{}", ex.to_string()),
+            NodeKind::BITNOT(ex) => format!("~{}", ex.to_string()),
+            NodeKind::ADDRESSOF(ex) => format!("&{}", ex.to_string()),
+            NodeKind::DEREFERENCE(ex) => format!("*{}", ex.to_string()),
+            NodeKind::SIZEOF(ex) => format!("sizeof {}", ex.to_string()),
+            NodeKind::VASTART(ap) => format!("va_start({})", ap.to_string()),
+            NodeKind::VAARG(ap) => {
+                format!("va_arg({}, {})", ap.to_string(), self.ctype.to_string())
+            }
+            NodeKind::VAEND(ap) => format!("va_end({})", ap.to_string()),
+            NodeKind::MEMBER(ex, member) => format!("({}).{}", ex.to_string(), member),
+            NodeKind::CAST(ex) => format!("({}){}", self.ctype.to_string(), ex.to_string()),
+            NodeKind::POSTINCREMENT(ex) => format!("{}++", ex.to_string()),
+            NodeKind::POSTDECREMENT(ex) => format!("{}--", ex.to_string()),
             NodeKind::INTEGER(v) => format!("{}", v),
             NodeKind::IDENTIFIER(name) => name.to_string(),
+            NodeKind::STRLIT(idx) => format!(".LC{}", idx),
+            NodeKind::INITLIST(elements) => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|(designators, value)| {
+                        let designation: String =
+                            designators.iter().map(|d| d.to_string()).collect();
+                        if designation.is_empty() {
+                            value.to_string()
+                        } else {
+                            format!("{} = {}", designation, value.to_string())
+                        }
+                    })
+                    .collect();
+                format!("{{{}}}", elements.join(", "))
+            }
+            NodeKind::FLATINIT(elements) => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|(offset, value)| format!("+{}: {}", offset, value.to_string()))
+                    .collect();
+                format!("{{{}}}", elements.join(", "))
+            }
             NodeKind::INVALID => "invalid".to_string(),
             NodeKind::NOP => "nop".to_string(),
         }
@@ -217,38 +486,102 @@ type Args = Vec<Node>;
 type Stmt = Box<Node>;
 type Stmts = Vec<Node>;
 type Label = String;
+type Cases = Vec<i128>;
+type Initializer = Option<Box<Node>>;
 #[derive(Debug, PartialEq, Clone)]
 pub enum NodeKind {
     // statement
     COMPOUNDSTMT(Stmts),
     RETURNSTMT(Expr),
     GOTOSTMT(Label),
+    BREAKSTMT,
+    CONTINUESTMT,
     IFSTMT(Expr, Stmt),
     IFELSESTMT(Expr, Stmt, Stmt),
     FORSTMT(Clause, Expr, Expr, Stmt),
     DOWHILESTMT(Stmt, Expr),
     WHILESTMT(Expr, Stmt),
     LABELEDSTMT(Label, Stmt),
+    SWITCHSTMT(Expr, Stmt, Cases, bool), // case の値と default の有無は意味解析で埋める
+    CASESTMT(Expr, Stmt),
+    DEFAULTSTMT(Stmt),
     EXPRSTMT(Expr),
-    DECLARATION(String, Type),
+    DECLARATION(String, Type, Initializer), // 初期化子は意味解析で FLATINIT に置き換える
 
     // expression
     ASSIGN(Expr, Expr),
+    COMPOUNDASSIGN(Expr),
+    CAST(Expr), // 暗黙の型変換.変換先はノードの型
+    COMMA(Expr, Expr),
+    CONDITIONAL(Expr, Expr, Expr),
     ADD(Expr, Expr),
     SUB(Expr, Expr),
     MUL(Expr, Expr),
     DIV(Expr, Expr),
+    MOD(Expr, Expr),
+    LSHIFT(Expr, Expr),
+    RSHIFT(Expr, Expr),
+    BITAND(Expr, Expr),
+    BITOR(Expr, Expr),
+    BITXOR(Expr, Expr),
+    LESSTHAN(Expr, Expr),
+    LESSTHANEQUAL(Expr, Expr),
+    GREATERTHAN(Expr, Expr),
+    GREATERTHANEQUAL(Expr, Expr),
+    EQUAL(Expr, Expr),
+    NOTEQUAL(Expr, Expr),
+    LOGICALAND(Expr, Expr),
+    LOGICALOR(Expr, Expr),
     CALL(Expr, Args),
     NEGATIVE(Expr),
+    LOGICALNOT(Expr),
+    BITNOT(Expr),
+    ADDRESSOF(Expr),
+    DEREFERENCE(Expr),
+    SIZEOF(Expr),
+    VASTART(Expr),
+    VAARG(Expr), // 取り出す値の型はノードの型
+    VAEND(Expr),
+    MEMBER(Expr, String),
+    POSTINCREMENT(Expr),
+    POSTDECREMENT(Expr),
     INTEGER(i128),
     IDENTIFIER(String),
+    STRLIT(usize),                          // 文字列リテラル表の番号
+    INITLIST(Vec<(Vec<Designator>, Node)>), // { [2] = 1, .x = 2, 3 } の各要素
+    FLATINIT(Vec<(usize, Node)>),           // 変数の先頭からのオフセットと,そこに置く値
     INVALID,
     NOP,
 }
 
+// 初期化子の指示子
+// [2] = ... や .x = ... のように,初期化する要素を指定する
+#[derive(Debug, PartialEq, Clone)]
+pub enum Designator {
+    INDEX(Expr),
+    MEMBER(String),
+}
+
+impl Designator {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::INDEX(index) => format!("[{}]", index.to_string()),
+            Self::MEMBER(member) => format!(".{}", member),
+        }
+    }
+}
+
 // 演算の優先順位を定義
 #[derive(Debug, PartialEq, Clone)]
 pub enum Priority {
+    LOGICALOR,
+    LOGICALAND,
+    BITWISEOR,
+    BITWISEXOR,
+    BITWISEAND,
+    EQUALITY,
+    RELATIONAL,
+    SHIFT,
     ADDITIVE,
     MULTIPLICATIVE,
 }
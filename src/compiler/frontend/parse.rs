@@ -1,60 +1,96 @@
-use crate::compiler::frontend::manager::Manager;
-use crate::compiler::frontend::node::{Function, Node, NodeKind, Priority};
+use crate::compiler::frontend::manager::{Binding, Manager, ParamList, Tag};
+use crate::compiler::frontend::node::{Designator, Function, Node, NodeKind, Priority, Signature};
 use crate::compiler::frontend::token;
-use crate::compiler::frontend::types::Type;
+use crate::compiler::frontend::types::{Qualifiers, Type, TypeKind, VA_LIST_TAG};
 use crate::compiler::frontend::variable::Variable;
 use crate::error::{Error, ErrorKind, ErrorMsg};
-use token::{Token, TokenKind};
+use token::{Position, Token, TokenKind};
+
+use std::collections::BTreeMap;
+
+// 記憶域クラス指定子
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StorageClass {
+    TYPEDEF,
+    EXTERN,
+    STATIC,
+    // auto/register は局所変数の扱いを変えない
+    AUTO,
+}
 
 impl Manager {
     pub fn parse(&mut self) {
         self.parse_toplevel();
     }
 
-    // toplevel -> (global-var | func-def)*
+    // toplevel -> (global-declaration | func-def | func-decl)*
     fn parse_toplevel(&mut self) {
         loop {
             if !self.is_function() {
-                // 本当はグローバル変数定義だけど,今はbreak
-                break;
+                if !self.is_declaration_specifier() {
+                    break;
+                }
+                self.parse_global_declaration();
+                continue;
             }
 
             self.params.clear();
             self.var_map.clear();
-            let mut func = self.parse_function();
-            func.local_map = self.var_map.clone();
-            func.params = self.params.clone();
-            self.functions.push(func);
+            self.outer_names.clear();
+            if let Some(mut func) = self.parse_function() {
+                func.local_map = self.var_map.clone();
+                func.params = self.params.clone();
+                self.functions.push(func);
+            }
         }
     }
 
-    // function = basetype declarator "(" params? ")" ("{" stmt* "}" | ";")
-    // params   = param ("," param)* | "void"
-    // param    = basetype declarator type-suffix
-    fn parse_function(&mut self) -> Function {
+    // function = declaration-specifiers declarator "(" params? ")" ("{" stmt* "}" | ";")
+    // 本体の無いプロトタイプ宣言であれば,関数の型だけを登録して None を返す
+    fn parse_function(&mut self) -> Option<Function> {
         let current_position = self.looking_token_clone().position;
 
-        let base_type = self.consume_base_type().unwrap();
+        let (storage_class, base_type) = self.consume_declaration_specifiers();
+        self.declarator_params = None;
         let (name, dec_type) = self.parse_declarator(base_type);
 
-        let mut func = Function::init(name, current_position, dec_type);
-
-        self.expect(TokenKind::LPAREN);
-        // voidは単純に無視すればいい
-        self.consume(TokenKind::VOID);
-
-        // 引数のパース
-        loop {
-            if self.consume(TokenKind::RPAREN) {
-                break;
+        // int (*pick(int i))(int, int) のように,引数並びを宣言子の括弧の中で読んだ関数
+        let (dec_type, param_list) = match (&dec_type.kind, self.declarator_params.take()) {
+            (TypeKind::FUNCTION(signature), Some(param_list)) => {
+                (signature.return_type.clone(), Some(param_list))
             }
+            _ => (dec_type, None),
+        };
 
-            let base_type = self.consume_base_type().unwrap();
-            let (arg_name, dec_type) = self.parse_declarator(base_type);
-            let argument = Variable::init_local(dec_type.clone());
-            self.params.insert(arg_name.to_string(), argument);
+        // 一度 static で宣言された関数は,定義で static を省略しても内部結合
+        if storage_class == Some(StorageClass::STATIC) {
+            self.static_functions.insert(name.to_string());
+        }
+        let mut func = Function::init(name.to_string(), current_position, dec_type.clone());
+        func.is_static = self.static_functions.contains(&name);
 
-            self.consume(TokenKind::COMMA);
+        // 引数と関数本体の直下の宣言は同じスコープに属する
+        self.enter_scope();
+
+        let param_list = match param_list {
+            Some(param_list) => param_list,
+            None => {
+                self.expect(TokenKind::LPAREN);
+                self.parse_param_list()
+            }
+        };
+        let param_types = self.declare_params(&mut func, param_list);
+        let signature = Signature {
+            return_type: dec_type,
+            param_types,
+            is_variadic: func.is_variadic,
+        };
+
+        let is_prototype = self.consume(TokenKind::SEMICOLON);
+        self.declare_function(current_position, &name, signature, !is_prototype);
+        if is_prototype {
+            self.leave_scope();
+            return None;
         }
 
         // 関数のボディ
@@ -68,11 +104,181 @@ impl Manager {
             let stmt = self.parse_statement();
             func.stmts.push(stmt);
         }
+        self.leave_scope();
+
+        Some(func)
+    }
+    // 関数の引数を,関数本体のスコープに登録する
+    // 引数並びを省略した場合(int f())は None を返す
+    fn declare_params(&mut self, func: &mut Function, param_list: ParamList) -> Option<Vec<Type>> {
+        let (params, is_variadic) = param_list;
+        func.is_variadic = is_variadic;
+
+        let mut param_types = Vec::new();
+        for (arg_name, dec_type) in params? {
+            param_types.push(dec_type.clone());
+
+            // プロトタイプでは引数名を省略できる
+            if !arg_name.is_empty() {
+                let argument = Variable::init_local(dec_type);
+                self.params.insert(arg_name.to_string(), argument);
+                self.scopes.last_mut().unwrap().insert(
+                    arg_name.to_string(),
+                    Binding::VARIABLE(arg_name.to_string()),
+                );
+                func.param_names.push(arg_name);
+            }
+        }
+        Some(param_types)
+    }
+    // params = param ("," param)* ("," "...")? | "void"
+    // param  = declaration-specifiers declarator function-suffix
+    // 引数の名前と型の並び,および ... があるかを返す
+    fn parse_param_list(&mut self) -> ParamList {
+        if self.consume(TokenKind::RPAREN) {
+            return (None, false);
+        }
+
+        // (void) は引数無し.void *p のような引数とは区別する
+        if self.looking_token().kind == TokenKind::VOID && self.next_token_is(TokenKind::RPAREN) {
+            self.read_token();
+            self.read_token();
+            return (Some(Vec::new()), false);
+        }
+
+        let mut params = Vec::new();
+        let mut is_variadic = false;
+        loop {
+            let cur = self.looking_token_clone();
+            if self.consume(TokenKind::ELLIPSIS) {
+                if params.is_empty() {
+                    let err = Error::new(
+                        ErrorKind::Parse,
+                        cur.position,
+                        ErrorMsg::NamedParameterBeforeEllipsis,
+                    );
+                    err.found();
+                }
+                is_variadic = true;
+                break;
+            }
+
+            // int f(int) のような関数型の引数もある
+            // 引数に付けられる register は読み捨てる
+            let (_storage_class, base_type) = self.consume_declaration_specifiers();
+            let (arg_name, dec_type) = self.parse_declarator(base_type);
+            let mut dec_type = self.parse_function_suffix(dec_type);
+            // 配列型の引数はポインタとして,関数型の引数は関数ポインタとして扱う
+            match dec_type.kind {
+                TypeKind::ARRAY(elem_type, _len) => dec_type = Type::pointer_to(*elem_type),
+                TypeKind::FUNCTION(_) => dec_type = Type::pointer_to(dec_type),
+                _ => {}
+            }
+            params.push((arg_name, dec_type));
+
+            if !self.consume(TokenKind::COMMA) {
+                break;
+            }
+        }
+        self.expect(TokenKind::RPAREN);
+
+        (Some(params), is_variadic)
+    }
+    // 関数の型を登録する.既に宣言されていれば型が一致するか検査する
+    fn declare_function(
+        &mut self,
+        pos: Position,
+        name: &str,
+        signature: Signature,
+        is_definition: bool,
+    ) {
+        if is_definition && self.functions.iter().any(|func| func.name == name) {
+            let err = Error::new(
+                ErrorKind::Parse,
+                pos,
+                ErrorMsg::Redefinition(name.to_string()),
+            );
+            err.found();
+        }
+
+        if let Some(declared) = self.signatures.get(name) {
+            if !declared.is_compatible_with(&signature) {
+                let err = Error::new(
+                    ErrorKind::Type,
+                    pos,
+                    ErrorMsg::ConflictingTypes(name.to_string()),
+                );
+                err.found();
+                return;
+            }
+            // 引数並びを省略した宣言で,先に宣言した引数の型を上書きしない
+            if signature.param_types.is_none() {
+                return;
+            }
+        }
+        self.signatures.insert(name.to_string(), signature);
+    }
+    // global-declaration = declaration-specifiers (global-declarator ("," global-declarator)*)? ";"
+    // global-declarator = declarator ("=" initializer)?
+    // 初期化子の無い extern 宣言は領域を確保しない
+    fn parse_global_declaration(&mut self) {
+        let (storage_class, base_type) = self.consume_declaration_specifiers();
+
+        // 構造体/共用体/列挙型の定義のみの宣言 e.g. struct S { int a; };
+        if self.consume(TokenKind::SEMICOLON) {
+            return;
+        }
+        if storage_class == Some(StorageClass::TYPEDEF) {
+            self.parse_typedef(base_type);
+            return;
+        }
+
+        loop {
+            let (var_name, var_type) = self.parse_declarator(base_type.clone());
+            let mut is_definition = storage_class != Some(StorageClass::EXTERN);
+            if self.consume(TokenKind::ASSIGN) {
+                let initializer = self.parse_initializer();
+                self.global_initializers
+                    .insert(var_name.to_string(), initializer);
+                is_definition = true;
+            }
+
+            if storage_class == Some(StorageClass::STATIC) {
+                self.global_map
+                    .insert(var_name, Variable::init_static(var_type));
+            } else if is_definition {
+                self.global_map
+                    .insert(var_name, Variable::init_global(var_type));
+            } else {
+                // 定義済みの変数を extern で宣言し直しても,定義はそのまま
+                self.global_map
+                    .entry(var_name)
+                    .or_insert_with(|| Variable::init_extern(var_type));
+            }
+
+            if !self.consume(TokenKind::COMMA) {
+                break;
+            }
+        }
+        self.expect(TokenKind::SEMICOLON);
+    }
+    // typedef 名を現在のスコープに登録する
+    // typedef int F(int); のように関数型にも名前を付けられる
+    fn parse_typedef(&mut self, base_type: Type) {
+        loop {
+            let (name, dec_type) = self.parse_declarator(base_type.clone());
+            let dec_type = self.parse_function_suffix(dec_type);
+            self.current_bindings()
+                .insert(name, Binding::TYPEDEF(dec_type));
 
-        func
+            if !self.consume(TokenKind::COMMA) {
+                break;
+            }
+        }
+        self.expect(TokenKind::SEMICOLON);
     }
     fn parse_statement(&mut self) -> Node {
-        if self.is_typename() {
+        if self.is_declaration_specifier() {
             return self.parse_declaration();
         }
         let cur = self.looking_token_clone();
@@ -81,14 +287,19 @@ impl Manager {
             TokenKind::RETURN => self.parse_return_stmt(),
             // goto-statement
             TokenKind::GOTO => self.parse_goto_stmt(),
+            // break-statement/continue-statement
+            TokenKind::BREAK | TokenKind::CONTINUE => self.parse_break_or_continue_stmt(),
             // labeled-statement
             TokenKind::IDENTIFIER(_name) if self.next_token_is(TokenKind::COLON) => {
                 self.parse_labeled_stmt()
             }
+            TokenKind::CASE => self.parse_case_stmt(),
+            TokenKind::DEFAULT => self.parse_default_stmt(),
             // compound-statement
             TokenKind::LBRACE => self.parse_compound_stmt(),
             // selection-statement
             TokenKind::IF => self.parse_selection_stmt(),
+            TokenKind::SWITCH => self.parse_switch_stmt(),
             // iteration-statement
             TokenKind::FOR => self.parse_for_stmt(),
             TokenKind::DO => self.parse_do_while_stmt(),
@@ -103,56 +314,351 @@ impl Manager {
         }
     }
 
-    // declaration =  basetype declarator type-suffix ("=" lvar-initializer)? ";"
-    //              | basetype ";"
+    // declaration = declaration-specifiers (init-declarator ("," init-declarator)*)? ";"
+    // init-declarator = declarator ("=" initializer)?
     fn parse_declaration(&mut self) -> Node {
         let current_position = self.looking_token_clone().position;
-        let base_type = self.consume_base_type().unwrap();
-
-        let (var_name, var_type) = self.parse_declarator(base_type);
+        let (storage_class, base_type) = self.consume_declaration_specifiers();
 
-        // TODO: type-suffix は考えない
+        // タグの宣言のみ
+        if self.consume(TokenKind::SEMICOLON) {
+            return Node::new_nop();
+        }
+        if storage_class == Some(StorageClass::TYPEDEF) {
+            self.parse_typedef(base_type);
+            return Node::new_nop();
+        }
 
-        // TODO: 今は初期化を実装する必要はない.
-        // if self.consume(TokenKind::ASSIGN) {
-        // ASTノードに意味を持たせない実装もあるが,ここでは持たせている.
-        // return Node::new_declaration(current_position, base_type);
-        // }
+        let mut declarations = Vec::new();
+        loop {
+            let (var_name, var_type) = self.parse_declarator(base_type.clone());
+
+            // static/extern な変数は実行時に初期化しないので,文を作らない
+            // static な変数の初期化子は,グローバル変数と同様に定数に畳み込む
+            match storage_class {
+                Some(StorageClass::STATIC) => {
+                    let storage_name =
+                        self.declare_static_local(current_position, var_name, var_type);
+                    if self.consume(TokenKind::ASSIGN) {
+                        let initializer = self.parse_initializer();
+                        self.global_initializers.insert(storage_name, initializer);
+                    }
+                }
+                Some(StorageClass::EXTERN) => {
+                    self.declare_extern_local(current_position, var_name, var_type);
+                }
+                _ => {
+                    // マップにエントリを登録
+                    // 初期化子の中では,宣言した変数自身が見える
+                    let storage_name =
+                        self.declare_local(current_position, var_name, var_type.clone());
+                    let initializer = if self.consume(TokenKind::ASSIGN) {
+                        Some(self.parse_initializer())
+                    } else {
+                        None
+                    };
+                    declarations.push(Node::new_declaration(
+                        current_position,
+                        storage_name,
+                        var_type,
+                        initializer,
+                    ));
+                }
+            }
 
-        // マップにエントリを登録
-        let local_var = Variable::init_local(var_type.clone());
-        self.var_map.insert(var_name.to_string(), local_var);
+            if !self.consume(TokenKind::COMMA) {
+                break;
+            }
+        }
         self.expect(TokenKind::SEMICOLON);
-        Node::new_declaration(current_position, var_name, var_type)
+
+        // 複数の宣言子は,宣言を並べた文として扱う
+        match declarations.len() {
+            0 => return Node::new_nop(),
+            1 => return declarations.pop().unwrap(),
+            _ => {}
+        }
+        Node::new_compound(current_position, declarations)
     }
+    // initializer = assign | "{" (initializer-element ("," initializer-element)* ","?)? "}"
+    // initializer-element = designation? initializer
+    // designation = ("[" conditional "]" | "." identifier)+ "="
+    fn parse_initializer(&mut self) -> Node {
+        let current_position = self.looking_token_clone().position;
+        if !self.consume(TokenKind::LBRACE) {
+            return self.parse_assign();
+        }
 
-    // declarator = "*"* ("(" declarator ")" | ident) type-suffix
+        let mut elements = Vec::new();
+        loop {
+            if self.consume(TokenKind::RBRACE) {
+                break;
+            }
+
+            let mut designators = Vec::new();
+            loop {
+                if self.consume(TokenKind::LBRACKET) {
+                    designators.push(Designator::INDEX(Box::new(self.parse_conditional())));
+                    self.expect(TokenKind::RBRACKET);
+                } else if self.consume(TokenKind::DOT) {
+                    designators.push(Designator::MEMBER(self.expect_ident()));
+                } else {
+                    break;
+                }
+            }
+            if !designators.is_empty() {
+                self.expect(TokenKind::ASSIGN);
+            }
+            elements.push((designators, self.parse_initializer()));
+
+            if !self.consume(TokenKind::COMMA) {
+                self.expect(TokenKind::RBRACE);
+                break;
+            }
+        }
+        Node::new_initlist(current_position, elements)
+    }
+    // 局所変数を現在のスコープに登録し,var_map 上の名前を返す
+    // 関数本体の直下で初めて現れた名前はそのまま使い,それ以外は番号を付けて区別する
+    fn declare_local(&mut self, pos: Position, var_name: String, var_type: Type) -> String {
+        self.check_redefinition(pos, &var_name);
+
+        let mut storage_name = var_name.to_string();
+        if self.scopes.len() != 1
+            || self.var_map.contains_key(&var_name)
+            || self.params.contains_key(&var_name)
+            || self.outer_names.contains(&var_name)
+        {
+            storage_name = self.unique_storage_name(&var_name);
+        }
+
+        let local_var = Variable::init_local(var_type);
+        self.var_map.insert(storage_name.to_string(), local_var);
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(var_name, Binding::VARIABLE(storage_name.to_string()));
+        storage_name
+    }
+    // static な局所変数は,番号を付けた名前でグローバル変数として登録する
+    // 他の関数の同名の変数とも区別するため,常に番号を付ける
+    fn declare_static_local(&mut self, pos: Position, var_name: String, var_type: Type) -> String {
+        self.check_redefinition(pos, &var_name);
+
+        let storage_name = self.unique_storage_name(&var_name);
+        self.global_map
+            .insert(storage_name.to_string(), Variable::init_static(var_type));
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(var_name, Binding::VARIABLE(storage_name.to_string()));
+        storage_name
+    }
+    // ブロック内の extern 宣言は,グローバル変数を指す
+    fn declare_extern_local(&mut self, pos: Position, var_name: String, var_type: Type) {
+        self.check_redefinition(pos, &var_name);
+
+        self.global_map
+            .entry(var_name.to_string())
+            .or_insert_with(|| Variable::init_extern(var_type));
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(var_name.to_string(), Binding::VARIABLE(var_name));
+    }
+    fn check_redefinition(&self, pos: Position, name: &str) {
+        if self.scopes.last().unwrap().contains_key(name) {
+            let err = Error::new(
+                ErrorKind::Parse,
+                pos,
+                ErrorMsg::Redefinition(name.to_string()),
+            );
+            err.found();
+        }
+    }
+    // name.1, name.2, ... のうち,局所変数にも静的な変数にも使われていない名前
+    fn unique_storage_name(&self, name: &str) -> String {
+        let mut suffix = 1;
+        loop {
+            let storage_name = format!("{}.{}", name, suffix);
+            if !self.var_map.contains_key(&storage_name)
+                && !self.global_map.contains_key(&storage_name)
+            {
+                return storage_name;
+            }
+            suffix += 1;
+        }
+    }
+    // 内側のスコープから順に名前を探す
+    // 見つからなければグローバル変数/関数を指す
+    fn resolve_name(&mut self, name: String) -> String {
+        if let Some(Binding::VARIABLE(storage_name)) = self.lookup_binding(&name) {
+            return storage_name.to_string();
+        }
+        if !self.scopes.is_empty() {
+            self.outer_names.insert(name.to_string());
+        }
+        name
+    }
+    // 内側のスコープから順に,ファイルスコープまで名前を探す
+    fn lookup_binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.file_scope.get(name))
+    }
+    // typedef 名や列挙定数を登録するスコープ
+    fn current_bindings(&mut self) -> &mut BTreeMap<String, Binding> {
+        match self.scopes.last_mut() {
+            Some(scope) => scope,
+            None => &mut self.file_scope,
+        }
+    }
+    // ブロックに入る.通常の識別子とタグのスコープを一緒に積む
+    fn enter_scope(&mut self) {
+        self.scopes.push(BTreeMap::new());
+        self.tag_scopes.push(BTreeMap::new());
+    }
+    fn leave_scope(&mut self) {
+        self.scopes.pop();
+        self.tag_scopes.pop();
+    }
+    // 内側のスコープから順に,ファイルスコープまでタグを探す
+    fn lookup_tag(&self, tag: &str) -> Option<&Tag> {
+        self.tag_scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(tag))
+            .or_else(|| self.file_tags.get(tag))
+    }
+    // タグを登録するスコープ
+    fn current_tags(&mut self) -> &mut BTreeMap<String, Tag> {
+        match self.tag_scopes.last_mut() {
+            Some(scope) => scope,
+            None => &mut self.file_tags,
+        }
+    }
+
+    // declarator = ("*" type-qualifier*)* ("(" declarator ")" function-suffix? | ident?) type-suffix
+    // 名前の無い宣言子(抽象宣言子)も受け付ける
     fn parse_declarator(&mut self, base_type: Type) -> (String, Type) {
+        self.parse_declarator_in_parens(base_type, false)
+    }
+    // is_nested: 括弧でくくった宣言子の中か
+    fn parse_declarator_in_parens(&mut self, base_type: Type, is_nested: bool) -> (String, Type) {
         let mut covered_type = base_type;
 
         // *がある間ポインタ型にくるむ
-        loop {
-            if !self.consume(TokenKind::ASTERISK) {
-                break;
-            }
-            covered_type = Type::pointer_to(covered_type);
+        // * の後ろの修飾子はポインタ自身に付く e.g. char *const p
+        while self.consume(TokenKind::ASTERISK) {
+            let mut qualifiers = Qualifiers::default();
+            while self.consume_qualifier(&mut qualifiers) {}
+            covered_type = Type::pointer_to(covered_type).qualified(qualifiers);
         }
 
+        // int (*p)[3] や int (*fp)(int) のような宣言子は,
+        // 括弧の後ろの type-suffix を先に読んでから括弧の中を解析し直す
         if self.consume(TokenKind::LPAREN) {
+            let cur_token = self.cur_token;
+            let next_token = self.next_token;
+
             let placeholder = Type::new_unknown();
-            let (name, new_type) = self.parse_declarator(placeholder);
+            let _ = self.parse_declarator_in_parens(placeholder, true);
             self.expect(TokenKind::RPAREN);
+            covered_type = self.parse_function_suffix(covered_type);
+            let (end_token, end_next_token) = (self.cur_token, self.next_token);
+
+            self.cur_token = cur_token;
+            self.next_token = next_token;
+            let (name, new_type) = self.parse_declarator_in_parens(covered_type, true);
+
+            self.cur_token = end_token;
+            self.next_token = end_next_token;
             return (name, new_type);
         }
 
-        let name = self.expect_ident();
-        (name, covered_type)
+        // 名前の無い宣言子(プロトタイプの引数など)は空の名前を返す
+        let name = match self.looking_token_clone().kind {
+            TokenKind::IDENTIFIER(_) => self.expect_ident(),
+            _ => String::new(),
+        };
+
+        // 括弧の外の引数並びは parse_function で読むが,
+        // int (*pick(int i))(int, int) の (int i) は括弧の中にしか書けないので,ここで読む
+        // 関数定義では引数の名前も要るので,引数並びを覚えておく
+        if is_nested && self.consume(TokenKind::LPAREN) {
+            let param_list = self.parse_param_list();
+            let func_type = Self::function_of_params(covered_type, &param_list);
+            self.declarator_params = Some(param_list);
+            return (name, func_type);
+        }
+        (name, self.parse_type_suffix(covered_type))
+    }
+
+    // function-suffix = "(" params? ")"
+    // 関数の定義や宣言の引数並びは parse_function で読むので,
+    // ここで読むのは括弧でくくった宣言子の後ろに来るものだけ
+    fn parse_function_suffix(&mut self, return_type: Type) -> Type {
+        if !self.consume(TokenKind::LPAREN) {
+            return self.parse_type_suffix(return_type);
+        }
+        let param_list = self.parse_param_list();
+        Self::function_of_params(return_type, &param_list)
+    }
+    fn function_of_params(return_type: Type, param_list: &ParamList) -> Type {
+        let (params, is_variadic) = param_list;
+        let param_types = params
+            .as_ref()
+            .map(|params| params.iter().map(|(_name, ty)| ty.clone()).collect());
+        Type::function_of(Signature {
+            return_type,
+            param_types,
+            is_variadic: *is_variadic,
+        })
+    }
+    // type-suffix = ("[" conditional? "]" type-suffix)?
+    // int a[2][3] は 要素数3の配列 を要素とする要素数2の配列
+    // 要素数を省略した配列(int a[] = {...})の要素数は,意味解析で初期化子から決める
+    // 要素数には列挙定数などを使った整数定数式も書ける
+    fn parse_type_suffix(&mut self, base_type: Type) -> Type {
+        if !self.consume(TokenKind::LBRACKET) {
+            return base_type;
+        }
+
+        let cur = self.looking_token_clone();
+        let len = if cur.kind == TokenKind::RBRACKET {
+            0
+        } else {
+            match Self::eval_constant(&self.parse_conditional()) {
+                Some(len) if len >= 0 => len as usize,
+                _ => {
+                    let err =
+                        Error::new(ErrorKind::Parse, cur.position, ErrorMsg::MustBeArrayLength);
+                    err.found();
+                    0
+                }
+            }
+        };
+        self.expect(TokenKind::RBRACKET);
+
+        let elem_type = self.parse_type_suffix(base_type);
+        Type::array_of(elem_type, len)
+    }
+
+    // type-name = basetype declarator
+    // 宣言子は名前を持たない e.g. int (*)(int)
+    fn parse_type_name(&mut self) -> Type {
+        let base_type = self.consume_base_type().unwrap();
+        let (_name, type_name) = self.parse_declarator(base_type);
+        type_name
     }
 
     // compound_stmt -> `{` statement * n `}`
     fn parse_compound_stmt(&mut self) -> Node {
         let current_position = self.looking_token_clone().position;
         self.expect(TokenKind::LBRACE);
+        self.enter_scope();
 
         let mut stmts: Vec<Node> = Vec::new();
         loop {
@@ -161,6 +667,7 @@ impl Manager {
             }
             stmts.push(self.parse_statement());
         }
+        self.leave_scope();
         Node::new_compound(current_position, stmts)
     }
     // do_while_stmt -> do statement while `(` expression `)`
@@ -198,10 +705,16 @@ impl Manager {
 
         let mut clause = Node::new_nop();
         let mut expr_2 = Node::new_nop();
+        let mut declaration = None;
         self.expect(TokenKind::FOR);
         self.expect(TokenKind::LPAREN);
 
-        if !self.consume(TokenKind::SEMICOLON) {
+        // 節で宣言した変数のスコープは for 文の終わりまで
+        self.enter_scope();
+        if self.is_declaration_specifier() {
+            // for (T x = e; ...) は { T x = e; for (; ...) } と同じ
+            declaration = Some(self.parse_declaration());
+        } else if !self.consume(TokenKind::SEMICOLON) {
             clause = self.parse_expression();
             self.expect(TokenKind::SEMICOLON);
         }
@@ -213,7 +726,13 @@ impl Manager {
         self.expect(TokenKind::RPAREN);
 
         let stmt = self.parse_statement();
-        Node::new_for(current_position, clause, expr_2, expr_3, stmt)
+        self.leave_scope();
+
+        let for_stmt = Node::new_for(current_position, clause, expr_2, expr_3, stmt);
+        match declaration {
+            Some(declaration) => Node::new_compound(current_position, vec![declaration, for_stmt]),
+            None => for_stmt,
+        }
     }
 
     fn parse_selection_stmt(&mut self) -> Node {
@@ -236,6 +755,36 @@ impl Manager {
         Node::new_if_else(current_position, cond_expr, any_statement, alter_statement)
     }
 
+    // switch_stmt -> switch + `(` + expression + `)` + statement
+    fn parse_switch_stmt(&mut self) -> Node {
+        let current_position = self.looking_token_clone().position;
+        self.expect(TokenKind::SWITCH);
+        self.expect(TokenKind::LPAREN);
+        let cond_expr = self.parse_expression();
+        self.expect(TokenKind::RPAREN);
+
+        let any_statement = self.parse_statement();
+        Node::new_switch(current_position, cond_expr, any_statement)
+    }
+    // case_stmt -> case + conditional + `:` + statement
+    fn parse_case_stmt(&mut self) -> Node {
+        let current_position = self.looking_token_clone().position;
+        self.expect(TokenKind::CASE);
+        let case_expr = self.parse_conditional();
+        self.expect(TokenKind::COLON);
+        let any_statement = self.parse_statement();
+
+        Node::new_case(current_position, case_expr, any_statement)
+    }
+    // default_stmt -> default + `:` + statement
+    fn parse_default_stmt(&mut self) -> Node {
+        let current_position = self.looking_token_clone().position;
+        self.expect(TokenKind::DEFAULT);
+        self.expect(TokenKind::COLON);
+        let any_statement = self.parse_statement();
+
+        Node::new_default(current_position, any_statement)
+    }
     // goto_stmt -> goto + identifier + `;`
     fn parse_goto_stmt(&mut self) -> Node {
         let current_position = self.looking_token_clone().position;
@@ -245,6 +794,19 @@ impl Manager {
 
         Node::new_goto(current_position, label_name)
     }
+    // break_stmt -> break + `;`
+    // continue_stmt -> continue + `;`
+    fn parse_break_or_continue_stmt(&mut self) -> Node {
+        let cur = self.looking_token_clone();
+        self.read_token();
+        self.expect(TokenKind::SEMICOLON);
+
+        if let TokenKind::BREAK = cur.kind {
+            Node::new_break(cur.position)
+        } else {
+            Node::new_continue(cur.position)
+        }
+    }
     // labeled_stmt -> identifier + `:` + statement
     fn parse_labeled_stmt(&mut self) -> Node {
         let current_position = self.looking_token_clone().position;
@@ -260,6 +822,11 @@ impl Manager {
 
         self.expect(TokenKind::RETURN);
 
+        // 値を返さない return
+        if self.consume(TokenKind::SEMICOLON) {
+            return Node::new_return(current_position, Node::new_nop());
+        }
+
         let return_expr = self.parse_expression();
 
         self.expect(TokenKind::SEMICOLON);
@@ -268,55 +835,99 @@ impl Manager {
     }
     // expr -> assign ("," assign)*
     fn parse_expression(&mut self) -> Node {
-        let assign_node = self.parse_assign();
+        let mut assign_node = self.parse_assign();
+        loop {
+            let current_position = self.looking_token_clone().position;
+            if !self.consume(TokenKind::COMMA) {
+                break;
+            }
+            assign_node = Node::new_comma(current_position, assign_node, self.parse_assign());
+        }
         assign_node
     }
     // assign -> conditional (assign-op assign)?
-    // assign-op = "=" | "+=" | "-=" | "*=" | "/=" | "<<=" | ">>="
+    // assign-op = "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "<<=" | ">>="
     //           | "&=" | "|=" | "^="
     #[allow(unconditional_recursion)]
     fn parse_assign(&mut self) -> Node {
-        // TODO: 今はconditionalをパースできない
-        // 現状最も優先度の低いadditiveを呼び出しておく
-        let lvalue_node = self.parse_additive();
+        let lvalue_node = self.parse_conditional();
 
-        // TODO: 今は `=` のみサポート
-        let current_position = self.looking_token_clone().position;
+        let cur = self.looking_token_clone();
         if self.consume(TokenKind::ASSIGN) {
-            return Node::new_assign(current_position, lvalue_node, self.parse_assign());
+            return Node::new_assign(cur.position, lvalue_node, self.parse_assign());
+        }
+        if self.current_token_is_in(&Self::compound_assign_operators()) {
+            self.read_token();
+            return Node::new_compound_assign(&cur, lvalue_node, self.parse_assign());
         }
 
         lvalue_node
     }
-    // additive -> multiplicative | additive-expression (+|-) multiplicative-expression
-    fn parse_additive(&mut self) -> Node {
-        let mut left_node: Node = self.parse_multiplicative();
-
-        // チェックする演算子の列挙
-        let operators = self.current_prio_operators(Priority::ADDITIVE);
-        loop {
-            // いずれにも合致しなければ終了
-            if !self.current_token_is_in(&operators) {
-                break;
-            }
-
-            // 演算子トークンを退避
-            let cur_token = self.looking_token_clone();
-            self.read_token();
-            let right_node = self.parse_multiplicative();
+    // conditional -> logical-or ("?" expression ":" conditional)?
+    fn parse_conditional(&mut self) -> Node {
+        let cond_node = self.parse_logical_or();
 
-            // コンストラクト
-            left_node = Node::new_binary_node(&cur_token, left_node, right_node);
+        let current_position = self.looking_token_clone().position;
+        if !self.consume(TokenKind::QUESTION) {
+            return cond_node;
         }
+        let then_node = self.parse_expression();
+        self.expect(TokenKind::COLON);
+        let else_node = self.parse_conditional();
 
-        left_node
+        Node::new_conditional(current_position, cond_node, then_node, else_node)
     }
-    // multiplicative -> unary-expression | multiplicative-expression (*|/) unary-expression
+    // logical-or -> logical-and | logical-or "||" logical-and
+    fn parse_logical_or(&mut self) -> Node {
+        self.parse_binary_operation(Priority::LOGICALOR, Self::parse_logical_and)
+    }
+    // logical-and -> bitwise-or | logical-and "&&" bitwise-or
+    fn parse_logical_and(&mut self) -> Node {
+        self.parse_binary_operation(Priority::LOGICALAND, Self::parse_bitwise_or)
+    }
+    // bitwise-or -> bitwise-xor | bitwise-or "|" bitwise-xor
+    fn parse_bitwise_or(&mut self) -> Node {
+        self.parse_binary_operation(Priority::BITWISEOR, Self::parse_bitwise_xor)
+    }
+    // bitwise-xor -> bitwise-and | bitwise-xor "^" bitwise-and
+    fn parse_bitwise_xor(&mut self) -> Node {
+        self.parse_binary_operation(Priority::BITWISEXOR, Self::parse_bitwise_and)
+    }
+    // bitwise-and -> equality | bitwise-and "&" equality
+    fn parse_bitwise_and(&mut self) -> Node {
+        self.parse_binary_operation(Priority::BITWISEAND, Self::parse_equality)
+    }
+    // equality -> relational | equality ("==" | "!=") relational
+    fn parse_equality(&mut self) -> Node {
+        self.parse_binary_operation(Priority::EQUALITY, Self::parse_relational)
+    }
+    // relational -> shift | relational ("<" | "<=" | ">" | ">=") shift
+    fn parse_relational(&mut self) -> Node {
+        self.parse_binary_operation(Priority::RELATIONAL, Self::parse_shift)
+    }
+    // shift -> additive | shift ("<<" | ">>") additive
+    fn parse_shift(&mut self) -> Node {
+        self.parse_binary_operation(Priority::SHIFT, Self::parse_additive)
+    }
+    // additive -> multiplicative | additive-expression (+|-) multiplicative-expression
+    fn parse_additive(&mut self) -> Node {
+        self.parse_binary_operation(Priority::ADDITIVE, Self::parse_multiplicative)
+    }
+    // multiplicative -> unary-expression | multiplicative-expression (*|/|%) unary-expression
     fn parse_multiplicative(&mut self) -> Node {
-        let mut left_node: Node = self.parse_unary();
+        self.parse_binary_operation(Priority::MULTIPLICATIVE, Self::parse_unary)
+    }
+    // 左結合な二項演算の共通処理
+    // operand_parser には一つ優先度の高い規則を渡す
+    fn parse_binary_operation(
+        &mut self,
+        priority: Priority,
+        operand_parser: fn(&mut Self) -> Node,
+    ) -> Node {
+        let mut left_node: Node = operand_parser(self);
 
         // チェックする演算子の列挙
-        let operators = self.current_prio_operators(Priority::MULTIPLICATIVE);
+        let operators = self.current_prio_operators(priority);
         loop {
             // いずれにも合致しなければ終了
             if !self.current_token_is_in(&operators) {
@@ -326,7 +937,7 @@ impl Manager {
             // 演算子トークンを退避
             let cur_token = self.looking_token_clone();
             self.read_token();
-            let right_node = self.parse_unary();
+            let right_node = operand_parser(self);
 
             // コンストラクト
             left_node = Node::new_binary_node(&cur_token, left_node, right_node);
@@ -334,41 +945,101 @@ impl Manager {
 
         left_node
     }
-    // unary -> postfix-expression | ("+" | "-")? unary-expression
+    // unary -> postfix-expression | ("-" | "!" | "~" | "&" | "*") unary-expression
+    //        | ("++" | "--") unary-expression
+    //        | "sizeof" unary-expression | "sizeof" "(" type-name ")"
     fn parse_unary(&mut self) -> Node {
         let cur = self.looking_token_clone();
         match cur.kind {
-            TokenKind::MINUS => {
+            TokenKind::SIZEOF => {
+                self.read_token();
+                self.parse_sizeof(cur.position)
+            }
+            // ++x は x += 1 と同じ
+            TokenKind::INCREMENT | TokenKind::DECREMENT => {
+                self.read_token();
+                let inner = self.parse_unary();
+                let one = Node::new(cur.position, NodeKind::INTEGER(1));
+                Node::new_compound_assign(&cur, inner, one)
+            }
+            TokenKind::MINUS
+            | TokenKind::BANG
+            | TokenKind::TILDE
+            | TokenKind::AMPERSAND
+            | TokenKind::ASTERISK => {
                 self.read_token();
                 Node::new_unary_node(&cur, self.parse_unary())
             }
             _ => self.parse_postfix(),
         }
     }
+    // 型名のサイズは構文解析の時点で決まるので,整数定数にしてしまう
+    // 式のサイズは意味解析で型が付いてから求める
+    fn parse_sizeof(&mut self, position: Position) -> Node {
+        let cur_token = self.cur_token;
+        let next_token = self.next_token;
+        if self.consume(TokenKind::LPAREN) && self.is_typename() {
+            let type_name = self.parse_type_name();
+            self.expect(TokenKind::RPAREN);
+            return Node::new(position, NodeKind::INTEGER(type_name.byte_size as i128));
+        }
+
+        self.cur_token = cur_token;
+        self.next_token = next_token;
+        Node::new_sizeof(position, self.parse_unary())
+    }
     // postfix -> primary-expression | postfix_expression `(` argument-expression-list_opt `)`
+    //          | postfix-expression ("++" | "--") | postfix-expression "[" expression "]"
+    //          | postfix-expression ("." | "->") identifier
     fn parse_postfix(&mut self) -> Node {
-        let primary_expr = self.parse_primary();
-        let cur = self.looking_token_clone();
-
-        match cur.kind {
-            // 関数呼び出し
-            // TODO: とりあえず引数なし
-            TokenKind::LPAREN => {
-                self.read_token();
-                let mut params: Vec<Node> = Vec::new();
-                loop {
-                    if self.consume(TokenKind::RPAREN) {
-                        break;
-                    }
-
-                    params.push(self.parse_assign());
-                    self.consume(TokenKind::COMMA);
+        let mut postfix_expr = self.parse_primary();
+        loop {
+            let cur = self.looking_token_clone();
+            match cur.kind {
+                // 関数呼び出し
+                // 関数名だけでなく, ops[i](x) のように関数ポインタの式も呼び出せる
+                TokenKind::LPAREN => {
+                    self.read_token();
+                    let args = self.parse_arguments();
+                    postfix_expr = Node::new_call(cur.position, postfix_expr, args);
+                }
+                TokenKind::LBRACKET => {
+                    self.read_token();
+                    let index = self.parse_expression();
+                    self.expect(TokenKind::RBRACKET);
+                    postfix_expr = Node::new_subscript(cur.position, postfix_expr, index);
+                }
+                TokenKind::INCREMENT | TokenKind::DECREMENT => {
+                    self.read_token();
+                    postfix_expr = Node::new_postfix_node(&cur, postfix_expr);
+                }
+                TokenKind::DOT => {
+                    self.read_token();
+                    let member = self.expect_ident();
+                    postfix_expr = Node::new_member(cur.position, postfix_expr, member);
+                }
+                TokenKind::ARROW => {
+                    self.read_token();
+                    let member = self.expect_ident();
+                    postfix_expr = Node::new_arrow(cur.position, postfix_expr, member);
                 }
-                Node::new_call(cur.position, primary_expr, params)
+                _ => break,
             }
-            // ただのprimary-expressionとしてパース
-            _ => primary_expr,
         }
+        postfix_expr
+    }
+    // argument-expression-list = assign ("," assign)* ")"
+    fn parse_arguments(&mut self) -> Vec<Node> {
+        let mut args: Vec<Node> = Vec::new();
+        loop {
+            if self.consume(TokenKind::RPAREN) {
+                break;
+            }
+
+            args.push(self.parse_assign());
+            self.consume(TokenKind::COMMA);
+        }
+        args
     }
     // primary -> identifier | constant | ( expression ) | string-literal | generic_selection
     fn parse_primary(&mut self) -> Node {
@@ -376,50 +1047,461 @@ impl Manager {
         self.read_token();
         match cur.kind {
             TokenKind::INTEGER(val) => Node::new(cur.position, NodeKind::INTEGER(val)),
-            // TODO: 関数コール等をチェックすべき
+            TokenKind::TYPEDINTEGER(val, ctype) => {
+                let mut n = Node::new(cur.position, NodeKind::INTEGER(val));
+                n.ctype = ctype;
+                n
+            }
+            // 列挙定数は整数定数になる
             TokenKind::IDENTIFIER(name) => {
-                Node::new(cur.position, NodeKind::IDENTIFIER(name.to_string()))
+                if let Some(Binding::ENUMERATOR(value)) = self.lookup_binding(&name) {
+                    return Node::new(cur.position, NodeKind::INTEGER(*value));
+                }
+                let name = self.resolve_name(name);
+                Node::new(cur.position, NodeKind::IDENTIFIER(name))
             }
             TokenKind::LPAREN => {
                 let paren_expr = self.parse_expression();
                 self.expect(TokenKind::RPAREN);
                 paren_expr
             }
+            // 隣接する文字列リテラルは1つに連結する
+            // "ab" "cd" -> "abcd"
+            TokenKind::STRLIT(mut contents) => {
+                while let TokenKind::STRLIT(next) = self.looking_token_clone().kind {
+                    self.read_token();
+                    contents.extend(next);
+                }
+                self.string_literals.push(contents);
+                Node::new(
+                    cur.position,
+                    NodeKind::STRLIT(self.string_literals.len() - 1),
+                )
+            }
+            TokenKind::VASTART | TokenKind::VAARG | TokenKind::VAEND => self.parse_va_builtin(cur),
             // エラーを吐いてINVALIDを返す
             _ => {
                 let err = Error::new(ErrorKind::Parse, cur.position, ErrorMsg::MustBePrimary);
                 err.found();
-                Node::new((0, 0), NodeKind::INVALID)
+                Node::new(Position::new(0, 0), NodeKind::INVALID)
             }
         }
     }
+    // va_start(ap, last) | va_arg(ap, type-name) | va_end(ap)
+    // 関数の形をしているが,呼び出しではなくその場で展開する
+    fn parse_va_builtin(&mut self, cur: Token) -> Node {
+        self.expect(TokenKind::LPAREN);
+        let ap = Box::new(self.parse_assign());
+        let va_node = match cur.kind {
+            TokenKind::VASTART => {
+                // 最後の名前付き引数は,関数の定義から分かるので読み捨てる
+                self.expect(TokenKind::COMMA);
+                let _ = self.parse_assign();
+                Node::new(cur.position, NodeKind::VASTART(ap))
+            }
+            TokenKind::VAARG => {
+                self.expect(TokenKind::COMMA);
+                let type_name = self.parse_type_name();
+                let mut va_arg = Node::new(cur.position, NodeKind::VAARG(ap));
+                va_arg.ctype = type_name;
+                va_arg
+            }
+            _ => Node::new(cur.position, NodeKind::VAEND(ap)),
+        };
+        self.expect(TokenKind::RPAREN);
+        va_node
+    }
+    // declaration-specifiers = (storage-class | type-qualifier)* basetype
+    // storage-class = "typedef" | "extern" | "static" | "auto" | "register"
+    fn consume_declaration_specifiers(&mut self) -> (Option<StorageClass>, Type) {
+        let mut storage_class = None;
+        let mut qualifiers = Qualifiers::default();
+        loop {
+            let cur = self.looking_token_clone();
+            let class = match cur.kind {
+                TokenKind::TYPEDEF => StorageClass::TYPEDEF,
+                TokenKind::EXTERN => StorageClass::EXTERN,
+                TokenKind::STATIC => StorageClass::STATIC,
+                TokenKind::AUTO | TokenKind::REGISTER => StorageClass::AUTO,
+                _ if self.consume_qualifier(&mut qualifiers) => continue,
+                _ => break,
+            };
+            self.read_token();
+            if storage_class.is_some() {
+                let err = Error::new(
+                    ErrorKind::Parse,
+                    cur.position,
+                    ErrorMsg::MultipleStorageClasses,
+                );
+                err.found();
+            }
+            storage_class = Some(class);
+        }
+
+        // 型指定子の無い宣言(static x; など)は int
+        let base_type = self.consume_base_type().unwrap_or_else(Type::new_integer);
+        (storage_class, base_type.qualified(qualifiers))
+    }
+    // basetype = type-qualifier* type-specifier type-qualifier*
+    // 修飾子は型指定子の前後,間のどこにでも書ける e.g. unsigned const char
     fn consume_base_type(&mut self) -> Option<Type> {
-        // TODO: 完全な実装でない
         if !self.is_typename() {
             return None;
         }
+        let mut qualifiers = Qualifiers::default();
+        while self.consume_qualifier(&mut qualifiers) {}
+        let base_type = self.consume_type_specifiers(&mut qualifiers);
+        while self.consume_qualifier(&mut qualifiers) {}
+        Some(base_type.qualified(qualifiers))
+    }
+    // type-specifier = ("void" | "char" | "short" | "int" | "long" | "signed" | "unsigned")+
+    //                | struct-or-union-specifier | enum-specifier | typedef-name | "__builtin_va_list"
+    fn consume_type_specifiers(&mut self, qualifiers: &mut Qualifiers) -> Type {
+        if self.consume(TokenKind::VALIST) {
+            return self.builtin_va_list_type();
+        }
+        match self.looking_token_clone().kind {
+            TokenKind::STRUCT | TokenKind::UNION => return self.parse_record_specifier(),
+            TokenKind::ENUM => return self.parse_enum_specifier(),
+            TokenKind::IDENTIFIER(name) => {
+                if let Some(Binding::TYPEDEF(ty)) = self.lookup_binding(&name) {
+                    let ty = ty.clone();
+                    self.read_token();
+                    return ty;
+                }
+            }
+            _ => {}
+        }
+
+        // 型指定子の並びを読み,組み合わせから型を決定する
+        // long long は long と同じに扱う
+        let mut base_token: Option<Token> = None;
+        let mut long_count = 0;
+        let mut is_unsigned = false;
+        let mut has_sign = false;
+        loop {
+            if self.consume_qualifier(qualifiers) {
+                continue;
+            }
+            let cur = self.looking_token_clone();
+            match cur.kind {
+                TokenKind::UNSIGNED => {
+                    is_unsigned = true;
+                    has_sign = true;
+                }
+                TokenKind::SIGNED => has_sign = true,
+                TokenKind::LONG => {
+                    long_count += 1;
+                    base_token = Some(cur);
+                }
+                // short int, long int などの int は読み飛ばす
+                TokenKind::INT if base_token.is_some() => {}
+                TokenKind::VOID | TokenKind::CHAR | TokenKind::SHORT | TokenKind::INT => {
+                    base_token = Some(cur)
+                }
+                // typedef 名と同じ名前の変数 e.g. unsigned T;
+                _ => break,
+            }
+            self.read_token();
+        }
+
+        let base_type = match base_token {
+            Some(tk) => Type::from_token(tk),
+            // unsigned/signed 単体は int
+            None => Type::new_integer(),
+        };
+        if long_count > 2 || (base_type.kind == TypeKind::VOID && has_sign) {
+            let err = Error::new(
+                ErrorKind::Parse,
+                self.looking_token_clone().position,
+                ErrorMsg::InvalidTypeSpecifier,
+            );
+            err.found();
+        }
+
+        if is_unsigned {
+            base_type.to_unsigned()
+        } else {
+            base_type
+        }
+    }
+    // type-qualifier = "const" | "volatile" | "restrict"
+    fn consume_qualifier(&mut self, qualifiers: &mut Qualifiers) -> bool {
+        match self.looking_token().kind {
+            TokenKind::CONST => qualifiers.is_const = true,
+            TokenKind::VOLATILE => qualifiers.is_volatile = true,
+            TokenKind::RESTRICT => qualifiers.is_restrict = true,
+            _ => return false,
+        }
+        self.read_token();
+        true
+    }
+    // enum-specifier = "enum" ident? ("{" enumerator ("," enumerator)* ","? "}")?
+    // enumerator = ident ("=" conditional)?
+    // 列挙型は int として扱い,列挙定数は現在のスコープに整数定数として登録する
+    fn parse_enum_specifier(&mut self) -> Type {
+        let cur = self.looking_token_clone();
+        self.read_token();
+        let tag = match self.looking_token_clone().kind {
+            TokenKind::IDENTIFIER(name) => {
+                self.read_token();
+                Some(name)
+            }
+            _ => None,
+        };
+
+        if !self.consume(TokenKind::LBRACE) {
+            match tag {
+                Some(tag) => self.find_or_declare_enum_tag(cur.position, tag),
+                None => {
+                    let err = Error::new(
+                        ErrorKind::Parse,
+                        cur.position,
+                        ErrorMsg::InvalidTypeSpecifier,
+                    );
+                    err.found();
+                }
+            }
+            return Type::new_integer();
+        }
+
+        // 値を省略した列挙定数は,直前の値 + 1
+        let mut value = 0;
+        while !self.consume(TokenKind::RBRACE) {
+            let enumerator = self.looking_token_clone();
+            let name = self.expect_ident();
+            if self.consume(TokenKind::ASSIGN) {
+                match Self::eval_constant(&self.parse_conditional()) {
+                    Some(constant) => value = constant,
+                    None => {
+                        let err = Error::new(
+                            ErrorKind::Parse,
+                            enumerator.position,
+                            ErrorMsg::EnumeratorIsNotConstant(name.to_string()),
+                        );
+                        err.found();
+                    }
+                }
+            }
+            if self.current_bindings().contains_key(&name) {
+                let err = Error::new(
+                    ErrorKind::Parse,
+                    enumerator.position,
+                    ErrorMsg::Redefinition(name.to_string()),
+                );
+                err.found();
+            }
+            self.current_bindings()
+                .insert(name, Binding::ENUMERATOR(value));
+            value += 1;
 
-        let int_type = self.looking_token_clone();
+            if !self.consume(TokenKind::COMMA) {
+                self.expect(TokenKind::RBRACE);
+                break;
+            }
+        }
+        // 同じ列挙定数を再び定義すれば,列挙定数の再定義として検出される
+        if let Some(tag) = tag {
+            if let Some(Tag::RECORD(_)) = self.current_tags().get(&tag) {
+                let err = Error::new(
+                    ErrorKind::Parse,
+                    cur.position,
+                    ErrorMsg::WrongKindOfTag(tag),
+                );
+                err.found();
+            } else {
+                self.current_tags().insert(tag, Tag::ENUM);
+            }
+        }
+        Type::new_integer()
+    }
+    // struct-or-union-specifier = ("struct" | "union") ident? ("{" struct-declaration* "}")?
+    fn parse_record_specifier(&mut self) -> Type {
+        let cur = self.looking_token_clone();
         self.read_token();
+        let is_union = cur.kind == TokenKind::UNION;
+        let tag = match self.looking_token_clone().kind {
+            TokenKind::IDENTIFIER(name) => {
+                self.read_token();
+                Some(name)
+            }
+            _ => None,
+        };
+
+        if !self.consume(TokenKind::LBRACE) {
+            if let Some(tag) = tag {
+                // struct S; だけの宣言は,外側の同名のタグを隠す新しい型を宣言する
+                if self.looking_token_clone().kind == TokenKind::SEMICOLON
+                    && !self.current_tags().contains_key(&tag)
+                {
+                    let id = self.declare_record(cur.position, Some(tag), is_union);
+                    return self.records[id].clone();
+                }
+                return self.find_or_declare_tag(cur.position, tag, is_union);
+            }
+            let err = Error::new(
+                ErrorKind::Parse,
+                cur.position,
+                ErrorMsg::InvalidTypeSpecifier,
+            );
+            err.found();
+            return Type::new_unknown();
+        }
+
+        // メンバが自身へのポインタを持てるように,先に不完全型として登録しておく
+        let id = self.declare_record(cur.position, tag.clone(), is_union);
+        let members = self.parse_member_declarations();
+        let record_type = if is_union {
+            Type::union_of(id, tag.clone(), members)
+        } else {
+            Type::struct_of(id, tag.clone(), members)
+        };
+
+        self.records[id] = record_type.clone();
+        if let Some(tag) = tag {
+            self.current_tags()
+                .insert(tag, Tag::RECORD(record_type.clone()));
+        }
+        record_type
+    }
+    // struct-declaration = basetype (declarator ("," declarator)*)? ";"
+    fn parse_member_declarations(&mut self) -> Vec<(String, Type)> {
+        let mut members = Vec::new();
+        while !self.consume(TokenKind::RBRACE) {
+            let base_type = self.consume_base_type().unwrap();
+
+            // タグを持たない構造体/共用体は,名前のないメンバとして埋め込む
+            if self.consume(TokenKind::SEMICOLON) {
+                if base_type
+                    .record()
+                    .is_some_and(|record| record.tag.is_none())
+                {
+                    members.push((String::new(), base_type));
+                }
+                continue;
+            }
 
-        Some(Type::from_token(int_type))
+            loop {
+                members.push(self.parse_declarator(base_type.clone()));
+                if !self.consume(TokenKind::COMMA) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::SEMICOLON);
+        }
+        members
+    }
+    // va_list の構造体は,最初に使われた時にファイルスコープで定義する
+    fn builtin_va_list_type(&mut self) -> Type {
+        let tag = VA_LIST_TAG.to_string();
+        let record_type = match self.file_tags.get(&tag) {
+            Some(Tag::RECORD(record_type)) => record_type.clone(),
+            _ => {
+                let id = self.records.len();
+                let record_type = Type::va_list_record(id);
+                self.records.push(record_type.clone());
+                self.file_tags.insert(tag, Tag::RECORD(record_type.clone()));
+                record_type
+            }
+        };
+        Type::array_of(record_type, 1)
+    }
+    // タグから型を引く.未知のタグであれば現在のスコープに不完全型として宣言する
+    fn find_or_declare_tag(&mut self, position: Position, tag: String, is_union: bool) -> Type {
+        match self.lookup_tag(&tag) {
+            Some(Tag::RECORD(record_type)) if record_type.is_union() == is_union => {
+                record_type.clone()
+            }
+            Some(_) => {
+                let err = Error::new(ErrorKind::Parse, position, ErrorMsg::WrongKindOfTag(tag));
+                err.found();
+                Type::new_unknown()
+            }
+            None => {
+                let id = self.declare_record(position, Some(tag), is_union);
+                self.records[id].clone()
+            }
+        }
+    }
+    // 列挙型のタグは,宣言済みの列挙型か確かめるだけ
+    fn find_or_declare_enum_tag(&mut self, position: Position, tag: String) {
+        match self.lookup_tag(&tag) {
+            Some(Tag::ENUM) => {}
+            Some(Tag::RECORD(_)) => {
+                let err = Error::new(ErrorKind::Parse, position, ErrorMsg::WrongKindOfTag(tag));
+                err.found();
+            }
+            None => {
+                self.current_tags().insert(tag, Tag::ENUM);
+            }
+        }
+    }
+    // 不完全型を現在のスコープに登録してidを返す
+    // 同じスコープで前方宣言されたタグを定義する場合は,同じidを使い回す
+    fn declare_record(&mut self, position: Position, tag: Option<String>, is_union: bool) -> usize {
+        if let Some(tag) = tag.as_ref() {
+            match self.current_tags().get(tag) {
+                Some(Tag::RECORD(declared)) if declared.is_union() == is_union => {
+                    if let Some(record) = declared.record() {
+                        if !record.is_complete {
+                            return record.id;
+                        }
+                    }
+                    let err = Error::new(
+                        ErrorKind::Parse,
+                        position,
+                        ErrorMsg::Redefinition(tag.to_string()),
+                    );
+                    err.found();
+                }
+                Some(_) => {
+                    let err = Error::new(
+                        ErrorKind::Parse,
+                        position,
+                        ErrorMsg::WrongKindOfTag(tag.to_string()),
+                    );
+                    err.found();
+                }
+                None => {}
+            }
+        }
+
+        let id = self.records.len();
+        let incomplete_type = Type::new_incomplete_record(id, tag.clone(), is_union);
+        self.records.push(incomplete_type.clone());
+        if let Some(tag) = tag {
+            self.current_tags()
+                .insert(tag, Tag::RECORD(incomplete_type));
+        }
+        id
     }
     fn is_function(&mut self) -> bool {
         // 現在位置を退避,後で戻す
+        // 先読み中に登録された構造体のタグや列挙定数も取り消す
         let cur_token = self.cur_token;
         let next_token = self.next_token;
+        let file_tags = self.file_tags.clone();
+        let records = self.records.clone();
+        let file_scope = self.file_scope.clone();
         let mut is_func = false;
 
         // 本当は6.9.1 Function definitions に従って正しくチェックする必要あり
-        if let Some(base_type) = self.consume_base_type() {
-            if !self.consume(TokenKind::SEMICOLON) {
-                let (name, _type) = self.parse_declarator(base_type);
-                is_func = (name.len() != 0) && self.consume(TokenKind::LPAREN);
+        if self.is_declaration_specifier() {
+            let (storage_class, base_type) = self.consume_declaration_specifiers();
+            if storage_class != Some(StorageClass::TYPEDEF) && !self.consume(TokenKind::SEMICOLON) {
+                self.declarator_params = None;
+                let (name, dec_type) = self.parse_declarator(base_type);
+                let has_params = matches!(dec_type.kind, TypeKind::FUNCTION(_))
+                    && self.declarator_params.is_some();
+                is_func = !name.is_empty() && (has_params || self.consume(TokenKind::LPAREN));
             }
         }
 
         self.cur_token = cur_token;
         self.next_token = next_token;
+        self.file_tags = file_tags;
+        self.records = records;
+        self.file_scope = file_scope;
         is_func
     }
     fn expect_ident(&mut self) -> String {
@@ -430,12 +1512,40 @@ impl Manager {
 
         panic!("expected typename");
     }
+    // 型名の先頭か.typedef 名は,同じ名前の変数で隠されていなければ型名
     fn is_typename(&mut self) -> bool {
-        match self.looking_token().kind {
-            TokenKind::INT | TokenKind::VOID => true,
+        match self.looking_token_clone().kind {
+            TokenKind::INT
+            | TokenKind::VOID
+            | TokenKind::CHAR
+            | TokenKind::SHORT
+            | TokenKind::LONG
+            | TokenKind::SIGNED
+            | TokenKind::UNSIGNED
+            | TokenKind::STRUCT
+            | TokenKind::UNION
+            | TokenKind::ENUM
+            | TokenKind::CONST
+            | TokenKind::VOLATILE
+            | TokenKind::RESTRICT
+            | TokenKind::VALIST => true,
+            TokenKind::IDENTIFIER(name) => {
+                matches!(self.lookup_binding(&name), Some(Binding::TYPEDEF(_)))
+            }
             _ => false,
         }
     }
+    // 宣言の先頭か
+    fn is_declaration_specifier(&mut self) -> bool {
+        match self.looking_token().kind {
+            TokenKind::TYPEDEF
+            | TokenKind::EXTERN
+            | TokenKind::STATIC
+            | TokenKind::AUTO
+            | TokenKind::REGISTER => true,
+            _ => self.is_typename(),
+        }
+    }
     fn current_token_is_in(&mut self, tks: &Vec<TokenKind>) -> bool {
         for t in tks {
             if &self.looking_token().kind == t {
@@ -446,11 +1556,39 @@ impl Manager {
     }
     fn current_prio_operators(&mut self, priority: Priority) -> Vec<TokenKind> {
         match priority {
+            Priority::LOGICALOR => vec![TokenKind::LOGOR],
+            Priority::LOGICALAND => vec![TokenKind::LOGAND],
+            Priority::BITWISEOR => vec![TokenKind::PIPE],
+            Priority::BITWISEXOR => vec![TokenKind::CARET],
+            Priority::BITWISEAND => vec![TokenKind::AMPERSAND],
+            Priority::EQUALITY => vec![TokenKind::EQUAL, TokenKind::NOTEQUAL],
+            Priority::RELATIONAL => vec![
+                TokenKind::LESS,
+                TokenKind::LESSEQ,
+                TokenKind::GREATER,
+                TokenKind::GREATEREQ,
+            ],
+            Priority::SHIFT => vec![TokenKind::LSHIFT, TokenKind::RSHIFT],
             Priority::ADDITIVE => vec![TokenKind::PLUS, TokenKind::MINUS],
-            // TODO: '%' を足す
-            Priority::MULTIPLICATIVE => vec![TokenKind::ASTERISK, TokenKind::SLASH],
+            Priority::MULTIPLICATIVE => {
+                vec![TokenKind::ASTERISK, TokenKind::SLASH, TokenKind::PERCENT]
+            }
         }
     }
+    fn compound_assign_operators() -> Vec<TokenKind> {
+        vec![
+            TokenKind::PLUSASSIGN,
+            TokenKind::MINUSASSIGN,
+            TokenKind::ASTERISKASSIGN,
+            TokenKind::SLASHASSIGN,
+            TokenKind::PERCENTASSIGN,
+            TokenKind::LSHIFTASSIGN,
+            TokenKind::RSHIFTASSIGN,
+            TokenKind::AMPERSANDASSIGN,
+            TokenKind::PIPEASSIGN,
+            TokenKind::CARETASSIGN,
+        ]
+    }
     fn consume(&mut self, tk: TokenKind) -> bool {
         if self.looking_token_clone().kind != tk {
             return false;
@@ -497,4 +1635,571 @@ impl Manager {
 }
 
 #[cfg(test)]
-mod parser_tests {}
+mod parser_tests {
+    use super::*;
+    use crate::compiler::file::SrcFile;
+    use crate::compiler::frontend::lex;
+    use crate::compiler::frontend::variable::VarKind;
+
+    #[test]
+    fn test_parse_relational_and_logical_precedence() {
+        // a || (b && ((c < 1) == d))
+        let expr = parse_return_expr("int main(){ return a || b && c < 1 == d; }");
+        let (left, right) = match expr.kind {
+            NodeKind::LOGICALOR(left, right) => (left, right),
+            _ => panic!("expected ||, but got {:?}", expr.kind),
+        };
+        assert_eq!(NodeKind::IDENTIFIER("a".to_string()), left.kind);
+
+        let equality = match right.kind {
+            NodeKind::LOGICALAND(_b, equality) => equality,
+            _ => panic!("expected &&, but got {:?}", right.kind),
+        };
+        match equality.kind {
+            NodeKind::EQUAL(left, _d) => {
+                assert!(matches!(left.kind, NodeKind::LESSTHAN(_, _)));
+            }
+            _ => panic!("expected ==, but got {:?}", equality.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_relational_is_left_associative() {
+        // (1 < 2) >= !3
+        let expr = parse_return_expr("int main(){ return 1 < 2 >= !3; }");
+        match expr.kind {
+            NodeKind::GREATERTHANEQUAL(left, right) => {
+                assert!(matches!(left.kind, NodeKind::LESSTHAN(_, _)));
+                assert!(matches!(right.kind, NodeKind::LOGICALNOT(_)));
+            }
+            _ => panic!("expected >=, but got {:?}", expr.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_bitwise_and_shift_precedence() {
+        // (a & (b == c)) | ((1 << (2 + 3)) ^ (~d % 4))
+        let expr = parse_return_expr("int main(){ return a & b == c | 1 << 2 + 3 ^ ~d % 4; }");
+        let (left, right) = match expr.kind {
+            NodeKind::BITOR(left, right) => (left, right),
+            _ => panic!("expected |, but got {:?}", expr.kind),
+        };
+        match left.kind {
+            NodeKind::BITAND(_a, equality) => {
+                assert!(matches!(equality.kind, NodeKind::EQUAL(_, _)));
+            }
+            _ => panic!("expected &, but got {:?}", left.kind),
+        }
+        match right.kind {
+            NodeKind::BITXOR(shift, modulo) => {
+                assert!(matches!(shift.kind, NodeKind::LSHIFT(_, _)));
+                match modulo.kind {
+                    NodeKind::MOD(bitnot, _four) => {
+                        assert!(matches!(bitnot.kind, NodeKind::BITNOT(_)));
+                    }
+                    _ => panic!("expected %, but got {:?}", modulo.kind),
+                }
+            }
+            _ => panic!("expected ^, but got {:?}", right.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_compound_assign_and_comma() {
+        // (a -= 2), ((a++) + (++b))
+        let expr = parse_return_expr("int main(){ return a -= 2, a++ + ++b; }");
+        let (left, right) = match expr.kind {
+            NodeKind::COMMA(left, right) => (left, right),
+            _ => panic!("expected ',', but got {:?}", expr.kind),
+        };
+        match left.kind {
+            NodeKind::COMPOUNDASSIGN(binary) => {
+                assert!(matches!(binary.kind, NodeKind::SUB(_, _)));
+            }
+            _ => panic!("expected -=, but got {:?}", left.kind),
+        }
+        match right.kind {
+            NodeKind::ADD(post, pre) => {
+                assert!(matches!(post.kind, NodeKind::POSTINCREMENT(_)));
+                assert_eq!("b += 1", pre.to_string());
+            }
+            _ => panic!("expected +, but got {:?}", right.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_conditional_is_right_associative() {
+        // a = (b || c) ? (1, 2) : (d ? 3 : 4)
+        let expr = parse_return_expr("int main(){ return a = b || c ? 1, 2 : d ? 3 : 4; }");
+        let rvalue = match expr.kind {
+            NodeKind::ASSIGN(_a, rvalue) => rvalue,
+            _ => panic!("expected =, but got {:?}", expr.kind),
+        };
+        match rvalue.kind {
+            NodeKind::CONDITIONAL(cond, then, alter) => {
+                assert!(matches!(cond.kind, NodeKind::LOGICALOR(_, _)));
+                assert!(matches!(then.kind, NodeKind::COMMA(_, _)));
+                assert!(matches!(alter.kind, NodeKind::CONDITIONAL(_, _, _)));
+            }
+            _ => panic!("expected ?:, but got {:?}", rvalue.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_address_of_and_dereference() {
+        // *p = *&x + 1
+        let expr = parse_return_expr("int main(){ return *p = *&x + 1; }");
+        match expr.kind {
+            NodeKind::ASSIGN(lvalue, rvalue) => {
+                assert!(matches!(lvalue.kind, NodeKind::DEREFERENCE(_)));
+                match rvalue.kind {
+                    NodeKind::ADD(left, _one) => match left.kind {
+                        NodeKind::DEREFERENCE(inner) => {
+                            assert!(matches!(inner.kind, NodeKind::ADDRESSOF(_)));
+                        }
+                        _ => panic!("expected *, but got {:?}", left.kind),
+                    },
+                    _ => panic!("expected +, but got {:?}", rvalue.kind),
+                }
+            }
+            _ => panic!("expected =, but got {:?}", expr.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_subscript_and_sizeof() {
+        // *(*(m + 1) + i) + sizeof(int *[3])
+        let expr = parse_return_expr("int main(){ return m[1][i] + sizeof(int *[3]); }");
+        let (left, right) = match expr.kind {
+            NodeKind::ADD(left, right) => (left, right),
+            _ => panic!("expected +, but got {:?}", expr.kind),
+        };
+        assert_eq!("**m + 1 + i", left.to_string());
+        assert_eq!(NodeKind::INTEGER(24), right.kind);
+
+        // 式に対する sizeof は意味解析まで残す
+        let expr = parse_return_expr("int main(){ return sizeof (a)[0]; }");
+        assert!(matches!(expr.kind, NodeKind::SIZEOF(_)));
+    }
+
+    #[test]
+    fn test_parse_array_declarator() {
+        let input = "int main(){ int a[2][3]; int (*p)[3]; return 0; }";
+        let manager = parse_input(input);
+        let local_map = &manager.functions[0].local_map;
+
+        let row_type = Type::array_of(Type::new_integer(), 3);
+        assert_eq!(
+            Type::array_of(row_type.clone(), 2),
+            local_map.get("a").unwrap().ctype
+        );
+        assert_eq!(24, local_map.get("a").unwrap().ctype.byte_size);
+        assert_eq!(
+            Type::pointer_to(row_type),
+            local_map.get("p").unwrap().ctype
+        );
+    }
+
+    #[test]
+    fn test_parse_function_pointer_declarator() {
+        let input = "int main(){ int (*fp)(int, char *); long (*ops[2])(); int (*cb)(int (*)(void), ...); return fp(1, 0); }";
+        let manager = parse_input(input);
+        let local_map = &manager.functions[0].local_map;
+
+        let fp_type = Type::pointer_to(Type::function_of(Signature {
+            return_type: Type::new_integer(),
+            param_types: Some(vec![
+                Type::new_integer(),
+                Type::pointer_to(Type::new_char()),
+            ]),
+            is_variadic: false,
+        }));
+        assert_eq!(fp_type, local_map.get("fp").unwrap().ctype);
+
+        let op_type = Type::pointer_to(Type::function_of(Signature {
+            return_type: Type::new_long(),
+            param_types: None,
+            is_variadic: false,
+        }));
+        assert_eq!(
+            Type::array_of(op_type, 2),
+            local_map.get("ops").unwrap().ctype
+        );
+
+        // 抽象宣言子の関数ポインタを引数に取る,可変長引数の関数へのポインタ
+        let cb_type = local_map.get("cb").unwrap().ctype.clone();
+        let cb_signature = cb_type.pointed_signature().unwrap();
+        assert!(cb_signature.is_variadic);
+        assert_eq!(
+            "Ptr<Fn<int()>>",
+            cb_signature.param_types.as_ref().unwrap()[0].to_string()
+        );
+
+        // 関数ポインタの式も呼び出せる
+        assert_eq!(
+            "return fp(1, 0);",
+            manager.functions[0].stmts[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_integer_type_specifiers() {
+        let input = "int main(){ unsigned char a; short int b; long long c; unsigned d; signed char e; long unsigned int f; return 0; }";
+        let manager = parse_input(input);
+        let local_map = &manager.functions[0].local_map;
+
+        let expected = vec![
+            ("a", Type::new_char().to_unsigned()),
+            ("b", Type::new_short()),
+            ("c", Type::new_long()),
+            ("d", Type::new_integer().to_unsigned()),
+            ("e", Type::new_char()),
+            ("f", Type::new_long().to_unsigned()),
+        ];
+        for (name, ty) in expected.iter() {
+            assert_eq!(ty, &local_map.get(*name).unwrap().ctype);
+        }
+    }
+
+    #[test]
+    fn test_parse_member_access() {
+        // (s.a) + ((*p).b)
+        let expr = parse_return_expr("int main(){ return s.a + p->b; }");
+        match expr.kind {
+            NodeKind::ADD(left, right) => {
+                assert_eq!(
+                    NodeKind::MEMBER(
+                        Box::new(Node::new(
+                            Position::new(1, 20),
+                            NodeKind::IDENTIFIER("s".to_string())
+                        )),
+                        "a".to_string()
+                    ),
+                    left.kind
+                );
+                match right.kind {
+                    NodeKind::MEMBER(pointer, member) => {
+                        assert!(matches!(pointer.kind, NodeKind::DEREFERENCE(_)));
+                        assert_eq!("b", member);
+                    }
+                    _ => panic!("expected ->, but got {:?}", right.kind),
+                }
+            }
+            _ => panic!("expected +, but got {:?}", expr.kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_struct_declaration() {
+        let input = "struct pair { char c; long l; }; int main(){ struct list; struct pair p; struct list *q; struct list { int v; struct list *next; } l; return 0; }";
+        let manager = parse_input(input);
+        let local_map = &manager.functions[0].local_map;
+
+        // 前方宣言したタグは,同じスコープの後の定義と同じ型になる
+        let list_type = &local_map.get("l").unwrap().ctype;
+        assert_eq!(16, list_type.byte_size);
+        assert_eq!(
+            Type::pointer_to(list_type.clone()),
+            local_map.get("q").unwrap().ctype
+        );
+
+        let pair_type = &local_map.get("p").unwrap().ctype;
+        assert_eq!(16, pair_type.byte_size);
+        assert_eq!(8, pair_type.find_member("l").unwrap().offset);
+    }
+
+    #[test]
+    fn test_parse_block_scope_tags() {
+        let input = "struct s { int a; }; enum e { A }; struct s *p; int main(){ struct s { long a; long b; } x; struct s *q; enum e y; { struct s; struct s *r; } return 0; }";
+        let manager = parse_input(input);
+        let local_map = &manager.functions[0].local_map;
+
+        // ブロック内で定義したタグは,ファイルスコープの同名のタグを隠す
+        let outer_type = match manager.file_tags.get("s") {
+            Some(Tag::RECORD(record_type)) => record_type.clone(),
+            tag => panic!("expected struct s, but got {:?}", tag),
+        };
+        let inner_type = local_map.get("x").unwrap().ctype.clone();
+        assert_eq!(4, outer_type.byte_size);
+        assert_eq!(16, inner_type.byte_size);
+        assert_eq!(
+            Type::pointer_to(outer_type.clone()),
+            manager.global_map.get("p").unwrap().ctype
+        );
+        assert_eq!(
+            Type::pointer_to(inner_type.clone()),
+            local_map.get("q").unwrap().ctype
+        );
+
+        // struct s; だけの宣言は,新しい不完全型を宣言する
+        let r_type = &local_map.get("r.1").unwrap().ctype;
+        assert_ne!(Type::pointer_to(inner_type), *r_type);
+        assert_ne!(Type::pointer_to(outer_type), *r_type);
+
+        // 列挙型のタグは構造体と別に扱い,ブロックを抜けるとタグも捨てる
+        assert_eq!(Some(&Tag::ENUM), manager.file_tags.get("e"));
+        assert_eq!(Type::new_integer(), local_map.get("y").unwrap().ctype);
+        assert!(manager.tag_scopes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_global_variables() {
+        let input =
+            "int a, *b = 0; struct pair { int x; int y; } p; long c[4]; int main(){ return a; }";
+        let manager = parse_input(input);
+
+        assert_eq!(4, manager.global_map.len());
+        let b = manager.global_map.get("b").unwrap();
+        assert_eq!(VarKind::GLOBAL(None), b.kind);
+        assert_eq!(Type::pointer_to(Type::new_integer()), b.ctype);
+        assert_eq!(8, manager.global_map.get("p").unwrap().ctype.byte_size);
+        assert_eq!(32, manager.global_map.get("c").unwrap().ctype.byte_size);
+
+        // 初期化式は意味解析まで保持しておく
+        assert_eq!(1, manager.global_initializers.len());
+        assert!(manager.global_initializers.contains_key("b"));
+
+        // グローバル変数の後の関数定義もパースできる
+        assert_eq!(1, manager.functions.len());
+    }
+
+    #[test]
+    fn test_parse_string_literals() {
+        let input = "int main(){ return \"ab\" \"c\"[1] + \"\"[0]; }";
+        let manager = parse_input(input);
+
+        // 隣接するリテラルは連結され,それぞれ番号が振られる
+        assert_eq!(vec![b"abc".to_vec(), Vec::new()], manager.string_literals);
+        match manager.functions[0].stmts[0].kind.clone() {
+            NodeKind::RETURNSTMT(expr) => {
+                assert_eq!("*.LC0 + 1 + *.LC1 + 0", expr.to_string())
+            }
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_return_without_value() {
+        let input = "void f(int n){ if (n) return; n = 1; }";
+        let manager = parse_input(input);
+
+        match manager.functions[0].stmts[0].kind.clone() {
+            NodeKind::IFSTMT(_cond, stmt) => {
+                assert_eq!(NodeKind::RETURNSTMT(Box::new(Node::new_nop())), stmt.kind)
+            }
+            _ => panic!("expected if statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_variadic_function() {
+        let input = "int f(int n, ...); int g(int n, ...){ __builtin_va_list ap; __builtin_va_start(ap, n); return __builtin_va_arg(ap, long); }";
+        let manager = parse_input(input);
+
+        assert!(manager.signatures.get("f").unwrap().is_variadic);
+        assert!(manager.functions[0].is_variadic);
+        assert_eq!("va_start(ap);", manager.functions[0].stmts[1].to_string());
+        assert_eq!(
+            "return va_arg(ap, long);",
+            manager.functions[0].stmts[2].to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_switch_statement() {
+        let input =
+            "int main(){ int x; switch (x) { case 1 + 1: return 2; default: case 3: return 0; } }";
+        let manager = parse_input(input);
+
+        match manager.functions[0].stmts[1].kind.clone() {
+            NodeKind::SWITCHSTMT(expr, stmt, cases, has_default) => {
+                assert_eq!("x", expr.to_string());
+                assert_eq!(
+                    "{\n    case 1 + 1: return 2;\n    default: case 3: return 0;\n  }\n",
+                    stmt.to_string()
+                );
+                // case の値は意味解析で集める
+                assert!(cases.is_empty());
+                assert!(!has_default);
+            }
+            _ => panic!("expected switch statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_break_and_continue() {
+        let input = "int main(){ while (1) { continue; break; } return 0; }";
+        let manager = parse_input(input);
+
+        match manager.functions[0].stmts[0].kind.clone() {
+            NodeKind::WHILESTMT(_expr, stmt) => {
+                assert_eq!("{\n    continue;\n    break;\n  }\n", stmt.to_string());
+            }
+            _ => panic!("expected while statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_block_scopes() {
+        let input = "int x; int main(int a){ int b; { int a; int x; x = a; } for (int b = 0; b < 2; b++) { int a; } return x + b; }";
+        let manager = parse_input(input);
+        let func = &manager.functions[0];
+
+        // 内側のブロックの宣言は,それぞれ別の名前で登録される
+        let names: Vec<&String> = func.local_map.keys().collect();
+        assert_eq!(vec!["a.1", "a.2", "b", "b.1", "x.1"], names);
+
+        match &func.stmts[1].kind {
+            NodeKind::COMPOUNDSTMT(stmts) => assert_eq!("x.1 = a.1;", stmts[2].to_string()),
+            _ => panic!("expected compound statement"),
+        }
+
+        // for 文の節の宣言は,for 文を囲むブロックに置かれる
+        match &func.stmts[2].kind {
+            NodeKind::COMPOUNDSTMT(stmts) => {
+                match &stmts[0].kind {
+                    NodeKind::DECLARATION(name, ty, Some(init)) => {
+                        assert_eq!("b.1", name);
+                        assert_eq!(Type::new_integer(), *ty);
+                        assert_eq!("0", init.to_string());
+                    }
+                    _ => panic!("expected declaration with initializer"),
+                }
+                match &stmts[1].kind {
+                    NodeKind::FORSTMT(clause, _, _, _) => assert_eq!("nop", clause.to_string()),
+                    _ => panic!("expected for statement"),
+                }
+            }
+            _ => panic!("expected compound statement"),
+        }
+
+        // スコープを抜けた後はグローバル変数/外側の変数を指す
+        assert_eq!("return x + b;", func.stmts[3].to_string());
+    }
+
+    #[test]
+    fn test_parse_initializers() {
+        let input = "int main(){ int a = 1, b[3] = {1, [2] = a,}, c; struct { int x; int y; } p = {.y = 2}; return a; }";
+        let manager = parse_input(input);
+        let func = &manager.functions[0];
+
+        // 複数の宣言子は,宣言を並べた複文になる
+        match &func.stmts[0].kind {
+            NodeKind::COMPOUNDSTMT(stmts) => {
+                assert_eq!(3, stmts.len());
+                assert_eq!("int a = 1;", stmts[0].to_string());
+                assert_eq!("Array<int, 3> b = {1, [2] = a};", stmts[1].to_string());
+                assert_eq!("int c;", stmts[2].to_string());
+            }
+            _ => panic!("expected compound statement"),
+        }
+        assert_eq!(
+            "struct <anonymous> p = {.y = 2};",
+            func.stmts[1].to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_prototypes_and_extern() {
+        let input = "int f(int, char *p); long g(); int h(void); extern int x; int main(){ return f(x, 0); } int f(int a, char *p) { return a; }";
+        let manager = parse_input(input);
+
+        // プロトタイプは関数の型だけを登録し,本体を持つ関数だけが並ぶ
+        let names: Vec<&String> = manager.functions.iter().map(|f| &f.name).collect();
+        assert_eq!(vec!["main", "f"], names);
+        assert_eq!(vec!["a", "p"], manager.functions[1].param_names);
+
+        let f = manager.signatures.get("f").unwrap();
+        assert_eq!(
+            Some(vec![
+                Type::new_integer(),
+                Type::pointer_to(Type::new_char())
+            ]),
+            f.param_types
+        );
+        assert_eq!(None, manager.signatures.get("g").unwrap().param_types);
+        assert_eq!(
+            Some(Vec::new()),
+            manager.signatures.get("h").unwrap().param_types
+        );
+
+        // extern 宣言は領域を確保しない
+        assert_eq!(VarKind::EXTERN, manager.global_map.get("x").unwrap().kind);
+    }
+
+    #[test]
+    fn test_parse_typedef_and_enum() {
+        let input = "typedef int T; typedef struct { T x; } S; enum E { A, B = 5, C }; T a[C]; int main(){ S s; typedef char T; T c; int S = B; return A + C; }";
+        let manager = parse_input(input);
+
+        // 省略した列挙定数の値は直前の値 + 1 で,配列の要素数にも使える
+        let a = manager.global_map.get("a").unwrap();
+        assert_eq!(Type::array_of(Type::new_integer(), 6), a.ctype);
+
+        // ブロック内の typedef はファイルスコープの typedef を隠す
+        // typedef 名と同じ名前の変数も宣言できる
+        let local_map = &manager.functions[0].local_map;
+        assert_eq!(
+            Type::new_integer(),
+            local_map
+                .get("s")
+                .unwrap()
+                .ctype
+                .find_member("x")
+                .unwrap()
+                .ctype
+        );
+        assert_eq!(Type::new_char(), local_map.get("c").unwrap().ctype);
+        assert_eq!(Type::new_integer(), local_map.get("S").unwrap().ctype);
+
+        // 列挙定数は整数定数になる
+        let stmts = &manager.functions[0].stmts;
+        assert_eq!("int S = 5;", stmts[stmts.len() - 2].to_string());
+        assert_eq!("return 0 + 6;", stmts[stmts.len() - 1].to_string());
+    }
+
+    #[test]
+    fn test_parse_qualifiers_and_storage_classes() {
+        let input = "static int g; const int *p; int *const q; unsigned const char uc; int main(){ static int n = 1; register int r; extern int e; return n + r + e; } static int f(void); int f(void) { return 0; }";
+        let manager = parse_input(input);
+
+        // * の前の修飾子は指す先の型に,後ろの修飾子はポインタ自身に付く
+        let type_name = |name: &str| manager.global_map.get(name).unwrap().ctype.to_string();
+        assert_eq!("Ptr<const int>", type_name("p"));
+        assert_eq!("const Ptr<int>", type_name("q"));
+        assert_eq!("const unsigned char", type_name("uc"));
+
+        // static な局所変数は,番号を付けた名前で静的な領域に置く
+        assert!(manager.global_map.get("g").unwrap().is_static);
+        assert!(manager.global_map.get("n.1").unwrap().is_static);
+        assert!(manager.global_initializers.contains_key("n.1"));
+        assert_eq!(VarKind::EXTERN, manager.global_map.get("e").unwrap().kind);
+
+        let func = &manager.functions[0];
+        assert_eq!(vec!["r"], func.local_map.keys().collect::<Vec<&String>>());
+        assert_eq!("return n.1 + r + e;", func.stmts[3].to_string());
+
+        // static で宣言した関数は,定義で static を省略しても内部結合
+        assert!(!func.is_static);
+        assert!(manager.functions[1].is_static);
+    }
+
+    fn parse_return_expr(input: &str) -> Node {
+        let manager = parse_input(input);
+        match manager.functions[0].stmts[0].kind.clone() {
+            NodeKind::RETURNSTMT(expr) => *expr,
+            _ => panic!("expected return statement"),
+        }
+    }
+    fn parse_input(input: &str) -> Manager {
+        let src_file = SrcFile {
+            abs_path: "test.c".to_string(),
+            contents: input.to_string(),
+        };
+        let mut manager = Manager::new(src_file);
+        manager.preprocessed = input.to_string();
+        lex::tokenize(&mut manager);
+        manager.parse();
+        manager
+    }
+}
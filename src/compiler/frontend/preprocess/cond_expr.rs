@@ -0,0 +1,409 @@
+use crate::compiler::frontend::preprocess::pp_token::{PPToken, PPTokenKind};
+use crate::compiler::frontend::preprocess::Preprocessor;
+use crate::compiler::frontend::token::Position;
+use crate::error::ErrorMsg;
+
+impl Preprocessor {
+    // #if/#elif の制御式を評価する
+    pub fn evaluate_condition(&mut self, line: Vec<PPToken>, pos: Position) -> bool {
+        // defined はマクロ展開前に置き換える
+        let line = self.replace_defined(line);
+        let line = self.expand_all(line);
+
+        // 展開後に残った識別子は0として扱う
+        let line: Vec<PPToken> = line
+            .into_iter()
+            .map(|mut t| {
+                if t.is_identifier() {
+                    t.kind = PPTokenKind::NUMBER;
+                    t.text = "0".to_string();
+                }
+                t
+            })
+            .collect();
+
+        let mut evaluator = ConstExprEvaluator::new(line);
+        match evaluator.evaluate() {
+            Ok(value) => value.is_true(),
+            Err(msg) => {
+                self.error(pos, msg);
+                false
+            }
+        }
+    }
+
+    fn replace_defined(&mut self, line: Vec<PPToken>) -> Vec<PPToken> {
+        let mut result = Vec::new();
+        let mut iter = line.into_iter().peekable();
+        while let Some(t) = iter.next() {
+            if !t.is("defined") {
+                result.push(t);
+                continue;
+            }
+
+            let has_paren = iter.peek().is_some_and(|n| n.is("("));
+            if has_paren {
+                iter.next();
+            }
+            let name = iter.next().filter(|n| n.is_identifier());
+            if has_paren && !iter.next().is_some_and(|n| n.is(")")) {
+                let pos = self.presumed_position(t.file, t.position);
+                self.error(pos, ErrorMsg::InvalidConstantExpression);
+            }
+
+            let defined = match name {
+                Some(name) => self.macros.contains_key(&name.text),
+                None => {
+                    let pos = self.presumed_position(t.file, t.position);
+                    self.error(pos, ErrorMsg::MacroNameMustBeIdentifier);
+                    false
+                }
+            };
+            let mut number = t.clone();
+            number.kind = PPTokenKind::NUMBER;
+            number.text = if defined { "1" } else { "0" }.to_string();
+            result.push(number);
+        }
+        result
+    }
+}
+
+// 整数定数式の評価器.値は intmax_t(i64) か uintmax_t(u64) として扱う.
+struct ConstExprEvaluator {
+    tokens: Vec<PPToken>,
+    cur: usize,
+    // 短絡評価で値を使わない部分を読んでいる間は,0除算をエラーにしない
+    skipping: bool,
+}
+
+// 整数定数式の値.符号なしの場合もビット列は i64 に入れておく
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Value {
+    bits: i64,
+    is_unsigned: bool,
+}
+
+impl Value {
+    fn signed(bits: i64) -> Self {
+        Self {
+            bits,
+            is_unsigned: false,
+        }
+    }
+    fn unsigned(bits: i64) -> Self {
+        Self {
+            bits,
+            is_unsigned: true,
+        }
+    }
+    fn is_true(&self) -> bool {
+        self.bits != 0
+    }
+}
+
+type EvalResult = Result<Value, ErrorMsg>;
+
+impl ConstExprEvaluator {
+    fn new(tokens: Vec<PPToken>) -> Self {
+        Self {
+            tokens,
+            cur: 0,
+            skipping: false,
+        }
+    }
+
+    fn evaluate(&mut self) -> EvalResult {
+        let value = self.conditional()?;
+        if self.cur != self.tokens.len() {
+            return Err(ErrorMsg::InvalidConstantExpression);
+        }
+        Ok(value)
+    }
+
+    fn conditional(&mut self) -> EvalResult {
+        let cond = self.binary(0)?;
+        if !self.consume("?") {
+            return Ok(cond);
+        }
+        // 選ばれない方の式も構文は確かめるが,評価はしない
+        let then = self.skip_unless(cond.is_true(), Self::conditional)?;
+        if !self.consume(":") {
+            return Err(ErrorMsg::InvalidConstantExpression);
+        }
+        let els = self.skip_unless(!cond.is_true(), Self::conditional)?;
+
+        // 2つの値は通常の算術型変換で揃える
+        let is_unsigned = then.is_unsigned || els.is_unsigned;
+        let bits = if cond.is_true() { then.bits } else { els.bits };
+        Ok(Value { bits, is_unsigned })
+    }
+
+    // 優先順位の低い順に並べた二項演算子
+    fn binary(&mut self, level: usize) -> EvalResult {
+        const LEVELS: [&[&str]; 10] = [
+            &["||"],
+            &["&&"],
+            &["|"],
+            &["^"],
+            &["&"],
+            &["==", "!="],
+            &["<", "<=", ">", ">="],
+            &["<<", ">>"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut lhs = self.binary(level + 1)?;
+        loop {
+            let op = match self.tokens.get(self.cur) {
+                Some(t) if LEVELS[level].iter().any(|op| t.is(op)) => t.text.to_string(),
+                _ => return Ok(lhs),
+            };
+            self.cur += 1;
+
+            // && と || は,左辺で結果が決まれば右辺を評価しない
+            let rhs = match op.as_str() {
+                "||" => self.skip_unless(!lhs.is_true(), |e| e.binary(level + 1))?,
+                "&&" => self.skip_unless(lhs.is_true(), |e| e.binary(level + 1))?,
+                _ => self.binary(level + 1)?,
+            };
+            lhs = self.apply(&op, lhs, rhs)?;
+        }
+    }
+
+    fn apply(&self, op: &str, lhs: Value, rhs: Value) -> EvalResult {
+        // シフト以外の二項演算は,どちらかが符号なしなら符号なしで計算する
+        let is_unsigned = lhs.is_unsigned || rhs.is_unsigned;
+        let (l, r) = (lhs.bits, rhs.bits);
+        let (ul, ur) = (l as u64, r as u64);
+        let compare = |signed: bool, unsigned: bool| {
+            Value::signed((if is_unsigned { unsigned } else { signed }) as i64)
+        };
+        let arith = |bits: i64| Value { bits, is_unsigned };
+
+        let value = match op {
+            "||" => Value::signed((lhs.is_true() || rhs.is_true()) as i64),
+            "&&" => Value::signed((lhs.is_true() && rhs.is_true()) as i64),
+            "|" => arith(l | r),
+            "^" => arith(l ^ r),
+            "&" => arith(l & r),
+            "==" => Value::signed((l == r) as i64),
+            "!=" => Value::signed((l != r) as i64),
+            "<" => compare(l < r, ul < ur),
+            "<=" => compare(l <= r, ul <= ur),
+            ">" => compare(l > r, ul > ur),
+            ">=" => compare(l >= r, ul >= ur),
+            // シフトの結果は左辺の型になる
+            "<<" => Value {
+                bits: l.wrapping_shl(r as u32),
+                is_unsigned: lhs.is_unsigned,
+            },
+            ">>" if lhs.is_unsigned => Value::unsigned(ul.wrapping_shr(r as u32) as i64),
+            ">>" => Value::signed(l.wrapping_shr(r as u32)),
+            "+" => arith(l.wrapping_add(r)),
+            "-" => arith(l.wrapping_sub(r)),
+            "*" => arith(l.wrapping_mul(r)),
+            "/" | "%" if r == 0 => {
+                if self.skipping {
+                    return Ok(arith(0));
+                }
+                return Err(ErrorMsg::DivisionByZeroInConstantExpression);
+            }
+            "/" if is_unsigned => arith((ul / ur) as i64),
+            "/" => arith(l.wrapping_div(r)),
+            _ if is_unsigned => arith((ul % ur) as i64),
+            _ => arith(l.wrapping_rem(r)),
+        };
+        Ok(value)
+    }
+
+    // evaluated が偽なら,値を使わない部分式として読む
+    fn skip_unless(
+        &mut self,
+        evaluated: bool,
+        parse: impl FnOnce(&mut Self) -> EvalResult,
+    ) -> EvalResult {
+        let skipping = self.skipping;
+        self.skipping = skipping || !evaluated;
+        let value = parse(self);
+        self.skipping = skipping;
+        value
+    }
+
+    fn unary(&mut self) -> EvalResult {
+        if self.consume("+") {
+            return self.unary();
+        }
+        if self.consume("-") {
+            let value = self.unary()?;
+            return Ok(Value {
+                bits: value.bits.wrapping_neg(),
+                ..value
+            });
+        }
+        if self.consume("~") {
+            let value = self.unary()?;
+            return Ok(Value {
+                bits: !value.bits,
+                ..value
+            });
+        }
+        if self.consume("!") {
+            return Ok(Value::signed(!self.unary()?.is_true() as i64));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> EvalResult {
+        if self.consume("(") {
+            let value = self.conditional()?;
+            if !self.consume(")") {
+                return Err(ErrorMsg::InvalidConstantExpression);
+            }
+            return Ok(value);
+        }
+
+        let t = match self.tokens.get(self.cur) {
+            Some(t) => t.clone(),
+            None => return Err(ErrorMsg::InvalidConstantExpression),
+        };
+        self.cur += 1;
+        match t.kind {
+            PPTokenKind::NUMBER => parse_integer(&t.text),
+            PPTokenKind::CHARACTER => parse_character(&t.text).map(Value::signed),
+            _ => Err(ErrorMsg::InvalidConstantExpression),
+        }
+    }
+
+    fn consume(&mut self, s: &str) -> bool {
+        match self.tokens.get(self.cur) {
+            Some(t) if t.is(s) => {
+                self.cur += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// 整数定数.接尾辞 u が付くか,intmax_t に収まらなければ符号なし
+fn parse_integer(text: &str) -> EvalResult {
+    let digits = text.trim_end_matches(|c| "uUlL".contains(c));
+    let has_unsigned_suffix = text[digits.len()..].contains(['u', 'U']);
+    let (radix, digits) = if digits.starts_with("0x") || digits.starts_with("0X") {
+        (16, &digits[2..])
+    } else if digits.starts_with("0b") || digits.starts_with("0B") {
+        (2, &digits[2..])
+    } else if digits.starts_with('0') && digits.len() > 1 {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+
+    match u64::from_str_radix(digits, radix) {
+        Ok(value) => Ok(Value {
+            bits: value as i64,
+            is_unsigned: has_unsigned_suffix || value > i64::MAX as u64,
+        }),
+        Err(_) => Err(ErrorMsg::InvalidConstantExpression),
+    }
+}
+
+// 文字定数
+fn parse_character(text: &str) -> Result<i64, ErrorMsg> {
+    let body = text.trim_start_matches(|c| c != '\'');
+    let chars: Vec<char> = body.chars().collect();
+    if chars.len() < 3 {
+        return Err(ErrorMsg::InvalidConstantExpression);
+    }
+    if chars[1] != '\\' {
+        return Ok(chars[1] as i64);
+    }
+
+    let value = match chars[2] {
+        'n' => 10,
+        't' => 9,
+        'r' => 13,
+        '0'..='7' => {
+            let octal: String = chars[2..]
+                .iter()
+                .take_while(|c| c.is_digit(8))
+                .take(3)
+                .collect();
+            i64::from_str_radix(&octal, 8).unwrap()
+        }
+        'x' => {
+            let hex: String = chars[3..]
+                .iter()
+                .take_while(|c| c.is_ascii_hexdigit())
+                .collect();
+            match i64::from_str_radix(&hex, 16) {
+                Ok(value) => value,
+                Err(_) => return Err(ErrorMsg::InvalidConstantExpression),
+            }
+        }
+        'a' => 7,
+        'b' => 8,
+        'f' => 12,
+        'v' => 11,
+        'e' => 27,
+        c => c as i64,
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod cond_expr_tests {
+    use super::*;
+    use crate::compiler::frontend::preprocess::pp_token::PPLexer;
+
+    #[test]
+    fn test_evaluate_with_precedence() {
+        assert_eq!(Ok(7), evaluate_test_expr("1 + 2 * 3"));
+        assert_eq!(Ok(1), evaluate_test_expr("1 << 4 == 16 && !(3 < 2)"));
+        assert_eq!(Ok(5), evaluate_test_expr("0 ? 3 : 1 ? 5 : 6"));
+        assert_eq!(Ok(-4), evaluate_test_expr("~3"));
+        assert_eq!(Ok(10), evaluate_test_expr("0x8 | 010 >> 2"));
+        assert_eq!(Ok(98), evaluate_test_expr("'a' + 1L"));
+        assert_eq!(Ok(10), evaluate_test_expr("'\\n'"));
+    }
+
+    #[test]
+    fn test_evaluate_invalid_expression() {
+        assert!(evaluate_test_expr("1 +").is_err());
+        assert!(evaluate_test_expr("(1").is_err());
+        assert!(evaluate_test_expr("4 / 0").is_err());
+        assert!(evaluate_test_expr("1 || 4 / 0 +").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_short_circuit() {
+        // 値を使わない側の0除算はエラーにしない
+        assert_eq!(Ok(0), evaluate_test_expr("0 && 10 / 0 > 1"));
+        assert_eq!(Ok(1), evaluate_test_expr("1 || 10 % 0"));
+        assert_eq!(Ok(3), evaluate_test_expr("1 ? 3 : 1 / 0"));
+        assert_eq!(Ok(4), evaluate_test_expr("0 ? 1 / 0 : 4"));
+        assert_eq!(Ok(0), evaluate_test_expr("0 && (1 || 2 / 0)"));
+        assert!(evaluate_test_expr("1 && 10 / 0").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_unsigned_promotion() {
+        assert_eq!(Ok(1), evaluate_test_expr("-1 > 0u"));
+        assert_eq!(Ok(0), evaluate_test_expr("-1 > 0"));
+        assert_eq!(Ok(1), evaluate_test_expr("18446744073709551615u > 0"));
+        assert_eq!(Ok(1), evaluate_test_expr("0xffffffffffffffff > 0"));
+        assert_eq!(Ok(1), evaluate_test_expr("(0 ? 1u : -1) > 0"));
+        assert_eq!(Ok(i64::MAX), evaluate_test_expr("-1u >> 1"));
+        assert_eq!(Ok(-1), evaluate_test_expr("-1 >> 1"));
+        assert_eq!(Ok(0x7fffffffffffffff), evaluate_test_expr("-2u / 2"));
+    }
+
+    fn evaluate_test_expr(expr: &str) -> Result<i64, ()> {
+        let mut evaluator = ConstExprEvaluator::new(PPLexer::new(expr, 0).tokenize());
+        evaluator.evaluate().map(|value| value.bits).map_err(|_| ())
+    }
+}
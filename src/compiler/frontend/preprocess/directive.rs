@@ -0,0 +1,410 @@
+use crate::compiler::frontend::preprocess::macros::{Macro, MacroKind};
+use crate::compiler::frontend::preprocess::pp_token::{PPToken, PPTokenKind};
+use crate::compiler::frontend::preprocess::{read_line, LineMark, Preprocessor};
+use crate::compiler::frontend::token::Position;
+use crate::error::ErrorMsg;
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+// #include のネストの上限
+const INCLUDE_DEPTH_LIMIT: usize = 200;
+
+pub struct CondIncl {
+    pub context: CondContext,
+    pub included: bool, // これまでのグループのいずれかを取り込んだか
+    pub file: usize,
+    pub position: Position,
+}
+
+#[derive(PartialEq)]
+pub enum CondContext {
+    THEN,
+    ELIF,
+    ELSE,
+}
+
+impl Preprocessor {
+    pub fn directive(&mut self, input: &mut VecDeque<PPToken>, hash: PPToken) {
+        let mut line = read_line(input);
+
+        // 空指令
+        if line.is_empty() {
+            return;
+        }
+        let name = line.remove(0);
+        let pos = self.presumed_position(name.file, name.position);
+
+        match name.text.as_str() {
+            "include" => self.include(input, line, &name),
+            "define" => self.define_macro(line),
+            "undef" => match line.first() {
+                Some(t) if t.is_identifier() => {
+                    self.macros.remove(&t.text);
+                }
+                _ => self.error(pos, ErrorMsg::MacroNameMustBeIdentifier),
+            },
+            "if" => {
+                let cond = self.evaluate_condition(line, pos);
+                self.push_cond(&hash, cond);
+                if !cond {
+                    self.skip_cond_incl(input);
+                }
+            }
+            "ifdef" | "ifndef" => {
+                let defined = match line.first() {
+                    Some(t) if t.is_identifier() => self.macros.contains_key(&t.text),
+                    _ => {
+                        self.error(pos, ErrorMsg::MacroNameMustBeIdentifier);
+                        false
+                    }
+                };
+                let cond = defined == (name.text == "ifdef");
+                self.push_cond(&hash, cond);
+                if !cond {
+                    self.skip_cond_incl(input);
+                }
+            }
+            "elif" => {
+                let included = match self.cond_stack.last_mut() {
+                    Some(cond) if cond.context != CondContext::ELSE => {
+                        cond.context = CondContext::ELIF;
+                        cond.included
+                    }
+                    _ => {
+                        self.error(pos, ErrorMsg::UnmatchedConditionalDirective);
+                        return;
+                    }
+                };
+
+                // 既に取り込んだグループがあれば式は評価しない
+                if !included && self.evaluate_condition(line, pos) {
+                    self.cond_stack.last_mut().unwrap().included = true;
+                } else {
+                    self.skip_cond_incl(input);
+                }
+            }
+            "else" => {
+                let included = match self.cond_stack.last_mut() {
+                    Some(cond) if cond.context != CondContext::ELSE => {
+                        cond.context = CondContext::ELSE;
+                        cond.included
+                    }
+                    _ => {
+                        self.error(pos, ErrorMsg::UnmatchedConditionalDirective);
+                        return;
+                    }
+                };
+                if included {
+                    self.skip_cond_incl(input);
+                }
+            }
+            "endif" => {
+                if self.cond_stack.pop().is_none() {
+                    self.error(pos, ErrorMsg::UnmatchedConditionalDirective);
+                }
+            }
+            "line" => self.line_directive(line, &name),
+            "error" => {
+                let message: Vec<String> = line.iter().map(|t| t.text.to_string()).collect();
+                self.error(pos, ErrorMsg::ErrorDirective(message.join(" ")));
+            }
+            "pragma" => {
+                if line.first().is_some_and(|t| t.is("once")) {
+                    let path = self.files[name.file].path.to_string();
+                    self.pragma_once.insert(canonicalize(&path));
+                }
+                // 他のプラグマは無視する
+            }
+            _ => self.error(pos, ErrorMsg::InvalidPreprocessingDirective),
+        }
+    }
+
+    pub fn define_macro(&mut self, mut line: Vec<PPToken>) {
+        if line.is_empty() || !line[0].is_identifier() || line[0].is("defined") {
+            let pos = match line.first() {
+                Some(t) => self.presumed_position(t.file, t.position),
+                None => Position::new(0, 0),
+            };
+            self.error(pos, ErrorMsg::MacroNameMustBeIdentifier);
+            return;
+        }
+        let name = line.remove(0);
+
+        // 識別子の直後に空白なしで ( が続けば関数形式
+        if !line.is_empty() && line[0].is("(") && !line[0].has_space {
+            line.remove(0);
+            let (params, is_variadic) = match self.read_macro_params(&mut line) {
+                Some(params) => params,
+                None => {
+                    let pos = self.presumed_position(name.file, name.position);
+                    self.error(pos, ErrorMsg::InvalidMacroParameters);
+                    return;
+                }
+            };
+
+            if !self.check_replacement_list(&line, Some(&params)) {
+                return;
+            }
+            self.insert_macro(name, MacroKind::FUNCTION(params, is_variadic), line);
+            return;
+        }
+
+        if !self.check_replacement_list(&line, None) {
+            return;
+        }
+        self.insert_macro(name, MacroKind::OBJECT, line);
+    }
+
+    fn insert_macro(&mut self, name: PPToken, kind: MacroKind, mut body: Vec<PPToken>) {
+        if let Some(first) = body.first_mut() {
+            first.has_space = false;
+        }
+        self.macros.insert(name.text, Macro { kind, body });
+    }
+
+    // 仮引数並びを読む. ) まで消費する.
+    fn read_macro_params(&mut self, line: &mut Vec<PPToken>) -> Option<(Vec<String>, bool)> {
+        let mut params = Vec::new();
+        if !line.is_empty() && line[0].is(")") {
+            line.remove(0);
+            return Some((params, false));
+        }
+
+        loop {
+            if line.is_empty() {
+                return None;
+            }
+            let t = line.remove(0);
+            if t.is("...") {
+                params.push("__VA_ARGS__".to_string());
+                if line.is_empty() || !line.remove(0).is(")") {
+                    return None;
+                }
+                return Some((params, true));
+            }
+            if !t.is_identifier() || params.contains(&t.text) {
+                return None;
+            }
+            params.push(t.text);
+
+            if line.is_empty() {
+                return None;
+            }
+            let delim = line.remove(0);
+            if delim.is(")") {
+                return Some((params, false));
+            }
+            if !delim.is(",") {
+                return None;
+            }
+        }
+    }
+
+    fn check_replacement_list(&mut self, body: &[PPToken], params: Option<&Vec<String>>) -> bool {
+        for (i, t) in body.iter().enumerate() {
+            // ## は置換リストの両端に置けない
+            if t.is("##") && (i == 0 || i == body.len() - 1) {
+                let pos = self.presumed_position(t.file, t.position);
+                self.error(pos, ErrorMsg::InvalidTokenPasting);
+                return false;
+            }
+
+            // 関数形式の # の後には仮引数が続かなければならない
+            if let Some(params) = params {
+                let is_param = |t: Option<&PPToken>| {
+                    t.is_some_and(|t| t.is_identifier() && params.contains(&t.text))
+                };
+                if t.is("#") && !is_param(body.get(i + 1)) {
+                    let pos = self.presumed_position(t.file, t.position);
+                    self.error(pos, ErrorMsg::StringizingMustBeFollowedByParameter);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn include(&mut self, input: &mut VecDeque<PPToken>, mut line: Vec<PPToken>, name: &PPToken) {
+        let pos = self.presumed_position(name.file, name.position);
+
+        // "..." でも <...> でもなければマクロ展開してから解釈する
+        let is_header_name = |line: &Vec<PPToken>| match line.first() {
+            Some(t) => t.kind == PPTokenKind::STRING || t.is("<"),
+            None => false,
+        };
+        if !is_header_name(&line) {
+            line = self.expand_all(line);
+        }
+
+        let (header, is_quoted) = match line.first() {
+            Some(t) if t.kind == PPTokenKind::STRING && t.text.starts_with('"') => {
+                (t.text[1..t.text.len() - 1].to_string(), true)
+            }
+            Some(t) if t.is("<") => {
+                let mut header = String::new();
+                let mut closed = false;
+                for t in line.iter().skip(1) {
+                    if t.is(">") {
+                        closed = true;
+                        break;
+                    }
+                    if t.has_space && !header.is_empty() {
+                        header.push(' ');
+                    }
+                    header += &t.text;
+                }
+                if !closed {
+                    self.error(pos, ErrorMsg::InvalidIncludeDirective);
+                    return;
+                }
+                (header, false)
+            }
+            _ => {
+                self.error(pos, ErrorMsg::InvalidIncludeDirective);
+                return;
+            }
+        };
+
+        let path = match self.search_include_file(&header, is_quoted, name.file) {
+            Some(path) => path,
+            None => {
+                self.error(pos, ErrorMsg::IncludeFileNotFound(header));
+                return;
+            }
+        };
+        if self.pragma_once.contains(&canonicalize(&path)) {
+            return;
+        }
+        if INCLUDE_DEPTH_LIMIT <= self.include_depth {
+            self.error(pos, ErrorMsg::IncludeNestedTooDeeply);
+            return;
+        }
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => {
+                self.error(pos, ErrorMsg::IncludeFileNotFound(header));
+                return;
+            }
+        };
+
+        // 取り込んだファイルの字句を入力の先頭に置く.
+        // 末尾の目印を読んだ時点でネストが1段浅くなる.
+        self.include_depth += 1;
+        let mut tokens = self.tokenize_file(&path, &contents);
+        tokens.push_back(PPToken::new_end_of_file(self.files.len() - 1));
+        for t in tokens.into_iter().rev() {
+            input.push_front(t);
+        }
+    }
+
+    fn search_include_file(&self, header: &str, is_quoted: bool, file: usize) -> Option<String> {
+        if Path::new(header).is_absolute() {
+            return Some(header.to_string()).filter(|p| Path::new(p).is_file());
+        }
+
+        let mut dirs: Vec<String> = Vec::new();
+        if is_quoted {
+            // 取り込み元ファイルのディレクトリを最初に探す
+            let current = Path::new(&self.files[file].path);
+            let dir = match current.parent() {
+                Some(dir) => dir.to_string_lossy().to_string(),
+                None => String::new(),
+            };
+            dirs.push(dir);
+        }
+        dirs.extend(self.include_dirs.iter().cloned());
+        dirs.extend(self.system_dirs.iter().cloned());
+
+        dirs.iter()
+            .map(|dir| {
+                if dir.is_empty() {
+                    header.to_string()
+                } else {
+                    format!("{}/{}", dir, header)
+                }
+            })
+            .find(|path| Path::new(path).is_file())
+    }
+
+    // #line digit-sequence "s-char-sequence"(opt)
+    fn line_directive(&mut self, line: Vec<PPToken>, name: &PPToken) {
+        let pos = self.presumed_position(name.file, name.position);
+        let line = self.expand_all(line);
+
+        let number = match line.first() {
+            Some(t) if t.kind == PPTokenKind::NUMBER => t.text.parse::<usize>().ok(),
+            _ => None,
+        };
+        let number = match number {
+            Some(n) => n,
+            None => {
+                self.error(pos, ErrorMsg::InvalidLineDirective);
+                return;
+            }
+        };
+
+        let presumed_name = match line.get(1) {
+            Some(t) if t.kind != PPTokenKind::STRING || !t.text.starts_with('"') => {
+                self.error(pos, ErrorMsg::InvalidLineDirective);
+                return;
+            }
+            Some(t) => t.text[1..t.text.len() - 1].to_string(),
+            None => pos.file.to_string(),
+        };
+
+        // 次の行から,見かけの行番号を number にする
+        let row = name.position.row + 1;
+        self.files[name.file].line_marks.push(LineMark {
+            row,
+            name: presumed_name,
+            line_delta: number as isize - row as isize,
+        });
+    }
+
+    // 条件が偽のグループを読み飛ばす.
+    // 対応する #elif/#else/#endif の # を入力に残して戻る.
+    fn skip_cond_incl(&mut self, input: &mut VecDeque<PPToken>) {
+        let mut depth = 0;
+        while let Some(t) = input.pop_front() {
+            // ファイルを跨いで読み飛ばさない
+            if t.is_end_of_file() {
+                input.push_front(t);
+                return;
+            }
+
+            let is_directive = t.is("#") && t.at_bol;
+            let name = match input.front() {
+                Some(n) if is_directive && !n.at_bol => n.text.to_string(),
+                _ => continue,
+            };
+
+            match name.as_str() {
+                "if" | "ifdef" | "ifndef" => depth += 1,
+                "endif" if depth != 0 => depth -= 1,
+                "elif" | "else" | "endif" if depth == 0 => {
+                    input.push_front(t);
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_cond(&mut self, hash: &PPToken, included: bool) {
+        self.cond_stack.push(CondIncl {
+            context: CondContext::THEN,
+            included,
+            file: hash.file,
+            position: hash.position,
+        });
+    }
+}
+
+fn canonicalize(path: &str) -> String {
+    match std::fs::canonicalize(path) {
+        Ok(p) => p.to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
@@ -0,0 +1,338 @@
+use crate::compiler::frontend::preprocess::pp_token::{PPLexer, PPToken, PPTokenKind};
+use crate::compiler::frontend::preprocess::Preprocessor;
+use crate::error::ErrorMsg;
+
+use std::collections::{BTreeSet, VecDeque};
+
+pub struct Macro {
+    pub kind: MacroKind,
+    pub body: Vec<PPToken>,
+}
+
+pub enum MacroKind {
+    OBJECT,                      // オブジェクト形式
+    FUNCTION(Vec<String>, bool), // 関数形式(仮引数,可変長引数か)
+    FILE,                        // __FILE__
+    LINE,                        // __LINE__
+}
+
+impl Preprocessor {
+    pub fn define_builtin_macros(&mut self) {
+        self.define_from_text("__STDC__ 1");
+        self.define_from_text("__STDC_HOSTED__ 1");
+        self.define_from_text("__x86_64__ 1");
+        self.define_from_text("__linux__ 1");
+
+        for (name, kind) in [("__FILE__", MacroKind::FILE), ("__LINE__", MacroKind::LINE)] {
+            self.macros.insert(
+                name.to_string(),
+                Macro {
+                    kind,
+                    body: Vec::new(),
+                },
+            );
+        }
+    }
+
+    // -D NAME[=VALUE]
+    pub fn define_from_command_line(&mut self, def: &str) {
+        let text = match def.find('=') {
+            Some(idx) => format!("{} {}", &def[..idx], &def[idx + 1..]),
+            None => format!("{} 1", def),
+        };
+        self.define_from_text(&text);
+    }
+
+    fn define_from_text(&mut self, text: &str) {
+        self.files.push(super::SourceInfo::new("", "<built-in>"));
+        let file = self.files.len() - 1;
+        let line = PPLexer::new(text, file).tokenize();
+        self.define_macro(line);
+    }
+
+    // マクロ呼び出しであれば展開し,結果を入力に積み直してtrueを返す
+    pub fn expand_macro(&mut self, input: &mut VecDeque<PPToken>, t: &PPToken) -> bool {
+        if !t.is_identifier() || t.hideset.contains(&t.text) {
+            return false;
+        }
+        let mac = match self.macros.get(&t.text) {
+            Some(mac) => mac,
+            None => return false,
+        };
+
+        let expanded = match &mac.kind {
+            MacroKind::FILE => {
+                let name = self.presumed_position(t.file, t.position).file;
+                let text = format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+                vec![PPToken::new(PPTokenKind::STRING, text, t.file, t.position)]
+            }
+            MacroKind::LINE => {
+                let row = self.presumed_position(t.file, t.position).row;
+                vec![PPToken::new(
+                    PPTokenKind::NUMBER,
+                    row.to_string(),
+                    t.file,
+                    t.position,
+                )]
+            }
+            MacroKind::OBJECT => {
+                let mut hideset = t.hideset.clone();
+                hideset.insert(t.text.to_string());
+                let mut body = mac.body.clone();
+                for b in body.iter_mut() {
+                    b.hideset.extend(hideset.iter().cloned());
+                }
+                body
+            }
+            MacroKind::FUNCTION(params, is_variadic) => {
+                // 直後が ( でなければただの識別子
+                match input.front() {
+                    Some(next) if next.is("(") => {}
+                    _ => return false,
+                }
+                let params = params.clone();
+                let is_variadic = *is_variadic;
+                let body = mac.body.clone();
+
+                let (args, rparen) = match self.read_macro_args(input, t, &params, is_variadic) {
+                    Some(result) => result,
+                    None => return true,
+                };
+
+                let mut hideset: BTreeSet<String> =
+                    t.hideset.intersection(&rparen.hideset).cloned().collect();
+                hideset.insert(t.text.to_string());
+                self.substitute(&body, &params, &args, &hideset)
+            }
+        };
+
+        // 展開結果はマクロ呼び出しの位置を引き継ぐ
+        for (i, mut e) in expanded.into_iter().enumerate().rev() {
+            e.file = t.file;
+            e.position = t.position;
+            e.at_bol = false;
+            e.from_macro = true;
+            if i == 0 {
+                e.has_space = t.has_space;
+            }
+            input.push_front(e);
+        }
+        true
+    }
+
+    // 実引数列を読み出す.最後の ) も返す.
+    fn read_macro_args(
+        &mut self,
+        input: &mut VecDeque<PPToken>,
+        name: &PPToken,
+        params: &[String],
+        is_variadic: bool,
+    ) -> Option<(Vec<Vec<PPToken>>, PPToken)> {
+        // (
+        input.pop_front();
+
+        let mut args: Vec<Vec<PPToken>> = vec![Vec::new()];
+        let mut depth = 0;
+        loop {
+            let t = match input.pop_front() {
+                Some(t) => t,
+                None => {
+                    let pos = self.presumed_position(name.file, name.position);
+                    self.error(pos, ErrorMsg::UnterminatedMacroInvocation);
+                    return None;
+                }
+            };
+
+            if depth == 0 && t.is(")") {
+                if !self.check_macro_args(&mut args, params, is_variadic) {
+                    let pos = self.presumed_position(name.file, name.position);
+                    self.error(pos, ErrorMsg::MacroArgumentsMismatch);
+                    return None;
+                }
+                return Some((args, t));
+            }
+
+            // 可変長部分のカンマは区切りとして扱わない
+            let in_variadic = is_variadic && args.len() == params.len();
+            if depth == 0 && t.is(",") && !in_variadic {
+                args.push(Vec::new());
+                continue;
+            }
+
+            if t.is("(") {
+                depth += 1;
+            } else if t.is(")") {
+                depth -= 1;
+            }
+            args.last_mut().unwrap().push(t);
+        }
+    }
+
+    fn check_macro_args(
+        &self,
+        args: &mut Vec<Vec<PPToken>>,
+        params: &[String],
+        is_variadic: bool,
+    ) -> bool {
+        // F() は引数0個の呼び出しとして扱う
+        if params.is_empty() && args.len() == 1 && args[0].is_empty() {
+            args.clear();
+        }
+
+        // __VA_ARGS__ には何も渡されないこともある
+        if is_variadic && args.len() + 1 == params.len() {
+            args.push(Vec::new());
+        }
+
+        args.len() == params.len()
+    }
+
+    // 置換リスト中の仮引数を実引数で置き換える
+    fn substitute(
+        &mut self,
+        body: &[PPToken],
+        params: &[String],
+        args: &[Vec<PPToken>],
+        hideset: &BTreeSet<String>,
+    ) -> Vec<PPToken> {
+        let find_arg = |t: &PPToken| -> Option<&Vec<PPToken>> {
+            if !t.is_identifier() {
+                return None;
+            }
+            params.iter().position(|p| p == &t.text).map(|i| &args[i])
+        };
+
+        let mut result: Vec<PPToken> = Vec::new();
+        let mut i = 0;
+        while i < body.len() {
+            let t = &body[i];
+
+            // #x
+            if t.is("#") && i + 1 < body.len() {
+                if let Some(arg) = find_arg(&body[i + 1]) {
+                    let mut s = stringize(arg, t);
+                    s.has_space = t.has_space;
+                    result.push(s);
+                    i += 2;
+                    continue;
+                }
+            }
+
+            // x ## y
+            if t.is("##") && i + 1 < body.len() {
+                let rhs_tok = &body[i + 1];
+                let mut rhs = match find_arg(rhs_tok) {
+                    Some(arg) => arg.clone(),
+                    None => vec![rhs_tok.clone()],
+                };
+
+                // GNU拡張: , ## __VA_ARGS__ は可変長引数が空ならカンマを消す
+                if rhs_tok.is("__VA_ARGS__") && result.last().is_some_and(|l| l.is(",")) {
+                    if rhs.is_empty() {
+                        result.pop();
+                    } else {
+                        result.append(&mut rhs);
+                    }
+                    i += 2;
+                    continue;
+                }
+
+                if rhs.is_empty() {
+                    rhs.push(PPToken::new_placemarker(t.file, t.position));
+                }
+                let lhs = result.pop().unwrap();
+                let pasted = self.paste(lhs, rhs.remove(0));
+                result.push(pasted);
+                result.append(&mut rhs);
+                i += 2;
+                continue;
+            }
+
+            if let Some(arg) = find_arg(t) {
+                if i + 1 < body.len() && body[i + 1].is("##") {
+                    // ## の被演算子は展開しない
+                    if arg.is_empty() {
+                        result.push(PPToken::new_placemarker(t.file, t.position));
+                    } else {
+                        result.append(&mut arg.clone());
+                    }
+                } else {
+                    let mut expanded = self.expand_all(arg.clone());
+                    if let Some(first) = expanded.first_mut() {
+                        first.has_space = t.has_space;
+                    }
+                    result.append(&mut expanded);
+                }
+                i += 1;
+                continue;
+            }
+
+            result.push(t.clone());
+            i += 1;
+        }
+
+        result
+            .into_iter()
+            .filter(|t| !t.is_placemarker())
+            .map(|mut t| {
+                t.hideset.extend(hideset.iter().cloned());
+                t
+            })
+            .collect()
+    }
+
+    // トークン列を(前処理指令を解釈せずに)完全にマクロ展開する
+    pub fn expand_all(&mut self, tokens: Vec<PPToken>) -> Vec<PPToken> {
+        let mut input: VecDeque<PPToken> = tokens.into_iter().collect();
+        let mut output = Vec::new();
+        while let Some(t) = input.pop_front() {
+            if self.expand_macro(&mut input, &t) {
+                continue;
+            }
+            output.push(t);
+        }
+        output
+    }
+
+    // トークン連結.結果は1つの前処理字句になっていなければならない.
+    fn paste(&mut self, lhs: PPToken, rhs: PPToken) -> PPToken {
+        if lhs.is_placemarker() {
+            return rhs;
+        }
+        if rhs.is_placemarker() {
+            return lhs;
+        }
+
+        let text = format!("{}{}", lhs.text, rhs.text);
+        let mut tokens = PPLexer::new(&text, lhs.file).tokenize();
+        if tokens.len() != 1 {
+            let pos = self.presumed_position(lhs.file, lhs.position);
+            self.error(pos, ErrorMsg::InvalidTokenPasting);
+            return lhs;
+        }
+
+        let mut t = tokens.remove(0);
+        t.position = lhs.position;
+        t.has_space = lhs.has_space;
+        t.hideset = lhs.hideset;
+        t
+    }
+}
+
+// #演算子
+fn stringize(arg: &[PPToken], hash: &PPToken) -> PPToken {
+    let mut text = String::from("\"");
+    for (i, t) in arg.iter().enumerate() {
+        if i != 0 && t.has_space {
+            text.push(' ');
+        }
+        match t.kind {
+            PPTokenKind::STRING | PPTokenKind::CHARACTER => {
+                text += &t.text.replace('\\', "\\\\").replace('"', "\\\"");
+            }
+            _ => text += &t.text,
+        }
+    }
+    text.push('"');
+    PPToken::new(PPTokenKind::STRING, text, hash.file, hash.position)
+}
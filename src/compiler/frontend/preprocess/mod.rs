@@ -0,0 +1,354 @@
+pub mod cond_expr;
+pub mod directive;
+pub mod macros;
+pub mod pp_token;
+
+use crate::compiler::frontend::manager::Manager;
+use crate::compiler::frontend::token::{intern_file_name, Position};
+use crate::error::{Error, ErrorKind, ErrorMsg};
+use pp_token::{PPLexer, PPToken};
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+pub fn preprocess(manager: &mut Manager, include_dirs: Vec<String>, defines: Vec<String>) {
+    let mut preprocessor = Preprocessor::new(include_dirs);
+
+    // 定義済みマクロ/コマンドラインで与えられたマクロ
+    preprocessor.define_builtin_macros();
+    for def in defines.iter() {
+        preprocessor.define_from_command_line(def);
+    }
+
+    let tokens =
+        preprocessor.preprocess_file(&manager.src_file.abs_path, &manager.src_file.contents);
+
+    if preprocessor.error_occurred {
+        std::process::exit(1);
+    }
+
+    manager.preprocessed = preprocessor.emit(tokens);
+}
+
+pub struct Preprocessor {
+    // 読み込んだファイルの一覧.#includeされる度に追加される.
+    pub files: Vec<SourceInfo>,
+    pub macros: BTreeMap<String, macros::Macro>,
+
+    // 条件付き取り込みのネスト
+    pub cond_stack: Vec<directive::CondIncl>,
+
+    // 検索パス
+    pub include_dirs: Vec<String>,
+    pub system_dirs: Vec<String>,
+    pub pragma_once: BTreeSet<String>,
+    pub include_depth: usize,
+
+    pub error_occurred: bool,
+}
+
+// ファイル毎の情報
+pub struct SourceInfo {
+    pub path: String, // 実際のパス(#include の検索に使う)
+
+    // #line で変更されうる見かけのファイル名と行番号.行の昇順に並ぶ
+    // #line はそれ以降の行にだけ効くので,前処理の後でも各行の見かけの位置が分かるように全て残す
+    pub line_marks: Vec<LineMark>,
+}
+
+pub struct LineMark {
+    pub row: usize, // この行から適用する
+    pub name: String,
+    pub line_delta: isize,
+}
+
+impl SourceInfo {
+    pub fn new(path: &str, name: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            line_marks: vec![LineMark {
+                row: 0,
+                name: name.to_string(),
+                line_delta: 0,
+            }],
+        }
+    }
+    // row 行目に適用される行標識
+    pub fn line_mark(&self, row: usize) -> &LineMark {
+        self.line_marks
+            .iter()
+            .rev()
+            .find(|mark| mark.row <= row)
+            .unwrap()
+    }
+}
+
+impl Preprocessor {
+    pub fn new(include_dirs: Vec<String>) -> Self {
+        let mut system_dirs = Vec::new();
+        if let Ok(root) = std::env::var("C_ROOT") {
+            system_dirs.push(format!("{}/include", root));
+        }
+        system_dirs.push("/usr/local/include".to_string());
+        system_dirs.push("/usr/include".to_string());
+
+        Self {
+            files: Vec::new(),
+            macros: BTreeMap::new(),
+            cond_stack: Vec::new(),
+            include_dirs,
+            system_dirs,
+            pragma_once: BTreeSet::new(),
+            include_depth: 0,
+            error_occurred: false,
+        }
+    }
+
+    pub fn preprocess_file(&mut self, path: &str, contents: &str) -> Vec<PPToken> {
+        let input = self.tokenize_file(path, contents);
+        let file = self.files.len() - 1;
+        let tokens = self.preprocess_tokens(input);
+        self.leave_file(file);
+        tokens
+    }
+
+    // ファイル内で閉じられていない条件付き取り込みを検査する
+    fn leave_file(&mut self, file: usize) {
+        while let Some(cond) = self.cond_stack.last() {
+            if cond.file != file {
+                break;
+            }
+            let pos = self.presumed_position(cond.file, cond.position);
+            self.cond_stack.pop();
+            self.error(pos, ErrorMsg::UnterminatedConditionalDirective);
+        }
+        self.include_depth = self.include_depth.saturating_sub(1);
+    }
+
+    pub fn tokenize_file(&mut self, path: &str, contents: &str) -> VecDeque<PPToken> {
+        self.files.push(SourceInfo::new(path, path));
+        let file = self.files.len() - 1;
+        PPLexer::new(contents, file)
+            .tokenize()
+            .into_iter()
+            .collect()
+    }
+
+    // 前処理のメインループ
+    fn preprocess_tokens(&mut self, mut input: VecDeque<PPToken>) -> Vec<PPToken> {
+        let mut output: Vec<PPToken> = Vec::new();
+
+        while let Some(t) = input.pop_front() {
+            if t.is_end_of_file() {
+                self.leave_file(t.file);
+                continue;
+            }
+
+            // 行頭の # は前処理指令
+            if t.is("#") && t.at_bol && !t.from_macro {
+                self.directive(&mut input, t);
+                continue;
+            }
+
+            if self.expand_macro(&mut input, &t) {
+                continue;
+            }
+
+            output.push(t);
+        }
+
+        output
+    }
+
+    // 前処理後のトークン列を,行標識( # <line> "<file>" )付きのソースコードに戻す.
+    // 字句解析器は行標識を解釈するので,トークンの位置は元のファイルを指し続ける.
+    pub fn emit(&self, tokens: Vec<PPToken>) -> String {
+        let mut code = String::new();
+        let mut cur_name = String::new();
+        let mut cur_row = 0;
+        let mut cur_column = 1;
+
+        for t in tokens.iter() {
+            let Position {
+                file: name,
+                row,
+                column,
+            } = self.presumed_position(t.file, t.position);
+
+            if name != cur_name || row < cur_row || cur_row + 8 < row {
+                if cur_column != 1 {
+                    code.push('\n');
+                }
+                code += &format!("# {} \"{}\"\n", row, name);
+                cur_name = name.to_string();
+                cur_row = row;
+                cur_column = 1;
+            }
+            while cur_row < row {
+                code.push('\n');
+                cur_row += 1;
+                cur_column = 1;
+            }
+
+            if cur_column < column {
+                code += &" ".repeat(column - cur_column);
+                cur_column = column;
+            } else if cur_column != 1 && (t.has_space || t.from_macro) {
+                // 展開結果が隣のトークンとくっつかないように
+                code.push(' ');
+                cur_column += 1;
+            }
+
+            code += &t.text;
+            cur_column += t.text.chars().count();
+        }
+        code.push('\n');
+        code
+    }
+
+    // #line で変更された見かけのファイル名と行番号
+    pub fn presumed_position(&self, file: usize, pos: Position) -> Position {
+        let mark = self.files[file].line_mark(pos.row);
+        let row = pos.row as isize + mark.line_delta;
+        Position::new(row.max(1) as usize, pos.column).in_file(intern_file_name(&mark.name))
+    }
+
+    pub fn error(&mut self, pos: Position, msg: ErrorMsg) {
+        let err = Error::new(ErrorKind::Preprocess, pos, msg);
+        err.found();
+        self.error_occurred = true;
+    }
+}
+
+// 行末までのトークンを読み出す
+pub fn read_line(input: &mut VecDeque<PPToken>) -> Vec<PPToken> {
+    let mut line = Vec::new();
+    while let Some(t) = input.front() {
+        if t.at_bol {
+            break;
+        }
+        line.push(input.pop_front().unwrap());
+    }
+    line
+}
+
+#[cfg(test)]
+mod preprocess_tests {
+    use super::*;
+
+    #[test]
+    fn test_object_like_macro() {
+        let actual = preprocess_test_source("#define N 3\nint x = N + N;\n");
+        assert_eq!("int x = 3 + 3 ;", actual);
+    }
+
+    #[test]
+    fn test_function_like_macro() {
+        let actual =
+            preprocess_test_source("#define MAX(a, b) ((a) > (b) ? (a) : (b))\nMAX(x, 1 + 2)\n");
+        assert_eq!("( ( x ) > ( 1 + 2 ) ? ( x ) : ( 1 + 2 ) )", actual);
+    }
+
+    #[test]
+    fn test_stringize_and_concat() {
+        let actual = preprocess_test_source(
+            "#define STR(x) #x\n#define CAT(a, b) a ## b\nSTR(a  \"b\" 'c') CAT(foo, 12) CAT(, x)\n",
+        );
+        assert_eq!("\"a \\\"b\\\" 'c'\" foo12 x", actual);
+    }
+
+    #[test]
+    fn test_variadic_macro() {
+        let actual = preprocess_test_source(
+            "#define F(fmt, ...) f(fmt, __VA_ARGS__)\n#define G(...) g(0, ## __VA_ARGS__)\nF(1, 2, 3) G()\n",
+        );
+        assert_eq!("f ( 1 , 2 , 3 ) g ( 0 )", actual);
+    }
+
+    #[test]
+    fn test_recursive_macro_is_not_expanded_again() {
+        let actual =
+            preprocess_test_source("#define foo foo + 1\n#define f(x) f(x + foo)\nfoo f(2)\n");
+        assert_eq!("foo + 1 f ( 2 + foo + 1 )", actual);
+    }
+
+    #[test]
+    fn test_undef() {
+        let actual = preprocess_test_source("#define A 1\n#undef A\nA\n");
+        assert_eq!("A", actual);
+    }
+
+    #[test]
+    fn test_conditional_inclusion() {
+        let actual = preprocess_test_source(
+            "#define A 2\n#if A == 1\none\n#elif defined(A) && A * 2 == 4\ntwo\n#else\nthree\n#endif\n#ifdef B\nb\n#endif\n#ifndef B\nnotb\n#endif\n",
+        );
+        assert_eq!("two notb", actual);
+    }
+
+    #[test]
+    fn test_nested_conditional_in_skipped_group() {
+        let actual = preprocess_test_source(
+            "#if 0\n#if 1\na\n#else\nb\n#endif\n#elif 1 ? 0 : 1\nc\n#else\nd\n#endif\n",
+        );
+        assert_eq!("d", actual);
+    }
+
+    #[test]
+    fn test_predefined_macros() {
+        let actual = preprocess_test_source(
+            "__STDC__\n\n__LINE__ __FILE__\n#line 100 \"foo.c\"\n__LINE__ __FILE__\n",
+        );
+        assert_eq!("1 3 \"test.c\" 100 \"foo.c\"", actual);
+    }
+
+    #[test]
+    fn test_emit_keeps_original_positions() {
+        let mut preprocessor = Preprocessor::new(Vec::new());
+        let tokens = preprocessor.preprocess_file(
+            "test.c",
+            "#define X 1\n\nint main() {\n  /* comment */ return X;\n}\n",
+        );
+        let code = preprocessor.emit(tokens);
+        assert_eq!(
+            "# 3 \"test.c\"\nint main() {\n                return 1;\n}\n",
+            code
+        );
+    }
+
+    #[test]
+    fn test_line_directive_affects_only_following_lines() {
+        let mut preprocessor = Preprocessor::new(Vec::new());
+        let tokens = preprocessor.preprocess_file(
+            "test.c",
+            "int a;\n#line 100 \"a.c\"\nint b;\n#line 200 \"b.c\"\nint c;\n",
+        );
+
+        // #line より前のトークンは元のファイル名と行番号のまま
+        let positions: Vec<String> = tokens
+            .iter()
+            .filter(|t| t.text == "int")
+            .map(|t| {
+                preprocessor
+                    .presumed_position(t.file, t.position)
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(vec!["test.c:1:1", "a.c:100:1", "b.c:200:1"], positions);
+
+        let code = preprocessor.emit(tokens);
+        assert_eq!(
+            "# 1 \"test.c\"\nint a;\n# 100 \"a.c\"\nint b;\n# 200 \"b.c\"\nint c;\n",
+            code
+        );
+    }
+
+    fn preprocess_test_source(contents: &str) -> String {
+        let mut preprocessor = Preprocessor::new(Vec::new());
+        preprocessor.define_builtin_macros();
+        let tokens = preprocessor.preprocess_file("test.c", contents);
+        assert!(!preprocessor.error_occurred);
+
+        let texts: Vec<String> = tokens.iter().map(|t| t.text.to_string()).collect();
+        texts.join(" ")
+    }
+}
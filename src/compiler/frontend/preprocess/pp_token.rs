@@ -0,0 +1,322 @@
+use crate::compiler::frontend::token::Position;
+
+use std::collections::BTreeSet;
+
+// 前処理字句(preprocessing-token)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PPToken {
+    pub kind: PPTokenKind,
+    pub text: String,
+
+    // 元のファイル(Preprocessor::filesの添字)と位置
+    pub file: usize,
+    pub position: Position,
+
+    pub at_bol: bool,     // 行頭のトークンか
+    pub has_space: bool,  // 直前に空白類があったか
+    pub from_macro: bool, // マクロ展開によって生成されたか
+
+    // 展開を抑止するマクロ名の集合(hide set)
+    pub hideset: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PPTokenKind {
+    IDENTIFIER,  // 識別子
+    NUMBER,      // pp-number
+    STRING,      // 文字列リテラル
+    CHARACTER,   // 文字定数
+    PUNCTUATOR,  // 区切り子
+    OTHER,       // 上記以外の1文字
+    PLACEMARKER, // ## の片側が空だった時の目印(展開後には残らない)
+    ENDOFFILE,   // #include で取り込んだファイルの終端
+}
+
+impl PPToken {
+    pub fn new(kind: PPTokenKind, text: String, file: usize, pos: Position) -> Self {
+        Self {
+            kind,
+            text,
+            file,
+            position: pos,
+            at_bol: false,
+            has_space: false,
+            from_macro: false,
+            hideset: BTreeSet::new(),
+        }
+    }
+    pub fn new_placemarker(file: usize, pos: Position) -> Self {
+        Self::new(PPTokenKind::PLACEMARKER, String::new(), file, pos)
+    }
+    pub fn new_end_of_file(file: usize) -> Self {
+        let mut t = Self::new(
+            PPTokenKind::ENDOFFILE,
+            String::new(),
+            file,
+            Position::new(0, 0),
+        );
+        t.at_bol = true;
+        t
+    }
+    pub fn is(&self, s: &str) -> bool {
+        match self.kind {
+            PPTokenKind::PUNCTUATOR | PPTokenKind::IDENTIFIER => self.text == s,
+            _ => false,
+        }
+    }
+    pub fn is_identifier(&self) -> bool {
+        matches!(self.kind, PPTokenKind::IDENTIFIER)
+    }
+    pub fn is_end_of_file(&self) -> bool {
+        matches!(self.kind, PPTokenKind::ENDOFFILE)
+    }
+    pub fn is_placemarker(&self) -> bool {
+        matches!(self.kind, PPTokenKind::PLACEMARKER)
+    }
+}
+
+// 区切り子は最長一致で切り出すので,長いものから並べる
+const PUNCTUATORS: [&str; 47] = [
+    "<<=", ">>=", "...", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "*=",
+    "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##", "[", "]", "(", ")", "{", "}", ".", "&", "*",
+    "+", "-", "~", "!", "/", "%", "<", ">", "^", "|", "?", ":", ";", "=", ",",
+];
+
+// 翻訳フェーズ1~3に相当.
+// 行の連結(\ + 改行)とコメントの除去をしながら前処理字句に分割する.
+pub struct PPLexer {
+    chars: Vec<char>,
+    offset: usize,
+    row: usize,
+    column: usize,
+    file: usize,
+}
+
+impl PPLexer {
+    pub fn new(contents: &str, file: usize) -> Self {
+        Self {
+            chars: contents.chars().collect(),
+            offset: 0,
+            row: 1,
+            column: 1,
+            file,
+        }
+    }
+
+    pub fn tokenize(&mut self) -> Vec<PPToken> {
+        let mut tokens: Vec<PPToken> = Vec::new();
+        let mut at_bol = true;
+        let mut has_space = false;
+
+        loop {
+            self.skip_line_splices();
+            let c = match self.peek(0) {
+                Some(c) => c,
+                None => break,
+            };
+
+            if c == '\n' {
+                self.advance();
+                at_bol = true;
+                has_space = false;
+                continue;
+            }
+            if c == ' ' || c == '\t' || c == '\r' || c == '\x0b' || c == '\x0c' {
+                self.advance();
+                has_space = true;
+                continue;
+            }
+            if c == '/' && self.peek(1) == Some('/') {
+                while let Some(c) = self.peek(0) {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.advance();
+                }
+                has_space = true;
+                continue;
+            }
+            if c == '/' && self.peek(1) == Some('*') {
+                self.advance();
+                self.advance();
+                while self.peek(0).is_some() {
+                    if self.peek(0) == Some('*') && self.peek(1) == Some('/') {
+                        self.advance();
+                        self.advance();
+                        break;
+                    }
+                    self.advance();
+                }
+                has_space = true;
+                continue;
+            }
+
+            let mut t = self.scan_one_token();
+            t.at_bol = at_bol;
+            t.has_space = has_space;
+            tokens.push(t);
+            at_bol = false;
+            has_space = false;
+        }
+        tokens
+    }
+
+    fn scan_one_token(&mut self) -> PPToken {
+        let pos = Position::new(self.row, self.column);
+        let c = self.peek(0).unwrap();
+
+        // 接頭辞付きの文字列リテラル/文字定数
+        for prefix in ["u8", "u", "U", "L"].iter() {
+            if self.starts_with(prefix) {
+                let next = self.peek(prefix.len());
+                if next == Some('"') || next == Some('\'') {
+                    let mut text = self.take(prefix.len());
+                    let quote = next.unwrap();
+                    text += &self.scan_quoted(quote);
+                    let kind = if quote == '"' {
+                        PPTokenKind::STRING
+                    } else {
+                        PPTokenKind::CHARACTER
+                    };
+                    return PPToken::new(kind, text, self.file, pos);
+                }
+            }
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut text = String::new();
+            while let Some(c) = self.peek(0) {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                text.push(c);
+                self.advance();
+            }
+            return PPToken::new(PPTokenKind::IDENTIFIER, text, self.file, pos);
+        }
+
+        let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+        if c.is_ascii_digit() || (c == '.' && is_digit(self.peek(1))) {
+            let mut text = String::new();
+            text.push(c);
+            self.advance();
+            while let Some(c) = self.peek(0) {
+                let last = text.chars().last().unwrap();
+                let is_exponent_sign = (c == '+' || c == '-') && "eEpP".contains(last);
+                if !(is_exponent_sign || c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                text.push(c);
+                self.advance();
+            }
+            return PPToken::new(PPTokenKind::NUMBER, text, self.file, pos);
+        }
+
+        if c == '"' {
+            let text = self.scan_quoted('"');
+            return PPToken::new(PPTokenKind::STRING, text, self.file, pos);
+        }
+        if c == '\'' {
+            let text = self.scan_quoted('\'');
+            return PPToken::new(PPTokenKind::CHARACTER, text, self.file, pos);
+        }
+
+        for punct in PUNCTUATORS.iter() {
+            if self.starts_with(punct) {
+                let text = self.take(punct.len());
+                return PPToken::new(PPTokenKind::PUNCTUATOR, text, self.file, pos);
+            }
+        }
+
+        // '#' も区切り子だが,"##"より後に判定したいのでここで扱う
+        let text = self.take(1);
+        let kind = if c == '#' {
+            PPTokenKind::PUNCTUATOR
+        } else {
+            PPTokenKind::OTHER
+        };
+        PPToken::new(kind, text, self.file, pos)
+    }
+
+    // 引用符で囲まれた部分を切り出す.閉じられていなければ行末まで.
+    fn scan_quoted(&mut self, quote: char) -> String {
+        let mut text = self.take(1);
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            text.push(c);
+            self.advance();
+            if c == '\\' {
+                if let Some(escaped) = self.peek(0) {
+                    text.push(escaped);
+                    self.advance();
+                }
+            } else if c == quote {
+                break;
+            }
+        }
+        text
+    }
+
+    fn skip_line_splices(&mut self) {
+        while self.peek(0) == Some('\\') && self.peek(1) == Some('\n') {
+            self.offset += 2;
+            self.row += 1;
+            self.column = 1;
+        }
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+    fn take(&mut self, len: usize) -> String {
+        let mut s = String::new();
+        for _ in 0..len {
+            s.push(self.peek(0).unwrap());
+            self.advance();
+        }
+        s
+    }
+    fn peek(&self, n: usize) -> Option<char> {
+        self.chars.get(self.offset + n).copied()
+    }
+    // 1文字進める.字句の途中にある行の連結も読み飛ばす.
+    fn advance(&mut self) {
+        if self.chars[self.offset] == '\n' {
+            self.row += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.offset += 1;
+        self.skip_line_splices();
+    }
+}
+
+#[cfg(test)]
+mod pp_lexer_tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_with_comments() {
+        let tokens = PPLexer::new("a /* x\ny */ b // c\n#define", 0).tokenize();
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(vec!["a", "b", "#", "define"], texts);
+
+        assert_eq!(Position::new(1, 1), tokens[0].position);
+        assert_eq!(Position::new(2, 6), tokens[1].position);
+        assert!(tokens[1].has_space);
+        assert!(!tokens[1].at_bol);
+        assert!(tokens[2].at_bol);
+    }
+
+    #[test]
+    fn test_tokenize_with_line_splice() {
+        let tokens = PPLexer::new("ab\\\ncd <<= \"x\\\"y\" 1.5e+3f", 0).tokenize();
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(vec!["abcd", "<<=", "\"x\\\"y\"", "1.5e+3f"], texts);
+        assert_eq!(PPTokenKind::STRING, tokens[2].kind);
+        assert_eq!(PPTokenKind::NUMBER, tokens[3].kind);
+    }
+}
@@ -1,21 +1,72 @@
-use crate::compiler::frontend::manager::Manager;
-use crate::compiler::frontend::node::{Function, Node, NodeKind};
-use crate::compiler::frontend::types::Type;
+use crate::compiler::frontend::manager::{Manager, SwitchScope};
+use crate::compiler::frontend::node::{Designator, Function, Node, NodeKind, Signature};
+use crate::compiler::frontend::token::Position;
+use crate::compiler::frontend::types::{Type, TypeKind};
+use crate::compiler::frontend::variable::{StaticValue, VarKind, Variable};
 use crate::error::{Error, ErrorKind, ErrorMsg};
 
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
 impl Manager {
     pub fn semantics(&mut self) {
+        self.walk_global_initializers();
+
         // 各関数に対し意味解析を実行
         let mut functions = self.functions.clone();
         let functions_number = functions.len();
         for func_idx in 0..functions_number {
             self.var_map = functions[func_idx].local_map.clone();
+            self.params = functions[func_idx].params.clone();
             self.walk_function(&mut functions[func_idx]);
+            functions[func_idx].local_map = self.var_map.clone();
             self.var_map.clear();
+            self.params.clear();
         }
         self.functions = functions;
     }
+    // グローバル変数の初期化子を型変換して,要素ごとに定数に畳み込む
+    fn walk_global_initializers(&mut self) {
+        let initializers = self.global_initializers.clone();
+        for (name, initializer) in initializers {
+            let var_type = match self.global_map.get(&name) {
+                Some(var) => var.ctype.clone(),
+                None => continue,
+            };
+            let (init_type, values) = self.walk_initializer(&var_type, &initializer);
+
+            let mut static_values = Vec::new();
+            for (offset, value) in values.iter() {
+                let static_value = match Self::eval_constant(value) {
+                    Some(constant) => Some(StaticValue::INTEGER(constant)),
+                    None => self
+                        .eval_address_constant(value)
+                        .map(|(name, offset)| StaticValue::ADDRESS(name, offset)),
+                };
+                match static_value {
+                    Some(static_value) if !value.ctype.is_record() => {
+                        static_values.push((*offset, value.ctype.byte_size, static_value));
+                    }
+                    _ => {
+                        self.error(value.position, ErrorMsg::InitializerIsNotConstant);
+                        break;
+                    }
+                }
+            }
+            if static_values.len() != values.len() {
+                continue;
+            }
+
+            if let Some(var) = self.global_map.get_mut(&name) {
+                var.ctype = init_type;
+                var.kind = VarKind::GLOBAL(Some(static_values));
+            }
+        }
+    }
     fn walk_function(&mut self, func: &mut Function) {
+        self.return_type = func.return_type.clone();
+        self.is_variadic = func.is_variadic;
+
         // 各文に対し意味解析を実行
         let mut statements = func.stmts.clone();
         let statements_number = func.stmts.len();
@@ -25,9 +76,18 @@ impl Manager {
         func.stmts = statements;
     }
     fn walk_statement(&mut self, stmt: &mut Node) {
+        let position = stmt.position;
         match stmt.kind {
+            NodeKind::RETURNSTMT(ref return_expr) if return_expr.kind == NodeKind::NOP => {}
             NodeKind::RETURNSTMT(ref mut return_expr) => {
-                self.walk_expression(return_expr);
+                let expr_type = self.walk_expression(return_expr);
+                let return_type = self.return_type.clone();
+                if (expr_type.is_integer() && return_type.is_integer())
+                    || Self::is_void_pointer_conversion(&return_type, &expr_type)
+                {
+                    Self::cast_to(return_expr, &return_type);
+                }
+                self.check_qualifiers_kept(&return_type, &expr_type, position);
             }
             NodeKind::LABELEDSTMT(ref mut _label_name, ref mut any_stmt) => {
                 self.walk_statement(any_stmt);
@@ -46,106 +106,1394 @@ impl Manager {
             }
             NodeKind::WHILESTMT(ref mut expr, ref mut stmt) => {
                 self.walk_expression(expr);
-                self.walk_statement(stmt);
+                self.walk_loop_body(stmt);
             }
             NodeKind::DOWHILESTMT(ref mut stmt, ref mut expr) => {
-                self.walk_statement(stmt);
+                self.walk_loop_body(stmt);
                 self.walk_expression(expr);
             }
             NodeKind::FORSTMT(ref mut cl, ref mut ex, ref mut ex2, ref mut stmt) => {
-                self.walk_expression(cl);
-                self.walk_expression(ex);
+                // 省略された節には型を付けない
+                if cl.kind != NodeKind::NOP {
+                    self.walk_expression(cl);
+                }
+                if ex.kind != NodeKind::NOP {
+                    self.walk_expression(ex);
+                }
                 self.walk_expression(ex2);
-                self.walk_statement(stmt);
+                self.walk_loop_body(stmt);
             }
             NodeKind::IFELSESTMT(ref mut expr, ref mut stmt, ref mut alt) => {
                 self.walk_expression(expr);
                 self.walk_statement(stmt);
                 self.walk_statement(alt);
             }
+            NodeKind::SWITCHSTMT(
+                ref mut expr,
+                ref mut stmt,
+                ref mut cases,
+                ref mut has_default,
+            ) => {
+                let mut cond_type = self.walk_expression(expr);
+                if cond_type.is_integer() {
+                    cond_type = cond_type.promoted();
+                    Self::cast_to(expr, &cond_type);
+                } else {
+                    self.error(position, ErrorMsg::SwitchRequiresInteger);
+                }
+
+                self.switch_scopes.push(SwitchScope {
+                    cond_type,
+                    cases: Vec::new(),
+                    has_default: false,
+                });
+                self.walk_statement(stmt);
+                let scope = self.switch_scopes.pop().unwrap();
+                *cases = scope.cases;
+                *has_default = scope.has_default;
+            }
+            NodeKind::CASESTMT(ref mut expr, ref mut stmt) => {
+                self.walk_case_value(expr, position);
+                self.walk_statement(stmt);
+            }
+            NodeKind::DEFAULTSTMT(ref mut stmt) => {
+                let msg = match self.switch_scopes.last_mut() {
+                    None => Some(ErrorMsg::NotInSwitch("default".to_string())),
+                    Some(scope) if scope.has_default => Some(ErrorMsg::MultipleDefaultLabels),
+                    Some(scope) => {
+                        scope.has_default = true;
+                        None
+                    }
+                };
+                if let Some(msg) = msg {
+                    self.error(position, msg);
+                }
+                self.walk_statement(stmt);
+            }
+            NodeKind::BREAKSTMT => {
+                if self.loop_depth == 0 && self.switch_scopes.is_empty() {
+                    self.error(position, ErrorMsg::BreakNotInLoopOrSwitch);
+                }
+            }
+            NodeKind::CONTINUESTMT => {
+                if self.loop_depth == 0 {
+                    self.error(position, ErrorMsg::ContinueNotInLoop);
+                }
+            }
             NodeKind::GOTOSTMT(ref mut _label_name) => {}
-            NodeKind::DECLARATION(ref mut _name, ref mut _type) => {}
+            NodeKind::DECLARATION(ref name, ref mut var_type, Some(ref mut initializer)) => {
+                let (init_type, values) = self.walk_initializer(var_type, initializer);
+                // 要素数を省略した配列は,ここで型が決まる
+                if let Some(var) = self.var_map.get_mut(name) {
+                    var.ctype = init_type.clone();
+                }
+                *var_type = init_type;
+                **initializer = Node::new(initializer.position, NodeKind::FLATINIT(values));
+            }
+            NodeKind::DECLARATION(ref mut _name, ref mut _type, None) => {}
+            NodeKind::NOP => {}
             _ => {
                 self.output_invalid_node_type_error(stmt.position);
             }
         }
     }
+    // case の値を制御式の型の定数に畳み込み,重複を調べる
+    fn walk_case_value(&mut self, expr: &mut Node, position: Position) {
+        let value_type = self.walk_expression(expr);
+        let cond_type = match self.switch_scopes.last() {
+            Some(scope) => scope.cond_type.clone(),
+            None => {
+                self.error(position, ErrorMsg::NotInSwitch("case".to_string()));
+                return;
+            }
+        };
+        if value_type.is_integer() {
+            Self::cast_to(expr, &cond_type);
+        }
+
+        let value = match Self::eval_constant(expr) {
+            Some(value) if value_type.is_integer() => value,
+            _ => {
+                self.error(expr.position, ErrorMsg::CaseIsNotConstant);
+                return;
+            }
+        };
+        *expr = Node::new(expr.position, NodeKind::INTEGER(value));
+        expr.ctype = cond_type;
+
+        let scope = self.switch_scopes.last_mut().unwrap();
+        if scope.cases.contains(&value) {
+            self.error(position, ErrorMsg::DuplicateCaseValue(value));
+            return;
+        }
+        scope.cases.push(value);
+    }
+    // 初期化子を (変数の先頭からのオフセット, 値) の並びに平坦化する
+    // 書かれていない要素は0で埋め,要素数を省略した配列は初期化子から型を決める
+    fn walk_initializer(
+        &mut self,
+        var_type: &Type,
+        initializer: &Node,
+    ) -> (Type, Vec<(usize, Node)>) {
+        let mut var_type = self.complete_type(var_type.clone());
+        let mut explicit_values = BTreeMap::new();
+        let top_level = [(Vec::new(), initializer.clone())];
+        let length = self.init_element(&var_type, &top_level, &mut 0, 0, &mut explicit_values);
+        if let TypeKind::ARRAY(elem_type, 0) = var_type.kind {
+            var_type = Type::array_of(*elem_type, length);
+        }
+
+        let mut values = BTreeMap::new();
+        self.zero_fill(&var_type, 0, &mut values);
+        for (offset, value) in explicit_values {
+            Self::insert_init_value(&mut values, offset, value);
+        }
+        (var_type, values.into_iter().collect())
+    }
+    // 要素1つ分を初期化子の列の先頭から初期化し,列を読み進める
+    // 集成体の波括弧が省略されていれば,後続の初期化子もまとめて使う
+    // 配列であれば,初期化した要素数を返す
+    fn init_element(
+        &mut self,
+        ty: &Type,
+        elements: &[(Vec<Designator>, Node)],
+        cursor: &mut usize,
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) -> usize {
+        let ty = self.complete_type(ty.clone());
+        let value = &elements[*cursor].1;
+        if let NodeKind::INITLIST(inner) = &value.kind {
+            *cursor += 1;
+            return self.init_braced(&ty, inner, offset, values);
+        }
+
+        match &ty.kind {
+            // char の配列は文字列リテラルで初期化できる
+            TypeKind::ARRAY(elem_type, len) => match value.kind {
+                NodeKind::STRLIT(idx) if elem_type.kind == TypeKind::CHAR => {
+                    *cursor += 1;
+                    self.init_string(elem_type, *len, idx, offset, values)
+                }
+                _ => self.init_elided(&ty, elements, cursor, offset, values),
+            },
+            // 同じ型の式であれば領域ごとコピーする
+            TypeKind::STRUCT(_) | TypeKind::UNION(_) => {
+                let mut record_value = value.clone();
+                if self.walk_expression(&mut record_value) != ty {
+                    return self.init_elided(&ty, elements, cursor, offset, values);
+                }
+                *cursor += 1;
+                Self::insert_init_value(values, offset, record_value);
+                0
+            }
+            _ => {
+                *cursor += 1;
+                self.init_scalar(&ty, value, offset, values);
+                0
+            }
+        }
+    }
+    // { ... } の各要素を,指示子が無ければ先頭から順に割り当てる
+    // 指示子があればその位置から割り当て直す
+    fn init_braced(
+        &mut self,
+        ty: &Type,
+        elements: &[(Vec<Designator>, Node)],
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) -> usize {
+        let mut cursor = 0;
+        let mut next_position = 0;
+        let mut length = 0;
+        while cursor < elements.len() {
+            let (designators, value) = &elements[cursor];
+            if let Some(designator) = designators.first() {
+                match self.designated_subobject(ty, designator) {
+                    Some((position, sub_type, sub_offset)) => {
+                        let rest = &designators[1..];
+                        self.init_designated(&sub_type, rest, value, offset + sub_offset, values);
+                        next_position = position + 1;
+                    }
+                    None => {
+                        self.error(value.position, ErrorMsg::InvalidDesignator);
+                    }
+                }
+                cursor += 1;
+            } else {
+                match Self::subobject_at(ty, next_position) {
+                    Some((sub_type, sub_offset)) => {
+                        self.init_element(
+                            &sub_type,
+                            elements,
+                            &mut cursor,
+                            offset + sub_offset,
+                            values,
+                        );
+                        next_position += 1;
+                    }
+                    None => {
+                        self.error(value.position, ErrorMsg::ExcessElementsInInitializer);
+                        break;
+                    }
+                }
+            }
+            length = length.max(next_position);
+        }
+        length
+    }
+    // 波括弧が省略された集成体は,後続の初期化子を入るだけ使う
+    // 指示子の付いた初期化子は外側の波括弧に属するので,そこで止める
+    fn init_elided(
+        &mut self,
+        ty: &Type,
+        elements: &[(Vec<Designator>, Node)],
+        cursor: &mut usize,
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) -> usize {
+        let mut position = 0;
+        while *cursor < elements.len() && elements[*cursor].0.is_empty() {
+            match Self::subobject_at(ty, position) {
+                Some((sub_type, sub_offset)) => {
+                    self.init_element(&sub_type, elements, cursor, offset + sub_offset, values);
+                    position += 1;
+                }
+                None => break,
+            }
+        }
+        position
+    }
+    // .x.y = v や [1][2] = v のような指示子の並びを辿って初期化する
+    fn init_designated(
+        &mut self,
+        ty: &Type,
+        designators: &[Designator],
+        value: &Node,
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) {
+        let ty = self.complete_type(ty.clone());
+        let designator = match designators.first() {
+            Some(designator) => designator,
+            None => {
+                let element = [(Vec::new(), value.clone())];
+                self.init_element(&ty, &element, &mut 0, offset, values);
+                return;
+            }
+        };
+        match self.designated_subobject(&ty, designator) {
+            Some((_position, sub_type, sub_offset)) => {
+                let rest = &designators[1..];
+                self.init_designated(&sub_type, rest, value, offset + sub_offset, values);
+            }
+            None => {
+                self.error(value.position, ErrorMsg::InvalidDesignator);
+            }
+        }
+    }
+    // 文字列リテラルの各文字で char の配列を初期化する
+    // 終端のnull文字が配列に収まらなければ省く
+    fn init_string(
+        &mut self,
+        elem_type: &Type,
+        len: usize,
+        idx: usize,
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) -> usize {
+        let mut contents = self.string_literals[idx].clone();
+        contents.push(0);
+        if len != 0 {
+            contents.truncate(len);
+        }
+        for (i, byte) in contents.iter().enumerate() {
+            let mut value = Node::new(Position::new(0, 0), NodeKind::INTEGER(*byte as i128));
+            value.ctype = Type::new_integer();
+            Self::cast_to(&mut value, elem_type);
+            Self::insert_init_value(values, offset + i, value);
+        }
+        contents.len()
+    }
+    // スカラは代入と同じ規則で型を合わせる
+    fn init_scalar(
+        &mut self,
+        ty: &Type,
+        value: &Node,
+        offset: usize,
+        values: &mut BTreeMap<usize, Node>,
+    ) {
+        let mut value = value.clone();
+        let value_type = self.walk_expression(&mut value);
+        let is_null_init =
+            matches!(ty.kind, TypeKind::POINTER(_)) && value.is_null_pointer_constant();
+        if (ty.is_integer() && value_type.is_integer())
+            || is_null_init
+            || Self::is_void_pointer_conversion(ty, &value_type)
+        {
+            Self::cast_to(&mut value, ty);
+        } else if ty != &value_type {
+            self.output_type_difference_error(value.position);
+            return;
+        }
+        self.check_qualifiers_kept(ty, &value_type, value.position);
+        Self::insert_init_value(values, offset, value);
+    }
+    // 集成体の position 番目の要素の型と,集成体の先頭からのオフセット
+    // 共用体は先頭のメンバだけを順に初期化する
+    fn subobject_at(ty: &Type, position: usize) -> Option<(Type, usize)> {
+        match &ty.kind {
+            // 要素数を省略した配列はいくらでも要素を持てる
+            TypeKind::ARRAY(elem_type, len) if *len == 0 || position < *len => {
+                Some((*elem_type.clone(), position * elem_type.byte_size))
+            }
+            TypeKind::ARRAY(_elem_type, _len) => None,
+            TypeKind::STRUCT(record) => record
+                .members
+                .get(position)
+                .map(|member| (member.ctype.clone(), member.offset)),
+            TypeKind::UNION(record) if position == 0 => record
+                .members
+                .first()
+                .map(|member| (member.ctype.clone(), member.offset)),
+            TypeKind::UNION(_record) => None,
+            // スカラを { } で囲んだ場合
+            _ if position == 0 => Some((ty.clone(), 0)),
+            _ => None,
+        }
+    }
+    // 指示子が指す要素の位置,型,集成体の先頭からのオフセット
+    fn designated_subobject(
+        &mut self,
+        ty: &Type,
+        designator: &Designator,
+    ) -> Option<(usize, Type, usize)> {
+        match (designator, &ty.kind) {
+            (Designator::INDEX(index), TypeKind::ARRAY(_elem_type, len)) => {
+                let mut index = index.clone();
+                self.walk_expression(&mut index);
+                let position = usize::try_from(Self::eval_constant(&index)?).ok()?;
+                if *len != 0 && position >= *len {
+                    return None;
+                }
+                let (sub_type, sub_offset) = Self::subobject_at(ty, position)?;
+                Some((position, sub_type, sub_offset))
+            }
+            (Designator::MEMBER(name), TypeKind::STRUCT(record))
+            | (Designator::MEMBER(name), TypeKind::UNION(record)) => {
+                let position = record.members.iter().position(|m| &m.name == name)?;
+                let member = &record.members[position];
+                Some((position, member.ctype.clone(), member.offset))
+            }
+            _ => None,
+        }
+    }
+    // 書かれていない要素の初期値.スカラの要素ごとに0を置く
+    fn zero_fill(&self, ty: &Type, offset: usize, values: &mut BTreeMap<usize, Node>) {
+        let ty = self.complete_type(ty.clone());
+        match &ty.kind {
+            TypeKind::ARRAY(elem_type, len) => {
+                for i in 0..*len {
+                    self.zero_fill(elem_type, offset + i * elem_type.byte_size, values);
+                }
+            }
+            TypeKind::STRUCT(record) => {
+                for member in record.members.iter() {
+                    self.zero_fill(&member.ctype, offset + member.offset, values);
+                }
+            }
+            TypeKind::UNION(record) => {
+                if let Some(member) = record.members.first() {
+                    self.zero_fill(&member.ctype, offset, values);
+                }
+            }
+            _ => {
+                let mut zero = Node::new(Position::new(0, 0), NodeKind::INTEGER(0));
+                zero.ctype = ty.clone();
+                values.insert(offset, zero);
+            }
+        }
+    }
+    // 後から書いた初期化子が優先されるので,覆われる位置にある値は取り除く
+    fn insert_init_value(values: &mut BTreeMap<usize, Node>, offset: usize, value: Node) {
+        let end = offset + value.ctype.byte_size.max(1);
+        let covered: Vec<usize> = values.range(offset..end).map(|(o, _)| *o).collect();
+        for covered_offset in covered {
+            values.remove(&covered_offset);
+        }
+        values.insert(offset, value);
+    }
+    // ループ本体の中では continue が使える
+    fn walk_loop_body(&mut self, stmt: &mut Node) {
+        self.loop_depth += 1;
+        self.walk_statement(stmt);
+        self.loop_depth -= 1;
+    }
+    // 実引数を仮引数の型に代入するときと同じ規則で変換する
+    // 引数並びを省略した関数には,整数拡張だけを施して渡す
+    fn walk_arguments(
+        &mut self,
+        pos: Position,
+        name: &str,
+        signature: &Signature,
+        args: &mut [Node],
+    ) {
+        let param_types = match &signature.param_types {
+            Some(param_types) => param_types,
+            None => {
+                self.walk_promoted_arguments(args);
+                return;
+            }
+        };
+
+        // 可変長引数の関数には,名前付き引数より多く渡せる
+        let count_mismatch = if signature.is_variadic {
+            param_types.len() > args.len()
+        } else {
+            param_types.len() != args.len()
+        };
+        if count_mismatch {
+            self.error(
+                pos,
+                ErrorMsg::ArgumentCountMismatch(name.to_string(), param_types.len(), args.len()),
+            );
+            for arg in args.iter_mut() {
+                self.walk_expression(arg);
+            }
+            return;
+        }
+
+        for (index, (arg, param_type)) in args.iter_mut().zip(param_types.iter()).enumerate() {
+            let arg_type = self.walk_expression(arg);
+            let is_null_arg =
+                matches!(param_type.kind, TypeKind::POINTER(_)) && arg.is_null_pointer_constant();
+            if (param_type.is_integer() && arg_type.is_integer())
+                || is_null_arg
+                || Self::is_void_pointer_conversion(param_type, &arg_type)
+            {
+                Self::cast_to(arg, param_type);
+            } else if param_type != &arg_type {
+                // 何番目の引数か(1から数える)
+                let msg = ErrorMsg::ArgumentTypeMismatch(
+                    name.to_string(),
+                    index + 1,
+                    param_type.to_string(),
+                    arg_type.to_string(),
+                );
+                self.error(arg.position, msg);
+                continue;
+            }
+            self.check_qualifiers_kept(param_type, &arg_type, arg.position);
+        }
+        self.walk_promoted_arguments(&mut args[param_types.len()..]);
+    }
+    // 型の分からない引数は,整数拡張だけを行って渡す
+    fn walk_promoted_arguments(&mut self, args: &mut [Node]) {
+        for arg in args.iter_mut() {
+            let arg_type = self.walk_expression(arg);
+            if arg_type.is_integer() {
+                Self::cast_to(arg, &arg_type.promoted());
+            }
+        }
+    }
+    // va_list は配列なので,式の型は構造体へのポインタになる
+    fn walk_va_list(&mut self, ap: &mut Node, builtin: &str) {
+        let ap_type = self.walk_expression(ap);
+        if !ap_type.is_va_list() {
+            self.error(
+                ap.position,
+                ErrorMsg::FirstArgumentMustBeVaList(builtin.to_string()),
+            );
+        }
+    }
+    // 配列型の式は先頭要素へのポインタに,関数型の式は関数ポインタに変換する
+    // a -> &a (型は要素へのポインタ), f -> &f (型は関数へのポインタ)
     fn walk_expression(&mut self, n: &mut Node) -> Type {
+        let expr_type = self.walk_expression_without_decay(n);
+        let decayed_type = match expr_type.kind {
+            TypeKind::ARRAY(elem_type, _len) => Type::pointer_to(*elem_type),
+            TypeKind::FUNCTION(_) => Type::pointer_to(expr_type),
+            _ => return expr_type,
+        };
+        let designator = n.clone();
+        *n = Node::new(
+            designator.position,
+            NodeKind::ADDRESSOF(Box::new(designator)),
+        );
+        n.ctype = decayed_type;
+        n.ctype.clone()
+    }
+    // 名前の指す変数.内側のスコープから順に探す
+    fn find_variable(&self, name: &str) -> Option<&Variable> {
+        self.var_map
+            .get(name)
+            .or_else(|| self.params.get(name))
+            .or_else(|| self.global_map.get(name))
+    }
+    // sizeof や & のオペランドは配列のまま扱う
+    fn walk_expression_without_decay(&mut self, n: &mut Node) -> Type {
         match n.kind {
-            // TODO: 後で全探索じゃない方法に書き換える
-            // 関数列をマップで持たせれば解決する.
-            NodeKind::CALL(ref mut ident, ref mut _args) => {
-                for func in self.functions.iter() {
-                    if func.name == ident.ident_name() {
-                        return func.return_type.clone();
+            // 関数名であれば直接呼び出し,それ以外の式は関数ポインタとして呼び出す
+            NodeKind::CALL(ref mut callee, ref mut args) => {
+                let is_direct = match &callee.kind {
+                    NodeKind::IDENTIFIER(name) => self.find_variable(name).is_none(),
+                    _ => false,
+                };
+                let signature = if is_direct {
+                    let name = callee.ident_name();
+                    match self.signatures.get(&name) {
+                        Some(signature) => signature.clone(),
+                        None => {
+                            self.error(n.position, ErrorMsg::ImplicitFunctionDeclaration(name));
+                            return Type::new_unknown();
+                        }
+                    }
+                } else {
+                    let callee_type = self.walk_expression(callee);
+                    match callee_type.pointed_signature() {
+                        Some(signature) => signature.clone(),
+                        None => {
+                            self.error(
+                                n.position,
+                                ErrorMsg::CalledObjectIsNotFunction(callee_type.to_string()),
+                            );
+                            return Type::new_unknown();
+                        }
                     }
+                };
+                if is_direct {
+                    callee.ctype = Type::function_of(signature.clone());
                 }
-                Type::new_unknown()
+                self.walk_arguments(n.position, &callee.to_string(), &signature, args);
+                n.ctype = signature.return_type;
+                n.ctype.clone()
             }
-            NodeKind::INTEGER(_val) => {
-                n.ctype = Type::new_integer();
+            // 接尾辞などで型の決まっている整数定数は,その型の表現に揃える
+            NodeKind::INTEGER(val) if n.ctype.is_integer() => {
+                n.kind = NodeKind::INTEGER(Self::truncate_integer(val, &n.ctype));
+                n.ctype.clone()
+            }
+            // int に収まらない整数定数は long
+            NodeKind::INTEGER(val) => {
+                n.ctype = if i32::MIN as i128 <= val && val <= i32::MAX as i128 {
+                    Type::new_integer()
+                } else {
+                    Type::new_long()
+                };
+                n.ctype.clone()
+            }
+            // 終端のnull文字を含めた char の配列
+            NodeKind::STRLIT(idx) => {
+                let length = self.string_literals[idx].len() + 1;
+                n.ctype = Type::array_of(Type::new_char(), length);
                 n.ctype.clone()
             }
+            // 変数でない名前は関数を指す
             NodeKind::IDENTIFIER(ref name) => {
-                if let Some(var) = self.var_map.get(name) {
-                    return var.ctype.clone();
+                if let Some(var) = self.find_variable(name) {
+                    n.ctype = var.ctype.clone();
+                    return n.ctype.clone();
+                }
+                if let Some(signature) = self.signatures.get(name) {
+                    n.ctype = Type::function_of(signature.clone());
+                    return n.ctype.clone();
                 }
                 Type::new_unknown()
             }
+            NodeKind::ADDRESSOF(ref mut inner) => {
+                let inner_type = self.walk_expression_without_decay(inner);
+                if !inner.is_lvalue() {
+                    self.error(n.position, ErrorMsg::CantTakeAddressOfRvalue);
+                    return Type::new_unknown();
+                }
+                n.ctype = Type::pointer_to(inner_type);
+                n.ctype.clone()
+            }
+            NodeKind::DEREFERENCE(ref mut inner) => match self.walk_expression(inner).kind {
+                TypeKind::POINTER(base) => {
+                    n.ctype = self.complete_type(*base);
+                    n.ctype.clone()
+                }
+                _ => {
+                    self.error(n.position, ErrorMsg::IndirectionRequiresPointer);
+                    Type::new_unknown()
+                }
+            },
+            // メンバの型とオフセットは構造体の定義から引く
+            NodeKind::MEMBER(ref mut record, ref member_name) => {
+                let record_type = self.walk_expression(record);
+                record.ctype = self.complete_type(record_type);
+                if !record.ctype.is_record() {
+                    self.error(n.position, ErrorMsg::MemberReferenceRequiresRecord);
+                    return Type::new_unknown();
+                }
+                // const な構造体のメンバは const
+                match record.ctype.find_member(member_name) {
+                    Some(member) => {
+                        n.ctype = member.ctype.qualified(record.ctype.qualifiers);
+                        n.ctype.clone()
+                    }
+                    None => {
+                        self.error(n.position, ErrorMsg::NoSuchMember(member_name.to_string()));
+                        Type::new_unknown()
+                    }
+                }
+            }
+            // 可変長引数の組み込み関数
+            NodeKind::VASTART(ref mut ap) => {
+                self.walk_va_list(ap, "va_start");
+                if !self.is_variadic {
+                    self.error(n.position, ErrorMsg::VaStartInFixedArgsFunction);
+                }
+                n.ctype = Type::new_void();
+                n.ctype.clone()
+            }
+            NodeKind::VAARG(ref mut ap) => {
+                self.walk_va_list(ap, "va_arg");
+                let is_pointer = matches!(n.ctype.kind, TypeKind::POINTER(_));
+                if !n.ctype.is_integer() && !is_pointer {
+                    self.error(n.position, ErrorMsg::InvalidVaArgType);
+                }
+                n.ctype.clone()
+            }
+            NodeKind::VAEND(ref mut ap) => {
+                self.walk_va_list(ap, "va_end");
+                n.ctype = Type::new_void();
+                n.ctype.clone()
+            }
+            NodeKind::SIZEOF(ref mut inner) => {
+                let size = self.walk_expression_without_decay(inner).byte_size;
+                n.kind = NodeKind::INTEGER(size as i128);
+                n.ctype = Self::size_type();
+                n.ctype.clone()
+            }
 
+            // 右辺は左辺の型に変換してから代入する
             NodeKind::ASSIGN(ref mut lv, ref mut rv) => {
                 let left_type = self.walk_expression(lv);
                 let right_type = self.walk_expression(rv);
-                if left_type == right_type {
-                    n.ctype = left_type;
-                    return right_type;
+                self.check_assignable(&left_type, lv.position);
+                let is_null_assign =
+                    matches!(left_type.kind, TypeKind::POINTER(_)) && rv.is_null_pointer_constant();
+                if (left_type.is_integer() && right_type.is_integer())
+                    || is_null_assign
+                    || Self::is_void_pointer_conversion(&left_type, &right_type)
+                {
+                    Self::cast_to(rv, &left_type);
+                } else if left_type != right_type {
+                    self.output_type_difference_error(lv.position);
+                    return Type::new_unknown();
                 }
-                self.output_type_difference_error(lv.position);
-                Type::new_unknown()
+                self.check_qualifiers_kept(&left_type, &right_type, rv.position);
+                n.ctype = left_type;
+                n.ctype.clone()
+            }
+            // 二項演算の結果を左辺の型に戻して書き込む
+            // 二項演算の左オペランドは型変換で包まれていることがある
+            NodeKind::COMPOUNDASSIGN(ref mut binary) => {
+                self.walk_expression(binary);
+                let (_operator, lv, _rv) = binary.split_binary();
+                n.ctype = match &lv.kind {
+                    NodeKind::CAST(inner) => inner.ctype.clone(),
+                    _ => lv.ctype.clone(),
+                };
+                self.check_assignable(&n.ctype, n.position);
+                n.ctype.clone()
+            }
+            NodeKind::COMMA(ref mut left, ref mut right) => {
+                self.walk_expression(left);
+                n.ctype = self.walk_expression(right);
+                n.ctype.clone()
             }
+            NodeKind::CONDITIONAL(ref mut cond, ref mut then, ref mut alter) => {
+                self.walk_expression(cond);
+                let then_type = self.walk_expression(then);
+                let else_type = self.walk_expression(alter);
+                if then_type.is_integer() && else_type.is_integer() {
+                    let common_type = Type::common_arithmetic(&then_type, &else_type);
+                    Self::cast_to(then, &common_type);
+                    Self::cast_to(alter, &common_type);
+                    n.ctype = common_type;
+                    return n.ctype.clone();
+                }
+                match Self::conditional_type(then_type, else_type, then, alter) {
+                    Some(result_type) => {
+                        n.ctype = result_type;
+                        n.ctype.clone()
+                    }
+                    None => {
+                        self.error(n.position, ErrorMsg::IncompatibleOperandsInConditional);
+                        Type::new_unknown()
+                    }
+                }
+            }
+            // 意味解析で挿入済みの型変換
+            NodeKind::CAST(ref mut _inner) => n.ctype.clone(),
             // 単項演算
-            NodeKind::NEGATIVE(ref mut inner) => {
+            // 算術演算はオペランドを整数拡張してから行う
+            NodeKind::NEGATIVE(ref mut inner) | NodeKind::BITNOT(ref mut inner) => {
                 let inner_type = self.walk_expression(inner);
+                if !inner_type.is_integer() {
+                    self.output_type_difference_error(inner.position);
+                    return Type::new_unknown();
+                }
+                n.ctype = inner_type.promoted();
+                Self::cast_to(inner, &n.ctype);
+                n.ctype.clone()
+            }
+            // 更新した値は元の型に戻して書き込むので,拡張しない
+            NodeKind::POSTINCREMENT(ref mut inner) | NodeKind::POSTDECREMENT(ref mut inner) => {
+                let inner_type = self.walk_expression(inner);
+                self.check_assignable(&inner_type, n.position);
                 n.ctype = inner_type.clone();
                 return inner_type;
             }
+            NodeKind::ADD(_, _) | NodeKind::SUB(_, _) => self.walk_additive(n),
+            // シフトは各オペランドを別々に拡張し,結果は左オペランドの型になる
+            NodeKind::LSHIFT(ref mut left, ref mut right)
+            | NodeKind::RSHIFT(ref mut left, ref mut right) => {
+                let left_type = self.walk_expression(left);
+                let right_type = self.walk_expression(right);
+                if !left_type.is_integer() || !right_type.is_integer() {
+                    self.output_type_difference_error(left.position);
+                    return Type::new_unknown();
+                }
+                Self::cast_to(right, &right_type.promoted());
+                n.ctype = left_type.promoted();
+                Self::cast_to(left, &n.ctype);
+                n.ctype.clone()
+            }
             // 二項演算
-            NodeKind::ADD(ref mut left, ref mut right)
-            | NodeKind::SUB(ref mut left, ref mut right)
-            | NodeKind::MUL(ref mut left, ref mut right)
-            | NodeKind::DIV(ref mut left, ref mut right) => {
+            NodeKind::MUL(ref mut left, ref mut right)
+            | NodeKind::DIV(ref mut left, ref mut right)
+            | NodeKind::MOD(ref mut left, ref mut right)
+            | NodeKind::BITAND(ref mut left, ref mut right)
+            | NodeKind::BITOR(ref mut left, ref mut right)
+            | NodeKind::BITXOR(ref mut left, ref mut right) => {
                 let left_type = self.walk_expression(left);
                 let right_type = self.walk_expression(right);
-                if left_type == right_type {
-                    n.ctype = left_type;
-                    return right_type;
+                if left_type.is_integer() && right_type.is_integer() {
+                    n.ctype = Self::convert_arithmetic_operands(left, right);
+                    return n.ctype.clone();
                 }
-                // floatとintなど,暗黙的型変換が可能な組み合わせならそれを返す
-                // self.implicit_type_inference();
 
                 self.output_type_difference_error(left.position);
                 Type::new_unknown()
             }
+            // 比較演算の結果はint
+            NodeKind::LESSTHAN(ref mut left, ref mut right)
+            | NodeKind::LESSTHANEQUAL(ref mut left, ref mut right)
+            | NodeKind::GREATERTHAN(ref mut left, ref mut right)
+            | NodeKind::GREATERTHANEQUAL(ref mut left, ref mut right)
+            | NodeKind::EQUAL(ref mut left, ref mut right)
+            | NodeKind::NOTEQUAL(ref mut left, ref mut right) => {
+                let left_type = self.walk_expression(left);
+                let right_type = self.walk_expression(right);
+                if left_type.is_integer() && right_type.is_integer() {
+                    Self::convert_arithmetic_operands(left, right);
+                } else if left_type != right_type {
+                    // ポインタとヌルポインタ定数, void * の比較
+                    match (&left_type.kind, &right_type.kind) {
+                        _ if Self::is_void_pointer_conversion(&left_type, &right_type) => {
+                            Self::cast_to(right, &left_type);
+                        }
+                        (TypeKind::POINTER(_), _) if right.is_null_pointer_constant() => {
+                            Self::cast_to(right, &left_type);
+                        }
+                        (_, TypeKind::POINTER(_)) if left.is_null_pointer_constant() => {
+                            Self::cast_to(left, &right_type);
+                        }
+                        _ => {
+                            self.output_type_difference_error(left.position);
+                            return Type::new_unknown();
+                        }
+                    }
+                }
+                n.ctype = Type::new_integer();
+                n.ctype.clone()
+            }
+            // 論理演算は各オペランドを0と比較するだけなので,型を揃える必要はない
+            NodeKind::LOGICALAND(ref mut left, ref mut right)
+            | NodeKind::LOGICALOR(ref mut left, ref mut right) => {
+                self.walk_expression(left);
+                self.walk_expression(right);
+                n.ctype = Type::new_integer();
+                n.ctype.clone()
+            }
+            NodeKind::LOGICALNOT(ref mut inner) => {
+                self.walk_expression(inner);
+                n.ctype = Type::new_integer();
+                n.ctype.clone()
+            }
             _ => {
                 self.output_invalid_node_type_error(n.position);
                 Type::new_unknown()
             }
         }
     }
-    fn output_type_difference_error(&mut self, position: (usize, usize)) {
-        let err = Error::new(
-            ErrorKind::Type,
-            position,
-            ErrorMsg::MustBeSameTypeInBinaryOperation,
-        );
-        err.found();
+    // 加減算
+    // ポインタと整数の演算では整数を要素のサイズ倍し,ポインタ同士の差は要素のサイズで割る
+    fn walk_additive(&mut self, n: &mut Node) -> Type {
+        let is_add = matches!(n.kind, NodeKind::ADD(_, _));
+        let (left, right) = match n.kind {
+            NodeKind::ADD(ref mut left, ref mut right)
+            | NodeKind::SUB(ref mut left, ref mut right) => (left, right),
+            _ => panic!("not additive operation"),
+        };
+        let left_type = self.walk_expression(left);
+        let right_type = self.walk_expression(right);
+
+        match (&left_type.kind, &right_type.kind) {
+            (TypeKind::POINTER(base), _) if right_type.is_integer() => {
+                let base = self.complete_type(*base.clone());
+                Self::scale_by_element_size(right, base.byte_size);
+                n.ctype = left_type.clone();
+            }
+            (_, TypeKind::POINTER(base)) if is_add && left_type.is_integer() => {
+                let base = self.complete_type(*base.clone());
+                Self::scale_by_element_size(left, base.byte_size);
+                n.ctype = right_type.clone();
+            }
+            (TypeKind::POINTER(base), TypeKind::POINTER(_))
+                if !is_add && left_type == right_type =>
+            {
+                // p - q -> (p - q) / sizeof(*p)
+                // 差は long で表す
+                let base = self.complete_type(*base.clone());
+                let mut size_node =
+                    Node::new(n.position, NodeKind::INTEGER(base.byte_size as i128));
+                size_node.ctype = Type::new_long();
+                n.ctype = Type::new_long();
+                let diff = n.clone();
+                *n = Node::new(
+                    diff.position,
+                    NodeKind::DIV(Box::new(diff), Box::new(size_node)),
+                );
+                n.ctype = Type::new_long();
+            }
+            (TypeKind::POINTER(_), _) | (_, TypeKind::POINTER(_)) => {
+                self.output_type_difference_error(n.position);
+                return Type::new_unknown();
+            }
+            _ if left_type.is_integer() && right_type.is_integer() => {
+                n.ctype = Self::convert_arithmetic_operands(left, right);
+            }
+            _ => {
+                self.output_type_difference_error(n.position);
+                return Type::new_unknown();
+            }
+        }
+        n.ctype.clone()
+    }
+    // 整数オペランドを要素のサイズ倍する.整数定数なら畳み込む.
+    // アドレス計算は long で行う
+    fn scale_by_element_size(operand: &mut Node, size: usize) {
+        Self::cast_to(operand, &Type::new_long());
+        let position = operand.position;
+        let mut scaled = match operand.kind {
+            NodeKind::INTEGER(value) => {
+                Node::new(position, NodeKind::INTEGER(value * size as i128))
+            }
+            _ => {
+                let mut size_node = Node::new(position, NodeKind::INTEGER(size as i128));
+                size_node.ctype = Type::new_long();
+                Node::new(
+                    position,
+                    NodeKind::MUL(Box::new(operand.clone()), Box::new(size_node)),
+                )
+            }
+        };
+        scaled.ctype = Type::new_long();
+        *operand = scaled;
+    }
+    // 通常の算術型変換を両オペランドに適用し,変換後の型を返す
+    fn convert_arithmetic_operands(left: &mut Node, right: &mut Node) -> Type {
+        let common_type = Type::common_arithmetic(&left.ctype, &right.ctype);
+        Self::cast_to(left, &common_type);
+        Self::cast_to(right, &common_type);
+        common_type
+    }
+    // 式を指定の型に変換する.整数定数ならその場で畳み込む.
+    fn cast_to(n: &mut Node, ty: &Type) {
+        if &n.ctype == ty {
+            return;
+        }
+        if let NodeKind::INTEGER(value) = n.kind {
+            n.kind = NodeKind::INTEGER(Self::truncate_integer(value, ty));
+            n.ctype = ty.clone();
+            return;
+        }
+        let inner = n.clone();
+        *n = Node::new(inner.position, NodeKind::CAST(Box::new(inner)));
+        n.ctype = ty.clone();
+    }
+    // 整数定数を型の表現範囲に切り詰める
+    // 8バイトの値はレジスタ上のビット列が同じになるよう符号付きで表す
+    fn truncate_integer(value: i128, ty: &Type) -> i128 {
+        if !ty.is_integer() {
+            return value;
+        }
+        let bits = ty.byte_size * 8;
+        let truncated = value & ((1 << bits) - 1);
+        let is_negative = (truncated >> (bits - 1)) & 1 == 1;
+        if is_negative && (!ty.is_unsigned || bits == 64) {
+            truncated - (1 << bits)
+        } else {
+            truncated
+        }
+    }
+    // アドレス定数 (&x, 関数名, 配列名, 文字列リテラル) であれば,指すシンボルの名前と
+    // シンボルからのオフセットを返す
+    // e.g. &arr[1] -> &*(&arr + 4) -> (arr, 4)
+    fn eval_address_constant(&self, n: &Node) -> Option<(String, i128)> {
+        match &n.kind {
+            NodeKind::ADDRESSOF(inner) => self.eval_designator_address(inner),
+            // void * への変換など,ポインタ同士の型変換は値を変えない
+            NodeKind::CAST(inner) if matches!(inner.ctype.kind, TypeKind::POINTER(_)) => {
+                self.eval_address_constant(inner)
+            }
+            NodeKind::ADD(left, right) | NodeKind::SUB(left, right) => {
+                let (name, offset) = self.eval_address_constant(left)?;
+                let delta = Self::eval_constant(right)?;
+                match n.kind {
+                    NodeKind::ADD(_, _) => Some((name, offset + delta)),
+                    _ => Some((name, offset - delta)),
+                }
+            }
+            _ => None,
+        }
+    }
+    // 静的な領域にある左辺値のアドレス
+    fn eval_designator_address(&self, n: &Node) -> Option<(String, i128)> {
+        match &n.kind {
+            NodeKind::IDENTIFIER(name)
+                if self.global_map.contains_key(name) || self.signatures.contains_key(name) =>
+            {
+                Some((name.to_string(), 0))
+            }
+            NodeKind::STRLIT(idx) => Some((format!(".LC{}", idx), 0)),
+            NodeKind::DEREFERENCE(inner) => self.eval_address_constant(inner),
+            NodeKind::MEMBER(record, member_name) => {
+                let (name, offset) = self.eval_designator_address(record)?;
+                let member = self
+                    .complete_type(record.ctype.clone())
+                    .record()?
+                    .members
+                    .iter()
+                    .find(|member| &member.name == member_name)?
+                    .clone();
+                Some((name, offset + member.offset as i128))
+            }
+            _ => None,
+        }
+    }
+    // 整数定数式を評価する
+    // 結果はノードの型の表現範囲に切り詰める
+    // 構文解析中(列挙定数の値や配列の要素数)は型が付いていないので,切り詰めない
+    pub fn eval_constant(n: &Node) -> Option<i128> {
+        let value = match &n.kind {
+            NodeKind::INTEGER(value) => *value,
+            NodeKind::CAST(inner) => Self::eval_operand(inner)?,
+            NodeKind::NEGATIVE(inner) => -Self::eval_operand(inner)?,
+            NodeKind::BITNOT(inner) => !Self::eval_operand(inner)?,
+            NodeKind::LOGICALNOT(inner) => (Self::eval_operand(inner)? == 0) as i128,
+            NodeKind::LOGICALAND(left, right) => {
+                (Self::eval_operand(left)? != 0 && Self::eval_operand(right)? != 0) as i128
+            }
+            NodeKind::LOGICALOR(left, right) => {
+                (Self::eval_operand(left)? != 0 || Self::eval_operand(right)? != 0) as i128
+            }
+            NodeKind::CONDITIONAL(cond, then, alter) => {
+                if Self::eval_operand(cond)? != 0 {
+                    Self::eval_operand(then)?
+                } else {
+                    Self::eval_operand(alter)?
+                }
+            }
+            NodeKind::ADD(left, right)
+            | NodeKind::SUB(left, right)
+            | NodeKind::MUL(left, right)
+            | NodeKind::DIV(left, right)
+            | NodeKind::MOD(left, right)
+            | NodeKind::LSHIFT(left, right)
+            | NodeKind::RSHIFT(left, right)
+            | NodeKind::BITAND(left, right)
+            | NodeKind::BITOR(left, right)
+            | NodeKind::BITXOR(left, right)
+            | NodeKind::LESSTHAN(left, right)
+            | NodeKind::LESSTHANEQUAL(left, right)
+            | NodeKind::GREATERTHAN(left, right)
+            | NodeKind::GREATERTHANEQUAL(left, right)
+            | NodeKind::EQUAL(left, right)
+            | NodeKind::NOTEQUAL(left, right) => {
+                let left = Self::eval_operand(left)?;
+                let right = Self::eval_operand(right)?;
+                match &n.kind {
+                    NodeKind::ADD(_, _) => left + right,
+                    NodeKind::SUB(_, _) => left - right,
+                    NodeKind::MUL(_, _) => left * right,
+                    NodeKind::DIV(_, _) => left.checked_div(right)?,
+                    NodeKind::MOD(_, _) => left.checked_rem(right)?,
+                    NodeKind::LSHIFT(_, _) => left.checked_shl(u32::try_from(right).ok()?)?,
+                    NodeKind::RSHIFT(_, _) => left.checked_shr(u32::try_from(right).ok()?)?,
+                    NodeKind::BITAND(_, _) => left & right,
+                    NodeKind::BITOR(_, _) => left | right,
+                    NodeKind::BITXOR(_, _) => left ^ right,
+                    NodeKind::LESSTHAN(_, _) => (left < right) as i128,
+                    NodeKind::LESSTHANEQUAL(_, _) => (left <= right) as i128,
+                    NodeKind::GREATERTHAN(_, _) => (left > right) as i128,
+                    NodeKind::GREATERTHANEQUAL(_, _) => (left >= right) as i128,
+                    NodeKind::EQUAL(_, _) => (left == right) as i128,
+                    _ => (left != right) as i128,
+                }
+            }
+            _ => return None,
+        };
+        Some(Self::truncate_integer(value, &n.ctype))
+    }
+    // 演算に使うために,unsigned の値は負にならないよう戻しておく
+    // (truncate_integer は unsigned long を符号付きで表すため)
+    fn eval_operand(n: &Node) -> Option<i128> {
+        let value = Self::eval_constant(n)?;
+        if n.ctype.is_integer() && n.ctype.is_unsigned {
+            Some(value & ((1 << (n.ctype.byte_size * 8)) - 1))
+        } else {
+            Some(value)
+        }
+    }
+    // 不完全な構造体/共用体型を,その後に定義された完全な型に置き換える
+    // e.g. struct node { struct node *next; } の next の指す先
+    fn complete_type(&self, ty: Type) -> Type {
+        match ty.record() {
+            Some(record) if !record.is_complete => {
+                self.records[record.id].clone().qualified(ty.qualifiers)
+            }
+            _ => ty,
+        }
+    }
+    // sizeof の結果の型(size_t)
+    fn size_type() -> Type {
+        Type::new_long().to_unsigned()
+    }
+    // 条件演算子の結果の型
+    // 算術型同士なら通常の算術型変換を,ポインタとヌルポインタ定数ならポインタ型を返す
+    // void * とオブジェクトへのポインタなら void * を返す
+    fn conditional_type(
+        then_type: Type,
+        else_type: Type,
+        then: &Node,
+        alter: &Node,
+    ) -> Option<Type> {
+        if then_type == else_type {
+            return Some(then_type);
+        }
+        match (&then_type.kind, &else_type.kind) {
+            (TypeKind::POINTER(_), _) if alter.is_null_pointer_constant() => Some(then_type),
+            (_, TypeKind::POINTER(_)) if then.is_null_pointer_constant() => Some(else_type),
+            // 片方が void * なら,結果も void *
+            (TypeKind::POINTER(base), _)
+                if Self::is_void_pointer_conversion(&then_type, &else_type) =>
+            {
+                if base.kind == TypeKind::VOID {
+                    Some(then_type)
+                } else {
+                    Some(else_type)
+                }
+            }
+            _ => None,
+        }
+    }
+    // void * とオブジェクトへのポインタは,互いに暗黙に変換できる
+    // e.g. int *p = malloc(4); の右辺
+    fn is_void_pointer_conversion(to: &Type, from: &Type) -> bool {
+        match (&to.kind, &from.kind) {
+            (TypeKind::POINTER(to_base), TypeKind::POINTER(from_base)) => {
+                let is_object = |base: &Type| !matches!(base.kind, TypeKind::FUNCTION(_));
+                (to_base.kind == TypeKind::VOID && is_object(from_base))
+                    || (from_base.kind == TypeKind::VOID && is_object(to_base))
+            }
+            _ => false,
+        }
+    }
+    // const 修飾された型の式には代入できない
+    fn check_assignable(&mut self, ty: &Type, position: Position) {
+        if ty.is_const() {
+            self.error(position, ErrorMsg::CantAssignToConst(ty.to_string()));
+        }
     }
-    fn output_invalid_node_type_error(&mut self, position: (usize, usize)) {
-        let err = Error::new(ErrorKind::Type, position, ErrorMsg::InvalidNodeCantHaveType);
+    // 代入と同じ規則の変換では,ポインタの指す先の型の修飾子を落とせない
+    // e.g. const int * の値を int * の変数に代入する
+    fn check_qualifiers_kept(&mut self, to: &Type, from: &Type, position: Position) {
+        if let (TypeKind::POINTER(to_base), TypeKind::POINTER(from_base)) = (&to.kind, &from.kind) {
+            let kept = to_base.qualifiers;
+            if kept.merge(from_base.qualifiers) != kept {
+                self.error(
+                    position,
+                    ErrorMsg::DiscardsQualifiers(from.to_string(), to.to_string()),
+                );
+            }
+        }
+    }
+    fn output_type_difference_error(&mut self, position: Position) {
+        self.error(position, ErrorMsg::MustBeSameTypeInBinaryOperation);
+    }
+    fn output_invalid_node_type_error(&mut self, position: Position) {
+        self.error(position, ErrorMsg::InvalidNodeCantHaveType);
+    }
+    fn error(&mut self, position: Position, msg: ErrorMsg) {
+        let err = Error::new(ErrorKind::Type, position, msg);
         err.found();
+        self.error_occurred = true;
     }
 }
 
 // ASTノードへの型付けについてテスト
 #[cfg(test)]
-mod walk_tests {}
+mod walk_tests {
+    use super::*;
+    use crate::compiler::file::SrcFile;
+    use crate::compiler::frontend::lex;
+
+    #[test]
+    fn test_fold_global_initializers() {
+        let input = "int a = 3 * (2 + 1); unsigned char b = -1; long c = 1 < 2 ? 4294967296 << 8 : 0; unsigned long d = -1; int e; int main(){ return 0; }";
+        let manager = walk_input(input);
+        let initial_value = |name: &str| match &manager.global_map.get(name).unwrap().kind {
+            VarKind::GLOBAL(value) => value.clone(),
+            _ => panic!("expected global variable"),
+        };
+
+        let integer = |value: i128| StaticValue::INTEGER(value);
+        assert_eq!(Some(vec![(0, 4, integer(9))]), initial_value("a"));
+        assert_eq!(Some(vec![(0, 1, integer(255))]), initial_value("b"));
+        assert_eq!(Some(vec![(0, 8, integer(1 << 40))]), initial_value("c"));
+        assert_eq!(Some(vec![(0, 8, integer(-1))]), initial_value("d"));
+        assert_eq!(None, initial_value("e"));
+    }
+
+    #[test]
+    fn test_fold_address_constants() {
+        let input = "struct P { int x; int y; }; int arr[4]; struct P p; char *s = \"hi\"; int *q = arr; int *r = &arr[1]; int *t = arr + 3 - 1; int *u = &p.y; void *v = &arr[2]; int main(){ return 0; }";
+        let manager = walk_input(input);
+        let initial_value = |name: &str| match &manager.global_map.get(name).unwrap().kind {
+            VarKind::GLOBAL(value) => value.clone(),
+            _ => panic!("expected global variable"),
+        };
+
+        let address = |name: &str, offset: i128| {
+            Some(vec![(0, 8, StaticValue::ADDRESS(name.to_string(), offset))])
+        };
+        assert_eq!(address(".LC0", 0), initial_value("s"));
+        assert_eq!(address("arr", 0), initial_value("q"));
+        assert_eq!(address("arr", 4), initial_value("r"));
+        assert_eq!(address("arr", 8), initial_value("t"));
+        assert_eq!(address("p", 4), initial_value("u"));
+        assert_eq!(address("arr", 8), initial_value("v"));
+    }
+
+    #[test]
+    fn test_walk_string_literal() {
+        let input = "int main(){ return sizeof(\"ab\\n\"); } int f(){ return \"xyz\"[1]; }";
+        let manager = walk_input(input);
+
+        // sizeof は終端のnull文字を含む
+        match &manager.functions[0].stmts[0].kind {
+            NodeKind::RETURNSTMT(expr) => assert_eq!(NodeKind::INTEGER(4), expr.kind),
+            _ => panic!("expected return statement"),
+        }
+
+        // 式中では先頭要素へのポインタになる
+        match &manager.functions[1].stmts[0].kind {
+            NodeKind::RETURNSTMT(expr) => assert_eq!("(int)*&.LC1 + 1", expr.to_string()),
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_switch_statement() {
+        let input = "int main(){ char c; switch (c) { case 'a': case 1 + 1: return 0; default: case 300: return 1; } return 2; }";
+        let manager = walk_input(input);
+
+        // case の値は制御式の型(昇格後の int)で畳み込まれる
+        match &manager.functions[0].stmts[1].kind {
+            NodeKind::SWITCHSTMT(expr, _stmt, cases, has_default) => {
+                assert_eq!("(int)c", expr.to_string());
+                assert_eq!(&vec![97, 2, 300], cases);
+                assert!(has_default);
+            }
+            _ => panic!("expected switch statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_local_initializers() {
+        let input = "struct P { int x; char c; long y; }; int main(){ int a[] = {1, [3] = 4, 5}; struct P p = {1, 2}; char s[] = \"hi\"; return a[0]; }";
+        let manager = walk_input(input);
+        let func = &manager.functions[0];
+
+        // 要素数を省略した配列は初期化子から長さが決まる
+        assert_eq!(20, func.local_map.get("a").unwrap().ctype.byte_size);
+        assert_eq!(3, func.local_map.get("s").unwrap().ctype.byte_size);
+
+        // 初期化子は先頭からのオフセットと値の並びに平坦化され,残りは0で埋める
+        assert_eq!(
+            "Array<int, 5> a = {+0: 1, +4: 0, +8: 0, +12: 4, +16: 5};",
+            func.stmts[0].to_string()
+        );
+        assert_eq!(
+            "struct P p = {+0: 1, +4: 2, +8: 0};",
+            func.stmts[1].to_string()
+        );
+        assert_eq!(
+            "Array<char, 3> s = {+0: 104, +1: 105, +2: 0};",
+            func.stmts[2].to_string()
+        );
+    }
+
+    #[test]
+    fn test_walk_call_arguments() {
+        let input =
+            "long f(char c, long l, int *p); int g(); int main(){ return f(300, 1, 0) + g(1, 2); }";
+        let manager = walk_input(input);
+
+        // 実引数は仮引数の型に変換され,返り値は宣言された型になる
+        match &manager.functions[0].stmts[0].kind {
+            NodeKind::RETURNSTMT(expr) => {
+                assert_eq!("(int)f(44, 1, 0) + (long)g(1, 2)", expr.to_string())
+            }
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_variadic_arguments() {
+        let input = "long f(long n, ...); int main(){ char c; short s; return f(1, c, s, 2); }";
+        let manager = walk_input(input);
+
+        // ... に対応する実引数は整数拡張だけを行う
+        match &manager.functions[0].stmts[2].kind {
+            NodeKind::RETURNSTMT(expr) => {
+                assert_eq!("(int)f(1, (int)c, (int)s, 2)", expr.to_string())
+            }
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_function_pointers() {
+        let input = "int add(int a, int b); int (*table[])(int, int) = {add, &add}; int main(){ int (*fp)(int, int); fp = add; return fp(1, 2); }";
+        let manager = walk_input(input);
+
+        // 関数名は関数へのポインタに変換され,静的な初期値はアドレスになる
+        let address = |name: &str| StaticValue::ADDRESS(name.to_string(), 0);
+        match &manager.global_map.get("table").unwrap().kind {
+            VarKind::GLOBAL(value) => assert_eq!(
+                &Some(vec![(0, 8, address("add")), (8, 8, address("add"))]),
+                value
+            ),
+            _ => panic!("expected global variable"),
+        }
+
+        let func = &manager.functions[0];
+        assert_eq!("fp = &add;", func.stmts[1].to_string());
+        match &func.stmts[2].kind {
+            NodeKind::RETURNSTMT(expr) => assert_eq!("fp(1, 2)", expr.to_string()),
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_static_locals_and_qualifiers() {
+        let input = "struct P { int x; }; int g; int f(){ static int n = 2 * 3; static int *p = &g; const struct P c = {1}; return n + c.x; }";
+        let manager = walk_input(input);
+
+        // static な局所変数の初期化子は,グローバル変数と同様に畳み込む
+        let initial_value = |name: &str| match &manager.global_map.get(name).unwrap().kind {
+            VarKind::GLOBAL(value) => value.clone(),
+            _ => panic!("expected static variable"),
+        };
+        assert_eq!(
+            Some(vec![(0, 4, StaticValue::INTEGER(6))]),
+            initial_value("n.1")
+        );
+        assert_eq!(
+            Some(vec![(0, 8, StaticValue::ADDRESS("g".to_string(), 0))]),
+            initial_value("p.1")
+        );
+
+        // const な構造体のメンバは const で,読み出した値は修飾子を持たない
+        match &manager.functions[0].stmts[3].kind {
+            NodeKind::RETURNSTMT(expr) => match &expr.kind {
+                NodeKind::ADD(_n, member) => {
+                    assert_eq!("const int", member.ctype.to_string());
+                    assert_eq!("int", expr.ctype.to_string());
+                }
+                _ => panic!("expected addition"),
+            },
+            _ => panic!("expected return statement"),
+        }
+    }
+
+    #[test]
+    fn test_walk_void_pointer_conversions() {
+        let input = "void *v; int *f(){ int *p = v; p = v; v = p; return v == p ? v : p; }";
+        let manager = walk_input(input);
+        assert!(!manager.error_occurred);
+
+        // 関数へのポインタは void * と変換できない
+        let manager = walk_input("int f(); int main(){ void *v = 0; int (*fp)() = v; return 0; }");
+        assert!(manager.error_occurred);
+    }
+
+    #[test]
+    fn test_walk_discarded_qualifiers() {
+        // 修飾子を加える変換はできる
+        let input = "int h(const int *p){ return *p; } int main(){ int x = 1; int *q = &x; const int *r = q; r = q; return h(q); }";
+        let manager = walk_input(input);
+        assert!(!manager.error_occurred);
+
+        // 初期化,代入,引数の受け渡しで const を落とせない
+        let inputs = [
+            "int main(){ const int y = 4; int *q = &y; return *q; }",
+            "int main(){ const int y = 4; int *q; q = &y; return *q; }",
+            "int g(int *p){ return *p; } int main(){ const int y = 4; return g(&y); }",
+            "int main(){ const int y = 4; void *v = &y; return 0; }",
+        ];
+        for input in inputs.iter() {
+            let manager = walk_input(input);
+            assert!(manager.error_occurred, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_walk_records_errors() {
+        let manager = walk_input("int main(){ return 0; }");
+        assert!(!manager.error_occurred);
+
+        // エラーを見つけても解析は続け,コード生成に進まないよう記録する
+        let inputs = [
+            "int main(){ break; return 0; }",
+            "int main(){ const int x = 1; x = 2; return x; }",
+            "int f(int a){ return a; } int main(){ return f(1, 2); }",
+            "struct s { int a; }; int f(int a){ return a; } int main(){ struct s v; return f(v); }",
+            "int main(){ return undefined_fn(3); }",
+        ];
+        for input in inputs.iter() {
+            let manager = walk_input(input);
+            assert!(manager.error_occurred, "{}", input);
+        }
+    }
+
+    fn walk_input(input: &str) -> Manager {
+        let src_file = SrcFile {
+            abs_path: "test.c".to_string(),
+            contents: input.to_string(),
+        };
+        let mut manager = Manager::new(src_file);
+        manager.preprocessed = input.to_string();
+        lex::tokenize(&mut manager);
+        manager.parse();
+        manager.semantics();
+        manager
+    }
+}
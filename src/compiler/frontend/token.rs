@@ -1,10 +1,74 @@
+use crate::compiler::frontend::types::Type;
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
 type Column = usize;
 type Row = usize;
 pub static GLOBAL_EOF_TOKEN: Token = Token {
-    position: (0, 0),
+    position: Position::new(0, 0),
     kind: TokenKind::EOF,
 };
-pub type Position = (Row, Column);
+
+// ソース上の位置.file は行標識 (# <line> "<file>") で与えられた見かけのファイル名
+// 位置情報を Copy のまま持ち回れるように,ファイル名は &'static str で持つ
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Position {
+    pub file: &'static str,
+    pub row: Row,
+    pub column: Column,
+}
+
+impl Position {
+    pub const fn new(row: Row, column: Column) -> Self {
+        Self {
+            file: "",
+            row,
+            column,
+        }
+    }
+    pub fn in_file(self, file: &'static str) -> Self {
+        Self { file, ..self }
+    }
+}
+
+// アセンブラやリンカは,ファイル名を持たない (行, 列) でエラーを作る
+impl From<(Row, Column)> for Position {
+    fn from((row, column): (Row, Column)) -> Self {
+        Self::new(row, column)
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.file.is_empty() {
+            write!(f, "{}:{}", self.row, self.column)
+        } else {
+            write!(f, "{}:{}:{}", self.file, self.row, self.column)
+        }
+    }
+}
+
+// ファイル名を Position に持たせるため,プロセスの終わりまで残る文字列にする
+// 同じ名前は一度だけ確保する
+pub fn intern_file_name(name: &str) -> &'static str {
+    thread_local! {
+        static FILE_NAMES: RefCell<BTreeSet<&'static str>> =
+            const { RefCell::new(BTreeSet::new()) };
+    }
+    FILE_NAMES.with(|names| {
+        let mut names = names.borrow_mut();
+        match names.get(name) {
+            Some(interned) => interned,
+            None => {
+                let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+                names.insert(interned);
+                interned
+            }
+        }
+    })
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct Token {
     pub position: Position,
@@ -28,8 +92,10 @@ impl Token {
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokenKind {
-    INTEGER(i128),      // 整数
-    IDENTIFIER(String), // 識別子
+    INTEGER(i128),            // 整数
+    TYPEDINTEGER(i128, Type), // 接尾辞や16進/8進表記で型の決まる整数
+    IDENTIFIER(String),       // 識別子
+    STRLIT(Vec<u8>),          // 文字列リテラル(エスケープ処理済みのバイト列)
 
     // 記号
     PLUS,      // +記号
@@ -40,22 +106,86 @@ pub enum TokenKind {
     RPAREN,    // )記号
     LBRACE,    // {記号
     RBRACE,    // }記号
+    LBRACKET,  // [記号
+    RBRACKET,  // ]記号
     COLON,     // :記号
     SEMICOLON, // ;記号
     ASSIGN,    // =記号
+    LESS,      // <記号
+    LESSEQ,    // <=記号
+    GREATER,   // >記号
+    GREATEREQ, // >=記号
+    EQUAL,     // ==記号
+    NOTEQUAL,  // !=記号
+    LOGAND,    // &&記号
+    LOGOR,     // ||記号
+    BANG,      // !記号
+    PERCENT,   // %記号
+    LSHIFT,    // <<記号
+    RSHIFT,    // >>記号
+    AMPERSAND, // &記号
+    PIPE,      // |記号
+    CARET,     // ^記号
+    TILDE,     // ~記号
+    QUESTION,  // ?記号
     BLANK,     // 空白類文字
     NEWLINE,   // 改行
     COMMA,     // ,記号
+    DOT,       // .記号
+    ARROW,     // ->記号
+    ELLIPSIS,  // ...記号
     EOF,
 
+    // 代入/増減演算子
+    INCREMENT,       // ++記号
+    DECREMENT,       // --記号
+    PLUSASSIGN,      // +=記号
+    MINUSASSIGN,     // -=記号
+    ASTERISKASSIGN,  // *=記号
+    SLASHASSIGN,     // /=記号
+    PERCENTASSIGN,   // %=記号
+    LSHIFTASSIGN,    // <<=記号
+    RSHIFTASSIGN,    // >>=記号
+    AMPERSANDASSIGN, // &=記号
+    PIPEASSIGN,      // |=記号
+    CARETASSIGN,     // ^=記号
+
     // 予約語
-    IF,     // if
-    DO,     // do
-    ELSE,   // else
-    FOR,    // for
-    WHILE,  // while
-    INT,    // int
-    GOTO,   // goto
-    VOID,   // void
-    RETURN, // return
+    IF,       // if
+    DO,       // do
+    ELSE,     // else
+    FOR,      // for
+    WHILE,    // while
+    SWITCH,   // switch
+    CASE,     // case
+    DEFAULT,  // default
+    INT,      // int
+    CHAR,     // char
+    SHORT,    // short
+    LONG,     // long
+    SIGNED,   // signed
+    UNSIGNED, // unsigned
+    GOTO,     // goto
+    BREAK,    // break
+    CONTINUE, // continue
+    VOID,     // void
+    RETURN,   // return
+    SIZEOF,   // sizeof
+    STRUCT,   // struct
+    UNION,    // union
+    EXTERN,   // extern
+    STATIC,   // static
+    AUTO,     // auto
+    REGISTER, // register
+    TYPEDEF,  // typedef
+    ENUM,     // enum
+    CONST,    // const
+    VOLATILE, // volatile
+    RESTRICT, // restrict
+
+    // 可変長引数の組み込み型/関数
+    VALIST,  // __builtin_va_list
+    VASTART, // __builtin_va_start
+    VAARG,   // __builtin_va_arg
+    VAEND,   // __builtin_va_end
 }
@@ -1,58 +1,478 @@
+use crate::compiler::frontend::node::Signature;
 use crate::compiler::frontend::token::{Token, TokenKind};
 
-#[derive(Clone, Eq, Debug, PartialEq)]
+// va_list の実体となる構造体のタグ
+pub const VA_LIST_TAG: &str = "__va_list_tag";
+
+#[derive(Clone, Eq, Debug)]
 pub struct Type {
     pub kind: TypeKind,
     pub byte_size: usize, // メモリ上のサイズ
+    pub is_unsigned: bool,
+    pub qualifiers: Qualifiers,
 }
 
 impl Type {
     pub fn from_token(tk: Token) -> Self {
         match tk.kind {
+            TokenKind::CHAR => Self::new_char(),
+            TokenKind::SHORT => Self::new_short(),
             TokenKind::INT => Self::new_integer(),
+            TokenKind::LONG => Self::new_long(),
             TokenKind::VOID => Self::new_void(),
             _ => panic!("can't translate {:?} to type", tk),
         }
     }
     pub fn new_void() -> Self {
-        Self {
-            kind: TypeKind::VOID,
-            byte_size: 0,
-        }
+        Self::new(TypeKind::VOID, 0)
+    }
+    pub fn new_char() -> Self {
+        Self::new(TypeKind::CHAR, 1)
+    }
+    pub fn new_short() -> Self {
+        Self::new(TypeKind::SHORT, 2)
     }
     pub fn new_integer() -> Self {
-        Self {
-            kind: TypeKind::INTEGER,
-            byte_size: 8,
-        }
+        Self::new(TypeKind::INTEGER, 4)
+    }
+    pub fn new_long() -> Self {
+        Self::new(TypeKind::LONG, 8)
     }
     pub fn pointer_to(base: Self) -> Self {
         Self {
             kind: TypeKind::POINTER(Box::new(base)),
             byte_size: 8,
+            is_unsigned: true,
+            qualifiers: Qualifiers::default(),
+        }
+    }
+    // 関数型.値としては扱えないが, sizeof は1とする
+    pub fn function_of(signature: Signature) -> Self {
+        Self::new(TypeKind::FUNCTION(Box::new(signature)), 1)
+    }
+    pub fn array_of(base: Self, len: usize) -> Self {
+        Self {
+            byte_size: base.byte_size * len,
+            kind: TypeKind::ARRAY(Box::new(base), len),
+            is_unsigned: false,
+            qualifiers: Qualifiers::default(),
         }
     }
+    // 不完全な構造体/共用体型
+    // メンバが後から定義されるまでサイズは0
+    pub fn new_incomplete_record(id: usize, tag: Option<String>, is_union: bool) -> Self {
+        let record = Record::new(id, tag, Vec::new(), false);
+        let kind = if is_union {
+            TypeKind::UNION(record)
+        } else {
+            TypeKind::STRUCT(record)
+        };
+        Self::new(kind, 0)
+    }
+    // メンバを宣言順に,それぞれの境界に合わせて配置する
+    // 全体のサイズはメンバの最大の境界に揃える
+    pub fn struct_of(id: usize, tag: Option<String>, members: Vec<(String, Type)>) -> Self {
+        let mut offset = 0;
+        let mut laid_out = Vec::new();
+        for (name, ctype) in members {
+            offset = align_to(offset, ctype.alignment());
+            let size = ctype.byte_size;
+            laid_out.push(Member::new(name, ctype, offset));
+            offset += size;
+        }
+        let record = Record::new(id, tag, laid_out, true);
+        let byte_size = align_to(offset, record.alignment());
+        Self::new(TypeKind::STRUCT(record), byte_size)
+    }
+    // 共用体のメンバは全て先頭に配置する
+    pub fn union_of(id: usize, tag: Option<String>, members: Vec<(String, Type)>) -> Self {
+        let mut max_size = 0;
+        let mut laid_out = Vec::new();
+        for (name, ctype) in members {
+            max_size = max_size.max(ctype.byte_size);
+            laid_out.push(Member::new(name, ctype, 0));
+        }
+        let record = Record::new(id, tag, laid_out, true);
+        let byte_size = align_to(max_size, record.alignment());
+        Self::new(TypeKind::UNION(record), byte_size)
+    }
+    // va_list は gcc と同じく,次に取り出す引数の位置を持つ構造体の要素数1の配列にする
+    // 関数に渡すと構造体へのポインタになるので,呼び出し先で va_arg を続けられる
+    pub fn va_list_record(id: usize) -> Self {
+        let members = vec![
+            ("gp_offset".to_string(), Self::new_integer().to_unsigned()),
+            ("fp_offset".to_string(), Self::new_integer().to_unsigned()),
+            (
+                "overflow_arg_area".to_string(),
+                Self::pointer_to(Self::new_void()),
+            ),
+            (
+                "reg_save_area".to_string(),
+                Self::pointer_to(Self::new_void()),
+            ),
+        ];
+        Self::struct_of(id, Some(VA_LIST_TAG.to_string()), members)
+    }
     pub fn new_unknown() -> Self {
+        Self::new(TypeKind::UNKNOWN, 4)
+    }
+    fn new(kind: TypeKind, byte_size: usize) -> Self {
         Self {
-            kind: TypeKind::UNKNOWN,
-            byte_size: 4,
+            kind,
+            byte_size,
+            is_unsigned: false,
+            qualifiers: Qualifiers::default(),
         }
     }
-    pub fn to_string(&self) -> String {
+
+    // 同じ型の unsigned 版を返す
+    pub fn to_unsigned(mut self) -> Self {
+        self.is_unsigned = true;
+        self
+    }
+    // 修飾子を付け加えた型を返す
+    // 配列型の修飾子は要素の型に付く e.g. const int a[3]
+    pub fn qualified(mut self, qualifiers: Qualifiers) -> Self {
+        if let TypeKind::ARRAY(elem_type, len) = self.kind {
+            return Self::array_of(elem_type.qualified(qualifiers), len);
+        }
+        self.qualifiers = self.qualifiers.merge(qualifiers);
+        self
+    }
+    // 値として読み出した時の型.修飾子は外れる
+    pub fn unqualified(&self) -> Self {
+        let mut ty = self.clone();
+        ty.qualifiers = Qualifiers::default();
+        ty
+    }
+    pub fn is_const(&self) -> bool {
+        self.qualifiers.is_const
+    }
+    pub fn is_integer(&self) -> bool {
         match &self.kind {
-            TypeKind::INTEGER => "int".to_string(),
+            TypeKind::CHAR | TypeKind::SHORT | TypeKind::INTEGER | TypeKind::LONG => true,
+            _ => false,
+        }
+    }
+
+    pub fn record(&self) -> Option<&Record> {
+        match &self.kind {
+            TypeKind::STRUCT(record) | TypeKind::UNION(record) => Some(record),
+            _ => None,
+        }
+    }
+    pub fn is_record(&self) -> bool {
+        self.record().is_some()
+    }
+    pub fn is_union(&self) -> bool {
+        matches!(self.kind, TypeKind::UNION(_))
+    }
+    pub fn signature(&self) -> Option<&Signature> {
+        match &self.kind {
+            TypeKind::FUNCTION(signature) => Some(signature),
+            _ => None,
+        }
+    }
+    // 関数ポインタであれば,指す関数の型
+    pub fn pointed_signature(&self) -> Option<&Signature> {
+        match &self.kind {
+            TypeKind::POINTER(base) => base.signature(),
+            _ => None,
+        }
+    }
+    // 配列から変換された va_list か
+    pub fn is_va_list(&self) -> bool {
+        match &self.kind {
+            TypeKind::POINTER(base) => base
+                .record()
+                .is_some_and(|record| record.tag.as_deref() == Some(VA_LIST_TAG)),
+            _ => false,
+        }
+    }
+    // メンバを名前で探す
+    // 無名のメンバの中のメンバも,外側のメンバとして見つかる
+    pub fn find_member(&self, name: &str) -> Option<Member> {
+        for member in self.record()?.members.iter() {
+            if member.name == name {
+                return Some(member.clone());
+            }
+            if member.name.is_empty() {
+                if let Some(mut inner) = member.ctype.find_member(name) {
+                    inner.offset += member.offset;
+                    return Some(inner);
+                }
+            }
+        }
+        None
+    }
+
+    // 整数拡張(integer promotion)
+    // int より小さい型は int に揃える
+    pub fn promoted(&self) -> Self {
+        match &self.kind {
+            TypeKind::CHAR | TypeKind::SHORT => Self::new_integer(),
+            _ => self.unqualified(),
+        }
+    }
+
+    // 通常の算術型変換(usual arithmetic conversion)
+    // 両オペランドが整数型である前提
+    pub fn common_arithmetic(lt: &Self, rt: &Self) -> Self {
+        let lt = lt.promoted();
+        let rt = rt.promoted();
+        if lt.is_unsigned == rt.is_unsigned {
+            return if lt.byte_size >= rt.byte_size { lt } else { rt };
+        }
+        let (unsigned_type, signed_type) = if lt.is_unsigned { (lt, rt) } else { (rt, lt) };
+
+        // unsigned 側のランクが同じか大きい -> unsigned 側
+        // signed 側のほうが大きい -> signed 側(全ての値を表現できる)
+        if unsigned_type.byte_size >= signed_type.byte_size {
+            unsigned_type
+        } else {
+            signed_type
+        }
+    }
+
+    // スタック上に配置する際の境界
+    // 配列は要素の境界に合わせる
+    pub fn alignment(&self) -> usize {
+        match &self.kind {
+            TypeKind::ARRAY(base, _len) => base.alignment(),
+            TypeKind::STRUCT(record) | TypeKind::UNION(record) => record.alignment(),
+            _ => self.byte_size.max(1),
+        }
+    }
+    // 修飾子は const Ptr<char> (char *const) のように先頭に付ける
+    pub fn to_string(&self) -> String {
+        let prefix = if self.is_unsigned && self.is_integer() {
+            "unsigned "
+        } else {
+            ""
+        };
+        let name = match &self.kind {
+            TypeKind::CHAR => format!("{}char", prefix),
+            TypeKind::SHORT => format!("{}short", prefix),
+            TypeKind::INTEGER => format!("{}int", prefix),
+            TypeKind::LONG => format!("{}long", prefix),
             TypeKind::VOID => "void".to_string(),
             TypeKind::POINTER(inner) => format!("Ptr<{}>", inner.to_string()),
+            TypeKind::ARRAY(inner, len) => format!("Array<{}, {}>", inner.to_string(), len),
+            TypeKind::STRUCT(record) => format!("struct {}", record.tag_name()),
+            TypeKind::UNION(record) => format!("union {}", record.tag_name()),
+            TypeKind::FUNCTION(signature) => {
+                let params = match &signature.param_types {
+                    Some(param_types) => param_types
+                        .iter()
+                        .map(|param_type| param_type.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    None => String::new(),
+                };
+                format!("Fn<{}({})>", signature.return_type.to_string(), params)
+            }
             TypeKind::UNKNOWN => "unknown".to_string(),
+        };
+        format!("{}{}", self.qualifiers.prefix(), name)
+    }
+}
+
+// サイズは型の種類から決まるので,比較しない
+// (不完全な構造体型と,後で完成した同じ構造体型を同一視するため)
+// 修飾子も代入や引数の受け渡しでは問わないので,比較しない
+impl PartialEq for Type {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.is_unsigned == other.is_unsigned
+    }
+}
+
+// 型修飾子
+#[derive(Clone, Copy, Eq, Debug, PartialEq, Default)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+    pub is_restrict: bool,
+}
+
+impl Qualifiers {
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+            is_restrict: self.is_restrict || other.is_restrict,
+        }
+    }
+    // 型名の前に付ける修飾子の並び e.g. "const volatile "
+    fn prefix(self) -> String {
+        let mut names = String::new();
+        for (is_qualified, name) in [
+            (self.is_const, "const "),
+            (self.is_volatile, "volatile "),
+            (self.is_restrict, "restrict "),
+        ] {
+            if is_qualified {
+                names += name;
+            }
         }
+        names
     }
 }
 
 type Base = Box<Type>;
 #[derive(Clone, Eq, Debug, PartialEq)]
 pub enum TypeKind {
+    CHAR,
+    SHORT,
     INTEGER,
+    LONG,
     VOID,
     POINTER(Base),
+    ARRAY(Base, usize),
+    STRUCT(Record),
+    UNION(Record),
+    FUNCTION(Box<Signature>),
     UNKNOWN,
 }
+
+// 構造体/共用体の定義
+// 定義ごとに一意なidを持ち,同じidであれば同じ型とみなす
+#[derive(Clone, Eq, Debug)]
+pub struct Record {
+    pub id: usize,
+    pub tag: Option<String>,
+    pub members: Vec<Member>,
+    pub is_complete: bool,
+}
+
+impl Record {
+    fn new(id: usize, tag: Option<String>, members: Vec<Member>, is_complete: bool) -> Self {
+        Self {
+            id,
+            tag,
+            members,
+            is_complete,
+        }
+    }
+    fn alignment(&self) -> usize {
+        self.members
+            .iter()
+            .map(|member| member.ctype.alignment())
+            .max()
+            .unwrap_or(1)
+    }
+    fn tag_name(&self) -> String {
+        match &self.tag {
+            Some(tag) => tag.to_string(),
+            None => "<anonymous>".to_string(),
+        }
+    }
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub ctype: Type,
+    pub offset: usize, // 構造体の先頭からのオフセット
+}
+
+impl Member {
+    fn new(name: String, ctype: Type, offset: usize) -> Self {
+        Self {
+            name,
+            ctype,
+            offset,
+        }
+    }
+}
+
+fn align_to(n: usize, align: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+#[cfg(test)]
+mod types_tests {
+    use super::*;
+
+    #[test]
+    fn test_common_arithmetic() {
+        let char_type = Type::new_char();
+        let int_type = Type::new_integer();
+        let long_type = Type::new_long();
+        let uint_type = Type::new_integer().to_unsigned();
+        let ulong_type = Type::new_long().to_unsigned();
+
+        // char同士でも int に拡張される
+        assert_eq!(int_type, Type::common_arithmetic(&char_type, &char_type));
+        assert_eq!(long_type, Type::common_arithmetic(&int_type, &long_type));
+        assert_eq!(uint_type, Type::common_arithmetic(&int_type, &uint_type));
+        assert_eq!(long_type, Type::common_arithmetic(&uint_type, &long_type));
+        assert_eq!(ulong_type, Type::common_arithmetic(&long_type, &ulong_type));
+    }
+
+    #[test]
+    fn test_qualified_type() {
+        let const_qualifier = Qualifiers {
+            is_const: true,
+            ..Default::default()
+        };
+
+        // 配列の修飾子は要素に付き,修飾子は型の一致に影響しない
+        let array_type = Type::array_of(Type::new_integer(), 2).qualified(const_qualifier);
+        assert_eq!("Array<const int, 2>", array_type.to_string());
+        assert_eq!(Type::array_of(Type::new_integer(), 2), array_type);
+
+        let const_char = Type::new_char().to_unsigned().qualified(const_qualifier);
+        assert!(const_char.is_const());
+        assert!(!const_char.promoted().is_const());
+        assert!(!const_char.unqualified().is_const());
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        // struct { char c; int i; short s; }
+        let members = vec![
+            ("c".to_string(), Type::new_char()),
+            ("i".to_string(), Type::new_integer()),
+            ("s".to_string(), Type::new_short()),
+        ];
+        let struct_type = Type::struct_of(0, None, members);
+        assert_eq!(12, struct_type.byte_size);
+        assert_eq!(4, struct_type.alignment());
+        assert_eq!(4, struct_type.find_member("i").unwrap().offset);
+        assert_eq!(8, struct_type.find_member("s").unwrap().offset);
+        assert_eq!(None, struct_type.find_member("x"));
+    }
+
+    #[test]
+    fn test_union_layout_and_anonymous_member() {
+        // union { char c[5]; int i; }
+        let union_type = Type::union_of(
+            0,
+            None,
+            vec![
+                ("c".to_string(), Type::array_of(Type::new_char(), 5)),
+                ("i".to_string(), Type::new_integer()),
+            ],
+        );
+        assert_eq!(8, union_type.byte_size);
+        assert_eq!(0, union_type.find_member("i").unwrap().offset);
+
+        // struct { long l; union { ... }; }
+        let struct_type = Type::struct_of(
+            1,
+            None,
+            vec![
+                ("l".to_string(), Type::new_long()),
+                ("".to_string(), union_type),
+            ],
+        );
+        assert_eq!(16, struct_type.byte_size);
+        assert_eq!(8, struct_type.find_member("i").unwrap().offset);
+    }
+}
@@ -4,23 +4,64 @@ use crate::compiler::frontend::types;
 pub struct Variable {
     pub kind: VarKind,
     pub ctype: types::Type,
+    // static な変数は内部結合を持ち,他のオブジェクトファイルからは見えない
+    pub is_static: bool,
 }
 impl Variable {
     pub fn init_local(ty: types::Type) -> Self {
         Self {
             kind: VarKind::LOCAL(0),
             ctype: ty,
+            is_static: false,
+        }
+    }
+    pub fn init_global(ty: types::Type) -> Self {
+        Self {
+            kind: VarKind::GLOBAL(None),
+            ctype: ty,
+            is_static: false,
+        }
+    }
+    // static なグローバル変数/局所変数.どちらも .data/.bss に置く
+    pub fn init_static(ty: types::Type) -> Self {
+        Self {
+            kind: VarKind::GLOBAL(None),
+            ctype: ty,
+            is_static: true,
+        }
+    }
+    pub fn init_extern(ty: types::Type) -> Self {
+        Self {
+            kind: VarKind::EXTERN,
+            ctype: ty,
+            is_static: false,
         }
     }
     pub fn get_local_offset(&self) -> usize {
         match &self.kind {
             VarKind::LOCAL(offset) => *offset,
+            VarKind::GLOBAL(_) | VarKind::EXTERN => {
+                panic!("global variable doesn't have stack offset")
+            }
         }
     }
 }
 
 type StackOffset = usize;
+// 初期値が無ければ .bss に,あれば .data に配置する
+// 初期値は (先頭からのオフセット, バイト数, 値) の並びで,並んでいない部分は0で埋める
+type InitialValue = Option<Vec<(usize, usize, StaticValue)>>;
+// 静的な領域に置ける値
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum StaticValue {
+    INTEGER(i128),
+    ADDRESS(String, i128), // 関数や静的な変数のアドレスとオフセット.リンク時に決まる
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum VarKind {
     LOCAL(StackOffset),
+    GLOBAL(InitialValue),
+    // extern 宣言のみで,このファイルでは領域を確保しない
+    EXTERN,
 }
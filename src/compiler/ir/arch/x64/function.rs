@@ -4,6 +4,10 @@ pub struct X64Function {
     pub func_name: String,
     pub blocks: Vec<X64BasicBlock>,
     pub frame_size: usize,
+    // プロローグで退避し,エピローグで戻す callee-saved なレジスタ
+    pub callee_saved: Vec<usize>,
+    // .global を付けない(内部結合の)関数か
+    pub is_static: bool,
 }
 impl X64Function {
     pub fn new(func_name: String, blocks: Vec<X64BasicBlock>, frame_size: usize) -> Self {
@@ -11,6 +15,8 @@ impl X64Function {
             func_name: func_name,
             blocks: blocks,
             frame_size: frame_size,
+            callee_saved: Vec::new(),
+            is_static: false,
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::compiler::ir::arch::x64::ir_kind;
-use ir_kind::{X64IRKind, X64Operand};
+use ir_kind::{X64CondKind, X64IRKind, X64Operand};
 
 #[derive(Debug, Clone)]
 pub struct X64IR {
@@ -12,6 +12,11 @@ impl X64IR {
             kind: X64IRKind::JMP(label_name),
         }
     }
+    pub fn new_jump_table(index: X64Operand, table_label: String, targets: Vec<String>) -> Self {
+        Self {
+            kind: X64IRKind::JMPTABLE(index, table_label, targets),
+        }
+    }
     pub fn new_mov(dst: X64Operand, src: X64Operand) -> Self {
         Self {
             kind: X64IRKind::MOV(dst, src),
@@ -37,16 +42,66 @@ impl X64IR {
             kind: X64IRKind::STORE(dst, src),
         }
     }
+    pub fn new_lea(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::LEA(dst, src),
+        }
+    }
+    pub fn new_load(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::LOAD(dst, src),
+        }
+    }
     pub fn new_div(dst: X64Operand, src: X64Operand) -> Self {
         Self {
             kind: X64IRKind::DIV(dst, src),
         }
     }
+    pub fn new_mod(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::MOD(dst, src),
+        }
+    }
+    pub fn new_shl(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::SHL(dst, src),
+        }
+    }
+    pub fn new_sar(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::SAR(dst, src),
+        }
+    }
+    pub fn new_and(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::AND(dst, src),
+        }
+    }
+    pub fn new_or(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::OR(dst, src),
+        }
+    }
+    pub fn new_xor(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::XOR(dst, src),
+        }
+    }
+    pub fn new_extend(dst: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::EXTEND(dst),
+        }
+    }
     pub fn new_neg(inner: X64Operand) -> Self {
         Self {
             kind: X64IRKind::NEGATIVE(inner),
         }
     }
+    pub fn new_not(inner: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::NOT(inner),
+        }
+    }
     pub fn new_ret(return_op: X64Operand) -> Self {
         Self {
             kind: X64IRKind::RET(return_op),
@@ -57,6 +112,21 @@ impl X64IR {
             kind: X64IRKind::CMPZERO(cmp_op),
         }
     }
+    pub fn new_cmp(dst: X64Operand, src: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::CMP(dst, src),
+        }
+    }
+    pub fn new_set(cond: X64CondKind, dst: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::SET(cond, dst),
+        }
+    }
+    pub fn new_jumpnotzero(label_name: String) -> Self {
+        Self {
+            kind: X64IRKind::JNZ(label_name),
+        }
+    }
     pub fn new_jumpzero(label_name: String) -> Self {
         Self {
             kind: X64IRKind::JZ(label_name),
@@ -67,6 +137,36 @@ impl X64IR {
             kind: X64IRKind::GENPARAM(reg_num, op),
         }
     }
+    pub fn new_push(reg: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::PUSHREG(reg),
+        }
+    }
+    pub fn new_pop(reg: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::POPREG(reg),
+        }
+    }
+    pub fn new_alloc_stack(size: usize) -> Self {
+        Self {
+            kind: X64IRKind::ALLOCSTACK(size),
+        }
+    }
+    pub fn new_free_stack(size: usize) -> Self {
+        Self {
+            kind: X64IRKind::FREESTACK(size),
+        }
+    }
+    pub fn new_call(name: String) -> Self {
+        Self {
+            kind: X64IRKind::CALL(name),
+        }
+    }
+    pub fn new_call_reg(callee: X64Operand) -> Self {
+        Self {
+            kind: X64IRKind::CALLREG(callee),
+        }
+    }
     pub fn new_pushparam(reg_num: usize, offset: usize) -> Self {
         Self {
             kind: X64IRKind::PUSHPARAM(reg_num, offset),
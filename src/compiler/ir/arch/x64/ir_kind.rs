@@ -1,3 +1,6 @@
+// 引数レジスタ(rdi, rsi, rdx, rcx, r8, r9)で渡せる引数の数.残りはスタックで渡す
+pub const REGISTER_ARGS: usize = 6;
+
 type LabelName = String;
 type Offset = usize;
 type RegNumber = usize;
@@ -10,13 +13,29 @@ pub enum X64IRKind {
     SUB(X64Operand, X64Operand),
     MUL(X64Operand, X64Operand),
     DIV(X64Operand, X64Operand),
+    MOD(X64Operand, X64Operand),
+    SHL(X64Operand, X64Operand),
+    SAR(X64Operand, X64Operand),
+    AND(X64Operand, X64Operand),
+    OR(X64Operand, X64Operand),
+    XOR(X64Operand, X64Operand),
+    CMP(X64Operand, X64Operand),
     JMP(LabelName),
     JZ(LabelName),
+    JNZ(LabelName),
+    JMPTABLE(X64Operand, LabelName, Vec<LabelName>), // 添字, テーブルのラベル, 飛び先
     CMPZERO(X64Operand),
     STORE(X64Operand, X64Operand),
+    SET(X64CondKind, X64Operand),
+    LEA(X64Operand, X64Operand),
+    LOAD(X64Operand, X64Operand),
+    CALL(LabelName),
+    CALLREG(X64Operand),
 
     // 1つオペランドを持つ系
+    EXTEND(X64Operand),
     NEGATIVE(X64Operand),
+    NOT(X64Operand),
     RET(X64Operand),
 
     // 具体的なIR
@@ -34,30 +53,107 @@ pub enum X64IRKind {
     MULREGTOREG(X64Operand, X64Operand),
     DIVIMMTOREG(X64Operand, X64Operand),
     DIVREGTOREG(X64Operand, X64Operand),
+    ADDMEMTOREG(X64Operand, X64Operand),
+    SUBMEMTOREG(X64Operand, X64Operand),
+    MULMEMTOREG(X64Operand, X64Operand),
+    DIVMEMTOREG(X64Operand, X64Operand),
+    CMPREGTOREG(X64Operand, X64Operand),
+    CMPIMMTOREG(X64Operand, X64Operand),
+    CMPMEMTOREG(X64Operand, X64Operand),
+    MODREGTOREG(X64Operand, X64Operand),
+    MODIMMTOREG(X64Operand, X64Operand),
+    MODMEMTOREG(X64Operand, X64Operand),
+    SHLREGTOREG(X64Operand, X64Operand),
+    SHLIMMTOREG(X64Operand, X64Operand),
+    SHLMEMTOREG(X64Operand, X64Operand),
+    SARREGTOREG(X64Operand, X64Operand),
+    SARIMMTOREG(X64Operand, X64Operand),
+    SARMEMTOREG(X64Operand, X64Operand),
+    ANDREGTOREG(X64Operand, X64Operand),
+    ANDIMMTOREG(X64Operand, X64Operand),
+    ANDMEMTOREG(X64Operand, X64Operand),
+    ORREGTOREG(X64Operand, X64Operand),
+    ORIMMTOREG(X64Operand, X64Operand),
+    ORMEMTOREG(X64Operand, X64Operand),
+    XORREGTOREG(X64Operand, X64Operand),
+    XORIMMTOREG(X64Operand, X64Operand),
+    XORMEMTOREG(X64Operand, X64Operand),
+    LEAMEMTOREG(X64Operand, X64Operand),
+    LEAGLOBALTOREG(X64Operand, X64Operand),
+    LOADREG(X64Operand, X64Operand),
 
     // 1つオペランドを持つ系
+    EXTENDREG(X64Operand),
     NEGREG(X64Operand),
+    NOTREG(X64Operand),
     RETREG(X64Operand),
     RETIMM(X64Operand),
     RETMEM(X64Operand),
-    RETCALL(X64Operand),
     STOREREG(X64Operand, X64Operand),
     STOREIMM(X64Operand, X64Operand),
     STOREMEM(X64Operand, X64Operand),
+    STOREREGTOPTR(X64Operand, X64Operand),
+    STOREIMMTOPTR(X64Operand, X64Operand),
+    STOREMEMTOPTR(X64Operand, X64Operand),
     CMPZEROREG(X64Operand),
     CMPZEROIMM(X64Operand),
     CMPZEROMEM(X64Operand),
+    SETREG(X64CondKind, X64Operand),
 
     // その他
+    PUSHREG(X64Operand),
+    POPREG(X64Operand),
+    ALLOCSTACK(Offset), // rsp を下げて領域を空ける
+    FREESTACK(Offset),  // 積んだ領域を捨てる
     GENPARAM(RegNumber, X64Operand),
     GENPARAMIMM(RegNumber, X64Operand),
+    GENPARAMREG(RegNumber, X64Operand),
+    GENPARAMMEM(RegNumber, X64Operand),
     PUSHPARAM(RegNumber, Offset),
 }
+
+// 比較結果を取り出す時の条件
+#[derive(Debug, Clone)]
+pub enum X64CondKind {
+    LESS,
+    LESSEQ,
+    GREATER,
+    GREATEREQ,
+    EQUAL,
+    NOTEQUAL,
+    // 符号なしの比較
+    BELOW,
+    BELOWEQ,
+    ABOVE,
+    ABOVEEQ,
+}
+
+impl X64CondKind {
+    // setcc/jcc 命令の接尾辞
+    pub fn to_suffix(&self) -> &str {
+        match self {
+            Self::LESS => "l",
+            Self::LESSEQ => "le",
+            Self::GREATER => "g",
+            Self::GREATEREQ => "ge",
+            Self::EQUAL => "e",
+            Self::NOTEQUAL => "ne",
+            Self::BELOW => "b",
+            Self::BELOWEQ => "be",
+            Self::ABOVE => "a",
+            Self::ABOVEEQ => "ae",
+        }
+    }
+}
+
+// size/is_unsigned はオペランドが表す値の型
 #[derive(Debug, Clone)]
 pub struct X64Operand {
     pub kind: X64OpeKind,
     pub virt: usize,
     pub phys: usize,
+    pub size: usize,
+    pub is_unsigned: bool,
 }
 
 impl X64Operand {
@@ -66,21 +162,21 @@ impl X64Operand {
             kind: kind,
             virt: virt,
             phys: phys,
+            size: 8,
+            is_unsigned: false,
         }
     }
     pub fn new_rax() -> Self {
-        Self {
-            kind: X64OpeKind::REG,
-            virt: 0,
-            phys: 6,
-        }
+        Self::new(X64OpeKind::REG, 0, 6)
     }
     pub fn new_inv() -> Self {
-        Self {
-            kind: X64OpeKind::INVALID,
-            virt: 0,
-            phys: 0,
-        }
+        Self::new(X64OpeKind::INVALID, 0, 0)
+    }
+    // 他のオペランドと同じ型として扱う
+    pub fn with_type_of(mut self, other: &Self) -> Self {
+        self.size = other.size;
+        self.is_unsigned = other.is_unsigned;
+        self
     }
     pub fn int_value(&self) -> i128 {
         match self.kind {
@@ -91,10 +187,17 @@ impl X64Operand {
     pub fn var_name(&self) -> &String {
         match &self.kind {
             X64OpeKind::AUTOVAR(name, _offset) => &name,
-            X64OpeKind::CALL(name) => &name,
+            X64OpeKind::GLOBALVAR(name) => name,
             _ => panic!("can't get variable-name without autovar"),
         }
     }
+    // 間接参照の変位.レジスタをそのままアドレスとして使う場合は0
+    pub fn displacement(&self) -> usize {
+        match &self.kind {
+            X64OpeKind::DEREFERENCE(offset) => *offset,
+            _ => 0,
+        }
+    }
     pub fn var_offset(&self) -> usize {
         match &self.kind {
             X64OpeKind::AUTOVAR(_name, offset) => *offset,
@@ -106,8 +209,9 @@ impl X64Operand {
 #[derive(Debug, Clone)]
 pub enum X64OpeKind {
     INTLIT(i128),
-    CALL(String),
     AUTOVAR(String, usize),
+    GLOBALVAR(String), // rip相対でアドレスを求める
     REG,
+    DEREFERENCE(usize), // physのレジスタが指すアドレスから,オフセットだけ後ろのメモリ
     INVALID,
 }
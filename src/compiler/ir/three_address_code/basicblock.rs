@@ -1,11 +1,9 @@
-use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 use crate::compiler::backend::cfg::ControlFlowGraphInBB;
 use crate::compiler::ir::three_address_code::tac::ThreeAddressCode;
 
 type RegisterNumber = usize;
-type LiveIn = usize;
-type LiveOut = usize;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct BasicBlock {
@@ -13,7 +11,9 @@ pub struct BasicBlock {
     pub tacs: Vec<ThreeAddressCode>,
 
     pub cfg_inbb: ControlFlowGraphInBB,
-    pub living: BTreeMap<RegisterNumber, (LiveIn, LiveOut)>,
+    // ブロックの入口/出口で生きている仮想レジスタ
+    pub live_in: BTreeSet<RegisterNumber>,
+    pub live_out: BTreeSet<RegisterNumber>,
 }
 
 impl BasicBlock {
@@ -22,7 +22,8 @@ impl BasicBlock {
             label: label,
             tacs: Vec::new(),
             cfg_inbb: ControlFlowGraphInBB::new(0),
-            living: BTreeMap::new(),
+            live_in: BTreeSet::new(),
+            live_out: BTreeSet::new(),
         }
     }
     pub fn dump_tacs_to_stderr(&self) {
@@ -38,8 +39,21 @@ impl BasicBlock {
         }
     }
     pub fn dump_liveness(&self) {
-        for (reg_number, range) in self.living.iter() {
-            eprintln!("t{} --> {}...{}", reg_number, range.0, range.1);
-        }
+        let to_string = |regs: &BTreeSet<RegisterNumber>| {
+            regs.iter()
+                .map(|reg_number| format!("t{}", reg_number))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        eprintln!(
+            "  {}'s live-in: {{{}}}",
+            self.label,
+            to_string(&self.live_in)
+        );
+        eprintln!(
+            "  {}'s live-out: {{{}}}",
+            self.label,
+            to_string(&self.live_out)
+        );
     }
 }
@@ -1,10 +1,21 @@
+use crate::compiler::backend::cfg::ControlFlowGraph;
 use crate::compiler::ir::three_address_code::basicblock::BasicBlock;
 
+use std::collections::BTreeMap;
+
+type RegisterNumber = usize;
+type LiveIn = usize;
+type LiveOut = usize;
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct IRFunction {
     pub name: String,
     pub blocks: Vec<BasicBlock>,
     pub frame_size: usize,
+    pub is_static: bool,
+    pub cfg: ControlFlowGraph,
+    // CFGの逆後順に命令を並べた時の,各仮想レジスタの生存区間
+    pub living: BTreeMap<RegisterNumber, (LiveIn, LiveOut)>,
 }
 
 impl IRFunction {
@@ -13,6 +24,18 @@ impl IRFunction {
             name: name,
             blocks: Vec::new(),
             frame_size: 0,
+            is_static: false,
+            cfg: ControlFlowGraph::new(0),
+            living: BTreeMap::new(),
+        }
+    }
+    pub fn dump_liveness(&self) {
+        eprintln!("{}'s liveness:", self.name);
+        for bb in self.blocks.iter() {
+            bb.dump_liveness();
+        }
+        for (reg_number, range) in self.living.iter() {
+            eprintln!("  t{} --> {}...{}", reg_number, range.0, range.1);
         }
     }
 }
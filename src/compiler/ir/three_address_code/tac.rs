@@ -24,8 +24,25 @@ impl ThreeAddressCode {
     pub fn new_iff(lv: Operand, label_name: String) -> Self {
         Self::new(TacKind::IFF(lv, label_name))
     }
-    pub fn new_genparam(reg_num: usize, op: Operand) -> Self {
-        Self::new(TacKind::GENPARAM(reg_num, op))
+    pub fn new_ift(lv: Operand, label_name: String) -> Self {
+        Self::new(TacKind::IFT(lv, label_name))
+    }
+    pub fn new_jump_table(index: Operand, table_label: String, targets: Vec<String>) -> Self {
+        Self::new(TacKind::JUMPTABLE(index, table_label, targets))
+    }
+    pub fn new_call(
+        return_op: Operand,
+        callee: Operand,
+        args: Vec<Operand>,
+        is_variadic: bool,
+    ) -> Self {
+        Self::new(TacKind::CALL(
+            return_op,
+            callee,
+            args,
+            is_variadic,
+            Vec::new(),
+        ))
     }
     pub fn new_pushparam(reg_num: usize, offset: usize) -> Self {
         Self::new(TacKind::PUSHPARAM(reg_num, offset))
@@ -48,6 +65,13 @@ impl ThreeAddressCode {
             TacKind::GOTO(label_name) => format!("goto {}", label_name),
             TacKind::ASSIGN(lv, rv) => format!("{} <- {}", lv.to_string(), rv.to_string()),
             TacKind::IFF(lv, label) => format!("if false {} goto {}", lv.to_string(), label),
+            TacKind::IFT(lv, label) => format!("if true {} goto {}", lv.to_string(), label),
+            TacKind::JUMPTABLE(index, table, targets) => format!(
+                "goto {}[{}] ({})",
+                table,
+                index.to_string(),
+                targets.join(", ")
+            ),
             TacKind::EXPR(var, op, left, right) => format!(
                 "{} <- {} {} {}",
                 var.to_string(),
@@ -62,7 +86,15 @@ impl ThreeAddressCode {
                 inner.to_string(),
             ),
             TacKind::PUSHPARAM(number, offset) => format!("pushparam {}, {}", number, offset),
-            TacKind::GENPARAM(reg_num, op) => format!("genparam {}, {}", reg_num, op.to_string()),
+            TacKind::CALL(return_op, callee, args, _is_variadic, _saved) => format!(
+                "{} <- call {}({})",
+                return_op.to_string(),
+                callee.to_string(),
+                args.iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             TacKind::RET(return_op) => format!("return {}", return_op.to_string()),
         }
     }
@@ -76,6 +108,22 @@ impl ThreeAddressCode {
                 right.to_string_physical()
             ),
             TacKind::RET(return_op) => format!("return {}", return_op.to_string_physical()),
+            TacKind::CALL(return_op, callee, args, _is_variadic, saved) => format!(
+                "{} <- call {}({}) saving {:?}",
+                return_op.to_string_physical(),
+                callee.to_string_physical(),
+                args.iter()
+                    .map(|arg| arg.to_string_physical())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                saved
+            ),
+            TacKind::JUMPTABLE(index, table, targets) => format!(
+                "goto {}[{}] ({})",
+                table,
+                index.to_string_physical(),
+                targets.join(", ")
+            ),
             _ => self.to_string(),
         }
     }
@@ -1,3 +1,5 @@
+use crate::compiler::frontend::types::Type;
+
 type Label = String;
 type Offset = usize;
 type RegNumber = usize;
@@ -10,10 +12,16 @@ pub enum TacKind {
     GOTO(Label),
     ASSIGN(Operand, Operand),
     IFF(Operand, Label),
+    IFT(Operand, Label),
+    // ジャンプテーブルの添字,テーブル自身のラベル,テーブルに並べる飛び先
+    JUMPTABLE(Operand, Label, Vec<Label>),
+    // 返り値を受け取るレジスタ,呼び出す関数(関数名か,関数ポインタを置いたレジスタ),実引数,
+    // 可変長引数(か引数並びの省略された)関数の呼び出しか,
+    // 呼び出しをまたいで生きている caller-saved な物理レジスタ(レジスタ割付で決まる)
+    CALL(Operand, Operand, Vec<Operand>, bool, Vec<RegNumber>),
 
     // ラベルを必要とするのは,CFG構築などで存在すると便利だから.
     // BasicBlockがこの情報を保持しているので,Low-IRに変換したときに捨てる.
-    GENPARAM(RegNumber, Operand),
     PUSHPARAM(RegNumber, Offset),
     LABEL(Label),
 }
@@ -24,6 +32,21 @@ pub enum Operator {
     MINUS,
     ASTERISK,
     SLASH,
+    LESS,
+    LESSEQ,
+    GREATER,
+    GREATEREQ,
+    EQUAL,
+    NOTEQUAL,
+    BANG,
+    PERCENT,
+    LSHIFT,
+    RSHIFT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
+    CAST,
 }
 
 impl Operator {
@@ -33,6 +56,21 @@ impl Operator {
             Self::MINUS => "-",
             Self::ASTERISK => "*",
             Self::SLASH => "/",
+            Self::LESS => "<",
+            Self::LESSEQ => "<=",
+            Self::GREATER => ">",
+            Self::GREATEREQ => ">=",
+            Self::EQUAL => "==",
+            Self::NOTEQUAL => "!=",
+            Self::BANG => "!",
+            Self::PERCENT => "%",
+            Self::LSHIFT => "<<",
+            Self::RSHIFT => ">>",
+            Self::AMPERSAND => "&",
+            Self::PIPE => "|",
+            Self::CARET => "^",
+            Self::TILDE => "~",
+            Self::CAST => "(cast)",
         }
     }
 }
@@ -41,11 +79,16 @@ impl Operator {
 // 最適化によってローカル変数をレジスタに割り付ける可能性があるため.
 //
 // OpeKind::REG(Virtual,Physical) のようにしてしまうとやりづらい.
+//
+// size/is_unsigned はオペランドが表す値の型.
+// レジスタ上の値は常に型に従って64bitに符号/ゼロ拡張された状態に保つ.
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
 pub struct Operand {
     pub kind: OpeKind,
     pub virt: usize,
     pub phys: usize,
+    pub size: usize,
+    pub is_unsigned: bool,
 }
 impl Operand {
     pub fn new(kind: OpeKind) -> Self {
@@ -53,22 +96,37 @@ impl Operand {
             kind: kind,
             virt: 0,
             phys: 0,
+            size: 8,
+            is_unsigned: false,
         }
     }
+    // 型の情報を付与する
+    // 整数型以外(ポインタ等)は8バイトとして扱う
+    pub fn with_type(mut self, ty: &Type) -> Self {
+        self.size = if ty.is_integer() { ty.byte_size } else { 8 };
+        self.is_unsigned = ty.is_unsigned;
+        self
+    }
     pub fn new_int_literal(val: i128) -> Self {
         Self::new(OpeKind::INTLIT(val))
     }
-    pub fn new_call(name: String) -> Self {
-        Self::new(OpeKind::CALL(name))
-    }
     pub fn new_auto_var(name: String, offset: usize) -> Self {
         Self::new(OpeKind::AUTOVARIABLE(name, offset))
     }
+    pub fn new_global_var(name: String) -> Self {
+        Self::new(OpeKind::GLOBALVARIABLE(name))
+    }
     pub fn new_virtreg(virt: usize) -> Self {
         let mut base_reg = Self::new(OpeKind::REG);
         base_reg.virt = virt;
         base_reg
     }
+    // レジスタの指すアドレスから,offsetバイト後ろのメモリ(間接参照)
+    pub fn new_dereference(virt: usize, offset: usize) -> Self {
+        let mut base_reg = Self::new(OpeKind::DEREFERENCE(offset));
+        base_reg.virt = virt;
+        base_reg
+    }
     pub fn new_invalid() -> Self {
         Self::new(OpeKind::INVALID)
     }
@@ -78,21 +136,41 @@ impl Operand {
             _ => false,
         }
     }
+    pub fn is_dereference(&self) -> bool {
+        matches!(self.kind, OpeKind::DEREFERENCE(_))
+    }
+    // 同じメモリ領域の,先頭からoffsetバイト後ろを指すオペランド
+    // e.g. 構造体のメンバ
+    pub fn offset_by(&self, offset: usize) -> Self {
+        let mut shifted = self.clone();
+        shifted.kind = match &self.kind {
+            OpeKind::AUTOVARIABLE(name, var_offset) => {
+                OpeKind::AUTOVARIABLE(name.to_string(), var_offset - offset)
+            }
+            OpeKind::DEREFERENCE(displacement) => OpeKind::DEREFERENCE(displacement + offset),
+            _ => panic!("can't get memory region of {:?}", self),
+        };
+        shifted
+    }
     pub fn to_string(&self) -> String {
         match &self.kind {
             OpeKind::INTLIT(val) => format!("{}", val),
-            OpeKind::CALL(name) => format!("{}()", name),
             OpeKind::AUTOVARIABLE(name, _offset) => format!("{}", name),
+            OpeKind::GLOBALVARIABLE(name) => name.to_string(),
             OpeKind::REG => format!("t{}", self.virt),
+            OpeKind::DEREFERENCE(0) => format!("*t{}", self.virt),
+            OpeKind::DEREFERENCE(offset) => format!("*(t{} + {})", self.virt, offset),
             OpeKind::INVALID => "invalid".to_string(),
         }
     }
     pub fn to_string_physical(&self) -> String {
         match &self.kind {
             OpeKind::INTLIT(val) => format!("{}", val),
-            OpeKind::CALL(name) => format!("{}()", name),
             OpeKind::AUTOVARIABLE(name, offset) => format!("{}[sp-{}]", name, offset),
+            OpeKind::GLOBALVARIABLE(name) => name.to_string(),
             OpeKind::REG => format!("t{}", self.phys),
+            OpeKind::DEREFERENCE(0) => format!("*t{}", self.phys),
+            OpeKind::DEREFERENCE(offset) => format!("*(t{} + {})", self.phys, offset),
             OpeKind::INVALID => "invalid".to_string(),
         }
     }
@@ -100,9 +178,10 @@ impl Operand {
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
 pub enum OpeKind {
-    CALL(String),
     INTLIT(i128),
     REG,
     AUTOVARIABLE(String, Offset),
+    GLOBALVARIABLE(String), // アドレスを求める(&x)か,関数を直接呼び出すためだけに使う
+    DEREFERENCE(Offset),    // virt/physが指すレジスタにオフセットを足したものをアドレスとして使う
     INVALID,
 }
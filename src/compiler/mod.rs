@@ -24,7 +24,13 @@ pub fn compile(
     let manager = frontend::frontend_process(matches, source_file, &target);
 
     // バックエンド部の処理
-    let s = backend::backend_process(matches, manager.ir_funcs, &target);
+    let s = backend::backend_process(
+        matches,
+        manager.ir_funcs,
+        manager.global_map,
+        manager.string_literals,
+        &target,
+    );
 
     if matches.is_present("atandt-syntax") {
         AssemblyFile::new_atandt_file(s, target)
@@ -172,9 +172,10 @@ impl ELF64 {
         }
     }
     pub fn clean_sections_offset(&mut self, mut base: u64) {
+        // .bss のようにファイル上のサイズが0のセクションもあるので,実際のバイト数で進める
         for section in self.sections.iter_mut() {
             section.header.sh_offset += base;
-            base += section.header.sh_size;
+            base += section.bytes.len() as u64;
         }
     }
     pub fn sum_given_and_section_sizes(&mut self, base: u64) -> u64 {
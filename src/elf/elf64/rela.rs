@@ -1,4 +1,11 @@
 use crate::elf::elf64::*;
+
+/* definitions for r_info(type) */
+pub const R_X86_64_64: Elf64Xword = 1; /* Direct 64 bit */
+pub const R_X86_64_PC32: Elf64Xword = 2; /* PC relative 32 bit signed */
+pub const R_X86_64_PLT32: Elf64Xword = 4; /* 32 bit PLT address */
+pub const R_X86_64_32S: Elf64Xword = 11; /* Direct 32 bit sign extended */
+
 #[derive(Debug)]
 pub struct Rela64 {
     pub r_offset: Elf64Addr,
@@ -20,6 +27,9 @@ impl Rela64 {
     pub fn bind(info: u64) -> usize {
         info as usize >> 32
     }
+    pub fn rel_type(info: u64) -> u64 {
+        info & 0xffffffff
+    }
     pub fn size() -> usize {
         24
     }
@@ -7,8 +7,10 @@ pub const SHT_PROGBITS: Elf64Word = 1;
 pub const SHT_SYMTAB: Elf64Word = 2;
 pub const SHT_STRTAB: Elf64Word = 3;
 pub const SHT_RELA: Elf64Word = 4;
+pub const SHT_NOBITS: Elf64Word = 8;
 
 /* definitions for sh_flags */
+pub const SHF_WRITE: Elf64Xword = 1 << 0;
 pub const SHF_ALLOC: Elf64Xword = 1 << 1;
 pub const SHF_EXECINSTR: Elf64Xword = 1 << 2;
 pub const SHF_INFO_LINK: Elf64Xword = 1 << 6;
@@ -94,7 +96,52 @@ impl Shdr64 {
             sh_entsize: 0,
         }
     }
-    pub fn init_symtab_header(size: Elf64Xword) -> Self {
+    // 書き込みを許さないデータ(文字列リテラルなど)
+    pub fn init_rodata_header(size: Elf64Xword) -> Self {
+        Self {
+            sh_name: 0,
+            sh_type: SHT_PROGBITS,
+            sh_flags: SHF_ALLOC,
+            sh_addr: 0,
+            sh_offset: 0,
+            sh_size: size,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 8,
+            sh_entsize: 0,
+        }
+    }
+    pub fn init_data_header(size: Elf64Xword) -> Self {
+        Self {
+            sh_name: 0,
+            sh_type: SHT_PROGBITS,
+            sh_flags: SHF_ALLOC | SHF_WRITE,
+            sh_addr: 0,
+            sh_offset: 0,
+            sh_size: size,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 8,
+            sh_entsize: 0,
+        }
+    }
+    // .bss はファイル上に実体を持たず,サイズだけを持つ
+    pub fn init_bss_header(size: Elf64Xword) -> Self {
+        Self {
+            sh_name: 0,
+            sh_type: SHT_NOBITS,
+            sh_flags: SHF_ALLOC | SHF_WRITE,
+            sh_addr: 0,
+            sh_offset: 0,
+            sh_size: size,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 8,
+            sh_entsize: 0,
+        }
+    }
+    // ローカルシンボルを先に並べ,sh_info には最初のグローバルシンボルの番号を入れる
+    pub fn init_symtab_header(size: Elf64Xword, first_global: Elf64Word) -> Self {
         Self {
             sh_name: 0,
             sh_type: SHT_SYMTAB,
@@ -102,8 +149,8 @@ impl Shdr64 {
             sh_addr: 0,
             sh_offset: 0,
             sh_size: size,
-            sh_link: 3, // .strtab が3番目にあることを決め打ち
-            sh_info: 1, // グローバルシンボルが1番目にあることを決め打ち
+            sh_link: 6, // .strtab が6番目にあることを決め打ち
+            sh_info: first_global,
             sh_addralign: 1,
             sh_entsize: Symbol64::size() as Elf64Xword,
         }
@@ -130,10 +177,22 @@ impl Shdr64 {
             sh_addr: 0,
             sh_offset: 0,
             sh_size: size,
-            sh_link: 2, // シンボルテーブルが二番目にあることを決め打ち
+            sh_link: 5, // シンボルテーブルが5番目にあることを決め打ち
             sh_info: 1, // .textセクションが一番目にあることを決め打ち
             sh_addralign: 8,
             sh_entsize: Rela64::size() as u64,
         }
     }
+    pub fn init_relarodata_header(size: Elf64Xword) -> Self {
+        Self {
+            sh_info: 2, // .rodataセクションが二番目にあることを決め打ち
+            ..Self::init_relatext_header(size)
+        }
+    }
+    pub fn init_reladata_header(size: Elf64Xword) -> Self {
+        Self {
+            sh_info: 3, // .dataセクションが三番目にあることを決め打ち
+            ..Self::init_relatext_header(size)
+        }
+    }
 }
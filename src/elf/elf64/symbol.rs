@@ -1,9 +1,15 @@
 use crate::elf::elf64::*;
 
+/* definitions for st_shndx */
+pub const SHN_UNDEF: Elf64Section = 0; /* Undefined section */
+
 /* definitions for st_info(bind) */
+pub const STB_LOCAL: u8 = 0; /* Local symbol */
 pub const STB_GLOBAL: u8 = 1; /* Global symbol */
 
 /* definitions for st_info(type) */
+pub const STT_NOTYPE: u8 = 0; /* Symbol type is unspecified */
+pub const STT_OBJECT: u8 = 1; /* Symbol is a data object */
 pub const STT_FUNC: u8 = 2; /* Symbol is a code object */
 
 #[repr(C)]
@@ -42,6 +48,52 @@ impl Symbol64 {
             st_size: length,
         }
     }
+    // 外部結合を持たない(.global されていない)関数
+    pub fn new_local_function(name_i: Elf64Word, length: Elf64Xword, offset: Elf64Addr) -> Self {
+        Self {
+            st_info: (STB_LOCAL << 4) + STT_FUNC,
+            ..Self::new_global_function(name_i, length, offset)
+        }
+    }
+    pub fn new_global_object(
+        name_i: Elf64Word,
+        length: Elf64Xword,
+        offset: Elf64Addr,
+        section_i: Elf64Section,
+    ) -> Self {
+        Self {
+            st_name: name_i,
+            st_info: (STB_GLOBAL << 4) + STT_OBJECT,
+            st_other: 0,
+            st_shndx: section_i,
+            st_value: offset,
+            st_size: length,
+        }
+    }
+    // static 変数や文字列リテラル,ジャンプテーブルなど,外部結合を持たないデータ
+    pub fn new_local_object(
+        name_i: Elf64Word,
+        length: Elf64Xword,
+        offset: Elf64Addr,
+        section_i: Elf64Section,
+    ) -> Self {
+        Self {
+            st_info: (STB_LOCAL << 4) + STT_OBJECT,
+            ..Self::new_global_object(name_i, length, offset, section_i)
+        }
+    }
+    // 他のオブジェクトファイルで定義されるシンボル
+    // 値とサイズはリンク時に決まる
+    pub fn new_undefined(name_i: Elf64Word) -> Self {
+        Self {
+            st_name: name_i,
+            st_info: (STB_GLOBAL << 4) + STT_NOTYPE,
+            st_other: 0,
+            st_shndx: SHN_UNDEF,
+            st_value: 0,
+            st_size: 0,
+        }
+    }
     pub fn to_binary(&self) -> Vec<u8> {
         let mut bytes: Vec<u8> = Vec::new();
         for byte in self.st_name.to_le_bytes().to_vec() {
@@ -10,11 +10,11 @@ pub struct Error {
 }
 
 impl Error {
-    pub fn new(kind: ErrorKind, pos: Position, msg: ErrorMsg) -> Self {
+    pub fn new(kind: ErrorKind, pos: impl Into<Position>, msg: ErrorMsg) -> Self {
         Self {
             kind: kind,
             message: msg,
-            position: pos,
+            position: pos.into(),
         }
     }
 
@@ -28,10 +28,9 @@ impl Error {
     }
     pub fn found(&self) {
         eprintln!(
-            "[{}] at {}:{}: {}",
+            "[{}] at {}: {}",
             self.kind.string().bold().red(),
-            self.position.0,
-            self.position.1,
+            self.position,
             self.message.string(),
         );
     }
@@ -45,6 +44,7 @@ impl Error {
 }
 pub enum ErrorKind {
     // コンパイラのエラー
+    Preprocess,
     Parse,
     Type,
     GenIR,
@@ -53,57 +53,244 @@ pub enum ErrorKind {
 
     // アセンブラのエラー
     AsmParse,
+
+    // リンカのエラー
+    Link,
 }
 
 impl ErrorKind {
     fn string(&self) -> &str {
         match self {
+            Self::Preprocess => "PreprocessError",
             Self::Parse => "ParseError",
             Self::Type => "TypeError",
             Self::GenIR => "GenerateIRError",
             Self::RegAlloc => "RegisterAllocationError",
             Self::Compile => "CompileError",
             Self::AsmParse => "AssemblyParseError",
+            Self::Link => "LinkError",
         }
     }
 }
 
 pub enum ErrorMsg {
+    // プリプロセッサのエラー
+    InvalidPreprocessingDirective,        // 未知の前処理指令
+    UnterminatedConditionalDirective,     // #if に対応する #endif が無い
+    UnmatchedConditionalDirective,        // #if の外で #elif/#else/#endif が現れた
+    MacroNameMustBeIdentifier,            // #define/#undef/#ifdef にマクロ名がない
+    InvalidMacroParameters,               // 関数形式マクロの仮引数並びが不正
+    StringizingMustBeFollowedByParameter, // # の後ろが仮引数でない
+    InvalidTokenPasting,                  // ## の結果が1つの字句にならない
+    UnterminatedMacroInvocation,          // マクロ呼び出しの ) が見つからない
+    MacroArgumentsMismatch,               // 実引数と仮引数の数が合わない
+    InvalidIncludeDirective,              // #include の形式が不正
+    IncludeFileNotFound(String),          // #include するファイルが見つからない
+    IncludeNestedTooDeeply,               // #include のネストが深すぎる
+    InvalidLineDirective,                 // #line の形式が不正
+    InvalidConstantExpression,            // #if の制御式が不正
+    DivisionByZeroInConstantExpression,   // #if の制御式でゼロ除算
+    ErrorDirective(String),               // #error
+
     // コンパイラのエラー
     MustBePrimary,           // パーサがPrimaryを期待する場所でPrimaryではなかった.後
     InvalidNodeCantHaveType, // 意味解析器がInvalidなASTノードを確認した
     MustBeSameTypeInBinaryOperation, // 二項演算時,暗黙の型変換が適用されない組み合わせだった
+    IncompatibleOperandsInConditional, // 条件演算子の2つの結果の型が合わない
+    CantTakeAddressOfRvalue, // 左辺値でない式のアドレスを取ろうとした
+    IndirectionRequiresPointer, // ポインタ型でない式を間接参照しようとした
+    MemberReferenceRequiresRecord, // 構造体/共用体でない式のメンバを参照しようとした
+    NoSuchMember(String),    // 構造体/共用体に存在しないメンバを参照しようとした
+    InitializerIsNotConstant, // グローバル変数の初期化式が定数式ではなかった
+    ExcessElementsInInitializer, // 初期化子の要素が初期化する対象より多い
+    InvalidDesignator,       // 指示子が初期化する対象の型と合わない
+    SwitchRequiresInteger,   // switch文の制御式が整数型ではなかった
+    CaseIsNotConstant,       // case の値が整数定数式ではなかった
+    DuplicateCaseValue(i128), // 同じswitch文に同じ値の case が複数ある
+    MultipleDefaultLabels,   // 同じswitch文に default が複数ある
+    NotInSwitch(String),     // switch文の外に case/default が現れた
+    BreakNotInLoopOrSwitch,  // ループ/switch文の外に break が現れた
+    ContinueNotInLoop,       // ループの外に continue が現れた
+    MustBeArrayLength,       // 配列宣言子の要素数が整数定数ではなかった
+    Redefinition(String),    // 同じスコープで同じ名前を宣言した
+    ConflictingTypes(String), // 関数の宣言と定義で型が一致しない
+    WrongKindOfTag(String),  // struct/union/enum のタグを別の種類のタグとして使った
+    ImplicitFunctionDeclaration(String), // 宣言されていない関数を呼び出した
+    ArgumentCountMismatch(String, usize, usize), // 関数呼び出しの引数の数が合わない
+    ArgumentTypeMismatch(String, usize, String, String), // 実引数を仮引数の型に変換できない
+    CalledObjectIsNotFunction(String), // 関数でも関数ポインタでもない式を呼び出した
+    NamedParameterBeforeEllipsis, // ... の前に名前付き引数がない
+    VaStartInFixedArgsFunction, // 可変長引数を取らない関数で va_start を使った
+    FirstArgumentMustBeVaList(String), // va_start/va_arg/va_end の第1引数が va_list でない
+    InvalidVaArgType,        // va_arg で整数/ポインタ以外の型を取り出そうとした
+    InvalidTypeSpecifier,    // 型指定子の組み合わせが不正
+    MultipleStorageClasses,  // 記憶域クラス指定子が複数ある
+    EnumeratorIsNotConstant(String), // 列挙定数の値が整数定数式ではなかった
+    CantAssignToConst(String), // const 修飾された型の式に代入しようとした
+    DiscardsQualifiers(String, String), // ポインタの暗黙の変換で,指す先の型の修飾子が落ちる
+    MissingTerminatingCharacter(char), // 文字定数/文字列リテラルが閉じられていない
+    EmptyCharConstant,       // 文字定数 '' が空
+    InvalidIntegerConstant(String), // 整数定数の接尾辞や数字が不正,または値が大きすぎる
     CantSupportSuchAnArchitecture, // 意図しないアーキテクチャ上でコンパイラが実行された
     CantUseNoMoreRegisters,  // レジスタ割付時エラー
     InvalidCFileOrDirectory, // ファイルが見つからない or ディレクトリであった
 
     // アセンブラのエラー
-    MustBeIntegerLiteral, // Lexerが整数を期待する場所で整数ではなかった.
-    InvalidOperand,       // 意図しないオペランドを受け取った
+    MustBeIntegerLiteral,      // Lexerが整数を期待する場所で整数ではなかった.
+    MustBeStringLiteral,       // .string/.ascii の引数が文字列ではなかった
+    ImmediateOutOfRange(i128), // 即値が命令のオペランドの幅に収まらない
+    InvalidOperand,            // 意図しないオペランドを受け取った
     MustSpecifySymbolNameInGlobalDirective, // .global <name> においてnameが見つからない
+
+    // リンカのエラー
+    UndefinedReference(String), // どのオブジェクトにも定義のないシンボルを参照した
 }
 
 impl ErrorMsg {
-    fn string(&self) -> &str {
+    fn string(&self) -> String {
         match self {
+            // プリプロセッサのエラー
+            Self::InvalidPreprocessingDirective => "invalid preprocessing directive".to_string(),
+            Self::UnterminatedConditionalDirective => {
+                "unterminated conditional directive".to_string()
+            }
+            Self::UnmatchedConditionalDirective => "#elif, #else or #endif without #if".to_string(),
+            Self::MacroNameMustBeIdentifier => "macro name must be an identifier".to_string(),
+            Self::InvalidMacroParameters => "invalid macro parameter list".to_string(),
+            Self::StringizingMustBeFollowedByParameter => {
+                "'#' is not followed by a macro parameter".to_string()
+            }
+            Self::InvalidTokenPasting => {
+                "'##' doesn't give a valid preprocessing token".to_string()
+            }
+            Self::UnterminatedMacroInvocation => "unterminated macro invocation".to_string(),
+            Self::MacroArgumentsMismatch => {
+                "the number of macro arguments doesn't match".to_string()
+            }
+            Self::InvalidIncludeDirective => {
+                "#include expects \"FILENAME\" or <FILENAME>".to_string()
+            }
+            Self::IncludeFileNotFound(name) => format!("'{}' file not found", name),
+            Self::IncludeNestedTooDeeply => "#include nested too deeply".to_string(),
+            Self::InvalidLineDirective => "invalid #line directive".to_string(),
+            Self::InvalidConstantExpression => "invalid constant expression in #if".to_string(),
+            Self::DivisionByZeroInConstantExpression => "division by zero in #if".to_string(),
+            Self::ErrorDirective(message) => format!("#error {}", message),
+
             // コンパイラのエラー
             Self::MustBePrimary => {
                 "must be (identifier | constant-expr | paren-expr | string_literal | `_Generic`)"
+                    .to_string()
             }
-            Self::InvalidNodeCantHaveType => "invalid node can't have any types",
+            Self::InvalidNodeCantHaveType => "invalid node can't have any types".to_string(),
             Self::MustBeSameTypeInBinaryOperation => {
-                "two expression must be same type in binary operation"
+                "two expression must be same type in binary operation".to_string()
+            }
+            Self::IncompatibleOperandsInConditional => {
+                "incompatible operand types in conditional expression".to_string()
+            }
+            Self::CantTakeAddressOfRvalue => "cannot take the address of an rvalue".to_string(),
+            Self::IndirectionRequiresPointer => "indirection requires pointer operand".to_string(),
+            Self::MemberReferenceRequiresRecord => {
+                "member reference base type is not a structure or union".to_string()
+            }
+            Self::NoSuchMember(name) => format!("no member named '{}'", name),
+            Self::InitializerIsNotConstant => {
+                "initializer element is not a compile-time constant".to_string()
+            }
+            Self::ExcessElementsInInitializer => "excess elements in initializer".to_string(),
+            Self::InvalidDesignator => {
+                "designator does not match the initialized object".to_string()
+            }
+            Self::SwitchRequiresInteger => {
+                "statement requires expression of integer type".to_string()
+            }
+            Self::CaseIsNotConstant => {
+                "case value is not an integer constant expression".to_string()
             }
-            Self::CantSupportSuchAnArchitecture => "not supporting such an architecture yet",
-            Self::CantUseNoMoreRegisters => "can't use no more registers",
-            Self::InvalidCFileOrDirectory => "invalid c file or directory given",
+            Self::DuplicateCaseValue(value) => format!("duplicate case value '{}'", value),
+            Self::MultipleDefaultLabels => "multiple default labels in one switch".to_string(),
+            Self::NotInSwitch(label) => {
+                format!("'{}' statement not in switch statement", label)
+            }
+            Self::BreakNotInLoopOrSwitch => {
+                "'break' statement not in loop or switch statement".to_string()
+            }
+            Self::ContinueNotInLoop => "'continue' statement not in loop statement".to_string(),
+            Self::MustBeArrayLength => "array size must be an integer constant".to_string(),
+            Self::Redefinition(name) => format!("redefinition of '{}'", name),
+            Self::ConflictingTypes(name) => format!("conflicting types for '{}'", name),
+            Self::WrongKindOfTag(tag) => format!("'{}' defined as wrong kind of tag", tag),
+            Self::ImplicitFunctionDeclaration(name) => {
+                format!("call to undeclared function '{}'", name)
+            }
+            Self::ArgumentCountMismatch(name, expected, given) => format!(
+                "too {} arguments to function call '{}', expected {}, have {}",
+                if expected < given { "many" } else { "few" },
+                name,
+                expected,
+                given
+            ),
+            Self::ArgumentTypeMismatch(name, index, param_type, arg_type) => format!(
+                "passing '{}' to parameter {} of '{}' of incompatible type '{}'",
+                arg_type, index, name, param_type
+            ),
+            Self::CalledObjectIsNotFunction(type_name) => format!(
+                "called object type '{}' is not a function or function pointer",
+                type_name
+            ),
+            Self::NamedParameterBeforeEllipsis => {
+                "ISO C requires a named parameter before '...'".to_string()
+            }
+            Self::VaStartInFixedArgsFunction => {
+                "'va_start' used in function with fixed arguments".to_string()
+            }
+            Self::FirstArgumentMustBeVaList(builtin) => {
+                format!("first argument to '{}' must be of type 'va_list'", builtin)
+            }
+            Self::InvalidVaArgType => {
+                "second argument to 'va_arg' must be an integer or pointer type".to_string()
+            }
+            Self::InvalidTypeSpecifier => "invalid combination of type specifiers".to_string(),
+            Self::MultipleStorageClasses => {
+                "multiple storage classes in declaration specifiers".to_string()
+            }
+            Self::EnumeratorIsNotConstant(name) => {
+                format!("enumerator value for '{}' is not an integer constant", name)
+            }
+            Self::CantAssignToConst(type_name) => format!(
+                "cannot assign to expression with const-qualified type '{}'",
+                type_name
+            ),
+            Self::DiscardsQualifiers(from, to) => {
+                format!("conversion from '{}' to '{}' discards qualifiers", from, to)
+            }
+            Self::MissingTerminatingCharacter(quote) => {
+                format!("missing terminating {} character", quote)
+            }
+            Self::EmptyCharConstant => "empty character constant".to_string(),
+            Self::InvalidIntegerConstant(constant) => {
+                format!("invalid integer constant '{}'", constant)
+            }
+            Self::CantSupportSuchAnArchitecture => {
+                "not supporting such an architecture yet".to_string()
+            }
+            Self::CantUseNoMoreRegisters => "can't use no more registers".to_string(),
+            Self::InvalidCFileOrDirectory => "invalid c file or directory given".to_string(),
 
             // アセンブラのエラー
-            Self::MustBeIntegerLiteral => "must be integer-literal",
-            Self::InvalidOperand => "invalid operand",
+            Self::MustBeIntegerLiteral => "must be integer-literal".to_string(),
+            Self::MustBeStringLiteral => "must be string-literal".to_string(),
+            Self::ImmediateOutOfRange(value) => {
+                format!("immediate value '{}' is out of range", value)
+            }
+            Self::InvalidOperand => "invalid operand".to_string(),
             Self::MustSpecifySymbolNameInGlobalDirective => {
-                "must specify symbol name in global directive"
+                "must specify symbol name in global directive".to_string()
             }
+
+            // リンカのエラー
+            Self::UndefinedReference(name) => format!("undefined reference to `{}`", name),
         }
     }
 }
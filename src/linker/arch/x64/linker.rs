@@ -1,5 +1,6 @@
 use crate::elf::elf64;
-use elf64::{ehdr, phdr, rela, symbol};
+use crate::error::{Error, ErrorKind, ErrorMsg};
+use elf64::{ehdr, phdr, rela, symbol, Elf64Word};
 
 pub static BASE_ADDRESS: u64 = 0x400000;
 pub static PAGE_SIZE: u64 = 0x1000;
@@ -15,8 +16,18 @@ impl X64StaticLinker {
         }
     }
     pub fn link(&mut self) {
+        // 静的リンクなので,未定義シンボルが残っていれば解決できない
+        self.check_undefined_symbols();
+
+        // 各セクションにロードアドレスを割り当てる
+        self.assign_section_addresses();
+
         // .textセクションだけをまとめたセグメントを作る
         self.init_phdr();
+        // .rodata だけの読み込み専用セグメントを作る
+        self.init_rodata_phdr();
+        // .data/.bss をまとめた書き込み可能なセグメントを作る
+        self.init_data_phdr();
         self.prepare_ehdr_for_staticlink();
 
         // ページサイズアラインの為にパディング
@@ -30,6 +41,29 @@ impl X64StaticLinker {
         // 調整をかける
         self.conditioning_section_offset();
     }
+    fn check_undefined_symbols(&self) {
+        let strtab: Vec<u8> = self.exec_file.get_section_binary(".strtab");
+        let symbols: Vec<symbol::Symbol64> = self.exec_file.get_symbol_table();
+
+        let mut found_undefined = false;
+        for sym in symbols.iter().skip(1) {
+            if sym.st_shndx != symbol::SHN_UNDEF {
+                continue;
+            }
+            let name: String = strtab[sym.st_name as usize..]
+                .iter()
+                .take_while(|b| **b != 0)
+                .map(|b| *b as char)
+                .collect();
+            let err = Error::new(ErrorKind::Link, (0, 0), ErrorMsg::UndefinedReference(name));
+            err.compile_error();
+            found_undefined = true;
+        }
+
+        if found_undefined {
+            std::process::exit(1);
+        }
+    }
     // 定義済みシンボルにアドレスを割り当てる
     fn link_symbols(&mut self) {
         // シンボル名検索用
@@ -37,14 +71,16 @@ impl X64StaticLinker {
 
         // 扱いやすくするため構造体列に変換
         let mut symbols: Vec<symbol::Symbol64> = self.exec_file.get_symbol_table();
-        for symbol in symbols.iter_mut() {
+        let text_number: usize = self.exec_file.get_section_number(".text");
+        for symbol in symbols.iter_mut().skip(1) {
+            // セクション内オフセット + セクションのアドレス
+            let section_number = symbol.st_shndx as usize;
+            symbol.st_value += self.exec_file.sections[section_number].header.sh_addr;
+
             // スタートアップルーチンであればエントリポイントに指定
-            if strtab[symbol.st_name as usize] as char == '_' {
-                self.exec_file.ehdr.e_entry = BASE_ADDRESS + symbol.st_value;
+            if section_number == text_number && strtab[symbol.st_name as usize] as char == '_' {
+                self.exec_file.ehdr.e_entry = symbol.st_value;
             }
-
-            // ファイルオフセット + 決め打ちアドレス
-            symbol.st_value += BASE_ADDRESS;
         }
 
         // バイナリに再変換して格納
@@ -54,22 +90,67 @@ impl X64StaticLinker {
 
     // 再配置テーブル等を利用したリンク
     fn resolve_symbols(&mut self) {
+        self.resolve_relocations(".rela.text", ".text");
+        self.resolve_relocations(".rela.rodata", ".rodata");
+        self.resolve_relocations(".rela.data", ".data");
+    }
+    // 再配置テーブルの各エントリに従って,対象セクションにアドレスを書き込む
+    fn resolve_relocations(&mut self, rela_name: &str, target_name: &str) {
         let symbols: Vec<symbol::Symbol64> = self.exec_file.get_symbol_table();
-        let mut relas: Vec<rela::Rela64> = self.exec_file.get_reloc_table(".rela.text");
+        let relas: Vec<rela::Rela64> = self.exec_file.get_reloc_table(rela_name);
+        let target_number: usize = self.exec_file.get_section_number(target_name);
+        let target_address = self.exec_file.sections[target_number].header.sh_addr;
 
-        for rel in relas.iter_mut() {
+        for rel in relas.iter() {
             // Relaオブジェクトに対応するシンボルテーブルエントリからアドレスを取り出す
             let symbol_table_entry_index = rela::Rela64::bind(rel.r_info);
-            let address = symbols[symbol_table_entry_index].st_value as u32;
-
-            // アドレスをバイト列に変換,機械語に書き込むことでアドレス解決
-            for (idx, b) in address.to_le_bytes().to_vec().iter().enumerate() {
-                let text_number: usize = self.exec_file.get_section_number(".text");
-                self.exec_file.sections[text_number].bytes[rel.r_offset as usize + idx] = *b;
+            let address = symbols[symbol_table_entry_index].st_value as i64 + rel.r_addend;
+
+            // rip相対なら参照位置からの差分(S + A - P),そうでなければ絶対アドレス(S + A)
+            let value: Vec<u8> = match rela::Rela64::rel_type(rel.r_info) {
+                rela::R_X86_64_PC32 | rela::R_X86_64_PLT32 => {
+                    let place = (target_address + rel.r_offset) as i64;
+                    ((address - place) as i32).to_le_bytes().to_vec()
+                }
+                rela::R_X86_64_64 => (address as u64).to_le_bytes().to_vec(),
+                _ => (address as u32).to_le_bytes().to_vec(),
+            };
+
+            // アドレスをバイト列に変換,機械語/データに書き込むことでアドレス解決
+            for (idx, b) in value.iter().enumerate() {
+                self.exec_file.sections[target_number].bytes[rel.r_offset as usize + idx] = *b;
             }
         }
     }
 
+    fn assign_section_addresses(&mut self) {
+        // .text は決め打ちしたアドレスに置く
+        let text_number: usize = self.exec_file.get_section_number(".text");
+        self.exec_file.sections[text_number].header.sh_addr = BASE_ADDRESS;
+
+        // 以降のセグメントは,ファイルオフセットと合同になるように
+        // 前のセグメントの次のページ以降に置く
+        let rodata_number: usize = self.exec_file.get_section_number(".rodata");
+        let rodata_address = BASE_ADDRESS + self.section_file_offset(rodata_number);
+        self.exec_file.sections[rodata_number].header.sh_addr = rodata_address;
+
+        let data_number: usize = self.exec_file.get_section_number(".data");
+        let data_address = BASE_ADDRESS + PAGE_SIZE + self.section_file_offset(data_number);
+        self.exec_file.sections[data_number].header.sh_addr = data_address;
+
+        // .bss は .data の直後
+        let data_size = self.exec_file.sections[data_number].bytes.len() as u64;
+        let bss_number: usize = self.exec_file.get_section_number(".bss");
+        self.exec_file.sections[bss_number].header.sh_addr = data_address + data_size;
+    }
+
+    // パディング後のファイル上でのセクションの位置
+    fn section_file_offset(&self, section_number: usize) -> u64 {
+        self.exec_file.sections[1..section_number]
+            .iter()
+            .fold(PAGE_SIZE, |sum, section| sum + section.bytes.len() as u64)
+    }
+
     fn init_phdr(&mut self) {
         let mut phdr: phdr::Phdr64 = phdr::Phdr64::new();
         // 機械語命令 -> PT_LOADに配置
@@ -92,6 +173,41 @@ impl X64StaticLinker {
         phdr.p_flags = phdr::PF_R | phdr::PF_X | phdr::PF_W;
         self.exec_file.phdrs.push(phdr);
     }
+    fn init_rodata_phdr(&mut self) {
+        let rodata_number: usize = self.exec_file.get_section_number(".rodata");
+        let rodata_size = self.exec_file.sections[rodata_number].bytes.len() as u64;
+        self.push_data_phdr(rodata_number, rodata_size, phdr::PF_R);
+    }
+    fn init_data_phdr(&mut self) {
+        let data_number: usize = self.exec_file.get_section_number(".data");
+        let bss_number: usize = self.exec_file.get_section_number(".bss");
+        let data_size = self.exec_file.sections[data_number].bytes.len() as u64;
+        let bss_size = self.exec_file.sections[bss_number].header.sh_size;
+
+        // .bss の分はファイル上に存在しないので,memsz だけに含める
+        self.push_data_phdr(data_number, data_size + bss_size, phdr::PF_R | phdr::PF_W);
+    }
+    // セクションの先頭から始まるセグメントを作る
+    fn push_data_phdr(&mut self, section_number: usize, memsz: u64, flags: Elf64Word) {
+        // 中身が無ければセグメントは不要
+        if memsz == 0 {
+            return;
+        }
+
+        let mut phdr: phdr::Phdr64 = phdr::Phdr64::new();
+        phdr.p_type = phdr::PT_LOAD;
+        phdr.p_offset = self.section_file_offset(section_number);
+        phdr.p_align = PAGE_SIZE;
+
+        phdr.p_vaddr = self.exec_file.sections[section_number].header.sh_addr;
+        phdr.p_paddr = phdr.p_vaddr;
+
+        phdr.p_filesz = self.exec_file.sections[section_number].bytes.len() as u64;
+        phdr.p_memsz = memsz;
+
+        phdr.p_flags = flags;
+        self.exec_file.phdrs.push(phdr);
+    }
     fn prepare_ehdr_for_staticlink(&mut self) {
         // スタティックリンク -> ET_EXEC
         self.exec_file.ehdr.e_type = ehdr::ET_EXEC;
@@ -126,9 +242,5 @@ impl X64StaticLinker {
                     PAGE_SIZE - ehdr::Ehdr64::size() as u64 + section.header.sh_offset;
             })
             .collect::<()>();
-
-        // .textセクションのアドレスをエントリポイントになおしておく.
-        let text_number: usize = self.exec_file.get_section_number(".text");
-        self.exec_file.sections[text_number].header.sh_addr = self.exec_file.ehdr.e_entry;
     }
 }
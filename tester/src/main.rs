@@ -20,15 +20,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // テストケースの構築
     let expected_map = build_expected()?;
 
+    // 残りの引数はそのままコンパイラに渡す (e.g. -O2)
+    let options: Vec<String> = std::env::args().skip(1).collect();
+
     // 各テストケースについて
     for entry in fs::read_dir(samples_path)? {
         let iter_file_name = entry?.file_name().into_string().unwrap();
+
+        // ヘッダファイル等はテストケースではない
+        if !iter_file_name.ends_with(".c") {
+            continue;
+        }
         let test_file_path = get_single_testcase_path(iter_file_name);
 
+        // 前のテストケースのバイナリが残っていると,コンパイル失敗に気づけない
+        let _ = fs::remove_file("a.out");
+
         // subprocess の起動
         let binary_path = get_cminus_binary_path();
 
-        exec_compile_command(binary_path, test_file_path.clone());
+        exec_compile_command(binary_path, &options, test_file_path.clone());
 
         let execute_status = get_executed_elf_status().code().unwrap();
 
@@ -36,6 +47,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         check_given_status_is_success(&expected_map, test_file_path, execute_status);
     }
 
+    // samples/errors 以下はコンパイルに失敗しなければならない
+    let errors_dir = samples_dir + "/errors";
+    for entry in fs::read_dir(Path::new(&errors_dir))? {
+        let test_file_path = entry?.path().to_str().unwrap().to_string();
+        let _ = fs::remove_file("a.out");
+
+        let binary_path = get_cminus_binary_path();
+        let compile_status = exec_compile_command(binary_path, &options, test_file_path.clone());
+
+        check_compile_is_rejected(test_file_path, compile_status);
+    }
+
     Ok(())
 }
 
@@ -50,6 +73,9 @@ fn build_expected() -> Result<BTreeMap<String, i32>, Box<dyn std::error::Error>>
     for entry in fs::read_dir(samples_path)? {
         // ファイルパスの階層を取り除いてパターンマッチ
         let iter_file_name = entry?.file_name().into_string().unwrap();
+        if !iter_file_name.ends_with(".c") {
+            continue;
+        }
         let test_file_path = get_single_testcase_path(iter_file_name);
 
         let splitted_path: Vec<&str> = test_file_path.split('/').collect();
@@ -72,6 +98,34 @@ fn build_expected() -> Result<BTreeMap<String, i32>, Box<dyn std::error::Error>>
             "do_while.c" => 2,
             "without_params.c" => 30,
             "with_params.c" => 3,
+            "macro.c" => 14,
+            "relational.c" => 1,
+            "logical.c" => 12,
+            "bitwise.c" => 195,
+            "modulo.c" => 51,
+            "compound_assign.c" => 60,
+            "conditional.c" => 56,
+            "pointer.c" => 72,
+            "array.c" => 47,
+            "integer_types.c" => 225,
+            "struct.c" => 49,
+            "global.c" => 41,
+            "string.c" => 164,
+            "switch.c" => 231,
+            "break_continue.c" => 105,
+            "block_scope.c" => 173,
+            "initializer.c" => 28,
+            "prototype.c" => 108,
+            "calling_convention.c" => 93,
+            "variadic.c" => 85,
+            "function_pointer.c" => 109,
+            "declaration_specifiers.c" => 241,
+            "spill.c" => 6,
+            "void_pointer.c" => 153,
+            "address_constant.c" => 23,
+            "integer_constants.c" => 51,
+            "tag_scope.c" => 27,
+            "function_returning_pointer.c" => 32,
             _ => {
                 eprintln!("not found such a test case -> {}", final_name);
                 0
@@ -95,12 +149,17 @@ fn get_cminus_binary_path() -> String {
     std::env::var("C_ROOT").unwrap() + "/target/debug/c--"
 }
 
-fn exec_compile_command(binary_path: String, test_file_path: String) {
-    // $ c-- <test_file_path>
-    let _compile_cmd = Command::new(&binary_path)
+fn exec_compile_command(
+    binary_path: String,
+    options: &[String],
+    test_file_path: String,
+) -> std::process::ExitStatus {
+    // $ c-- <options> <test_file_path>
+    Command::new(&binary_path)
+        .args(options)
         .arg(&test_file_path.clone())
         .status()
-        .expect("failed to spawn a process");
+        .expect("failed to spawn a process")
 }
 
 fn get_executed_elf_status() -> std::process::ExitStatus {
@@ -134,3 +193,22 @@ fn check_given_status_is_success(
         }
     }
 }
+
+fn check_compile_is_rejected(test_file_path: String, compile_status: std::process::ExitStatus) {
+    // 異常終了(パニック)ではなく,エラーとして終了ステータス 1 を返す
+    let rejected = compile_status.code() == Some(1) && !Path::new("a.out").exists();
+    if rejected {
+        eprintln!(
+            "{} -> {} (rejected)",
+            test_file_path,
+            "PASSED".bold().green()
+        );
+    } else {
+        eprintln!(
+            "{} -> {} expected compile error but actual {}",
+            test_file_path,
+            "FAILED".bold().red(),
+            compile_status
+        );
+    }
+}